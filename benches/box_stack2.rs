@@ -0,0 +1,55 @@
+//! Measures the time needed by a pyramid of boxes to settle, i.e., until all its boxes fall asleep.
+//!
+//! Run with `cargo bench --bench box_stack2`.
+
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+
+use std::time::Instant;
+
+use na::Vector2;
+use ncollide2d::shape::{Cuboid, ShapeHandle};
+use nphysics2d::material::{BasicMaterial, MaterialHandle};
+use nphysics2d::object::ColliderDesc;
+use nphysics2d::utils;
+use nphysics2d::world::World;
+
+const MAX_STEPS: usize = 3000;
+
+fn settle(base_count: usize) {
+    let mut world = World::<f32>::new();
+    world.set_gravity(Vector2::y() * -9.81);
+
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector2::new(50.0, 1.0)));
+    ColliderDesc::new(ground_shape)
+        .translation(-Vector2::y())
+        .build(&mut world);
+
+    let material = MaterialHandle::new(BasicMaterial::default());
+    let handles = utils::build_box_stack(&mut world, base_count, base_count, 0.5, material);
+
+    let start = Instant::now();
+    let mut nsteps = 0;
+
+    while nsteps < MAX_STEPS && world.bodies().any(|b| b.is_active()) {
+        world.step();
+        nsteps += 1;
+    }
+
+    let elapsed = start.elapsed();
+    println!(
+        "{} boxes: {} steps ({:.2}s simulated) in {:.3}s{}",
+        handles.len(),
+        nsteps,
+        nsteps as f32 * world.timestep(),
+        elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1.0e-9,
+        if nsteps == MAX_STEPS { " (not settled)" } else { "" }
+    );
+}
+
+fn main() {
+    for base_count in &[5, 10, 20] {
+        settle(*base_count);
+    }
+}
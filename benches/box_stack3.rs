@@ -0,0 +1,55 @@
+//! Measures the time needed by a pyramid of boxes to settle, i.e., until all its boxes fall asleep.
+//!
+//! Run with `cargo bench --bench box_stack3`.
+
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+
+use std::time::Instant;
+
+use na::Vector3;
+use ncollide3d::shape::{Cuboid, ShapeHandle};
+use nphysics3d::material::{BasicMaterial, MaterialHandle};
+use nphysics3d::object::ColliderDesc;
+use nphysics3d::utils;
+use nphysics3d::world::World;
+
+const MAX_STEPS: usize = 3000;
+
+fn settle(base_count: usize) {
+    let mut world = World::<f32>::new();
+    world.set_gravity(Vector3::y() * -9.81);
+
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector3::new(50.0, 1.0, 50.0)));
+    ColliderDesc::new(ground_shape)
+        .translation(-Vector3::y())
+        .build(&mut world);
+
+    let material = MaterialHandle::new(BasicMaterial::default());
+    let handles = utils::build_box_stack(&mut world, base_count, base_count, 0.5, material);
+
+    let start = Instant::now();
+    let mut nsteps = 0;
+
+    while nsteps < MAX_STEPS && world.bodies().any(|b| b.is_active()) {
+        world.step();
+        nsteps += 1;
+    }
+
+    let elapsed = start.elapsed();
+    println!(
+        "{} boxes: {} steps ({:.2}s simulated) in {:.3}s{}",
+        handles.len(),
+        nsteps,
+        nsteps as f32 * world.timestep(),
+        elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1.0e-9,
+        if nsteps == MAX_STEPS { " (not settled)" } else { "" }
+    );
+}
+
+fn main() {
+    for base_count in &[5, 10, 20] {
+        settle(*base_count);
+    }
+}
@@ -0,0 +1,65 @@
+//! Measures the time needed by the timesteps of a scene with about 5000 contacts, i.e., a grid of boxes resting
+//! on the ground.
+//!
+//! Run with `cargo bench --bench contact_manifolds3`, and with `--features parallel` to collect the contact
+//! manifolds in parallel.
+
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+
+use std::time::Instant;
+
+use na::Vector3;
+use ncollide3d::shape::{Cuboid, ShapeHandle};
+use nphysics3d::object::{ColliderDesc, RigidBodyDesc};
+use nphysics3d::world::World;
+
+const NUM_STEPS: usize = 100;
+
+fn main() {
+    let mut world = World::<f32>::new();
+    world.set_gravity(Vector3::y() * -9.81);
+
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector3::new(100.0, 1.0, 100.0)));
+    ColliderDesc::new(ground_shape)
+        .translation(-Vector3::y())
+        .build(&mut world);
+
+    // Each box resting on the ground generates 4 contacts, and the boxes are too far apart to touch each other.
+    let rad = 0.5;
+    let collider_desc = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector3::repeat(rad)))).density(1.0);
+    let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc).sleep_threshold(None);
+    let num = 35;
+    let shift = rad * 3.0;
+
+    for i in 0..num {
+        for j in 0..num {
+            let x = (i as f32 - num as f32 / 2.0) * shift;
+            let z = (j as f32 - num as f32 / 2.0) * shift;
+            let _ = rb_desc.set_translation(Vector3::new(x, rad, z)).build(&mut world);
+        }
+    }
+
+    // Let the contacts settle before measuring.
+    for _ in 0..10 {
+        world.step();
+    }
+
+    let ncontacts: usize = world.contact_manifolds().map(|(_, _, _, _, manifold)| manifold.len()).sum();
+
+    let start = Instant::now();
+
+    for _ in 0..NUM_STEPS {
+        world.step();
+    }
+
+    let elapsed = start.elapsed();
+    let elapsed = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1.0e-9;
+    println!(
+        "{} boxes, {} contacts: {:.3}ms per step",
+        num * num,
+        ncontacts,
+        elapsed * 1000.0 / NUM_STEPS as f64
+    );
+}
@@ -0,0 +1,78 @@
+//! Checks that the timesteps of a pyramid of boxes at rest, i.e., a contact-heavy scene where the number of
+//! constraints no longer changes, do not perform any allocation.
+//!
+//! The constraint buffers of the solver are cleared but not deallocated at each timestep, so once the scene is
+//! at rest, they are reused as they are. Panics if any allocation is performed by the measured timesteps.
+//!
+//! Run with `cargo bench --bench solver_allocations3`.
+
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use na::Vector3;
+use ncollide3d::shape::{Cuboid, ShapeHandle};
+use nphysics3d::material::{BasicMaterial, MaterialHandle};
+use nphysics3d::object::ColliderDesc;
+use nphysics3d::utils;
+use nphysics3d::world::World;
+
+const NUM_SETTLING_STEPS: usize = 200;
+const NUM_STEPS: usize = 100;
+
+/*
+ * An allocator counting the number of allocations.
+ */
+struct CountingAllocator;
+
+static NALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = NALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let mut world = World::<f32>::new();
+    world.set_gravity(Vector3::y() * -9.81);
+
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector3::new(50.0, 1.0, 50.0)));
+    ColliderDesc::new(ground_shape)
+        .translation(-Vector3::y())
+        .build(&mut world);
+
+    let material = MaterialHandle::new(BasicMaterial::default());
+    let _ = utils::build_box_stack(&mut world, 20, 20, 0.5, material);
+
+    // Keep the boxes awake so their contacts are solved at each timestep.
+    for body in world.bodies_mut() {
+        body.set_deactivation_threshold(None);
+    }
+
+    // Let the pyramid settle so the number of constraints becomes stable.
+    for _ in 0..NUM_SETTLING_STEPS {
+        world.step();
+    }
+
+    let nconstraints = world.performance_counters().nconstraints();
+    let nallocations = NALLOCATIONS.load(Ordering::SeqCst);
+
+    for _ in 0..NUM_STEPS {
+        world.step();
+    }
+
+    let allocs = NALLOCATIONS.load(Ordering::SeqCst) - nallocations;
+    assert_eq!(allocs, 0, "{} allocations in {} timesteps with {} constraints", allocs, NUM_STEPS, nconstraints);
+}
@@ -0,0 +1,119 @@
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+extern crate nphysics_testbed2d;
+
+use std::cell::RefCell;
+use na::{Isometry2, Point2, Point3, Vector2};
+use ncollide2d::shape::{Cuboid, ShapeHandle};
+use nphysics2d::object::{BodyStatus, ColliderDesc, RigidBodyDesc};
+use nphysics2d::world::{KinematicCharacterController, World};
+use nphysics_testbed2d::Testbed;
+
+
+fn main() {
+    let mut testbed = Testbed::new_empty();
+
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector2::new(0.0, -9.81));
+
+    /*
+     * Ground, with a few steps, a slope, and walls at both ends.
+     */
+    let ground = ShapeHandle::new(Cuboid::new(Vector2::new(12.0, 1.0)));
+    ColliderDesc::new(ground)
+        .translation(-Vector2::y())
+        .build(&mut world);
+
+    let step = ShapeHandle::new(Cuboid::new(Vector2::new(1.0, 0.1)));
+    for i in 0..3 {
+        ColliderDesc::new(step.clone())
+            .translation(Vector2::new(-6.0 + i as f32 * 0.5, 0.1 + i as f32 * 0.2))
+            .build(&mut world);
+    }
+
+    let slope = ShapeHandle::new(Cuboid::new(Vector2::new(2.0, 0.1)));
+    ColliderDesc::new(slope)
+        .position(Isometry2::new(Vector2::new(4.0, 0.5), 0.3))
+        .build(&mut world);
+
+    let wall = ShapeHandle::new(Cuboid::new(Vector2::new(0.2, 3.0)));
+    ColliderDesc::new(wall.clone())
+        .translation(Vector2::new(-10.0, 3.0))
+        .build(&mut world);
+    ColliderDesc::new(wall)
+        .translation(Vector2::new(10.0, 3.0))
+        .build(&mut world);
+
+    /*
+     * A few dynamic boxes the character cannot push.
+     */
+    let cuboid = ShapeHandle::new(Cuboid::new(Vector2::repeat(0.2)));
+    let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+    for i in 0..3 {
+        RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector2::new(-1.0 + i as f32 * 0.5, 2.0))
+            .build(&mut world);
+    }
+
+    /*
+     * The kinematic character.
+     */
+    let character = ShapeHandle::new(Cuboid::new(Vector2::new(0.3, 0.5)));
+    let character_desc = ColliderDesc::new(character);
+    let character_handle = RigidBodyDesc::new()
+        .collider(&character_desc)
+        .translation(Vector2::new(0.0, 2.0))
+        .status(BodyStatus::Kinematic)
+        .build(&mut world)
+        .handle();
+
+    testbed.set_body_color(character_handle, Point3::new(1.0, 0.0, 0.0));
+
+    /*
+     * Setup a callback to move the character: it runs back and forth, jumping regularly.
+     */
+    let controller = RefCell::new(KinematicCharacterController::new());
+    let state = RefCell::new((1.0f32, 0.0f32, 0.0f32)); // Direction, vertical speed, time of the last jump.
+
+    testbed.add_callback(move |world, _, time| {
+        let mut world = world.get_mut();
+        let mut controller = controller.borrow_mut();
+        let (direction, vertical_speed, last_jump) = &mut *state.borrow_mut();
+        let dt = world.timestep();
+
+        if controller.is_grounded() {
+            *vertical_speed = 0.0;
+
+            if time - *last_jump > 2.0 {
+                *vertical_speed = 6.0;
+                *last_jump = time;
+            }
+        }
+
+        *vertical_speed += world.gravity().y * dt;
+        let desired = Vector2::new(*direction * 3.0, *vertical_speed) * dt;
+        let moved = controller.move_and_slide(&mut world, character_handle, &desired);
+
+        // Turn back when blocked by a wall.
+        if moved.x.abs() < desired.x.abs() * 0.1 {
+            *direction = -*direction;
+        }
+
+        // Stop going up when hitting a ceiling.
+        if *vertical_speed > 0.0 && moved.y < desired.y * 0.5 {
+            *vertical_speed = 0.0;
+        }
+    });
+
+    /*
+     * Set up the testbed.
+     */
+    testbed.set_world(world);
+    testbed.look_at(Point2::new(0.0, -2.0), 50.0);
+    testbed.run();
+}
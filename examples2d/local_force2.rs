@@ -0,0 +1,62 @@
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+extern crate nphysics_testbed2d;
+
+use na::{Point2, Point3, Vector2};
+use ncollide2d::shape::{Cuboid, ShapeHandle};
+use nphysics2d::force_generator::LocalForce;
+use nphysics2d::object::{ColliderDesc, RigidBodyDesc};
+use nphysics2d::world::World;
+use nphysics_testbed2d::Testbed;
+
+
+fn main() {
+    let mut testbed = Testbed::new_empty();
+
+    /*
+     * World
+     */
+    let mut world = World::new();
+
+    /*
+     * Create two ships, each with a thruster pushing along its local x axis.
+     */
+    let rad = 0.5;
+    let geom = ShapeHandle::new(Cuboid::new(Vector2::new(rad * 2.0, rad)));
+    let collider_desc = ColliderDesc::new(geom)
+        .density(1.0);
+    let mut rb_desc = RigidBodyDesc::new()
+        .collider(&collider_desc);
+
+    // The first thruster is aligned with the center of mass: the ship moves in a straight line.
+    let centered = rb_desc
+        .set_translation(Vector2::new(-3.0, 2.0))
+        .build(&mut world)
+        .part_handle();
+    let mut centered_thruster = LocalForce::new(centered, Point2::new(-rad * 2.0, 0.0), Vector2::x() * 5.0);
+
+    // The second thruster is mounted off-center: the ship starts to spin.
+    let off_center = rb_desc
+        .set_translation(Vector2::new(-3.0, -2.0))
+        .build(&mut world)
+        .part_handle();
+    let mut off_center_thruster = LocalForce::new(off_center, Point2::new(-rad * 2.0, rad), Vector2::x() * 5.0);
+
+    // Both thrusters are shut down after two seconds.
+    centered_thruster.set_magnitude_callback(Box::new(|t| if t < 2.0 { 1.0 } else { 0.0 }));
+    off_center_thruster.set_magnitude_callback(Box::new(|t| if t < 2.0 { 1.0 } else { 0.0 }));
+
+    world.add_force_generator(centered_thruster);
+    world.add_force_generator(off_center_thruster);
+
+    testbed.set_body_color(centered.0, Point3::new(0.0, 1.0, 0.0));
+    testbed.set_body_color(off_center.0, Point3::new(0.0, 0.0, 1.0));
+
+    /*
+     * Set up the testbed.
+     */
+    testbed.set_world(world);
+    testbed.look_at(Point2::new(0.0, 0.0), 60.0);
+    testbed.run();
+}
@@ -0,0 +1,80 @@
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+extern crate nphysics_testbed2d;
+
+use std::f32;
+use na::{Point2, Point3, Vector2};
+use ncollide2d::shape::{Cuboid, Polyline, ShapeHandle};
+use nphysics2d::object::{MassConstraintSystemDesc, ColliderDesc};
+use nphysics2d::world::World;
+use nphysics_testbed2d::Testbed;
+
+
+fn main() {
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector2::new(0.0, -9.81));
+
+    /*
+     * Ground.
+     */
+    let ground_size = 25.0;
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector2::new(ground_size, 1.0)));
+
+    ColliderDesc::new(ground_shape)
+        .translation(Vector2::y() * -1.0)
+        .build(&mut world);
+
+    /*
+     * Create a star-shaped polygon.
+     */
+    let num_branches = 5;
+    let num_subdivs = 4;
+    let outer_radius = 2.0;
+    let inner_radius = 0.8;
+    let mut corners = Vec::new();
+
+    for i in 0..num_branches * 2 {
+        let angle = (i as f32) * f32::consts::PI / (num_branches as f32) + f32::consts::FRAC_PI_2;
+        let radius = if i % 2 == 0 { outer_radius } else { inner_radius };
+        corners.push(Point2::new(angle.cos(), angle.sin()) * radius);
+    }
+
+    // Subdivide each side of the star so the boundary can deform smoothly.
+    let mut vertices = Vec::new();
+    for i in 0..corners.len() {
+        let a = corners[i];
+        let b = corners[(i + 1) % corners.len()];
+
+        for k in 0..num_subdivs {
+            let t = (k as f32) / (num_subdivs as f32);
+            vertices.push(a + (b - a) * t);
+        }
+    }
+
+    let mut indices: Vec<_> = (0..).map(|i| Point2::new(i, i + 1)).take(vertices.len() - 1).collect();
+    indices.push(Point2::new(vertices.len() - 1, 0));
+    let polyline = Polyline::new(vertices, Some(indices));
+
+    /*
+     * Create the deformable body with area constraints and a collider for its boundary.
+     */
+    let deformable = MassConstraintSystemDesc::from_polyline(&polyline)
+        .translation(Vector2::y() * 5.0)
+        .stiffness(Some(1.0e3))
+        .area_constraints_enabled(true)
+        .collider_enabled(true)
+        .build(&mut world);
+    let deformable_handle = deformable.handle();
+
+    /*
+     * Set up the testbed.
+     */
+    let mut testbed = Testbed::new(world);
+    testbed.set_body_color(deformable_handle, Point3::new(0.0, 0.0, 1.0));
+    testbed.look_at(Point2::new(0.0, -3.0), 60.0);
+    testbed.run();
+}
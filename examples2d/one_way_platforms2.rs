@@ -0,0 +1,82 @@
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+extern crate nphysics_testbed2d;
+
+use std::cell::Cell;
+use na::{Point2, Point3, Vector2};
+use ncollide2d::shape::{Cuboid, ShapeHandle};
+use nphysics2d::object::{ColliderDesc, RigidBodyDesc};
+use nphysics2d::world::World;
+use nphysics_testbed2d::Testbed;
+
+
+fn main() {
+    let mut testbed = Testbed::new_empty();
+
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector2::new(0.0, -9.81));
+
+    /*
+     * Ground
+     */
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector2::new(10.0, 1.0)));
+    ColliderDesc::new(ground_shape)
+        .translation(-Vector2::y())
+        .build(&mut world);
+
+    /*
+     * Platforms that can be crossed from below but not from above.
+     */
+    let platform_shape = ShapeHandle::new(Cuboid::new(Vector2::new(2.0, 0.1)));
+    let mut platform_desc = ColliderDesc::new(platform_shape)
+        .one_way(Some(Vector2::y_axis()));
+
+    for i in 0..3 {
+        platform_desc
+            .set_translation(Vector2::new(i as f32 * 1.5 - 1.5, (i + 1) as f32 * 2.0))
+            .build(&mut world);
+    }
+
+    /*
+     * A character that cannot rotate.
+     */
+    let character_shape = ShapeHandle::new(Cuboid::new(Vector2::new(0.3, 0.5)));
+    let collider_desc = ColliderDesc::new(character_shape)
+        .density(1.0);
+    let character = RigidBodyDesc::new()
+        .collider(&collider_desc)
+        .translation(Vector2::new(-2.0, 0.5))
+        .kinematic_rotation(true)
+        .build(&mut world)
+        .handle();
+
+    testbed.set_body_color(character, Point3::new(1.0, 0.0, 0.0));
+
+    /*
+     * Setup a callback making the character jump regularly through the platforms, and land on them.
+     */
+    let last_jump = Cell::new(0.0);
+    testbed.add_callback(move |world, _, time| {
+        let mut world = world.get_mut();
+        if let Some(character) = world.rigid_body_mut(character) {
+            let mut vel = *character.velocity();
+
+            if time - last_jump.get() > 1.5 && vel.linear.y.abs() < 0.1 {
+                vel.linear = Vector2::new(0.75, 8.0);
+                character.set_velocity(vel);
+                last_jump.set(time);
+            }
+        }
+    });
+
+    /*
+     * Set up the testbed.
+     */
+    testbed.set_world(world);
+    testbed.look_at(Point2::new(0.0, -3.0), 60.0);
+    testbed.run();
+}
@@ -0,0 +1,84 @@
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+extern crate nphysics_testbed2d;
+
+use na::{Point2, Vector2};
+use ncollide2d::shape::{Ball, Cuboid, ShapeHandle};
+use nphysics2d::joint::RevoluteConstraint;
+use nphysics2d::object::{ColliderDesc, RigidBodyDesc};
+use nphysics2d::world::World;
+use nphysics_testbed2d::Testbed;
+
+fn main() {
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector2::new(0.0, -9.81));
+
+    /*
+     * Ground
+     */
+    let ground_size = 50.0;
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector2::new(ground_size, 1.0)));
+
+    ColliderDesc::new(ground_shape)
+        .translation(-Vector2::y())
+        .build(&mut world);
+
+    /*
+     * The cart chassis.
+     */
+    let chassis_shape = ShapeHandle::new(Cuboid::new(Vector2::new(2.0, 0.25)));
+    let chassis_collider = ColliderDesc::new(chassis_shape).density(1.0);
+    let chassis = RigidBodyDesc::new()
+        .collider(&chassis_collider)
+        .translation(Vector2::new(-10.0, 1.3))
+        .build(&mut world)
+        .part_handle();
+
+    /*
+     * The wheels, attached to the chassis by revolute constraints. Only the rear wheel is powered: its motor
+     * spins it clockwise so the cart drives to the right.
+     */
+    let wheel_rad = 0.5;
+    let wheel_shape = ShapeHandle::new(Ball::new(wheel_rad));
+    let wheel_collider = ColliderDesc::new(wheel_shape).density(1.0);
+    let mut wheel_desc = RigidBodyDesc::new().collider(&wheel_collider);
+
+    for x in &[-1.5, 1.5] {
+        let wheel = wheel_desc
+            .set_translation(Vector2::new(-10.0 + x, wheel_rad))
+            .build(&mut world)
+            .part_handle();
+
+        let mut constraint = RevoluteConstraint::new(chassis, wheel, Point2::new(*x, -0.8), Point2::origin());
+
+        if *x < 0.0 {
+            constraint.enable_motor(-5.0, 20.0);
+        }
+
+        world.add_constraint(constraint);
+    }
+
+    /*
+     * A few boxes for the cart to push.
+     */
+    let box_shape = ShapeHandle::new(Cuboid::new(Vector2::repeat(0.3)));
+    let box_collider = ColliderDesc::new(box_shape).density(0.2);
+    let mut box_desc = RigidBodyDesc::new().collider(&box_collider);
+
+    for i in 0usize..5 {
+        box_desc
+            .set_translation(Vector2::new(i as f32 * 2.0, 0.3 + box_collider.get_margin()))
+            .build(&mut world);
+    }
+
+    /*
+     * Set up the testbed.
+     */
+    let mut testbed = Testbed::new(world);
+    testbed.look_at(Point2::new(0.0, 2.0), 30.0);
+    testbed.run();
+}
@@ -5,7 +5,9 @@ extern crate nphysics_testbed2d;
 
 use na::Vector2;
 use ncollide2d::shape::{Cuboid, ShapeHandle};
-use nphysics2d::object::{ColliderDesc, RigidBodyDesc};
+use nphysics2d::material::{BasicMaterial, MaterialHandle};
+use nphysics2d::object::ColliderDesc;
+use nphysics2d::utils;
 use nphysics2d::world::World;
 use nphysics_testbed2d::Testbed;
 
@@ -31,33 +33,8 @@ fn main() {
     /*
      * Create the boxes
      */
-    let num = 25;
-    let rad = 0.1;
-
-    let cuboid = ShapeHandle::new(Cuboid::new(Vector2::repeat(rad)));
-    let collider_desc = ColliderDesc::new(cuboid)
-        .density(1.0);
-
-    let mut rb_desc = RigidBodyDesc::new()
-        .collider(&collider_desc);
-
-    let shift = 2.0 * (rad + collider_desc.get_margin());
-    let centerx = shift * (num as f32) / 2.0;
-    let centery = rad + collider_desc.get_margin() * 2.0;
-
-    for i in 0usize..num {
-        for j in i..num {
-            let fj = j as f32;
-            let fi = i as f32;
-            let x = (fi * shift / 2.0) + (fj - fi) * shift - centerx;
-            let y = fi * shift + centery;
-
-            // Build the rigid body and its collider.
-            rb_desc
-                .set_translation(Vector2::new(x, y))
-                .build(&mut world);
-        }
-    }
+    let material = MaterialHandle::new(BasicMaterial::default());
+    utils::build_box_stack(&mut world, 25, 25, 0.1, material);
 
     /*
      * Run the simulation.
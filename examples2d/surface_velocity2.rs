@@ -0,0 +1,80 @@
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+extern crate nphysics_testbed2d;
+
+use na::{Point2, Vector2};
+use ncollide2d::shape::{Cuboid, ShapeHandle};
+use nphysics2d::object::{BodyStatus, ColliderDesc, RigidBodyDesc};
+use nphysics2d::world::World;
+use nphysics_testbed2d::Testbed;
+
+
+fn main() {
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector2::new(0.0, -9.81));
+
+    /*
+     * Belts
+     *
+     * Each belt is a static rigid body with a surface velocity, expressed in its local frame.
+     * The belts alternate directions so the boxes zigzag down.
+     */
+    let belt_shape = ShapeHandle::new(Cuboid::new(Vector2::new(5.0, 0.2)));
+    let belt_collider = ColliderDesc::new(belt_shape);
+    let mut belt_desc = RigidBodyDesc::new()
+        .collider(&belt_collider)
+        .status(BodyStatus::Static);
+
+    for i in 0..5 {
+        belt_desc
+            .set_translation(Vector2::new(-2.0, 5.0 - i as f32 * 8.0))
+            .set_rotation(0.1)
+            .set_surface_velocity(Vector2::x() * 2.0)
+            .build(&mut world);
+
+        belt_desc
+            .set_translation(Vector2::new(2.0, 1.0 - i as f32 * 8.0))
+            .set_rotation(-0.1)
+            .set_surface_velocity(-Vector2::x() * 2.0)
+            .build(&mut world);
+    }
+
+    /*
+     * Create the boxes
+     */
+    let num = 5;
+    let rad = 0.1;
+
+    let cuboid = ShapeHandle::new(Cuboid::new(Vector2::repeat(rad)));
+    let collider_desc = ColliderDesc::new(cuboid)
+        .density(1.0);
+
+    let mut rb_desc = RigidBodyDesc::new()
+        .collider(&collider_desc);
+
+    let shift = (rad + collider_desc.get_margin()) * 2.0;
+    let centerx = shift * (num as f32) / 2.0 + 5.0;
+    let centery = shift / 2.0 + 6.0;
+
+    for i in 0usize..num {
+        for j in 0..num {
+            let x = i as f32 * shift - centerx;
+            let y = j as f32 * shift + centery;
+
+            rb_desc
+                .set_translation(Vector2::new(x, y))
+                .build(&mut world);
+        }
+    }
+
+    /*
+     * Set up the testbed.
+     */
+    let mut testbed = Testbed::new(world);
+    testbed.look_at(Point2::new(0.0, -10.0), 30.0);
+    testbed.run();
+}
@@ -0,0 +1,92 @@
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+extern crate nphysics_testbed3d;
+
+use na::{Isometry3, Point3, UnitQuaternion, Vector3};
+use ncollide3d::shape::{Cuboid, ShapeHandle};
+use nphysics3d::object::{BodyStatus, ColliderDesc, FEMVolume, FEMVolumeDesc, RigidBodyDesc};
+use nphysics3d::math::Velocity;
+use nphysics3d::world::World;
+use nphysics_testbed3d::Testbed;
+
+fn main() {
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector3::new(0.0, -9.81, 0.0));
+
+    /*
+     * Ground.
+     */
+    let ground_size = 50.0;
+    let ground = ShapeHandle::new(Cuboid::new(Vector3::repeat(ground_size)));
+
+    ColliderDesc::new(ground)
+        .translation(Vector3::y() * -ground_size)
+        .build(&mut world);
+
+    /*
+     * Create a rotating kinematic pole.
+     */
+    let pole_height = 3.0;
+    let pole = ShapeHandle::new(Cuboid::new(Vector3::new(0.05, pole_height / 2.0, 0.05)));
+    let pole_collider = ColliderDesc::new(pole).density(1.0);
+
+    let pole_handle = RigidBodyDesc::new()
+        .collider(&pole_collider)
+        .translation(Vector3::y() * pole_height / 2.0)
+        .velocity(Velocity::angular(0.0, 0.5, 0.0))
+        .status(BodyStatus::Kinematic)
+        .build(&mut world)
+        .handle();
+
+    /*
+     * Create the flag and pin the nodes of its left side to the pole.
+     */
+    let flag = FEMVolumeDesc::cube(10, 6, 1)
+        .scale(Vector3::new(1.5, 0.9, 0.03))
+        .translation(Vector3::new(0.8, pole_height - 0.5, 0.0))
+        .young_modulus(1.0e3)
+        .poisson_ratio(0.2)
+        .mass_damping(0.2)
+        .sleep_threshold(None)
+        .collider_enabled(true)
+        .build(&mut world);
+    let flag_handle = flag.handle();
+
+    let positions = flag.positions().clone();
+    let min_x = positions.iter().step_by(3).cloned().fold(std::f32::MAX, f32::min);
+    let pinned: Vec<usize> = (0..positions.len() / 3)
+        .filter(|i| positions[i * 3] <= min_x + 1.0e-3)
+        .collect();
+    let pole_pos = *world.rigid_body(pole_handle).unwrap().position();
+    world
+        .body_mut(flag_handle)
+        .unwrap()
+        .downcast_mut::<FEMVolume<f32>>()
+        .unwrap()
+        .pin_nodes(&pinned, &pole_pos);
+
+    /*
+     * Set up the testbed.
+     */
+    let mut testbed = Testbed::new(world);
+    testbed.add_callback(move |world, _, _| {
+        let mut world = world.get_mut();
+        let dt = world.timestep();
+
+        // The position of the pole after the next timestep.
+        let pole = world.rigid_body(pole_handle).unwrap();
+        let pole_pos = pole.position();
+        let rot = UnitQuaternion::new(pole.velocity().angular * dt) * pole_pos.rotation;
+        let next_pole_pos = Isometry3::from_parts(pole_pos.translation, rot);
+
+        if let Some(flag) = world.body_mut(flag_handle).and_then(|b| b.downcast_mut::<FEMVolume<f32>>()) {
+            flag.update_pinned_anchor(&next_pole_pos, dt);
+        }
+    });
+    testbed.look_at(Point3::new(0.0, 3.0, 6.0), Point3::new(0.0, 2.0, 0.0));
+    testbed.run();
+}
@@ -0,0 +1,76 @@
+extern crate nalgebra as na;
+extern crate nphysics3d;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use na::Vector3;
+use nphysics3d::object::FEMVolumeDesc;
+use nphysics3d::world::World;
+
+/*
+ * An allocator counting the number of allocations and of allocated bytes.
+ */
+struct CountingAllocator;
+
+static NALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static NBYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = NALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        let _ = NBYTES.fetch_add(layout.size(), Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let mut world = World::new();
+    world.set_gravity(Vector3::new(0.0, -9.81, 0.0));
+
+    /*
+     * The deformable body of the `fem_volume3` example, with more elements.
+     */
+    let handle = FEMVolumeDesc::cube(5, 5, 5)
+        .young_modulus(1.0e3)
+        .poisson_ratio(0.2)
+        .mass_damping(0.2)
+        .build(&mut world)
+        .handle();
+
+    /*
+     * Measure the allocations performed by the update of the dynamics of the volume,
+     * i.e., by the assembly and factorization of its augmented mass matrix.
+     */
+    let dt = world.integration_parameters().dt;
+    let nupdates = 10;
+    let volume = world.body_mut(handle).unwrap();
+
+    // The first update may allocate the factorization buffers.
+    let _ = volume.deformed_positions_mut();
+    volume.update_kinematics();
+    volume.update_dynamics(dt);
+
+    let nallocations = NALLOCATIONS.load(Ordering::SeqCst);
+    let nbytes = NBYTES.load(Ordering::SeqCst);
+
+    for _ in 0..nupdates {
+        // Moving the nodes forces the augmented mass to be recomputed.
+        let _ = volume.deformed_positions_mut();
+        volume.update_kinematics();
+        volume.update_dynamics(dt);
+    }
+
+    println!(
+        "Allocations per update of the dynamics: {} ({} bytes)",
+        (NALLOCATIONS.load(Ordering::SeqCst) - nallocations) / nupdates,
+        (NBYTES.load(Ordering::SeqCst) - nbytes) / nupdates
+    );
+}
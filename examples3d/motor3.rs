@@ -0,0 +1,93 @@
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+extern crate nphysics_testbed3d;
+
+use na::{Point3, Vector3};
+use ncollide3d::shape::{Ball, Cuboid, ShapeHandle};
+use nphysics3d::joint::CylindricalConstraint;
+use nphysics3d::object::{BodyPartHandle, ColliderDesc, RigidBodyDesc};
+use nphysics3d::world::World;
+use nphysics_testbed3d::Testbed;
+use std::f32::consts::FRAC_PI_2;
+
+fn main() {
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector3::new(0.0, -9.81, 0.0));
+
+    /*
+     * Ground
+     */
+    let ground_size = 50.0;
+    let ground_shape = ShapeHandle::new(Cuboid::new(Vector3::repeat(ground_size)));
+
+    ColliderDesc::new(ground_shape)
+        .translation(Vector3::y() * -ground_size)
+        .build(&mut world);
+
+    /*
+     * A paddle wheel made of two crossed planks.
+     */
+    let plank = ShapeHandle::new(Cuboid::new(Vector3::new(2.0, 0.1, 0.5)));
+    let plank_desc1 = ColliderDesc::new(plank.clone()).density(1.0);
+    let plank_desc2 = ColliderDesc::new(plank)
+        .density(1.0)
+        .rotation(Vector3::z() * FRAC_PI_2);
+    let wheel_center = Vector3::y() * 3.0;
+
+    let wheel = RigidBodyDesc::new()
+        .collider(&plank_desc1)
+        .collider(&plank_desc2)
+        .translation(wheel_center)
+        .build(&mut world)
+        .part_handle();
+
+    /*
+     * The wheel can only rotate and slide along the `z` axis. Its angular motor makes it spin
+     * at a constant velocity, while its linear motor keeps it from sliding.
+     */
+    let mut constraint = CylindricalConstraint::new(
+        BodyPartHandle::ground(),
+        wheel,
+        Point3::from(wheel_center),
+        Vector3::z_axis(),
+        Point3::origin(),
+        Vector3::z_axis(),
+    );
+    constraint.enable_angular_motor(2.0, 200.0);
+    constraint.enable_linear_motor(0.0, 200.0);
+    world.add_constraint(constraint);
+
+    /*
+     * Balls falling on the wheel.
+     */
+    let num = 5;
+    let rad = 0.2;
+    let ball = ShapeHandle::new(Ball::new(rad));
+    let collider_desc = ColliderDesc::new(ball).density(1.0);
+    let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc);
+
+    let shift = (rad + collider_desc.get_margin()) * 2.0 + 0.1;
+    let centerx = shift * (num as f32) / 2.0;
+
+    for i in 0usize..num {
+        for j in 0usize..num {
+            let x = i as f32 * shift - centerx;
+            let y = j as f32 * shift + 7.0;
+
+            rb_desc
+                .set_translation(Vector3::new(x, y, 0.0))
+                .build(&mut world);
+        }
+    }
+
+    /*
+     * Set up the testbed.
+     */
+    let mut testbed = Testbed::new(world);
+    testbed.look_at(Point3::new(0.0, 5.0, 15.0), Point3::new(0.0, 3.0, 0.0));
+    testbed.run();
+}
@@ -5,8 +5,10 @@ extern crate nphysics_testbed3d;
 
 use na::{Point3, Vector3};
 use ncollide3d::shape::{Cuboid, ShapeHandle};
+use nphysics3d::material::{BasicMaterial, MaterialHandle};
 use nphysics3d::world::World;
-use nphysics3d::object::{ColliderDesc, RigidBodyDesc};
+use nphysics3d::object::ColliderDesc;
+use nphysics3d::utils;
 use nphysics_testbed3d::Testbed;
 
 
@@ -31,33 +33,8 @@ fn main() {
     /*
      * Create the boxes
      */
-    let num = 30;
-    let rad = 0.1;
-
-    let cuboid = ShapeHandle::new(Cuboid::new(Vector3::repeat(rad)));
-    let collider_desc = ColliderDesc::new(cuboid)
-        .density(1.0);
-
-    let mut rb_desc = RigidBodyDesc::new()
-        .collider(&collider_desc);
-
-    let shift = (rad + collider_desc.get_margin()) * 2.0;
-    let centerx = shift * (num as f32) / 2.0;
-    let centery = shift / 2.0;
-
-    for i in 0usize..num {
-        for j in i..num {
-            let fi = i as f32;
-            let fj = (j - i) as f32;
-            let x = (fi * shift / 2.0) + fj * shift - centerx;
-            let y = fi * shift + centery;
-
-            // Build the rigid body and its collider.
-            rb_desc
-                .set_translation(Vector3::new(x, y, 0.0))
-                .build(&mut world);
-        }
-    }
+    let material = MaterialHandle::new(BasicMaterial::default());
+    utils::build_box_stack(&mut world, 30, 30, 0.1, material);
 
     /*
      * Set up the testbed.
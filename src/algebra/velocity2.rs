@@ -109,7 +109,7 @@ impl<N: RealField> Velocity2<N> {
     /// Compute the velocity of a point that is located at the coordinates `shift` relative to the point having `self` as velocity.
     #[inline]
     pub fn shift(&self, shift: &Vector2<N>) -> Self {
-        Self::new(self.linear + shift * self.angular, self.angular)
+        Self::new(self.linear + Vector2::new(-shift.y, shift.x) * self.angular, self.angular)
     }
 
     /// Rotate each component of `self` by `rot`.
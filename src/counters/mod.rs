@@ -6,6 +6,7 @@ pub use self::collision_detection_counters::CollisionDetectionCounters;
 pub use self::solver_counters::SolverCounters;
 pub use self::stages_counters::StagesCounters;
 pub use self::timer::Timer;
+pub(crate) use self::timer::now;
 
 mod collision_detection_counters;
 mod solver_counters;
@@ -88,11 +89,84 @@ impl Counters {
         self.custom.time()
     }
 
+    /// Reset the timers of the constraints resolution.
+    ///
+    /// Those timers are then resumed and paused for each island, so they measure the cumulated time
+    /// spent on all the islands.
+    pub fn reset_solver_timers(&mut self) {
+        if self.enabled {
+            self.solver.reset_timers()
+        }
+    }
+
     /// Set the number of constraints generated.
     pub fn set_nconstraints(&mut self, n: usize) {
         self.solver.nconstraints = n;
     }
 
+    /// The number of constraints generated during the last timestep.
+    pub fn nconstraints(&self) -> usize {
+        self.solver.nconstraints
+    }
+
+    /// Set the number of iterations performed by the velocity constraints solver.
+    pub fn set_nvelocity_iterations(&mut self, n: usize) {
+        self.solver.nvelocity_iterations = n;
+    }
+
+    /// The number of iterations performed by the velocity constraints solver during the last timestep.
+    ///
+    /// If there are several islands, this is the largest number of iterations performed on one of them.
+    pub fn nvelocity_iterations(&self) -> usize {
+        self.solver.nvelocity_iterations
+    }
+
+    /// Set the largest change of constraint impulse during the last iteration of the velocity constraints solver.
+    pub fn set_max_residual_impulse(&mut self, residual: f64) {
+        self.solver.max_residual_impulse = residual;
+    }
+
+    /// The largest change of constraint impulse during the last iteration of the velocity constraints solver, during
+    /// the last timestep.
+    ///
+    /// A value that remains large compared to the impulses applied to the bodies indicates that the solver did not
+    /// converge, in which case `IntegrationParameters::max_velocity_iterations` may need to be increased.
+    pub fn max_residual_impulse(&self) -> f64 {
+        self.solver.max_residual_impulse
+    }
+
+    /// Set the number of contacts ignored because the maximum number of constraints was exceeded.
+    pub fn set_nskipped_contacts(&mut self, n: usize) {
+        self.solver.nskipped_contacts = n;
+    }
+
+    /// The number of contacts ignored during the last timestep because the maximum number of constraints was exceeded.
+    pub fn nskipped_contacts(&self) -> usize {
+        self.solver.nskipped_contacts
+    }
+
+    /// Set the number of bodies slowed down because they exceeded the maximum velocities.
+    pub fn set_nclamped_velocities(&mut self, n: usize) {
+        self.solver.nclamped_velocities = n;
+    }
+
+    /// The number of bodies slowed down during the last timestep because they exceeded the maximum velocities.
+    pub fn nclamped_velocities(&self) -> usize {
+        self.solver.nclamped_velocities
+    }
+
+    /// Set whether the last timestep was interrupted because it exceeded its time budget.
+    pub fn set_budget_exceeded(&mut self, exceeded: bool) {
+        self.solver.budget_exceeded = exceeded;
+    }
+
+    /// Returns `true` if the last timestep was interrupted because it exceeded its time budget.
+    ///
+    /// This is always `false` for timesteps performed without a time budget.
+    pub fn budget_exceeded(&self) -> bool {
+        self.solver.budget_exceeded
+    }
+
     /// Set the number of contacts generated.
     pub fn set_ncontacts(&mut self, n: usize) {
         self.solver.ncontacts = n;
@@ -105,6 +179,18 @@ impl Counters {
 }
 
 macro_rules! measure_method {
+    ($started:ident, $resumed:ident, $stopped:ident, $time:ident, $info:ident. $timer:ident) => {
+        measure_method!($started, $stopped, $time, $info.$timer);
+
+        impl Counters {
+            /// Resume this timer without resetting its elapsed time.
+            pub fn $resumed(&mut self) {
+                if self.enabled {
+                    self.$info.$timer.resume()
+                }
+            }
+        }
+    };
     ($started:ident, $stopped:ident, $time:ident, $info:ident. $timer:ident) => {
         impl Counters {
             /// Start this timer.
@@ -160,24 +246,28 @@ measure_method!(
 
 measure_method!(
     assembly_started,
+    assembly_resumed,
     assembly_completed,
     assembly_time,
     solver.assembly_time
 );
 measure_method!(
     velocity_resolution_started,
+    velocity_resolution_resumed,
     velocity_resolution_completed,
     velocity_resolution_time,
     solver.velocity_resolution_time
 );
 measure_method!(
     velocity_update_started,
+    velocity_update_resumed,
     velocity_update_completed,
     velocity_update_time,
     solver.velocity_update_time
 );
 measure_method!(
     position_resolution_started,
+    position_resolution_resumed,
     position_resolution_completed,
     position_resolution_time,
     solver.position_resolution_time
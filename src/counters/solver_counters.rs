@@ -6,8 +6,20 @@ use std::fmt::{Display, Formatter, Result};
 pub struct SolverCounters {
     /// Number of constraints generated.
     pub nconstraints: usize,
+    /// Number of iterations performed by the velocity constraints solver.
+    ///
+    /// If there are several islands, this is the largest number of iterations performed on one of them.
+    pub nvelocity_iterations: usize,
+    /// The largest change of constraint impulse during the last iteration of the velocity constraints solver.
+    pub max_residual_impulse: f64,
     /// Number of contacts found.
     pub ncontacts: usize,
+    /// Number of contacts ignored by the solver because the maximum number of constraints was exceeded.
+    pub nskipped_contacts: usize,
+    /// Number of bodies slowed down because they exceeded the maximum velocities of the integration parameters.
+    pub nclamped_velocities: usize,
+    /// Whether the constraints resolution was interrupted because the timestep exceeded its time budget.
+    pub budget_exceeded: bool,
     /// Time spent for the resolution of the constraints (force computation).
     pub velocity_resolution_time: Timer,
     /// Time spent for the assembly of all the constraints into a linear complentarity problem.
@@ -23,19 +35,37 @@ impl SolverCounters {
     pub fn new() -> Self {
         SolverCounters {
             nconstraints: 0,
+            nvelocity_iterations: 0,
+            max_residual_impulse: 0.0,
             ncontacts: 0,
+            nskipped_contacts: 0,
+            nclamped_velocities: 0,
+            budget_exceeded: false,
             assembly_time: Timer::new(),
             velocity_resolution_time: Timer::new(),
             velocity_update_time: Timer::new(),
             position_resolution_time: Timer::new(),
         }
     }
+
+    /// Resets all the timers to zero.
+    pub fn reset_timers(&mut self) {
+        self.assembly_time = Timer::new();
+        self.velocity_resolution_time = Timer::new();
+        self.velocity_update_time = Timer::new();
+        self.position_resolution_time = Timer::new();
+    }
 }
 
 impl Display for SolverCounters {
     fn fmt(&self, f: &mut Formatter) -> Result {
         writeln!(f, "Number of contacts: {}", self.ncontacts)?;
         writeln!(f, "Number of constraints: {}", self.nconstraints)?;
+        writeln!(f, "Number of velocity iterations: {}", self.nvelocity_iterations)?;
+        writeln!(f, "Max residual impulse: {}", self.max_residual_impulse)?;
+        writeln!(f, "Number of skipped contacts: {}", self.nskipped_contacts)?;
+        writeln!(f, "Number of clamped velocities: {}", self.nclamped_velocities)?;
+        writeln!(f, "Budget exceeded: {}", self.budget_exceeded)?;
         writeln!(f, "Assembly time: {}", self.assembly_time)?;
         writeln!(
             f,
@@ -42,7 +42,7 @@ impl Timer {
 }
 
 #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
-fn now() -> f64 {
+pub(crate) fn now() -> f64 {
     use time;
     time::precise_time_s()
 }
@@ -52,7 +52,7 @@ fn now() -> f64 {
     feature = "stdweb",
 ))]
 #[allow(unused_results)] // Needed because the js macro triggers it.
-fn now() -> f64 {
+pub(crate) fn now() -> f64 {
     use stdweb::unstable::TryInto;
 
     // https://developer.mozilla.org/en-US/docs/Web/API/Performance/now
@@ -76,7 +76,7 @@ mod performance {
     any(target_arch = "wasm32", target_arch = "asmjs"),
     feature = "use-wasm-bindgen",
 ))]
-fn now() -> f64 {
+pub(crate) fn now() -> f64 {
     performance::now() / 1000.0
 }
 
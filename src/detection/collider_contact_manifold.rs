@@ -1,7 +1,11 @@
+use std::cmp::Ordering;
+
+use either::Either;
 use na::RealField;
 use ncollide::query::{ContactManifold, TrackedContact};
 use ncollide::shape::FeatureId;
 
+use crate::material::Material;
 use crate::object::{BodyHandle, BodyPartHandle, Collider, ColliderAnchor};
 
 /// A contact manifold between two bodies.
@@ -13,6 +17,12 @@ pub struct ColliderContactManifold<'a, N: RealField> {
     pub collider2: &'a Collider<N>,
     /// The contact manifold.
     pub manifold: &'a ContactManifold<N>,
+    /// The maximum distance between the colliders, margins included, of the contacts yielded by `self.contacts()`.
+    ///
+    /// If `None`, all the contacts of the manifold are yielded.
+    pub max_separation: Option<N>,
+    // The contacts of the manifold in the order set by `self.sort_contacts()`, if it was called.
+    sorted_contacts: Option<Vec<&'a TrackedContact<N>>>,
 }
 
 impl<'a, N: RealField> ColliderContactManifold<'a, N> {
@@ -26,22 +36,71 @@ impl<'a, N: RealField> ColliderContactManifold<'a, N> {
             collider1,
             collider2,
             manifold,
+            max_separation: None,
+            sorted_contacts: None,
         }
     }
 
-    /// The number of contacts on the manifold.
+    /// The number of contacts on the manifold, except those separated by more than `self.max_separation`.
     pub fn len(&self) -> usize {
-        self.manifold.len()
+        self.contacts().count()
     }
 
-    /// Get all the contacts from the manifold.
+    /// Get all the contacts from the manifold, except those separated by more than `self.max_separation`.
     pub fn contacts(&self) -> impl Iterator<Item = &TrackedContact<N>> {
-        self.manifold.contacts()
+        let max_separation = self.max_separation;
+        let margins = self.collider1.margin() + self.collider2.margin();
+
+        let contacts = match &self.sorted_contacts {
+            Some(sorted) => Either::Left(sorted.iter().map(|c| &**c)),
+            None => Either::Right(self.manifold.contacts()),
+        };
+
+        contacts.filter(move |c| {
+            max_separation.map(|max| -(c.contact.depth + margins) <= max).unwrap_or(true)
+        })
+    }
+
+    /// Sorts the contacts yielded by `self.contacts()` in an order that does not depend on their order in
+    /// the manifold, nor on the order of the two colliders.
+    ///
+    /// The contacts are sorted by their features, and then by their location, on the collider with the
+    /// smallest handle first.
+    pub fn sort_contacts(&mut self) {
+        let mut sorted: Vec<_> = self.manifold.contacts().collect();
+        sorted.sort_by(|a, b| self.compare_contacts(a, b));
+        self.sorted_contacts = Some(sorted);
     }
 
-    /// Get the deepest contact, if any, from the manifold.
+    // The order of the contacts used by `self.sort_contacts()`.
+    pub(crate) fn compare_contacts(&self, a: &TrackedContact<N>, b: &TrackedContact<N>) -> Ordering {
+        let flipped = self.collider2.handle() < self.collider1.handle();
+        let canonical = |c: &TrackedContact<N>| {
+            if flipped {
+                (feature_key(c.kinematic.feature2()), feature_key(c.kinematic.feature1()), c.contact.world2)
+            } else {
+                (feature_key(c.kinematic.feature1()), feature_key(c.kinematic.feature2()), c.contact.world1)
+            }
+        };
+        let (a1, a2, pa) = canonical(a);
+        let (b1, b2, pb) = canonical(b);
+
+        (a1, a2).cmp(&(b1, b2)).then_with(|| {
+            pa.coords
+                .iter()
+                .zip(pb.coords.iter())
+                .map(|(xa, xb)| xa.partial_cmp(xb).unwrap_or(Ordering::Equal))
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// Get the deepest contact, if any, from the manifold, except those separated by more than `self.max_separation`.
     pub fn deepest_contact(&self) -> Option<&TrackedContact<N>> {
-        self.manifold.deepest_contact()
+        self.contacts().fold(None, |deepest, c| match deepest {
+            Some(deepest) if deepest.contact.depth >= c.contact.depth => Some(deepest),
+            _ => Some(c),
+        })
     }
 
     /// The handle of the first body involved in the contact.
@@ -88,6 +147,28 @@ impl<'a, N: RealField> ColliderContactManifold<'a, N> {
         }
     }
 
+    /// The material of the first collider at the given feature.
+    ///
+    /// The feature is assumed to belong to the first collider involved in this contact.
+    pub fn material1(&self, feature1: FeatureId) -> &'a Material<N> {
+        Self::material_at_feature(self.collider1, feature1)
+    }
+
+    /// The material of the second collider at the given feature.
+    ///
+    /// The feature is assumed to belong to the second collider involved in this contact.
+    pub fn material2(&self, feature2: FeatureId) -> &'a Material<N> {
+        Self::material_at_feature(self.collider2, feature2)
+    }
+
+    fn material_at_feature(collider: &'a Collider<N>, feature: FeatureId) -> &'a Material<N> {
+        if collider.has_subshape_materials() {
+            collider.subshape_material(collider.shape().subshape_containing_feature(feature))
+        } else {
+            collider.material()
+        }
+    }
+
     /// The anchor between the fist collider and the body it is attached to.
     pub fn anchor1(&self) -> &ColliderAnchor<N> {
         self.collider1.anchor()
@@ -98,3 +179,14 @@ impl<'a, N: RealField> ColliderContactManifold<'a, N> {
         self.collider2.anchor()
     }
 }
+
+// A key ordering the features of a shape.
+fn feature_key(feature: FeatureId) -> (u8, usize) {
+    match feature {
+        FeatureId::Vertex(i) => (0, i),
+        #[cfg(feature = "dim3")]
+        FeatureId::Edge(i) => (1, i),
+        FeatureId::Face(i) => (2, i),
+        FeatureId::Unknown => (3, 0),
+    }
+}
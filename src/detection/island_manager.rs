@@ -0,0 +1,244 @@
+use std::ops::Range;
+
+use slab::Slab;
+
+use na::RealField;
+use crate::detection::ColliderContactManifold;
+use crate::joint::{ConstraintHandle, JointConstraint};
+use crate::object::{Body, BodyHandle, BodySet};
+use crate::utils::union_find::UnionFindSet;
+use crate::utils::union_find;
+
+/// Structure that partitions the active bodies into independent islands.
+///
+/// Two active bodies belong to the same island if they are connected, directly or not, by contacts
+/// or joints. The bodies that do not take part to the constraints resolution, e.g., static, kinematic,
+/// or disabled bodies, never merge the islands of the bodies they are connected to. Each island can
+/// then be solved independently from the others.
+pub struct IslandManager {
+    ufind: Vec<UnionFindSet>,
+    cursors: Vec<usize>,
+    island_ids: Vec<usize>,
+    bodies: Vec<BodyHandle>,
+    sorted_bodies: Vec<BodyHandle>,
+    body_offsets: Vec<usize>,
+    joints: Vec<ConstraintHandle>,
+    joint_offsets: Vec<usize>,
+    manifold_offsets: Vec<usize>,
+}
+
+impl IslandManager {
+    /// Creates a new island manager without any island.
+    pub fn new() -> Self {
+        IslandManager {
+            ufind: Vec::new(),
+            cursors: Vec::new(),
+            island_ids: Vec::new(),
+            bodies: Vec::new(),
+            sorted_bodies: Vec::new(),
+            body_offsets: vec![0],
+            joints: Vec::new(),
+            joint_offsets: vec![0],
+            manifold_offsets: vec![0],
+        }
+    }
+
+    /// The number of islands computed during the last update.
+    pub fn num_islands(&self) -> usize {
+        self.body_offsets.len() - 1
+    }
+
+    /// The bodies of the `i`-th island.
+    pub fn island(&self, i: usize) -> &[BodyHandle] {
+        &self.bodies[self.body_offsets[i]..self.body_offsets[i + 1]]
+    }
+
+    /// The joint constraints of the `i`-th island.
+    pub fn island_joints(&self, i: usize) -> &[ConstraintHandle] {
+        &self.joints[self.joint_offsets[i]..self.joint_offsets[i + 1]]
+    }
+
+    /// The range of the contact manifolds of the `i`-th island, within the manifolds given to the last update.
+    pub fn island_manifolds(&self, i: usize) -> Range<usize> {
+        self.manifold_offsets[i]..self.manifold_offsets[i + 1]
+    }
+
+    // The index of the given body on `self.bodies`, if it is one of the bodies partitioned by the last update.
+    fn index_of<N: RealField>(&self, body: &Body<N>) -> Option<usize> {
+        let id = body.companion_id();
+
+        if id < self.bodies.len() && self.bodies[id] == body.handle() {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
+    // The island of the first of the two given bodies that belongs to an island.
+    fn island_of_pair<N: RealField>(&self, bodies: &BodySet<N>, b1: BodyHandle, b2: BodyHandle) -> Option<usize> {
+        bodies.body(b1).and_then(|b| self.index_of(b))
+            .or_else(|| bodies.body(b2).and_then(|b| self.index_of(b)))
+            .map(|id| self.island_ids[id])
+    }
+
+    /// Partitions the `active_bodies` into islands.
+    ///
+    /// The `manifolds` are sorted by island, preserving their relative order within each island. The
+    /// manifolds that do not involve any active body are moved at the end and do not belong to any island.
+    /// After this, the companion ID of each active body is its index on the internal buffers of this
+    /// island manager.
+    pub fn update<N: RealField>(
+        &mut self,
+        bodies: &mut BodySet<N>,
+        active_bodies: &[BodyHandle],
+        joints: &Slab<Box<JointConstraint<N>>>,
+        manifolds: &mut [ColliderContactManifold<N>],
+    ) {
+        /*
+         *
+         * Run the union-find on the active bodies.
+         *
+         */
+        self.bodies.clear();
+
+        for handle in active_bodies {
+            let body = try_continue!(bodies.body_mut(*handle));
+            body.set_companion_id(self.bodies.len());
+            self.bodies.push(*handle);
+        }
+
+        let nbodies = self.bodies.len();
+        self.ufind.clear();
+        self.ufind.extend((0..nbodies).map(UnionFindSet::new));
+
+        for m in manifolds.iter() {
+            self.make_union(bodies, m.body1(), m.body2());
+        }
+
+        for (_, joint) in joints.iter() {
+            let (b1, b2) = joint.anchors();
+            self.make_union(bodies, b1.0, b2.0);
+        }
+
+        /*
+         *
+         * Number the islands in the order of their first body.
+         *
+         */
+        self.cursors.clear();
+        self.cursors.resize(nbodies, usize::max_value());
+        self.island_ids.clear();
+        self.body_offsets.clear();
+        self.body_offsets.push(0);
+
+        for i in 0..nbodies {
+            let root = union_find::find(i, &mut self.ufind[..]);
+
+            if self.cursors[root] == usize::max_value() {
+                self.cursors[root] = self.body_offsets.len() - 1;
+                self.body_offsets.push(0);
+            }
+
+            let island = self.cursors[root];
+            self.island_ids.push(island);
+            self.body_offsets[island + 1] += 1;
+        }
+
+        let nislands = self.num_islands();
+
+        for i in 0..nislands {
+            self.body_offsets[i + 1] += self.body_offsets[i];
+        }
+
+        /*
+         *
+         * Sort the bodies by island.
+         *
+         */
+        self.cursors.clear();
+        self.cursors.extend_from_slice(&self.body_offsets[..nislands]);
+        self.sorted_bodies.clear();
+        self.sorted_bodies.resize(nbodies, BodyHandle::ground());
+
+        for (handle, island) in self.bodies.iter().zip(self.island_ids.iter()) {
+            let id = self.cursors[*island];
+            self.cursors[*island] += 1;
+            self.sorted_bodies[id] = *handle;
+
+            if let Some(body) = bodies.body_mut(*handle) {
+                body.set_companion_id(id);
+            }
+        }
+
+        std::mem::swap(&mut self.bodies, &mut self.sorted_bodies);
+
+        for i in 0..nislands {
+            for id in self.body_offsets[i]..self.body_offsets[i + 1] {
+                self.island_ids[id] = i;
+            }
+        }
+
+        /*
+         *
+         * Partition the joints and the contact manifolds.
+         *
+         */
+        Self::reset_offsets(&mut self.joint_offsets, nislands);
+
+        for (_, joint) in joints.iter() {
+            let (b1, b2) = joint.anchors();
+
+            if let Some(island) = self.island_of_pair(bodies, b1.0, b2.0) {
+                self.joint_offsets[island + 1] += 1;
+            }
+        }
+
+        for i in 0..nislands {
+            self.joint_offsets[i + 1] += self.joint_offsets[i];
+        }
+
+        self.cursors.clear();
+        self.cursors.extend_from_slice(&self.joint_offsets[..nislands]);
+        self.joints.clear();
+        self.joints.resize(self.joint_offsets[nislands], 0);
+
+        for (handle, joint) in joints.iter() {
+            let (b1, b2) = joint.anchors();
+
+            if let Some(island) = self.island_of_pair(bodies, b1.0, b2.0) {
+                self.joints[self.cursors[island]] = handle;
+                self.cursors[island] += 1;
+            }
+        }
+
+        manifolds.sort_by_key(|m| self.island_of_pair(bodies, m.body1(), m.body2()).unwrap_or(nislands));
+        Self::reset_offsets(&mut self.manifold_offsets, nislands);
+
+        for m in manifolds.iter() {
+            if let Some(island) = self.island_of_pair(bodies, m.body1(), m.body2()) {
+                self.manifold_offsets[island + 1] += 1;
+            }
+        }
+
+        for i in 0..nislands {
+            self.manifold_offsets[i + 1] += self.manifold_offsets[i];
+        }
+    }
+
+    fn make_union<N: RealField>(&mut self, bodies: &BodySet<N>, b1: BodyHandle, b2: BodyHandle) {
+        let id1 = try_ret!(bodies.body(b1).and_then(|b| self.index_of(b)));
+        let id2 = try_ret!(bodies.body(b2).and_then(|b| self.index_of(b)));
+        union_find::union(id1, id2, &mut self.ufind)
+    }
+
+    fn reset_offsets(offsets: &mut Vec<usize>, nislands: usize) {
+        offsets.clear();
+        offsets.resize(nislands + 1, 0);
+    }
+}
+
+impl Default for IslandManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
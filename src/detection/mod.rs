@@ -1,7 +1,9 @@
 //! Collision detection information.
 
 pub use self::activation_manager::ActivationManager;
+pub use self::island_manager::IslandManager;
 pub use self::collider_contact_manifold::ColliderContactManifold;
 
 mod collider_contact_manifold;
 mod activation_manager;
+mod island_manager;
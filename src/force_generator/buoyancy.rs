@@ -0,0 +1,191 @@
+use na::RealField;
+
+use crate::force_generator::ForceGenerator;
+use crate::math::{ForceType, Point, Vector, DIM};
+use crate::object::{BodyPartHandle, BodySet};
+use crate::solver::IntegrationParameters;
+
+/// Generator of the buoyancy force applied by a fluid to floating body parts.
+///
+/// The fluid fills the half-space below its surface, wrt. the direction of the gravity. The volume of each body part
+/// is approximated by a set of sample points, each one representing a small cell of the volume. The buoyancy force is
+/// proportional to the volume of the submerged cells, and is applied at their centroid, i.e., the center of buoyancy.
+/// This results in a restoring torque that makes floating bodies self-right.
+pub struct Buoyancy<N: RealField> {
+    parts: Vec<BuoyantPart<N>>,
+    surface: Point<N>,
+    gravity: Vector<N>,
+    fluid_density: N,
+    drag: N,
+}
+
+struct BuoyantPart<N: RealField> {
+    part: BodyPartHandle,
+    // The sample points, in the local coordinates of the body part.
+    samples: Vec<Point<N>>,
+    sample_volume: N,
+}
+
+impl<N: RealField> Buoyancy<N> {
+    /// Initialize a buoyancy force generator for a fluid of density `fluid_density`.
+    ///
+    /// The surface of the fluid passes through the world-space point `surface` and is orthogonal to `gravity`, which
+    /// should be the same as the gravity of the world.
+    pub fn new(surface: Point<N>, gravity: Vector<N>, fluid_density: N) -> Self {
+        Buoyancy {
+            parts: Vec::new(),
+            surface,
+            gravity,
+            fluid_density,
+            drag: N::zero(),
+        }
+    }
+
+    /// Sets the coefficient of the drag force applied by the fluid to the submerged parts of the bodies.
+    ///
+    /// The drag applied to each submerged cell is opposite to its velocity, and proportional to its volume.
+    /// Defaults to zero.
+    pub fn set_drag(&mut self, drag: N) {
+        self.drag = drag;
+    }
+
+    /// The coefficient of the drag force applied by the fluid to the submerged parts of the bodies.
+    pub fn drag(&self) -> N {
+        self.drag
+    }
+
+    /// Add a body part to be affected by this force generator.
+    ///
+    /// The volume of the body part is approximated by a box of half-extents `half_extents` centered at the origin of
+    /// the body part, subdivided into `subdivisions` cells along each axis.
+    pub fn add_body_part(&mut self, part: BodyPartHandle, half_extents: Vector<N>, subdivisions: usize) {
+        assert!(subdivisions > 0, "Buoyancy: the number of subdivisions must be positive.");
+        let _2: N = na::convert(2.0);
+        let n: N = na::convert(subdivisions as f64);
+        let cell = half_extents * _2 / n;
+        let sample_volume = cell.iter().fold(N::one(), |volume, extent| volume * *extent);
+        let mut samples = Vec::with_capacity(subdivisions.pow(DIM as u32));
+
+        for id in 0..subdivisions.pow(DIM as u32) {
+            let sample = Vector::from_fn(|i, _| {
+                let k: N = na::convert(((id / subdivisions.pow(i as u32)) % subdivisions) as f64);
+                -half_extents[i] + cell[i] * (k + na::convert(0.5))
+            });
+            samples.push(Point::from(sample));
+        }
+
+        self.add_body_part_with_samples(part, samples, sample_volume);
+    }
+
+    /// Add a body part to be affected by this force generator.
+    ///
+    /// The volume of the body part is approximated by the given sample points, expressed in the local coordinates of
+    /// the body part, each one representing a cell of volume `sample_volume`.
+    pub fn add_body_part_with_samples(&mut self, part: BodyPartHandle, samples: Vec<Point<N>>, sample_volume: N) {
+        self.parts.push(BuoyantPart {
+            part,
+            samples,
+            sample_volume,
+        })
+    }
+}
+
+impl<N: RealField> ForceGenerator<N> for Buoyancy<N> {
+    fn apply(&mut self, _: &IntegrationParameters<N>, bodies: &mut BodySet<N>) -> bool {
+        let (surface, gravity, fluid_density, drag) = (self.surface, self.gravity, self.fluid_density, self.drag);
+        let mut submerged = Vec::new();
+
+        self.parts.retain(|p| {
+            let body = try_ret!(bodies.body(p.part.0), false);
+            let part = try_ret!(body.part(p.part.1), true);
+            let com = part.center_of_mass();
+            let velocity = part.velocity();
+
+            submerged.clear();
+            for sample in &p.samples {
+                let pt = body.world_point_at_material_point(part, sample);
+
+                // The sample is submerged if it is below the surface wrt. the gravity.
+                if (pt - surface).dot(&gravity) > N::zero() {
+                    submerged.push(pt);
+                }
+            }
+
+            if submerged.is_empty() {
+                return true;
+            }
+
+            let nsubmerged: N = na::convert(submerged.len() as f64);
+            let center = submerged.iter().fold(Point::origin(), |center, pt| center + pt.coords) / nsubmerged;
+            let buoyancy = -gravity * (fluid_density * p.sample_volume * nsubmerged);
+
+            let drag_forces: Vec<_> = if drag.is_zero() {
+                Vec::new()
+            } else {
+                submerged
+                    .iter()
+                    .map(|pt| (*pt, -velocity.shift(&(pt - com)).linear * (drag * p.sample_volume)))
+                    .collect()
+            };
+
+            let body = bodies.body_mut(p.part.0).unwrap();
+            body.apply_force_at_point(p.part.1, &buoyancy, &center, ForceType::Force, false);
+
+            for (pt, force) in &drag_forces {
+                body.apply_force_at_point(p.part.1, force, pt, ForceType::Force, false);
+            }
+
+            true
+        });
+
+        !self.parts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::force_generator::Buoyancy;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+    use crate::math::{Point, Vector, ANGULAR_DIM};
+    use crate::object::{ColliderDesc, RigidBodyDesc};
+    use crate::world::World;
+
+    #[test]
+    fn tilted_plank_rights_itself() {
+        let mut world = World::<f64>::new();
+        let gravity = Vector::y() * -9.81;
+        world.set_gravity(gravity);
+
+        // A plank half as dense as the fluid, initially tilted.
+        let mut half_extents = Vector::repeat(1.0);
+        half_extents.y = 0.25;
+        let collider = ColliderDesc::new(ShapeHandle::new(Cuboid::new(half_extents))).density(0.5);
+        #[cfg(feature = "dim2")]
+        let tilt = 0.4;
+        #[cfg(feature = "dim3")]
+        let tilt = Vector::z() * 0.4;
+        let body = RigidBodyDesc::new().collider(&collider).rotation(tilt).build(&mut world).part_handle();
+
+        let mut buoyancy = Buoyancy::new(Point::origin(), gravity, 1.0);
+        buoyancy.set_drag(2.0);
+        buoyancy.add_body_part(body, half_extents, 8);
+        let _ = world.add_force_generator(buoyancy);
+
+        // The center of buoyancy is offset to the lower side of the plank, so it starts rotating back.
+        for _ in 0..5 {
+            world.step();
+        }
+
+        let velocity = *world.rigid_body(body.0).unwrap().velocity();
+        assert!(velocity.angular_vector()[ANGULAR_DIM - 1] < 0.0);
+
+        for _ in 0..600 {
+            world.step();
+        }
+
+        // The plank floats flat, half submerged, up to the resolution of the sampling.
+        let position = world.rigid_body(body.0).unwrap().position();
+        assert!(position.rotation.angle().abs() < 5.0e-2, "angle: {}", position.rotation.angle());
+        assert_relative_eq!(position.translation.vector.y, 0.0, epsilon = 5.0e-2);
+    }
+}
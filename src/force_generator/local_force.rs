@@ -0,0 +1,129 @@
+use na::RealField;
+
+use crate::force_generator::ForceGenerator;
+use crate::math::{ForceType, Point, Vector};
+use crate::object::{BodyPartHandle, BodySet};
+use crate::solver::IntegrationParameters;
+
+/// Generator of a force applied at a point attached to a body part, e.g., a thruster.
+pub struct LocalForce<N: RealField> {
+    part: BodyPartHandle,
+    local_point: Point<N>,
+    force: Vector<N>,
+    force_in_local_frame: bool,
+    magnitude: Option<Box<FnMut(N) -> N + Send + Sync>>,
+}
+
+impl<N: RealField> LocalForce<N> {
+    /// Initialize a force applied to `part` at the point `local_point`.
+    ///
+    /// The application point is expressed in the local coordinates of the body part. By default,
+    /// `force` is expressed in the local coordinates of the body part too, so it rotates with it.
+    pub fn new(part: BodyPartHandle, local_point: Point<N>, force: Vector<N>) -> Self {
+        LocalForce {
+            part,
+            local_point,
+            force,
+            force_in_local_frame: true,
+            magnitude: None,
+        }
+    }
+
+    /// The body part this force is applied to.
+    pub fn body_part(&self) -> BodyPartHandle {
+        self.part
+    }
+
+    /// The application point of this force, in the local coordinates of the body part.
+    pub fn local_point(&self) -> &Point<N> {
+        &self.local_point
+    }
+
+    /// Sets the application point of this force, in the local coordinates of the body part.
+    pub fn set_local_point(&mut self, local_point: Point<N>) {
+        self.local_point = local_point
+    }
+
+    /// The force applied by this generator, before being scaled by the magnitude callback if any.
+    pub fn force(&self) -> &Vector<N> {
+        &self.force
+    }
+
+    /// Sets the force applied by this generator.
+    pub fn set_force(&mut self, force: Vector<N>) {
+        self.force = force
+    }
+
+    /// Whether the force is expressed in the local coordinates of the body part instead of world-space.
+    pub fn is_force_in_local_frame(&self) -> bool {
+        self.force_in_local_frame
+    }
+
+    /// Sets whether the force is expressed in the local coordinates of the body part instead of world-space.
+    pub fn set_force_in_local_frame(&mut self, local: bool) {
+        self.force_in_local_frame = local
+    }
+
+    /// Sets a callback scaling the force at each timestep.
+    ///
+    /// The callback is given the current simulation time and returns the factor the force is multiplied by.
+    pub fn set_magnitude_callback(&mut self, callback: Box<FnMut(N) -> N + Send + Sync>) {
+        self.magnitude = Some(callback)
+    }
+
+    /// Removes the magnitude callback so that the force is applied unscaled.
+    pub fn clear_magnitude_callback(&mut self) {
+        self.magnitude = None
+    }
+}
+
+impl<N: RealField> ForceGenerator<N> for LocalForce<N> {
+    fn apply(&mut self, params: &IntegrationParameters<N>, bodies: &mut BodySet<N>) -> bool {
+        let body = try_ret!(bodies.body_mut(self.part.0), false);
+
+        let force = match &mut self.magnitude {
+            Some(magnitude) => self.force * magnitude(params.t),
+            None => self.force
+        };
+
+        if self.force_in_local_frame {
+            body.apply_local_force_at_local_point(self.part.1, &force, &self.local_point, ForceType::Force, false);
+        } else {
+            body.apply_force_at_local_point(self.part.1, &force, &self.local_point, ForceType::Force, false);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::force_generator::LocalForce;
+    use crate::math::{Inertia, Point, Vector};
+    use crate::object::RigidBodyDesc;
+    use crate::world::World;
+
+    #[test]
+    fn off_center_force_induces_spin() {
+        let mut world = World::<f64>::new();
+        let centered = RigidBodyDesc::new().local_inertia(Inertia::new(1.0, na::one())).build(&mut world).part_handle();
+        let off_center = RigidBodyDesc::new().local_inertia(Inertia::new(1.0, na::one())).build(&mut world).part_handle();
+
+        let _ = world.add_force_generator(LocalForce::new(centered, Point::origin(), Vector::x()));
+        let mut thruster = LocalForce::new(off_center, Point::from(Vector::y()), Vector::x());
+        thruster.set_magnitude_callback(Box::new(|t| if t < 0.5 { 1.0 } else { 0.0 }));
+        let _ = world.add_force_generator(thruster);
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let centered = world.rigid_body(centered.0).unwrap().velocity();
+        let off_center = world.rigid_body(off_center.0).unwrap().velocity();
+        assert!(centered.angular_vector().norm() < 1.0e-10);
+        assert!(centered.linear.x > 0.9);
+        assert!(off_center.angular_vector().norm() > 1.0e-2);
+        // The thruster was shut down halfway.
+        assert!(off_center.linear.norm() < 0.75 * centered.linear.norm());
+    }
+}
@@ -3,7 +3,11 @@
 pub use self::force_generator::{ForceGenerator, ForceGeneratorHandle};
 pub use self::constant_acceleration::ConstantAcceleration;
 pub use self::spring::Spring;
+pub use self::local_force::LocalForce;
+pub use self::buoyancy::Buoyancy;
 
 mod force_generator;
 mod constant_acceleration;
 mod spring;
+mod local_force;
+mod buoyancy;
@@ -17,6 +17,8 @@ pub struct BallConstraint<N: RealField> {
     impulses: Vector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> BallConstraint<N> {
@@ -33,6 +35,8 @@ impl<N: RealField> BallConstraint<N> {
             impulses: Vector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
@@ -45,6 +49,8 @@ impl<N: RealField> BallConstraint<N> {
     pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
         self.anchor2 = anchor2;
     }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for BallConstraint<N> {
@@ -58,7 +64,7 @@ impl<N: RealField> JointConstraint<N> for BallConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -81,6 +87,7 @@ impl<N: RealField> JointConstraint<N> for BallConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -95,7 +102,7 @@ impl<N: RealField> JointConstraint<N> for BallConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.impulses,
+            &(self.impulses * warmstart),
             0,
             ground_j_id,
             j_id,
@@ -117,6 +124,16 @@ impl<N: RealField> JointConstraint<N> for BallConstraint<N> {
             self.impulses[c.impulse_id] = c.impulse;
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.impulses.as_slice().to_vec()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.impulses.copy_from_slice(impulses);
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for BallConstraint<N> {
@@ -2,7 +2,7 @@ use na::{DVector, RealField};
 use std::ops::Range;
 
 use crate::joint::JointConstraint;
-use crate::math::{AngularVector, Point, ANGULAR_DIM, Rotation};
+use crate::math::{AngularVector, Point, ANGULAR_DIM, Rotation, Vector};
 use crate::object::{BodyPartHandle, BodySet};
 use crate::solver::helper;
 use crate::solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
@@ -16,16 +16,20 @@ pub struct CartesianConstraint<N: RealField> {
     ref_frame1: Rotation<N>,
     anchor2: Point<N>,
     ref_frame2: Rotation<N>,
+    target_relative_rotation: Rotation<N>,
     ang_impulses: AngularVector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> CartesianConstraint<N> {
     /// Creates a cartesian constraint between two body parts.
     /// 
     /// This will ensure the rotational parts of the frames given identified by `ref_frame1` and
-    /// `ref_frame2` and attached to the corresponding bodies will coincide.
+    /// `ref_frame2` and attached to the corresponding bodies will coincide, up to the
+    /// target relative rotation set by `set_target_relative_rotation`.
     pub fn new(
         b1: BodyPartHandle,
         b2: BodyPartHandle,
@@ -41,9 +45,12 @@ impl<N: RealField> CartesianConstraint<N> {
             ref_frame1,
             anchor2,
             ref_frame2,
+            target_relative_rotation: Rotation::identity(),
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
@@ -57,6 +64,18 @@ impl<N: RealField> CartesianConstraint<N> {
         self.ref_frame2 = frame2
     }
 
+    /// The rotation the second reference frame is locked to, relative to the first reference frame.
+    pub fn target_relative_rotation(&self) -> &Rotation<N> {
+        &self.target_relative_rotation
+    }
+
+    /// Sets the rotation the second reference frame is locked to, relative to the first reference frame.
+    ///
+    /// Defaults to the identity, i.e., both reference frames are kept aligned.
+    pub fn set_target_relative_rotation(&mut self, rotation: Rotation<N>) {
+        self.target_relative_rotation = rotation
+    }
+
     /// Changes the attach point for the first body part.
     pub fn set_anchor_1(&mut self, anchor1: Point<N>) {
         self.anchor1 = anchor1
@@ -66,6 +85,8 @@ impl<N: RealField> CartesianConstraint<N> {
     pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
         self.anchor2 = anchor2
     }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for CartesianConstraint<N> {
@@ -79,7 +100,7 @@ impl<N: RealField> JointConstraint<N> for CartesianConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -100,6 +121,7 @@ impl<N: RealField> JointConstraint<N> for CartesianConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -114,7 +136,7 @@ impl<N: RealField> JointConstraint<N> for CartesianConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.ang_impulses,
+            &(self.ang_impulses * warmstart),
             0,
             ground_j_id,
             j_id,
@@ -136,6 +158,22 @@ impl<N: RealField> JointConstraint<N> for CartesianConstraint<N> {
             self.ang_impulses[c.impulse_id] = c.impulse;
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.ang_impulses.as_slice().to_vec()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.ang_impulses.copy_from_slice(impulses);
+    }
+
+    fn reaction_force(&self, dt: N) -> (Vector<N>, AngularVector<N>) {
+        // This constraint does not restrict the relative translations, and its impulses
+        // are applied positively to the first body part.
+        (Vector::zeros(), -self.ang_impulses / dt)
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for CartesianConstraint<N> {
@@ -166,7 +204,7 @@ impl<N: RealField> NonlinearConstraintGenerator<N> for CartesianConstraint<N> {
         let anchor1 = Point::from(pos1.translation.vector);
         let anchor2 = Point::from(pos2.translation.vector);
 
-        let rotation1 = pos1.rotation;
+        let rotation1 = pos1.rotation * self.target_relative_rotation;
         let rotation2 = pos2.rotation;
 
         helper::cancel_relative_rotation(
@@ -183,3 +221,74 @@ impl<N: RealField> NonlinearConstraintGenerator<N> for CartesianConstraint<N> {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::force_generator::LocalForce;
+    use crate::joint::{CartesianConstraint, JointConstraint};
+    use crate::math::{AngularVector, Inertia, Point, Rotation, Vector, Velocity};
+    use crate::object::{BodyPartHandle, RigidBodyDesc};
+    use crate::world::World;
+
+    #[test]
+    fn hold_relative_rotation() {
+        let mut world = World::<f64>::new();
+        let target = Rotation::from_scaled_axis(AngularVector::x() * std::f64::consts::FRAC_PI_4);
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .velocity(Velocity::new_with_vectors(Vector::zeros(), AngularVector::x() * 2.0))
+            .build(&mut world);
+        let handle = body.handle();
+
+        let mut constraint = CartesianConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Rotation::identity(),
+            Point::origin(),
+            Rotation::identity(),
+        );
+        constraint.set_target_relative_rotation(target);
+        let _ = world.add_constraint(constraint);
+
+        for _ in 0..200 {
+            world.step();
+        }
+
+        let rotation = world.rigid_body(handle).unwrap().position().rotation;
+        assert!(target.angle_to(&rotation).abs() < 1.0e-2);
+    }
+
+    #[test]
+    fn reaction_torque_cancels_applied_torque() {
+        let mut world = World::<f64>::new();
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .build(&mut world)
+            .part_handle();
+
+        let constraint = CartesianConstraint::new(
+            BodyPartHandle::ground(),
+            body,
+            Point::origin(),
+            Rotation::identity(),
+            Point::origin(),
+            Rotation::identity(),
+        );
+        let handle = world.add_constraint(constraint);
+        // An off-center force applying a unit torque.
+        let _ = world.add_force_generator(LocalForce::new(body, Point::from(Vector::y()), Vector::x()));
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        #[cfg(feature = "dim2")]
+        let applied_torque = AngularVector::new(-1.0);
+        #[cfg(feature = "dim3")]
+        let applied_torque = -AngularVector::z();
+        let (force, torque) = world.constraint(handle).reaction_force(world.timestep());
+        assert_relative_eq!(force, Vector::zeros());
+        assert_relative_eq!(torque, -applied_torque, epsilon = 1.0e-3);
+    }
+}
@@ -1,14 +1,22 @@
 use std::ops::Range;
+use alga::linear::FiniteDimInnerSpace;
 use na::{DVector, RealField, Unit};
 
 use crate::object::{BodyPartHandle, BodySet};
 use crate::solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
              NonlinearConstraintGenerator};
 use crate::solver::helper;
-use crate::joint::JointConstraint;
-use crate::math::{AngularVector, Point, Vector, DIM, SPATIAL_DIM};
+use crate::joint::{unit_constraint, JointConstraint, JointMotor};
+use crate::math::{AngularVector, Isometry, Point, Vector, DIM, SPATIAL_DIM};
 
 /// A constraint that removes all degrees of freedom (of one body part relative to a second one) except one translation along an axis and one rotation along the same axis.
+///
+/// Both the translation and the rotation can be limited. The angle of rotation is measured around the axis, from a
+/// direction orthogonal to `axis1` fixed to the first body part, to a direction orthogonal to `axis2` fixed to the
+/// second one. Those directions are chosen the same way for both axes, so if `axis1` and `axis2` are equal the angle
+/// is zero when both body parts have the same orientation.
+///
+/// Both the translation and the rotation can also be driven by a motor.
 pub struct CylindricalConstraint<N: RealField> {
     b1: BodyPartHandle,
     b2: BodyPartHandle,
@@ -16,13 +24,28 @@ pub struct CylindricalConstraint<N: RealField> {
     anchor2: Point<N>,
     axis1: Unit<Vector<N>>,
     axis2: Unit<Vector<N>>,
+    // The local directions, orthogonal to the axes, between which the angle of the joint is measured.
+    ref1: Unit<Vector<N>>,
+    ref2: Unit<Vector<N>>,
+    // The world-space axis of the first body part during the last timestep.
+    world_axis1: Unit<Vector<N>>,
     lin_impulses: Vector<N>,
     ang_impulses: AngularVector<N>,
+    offset_limit_impulse: N,
+    angle_limit_impulse: N,
+    linear_motor_impulse: N,
+    angular_motor_impulse: N,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
 
-    // min_offset: Option<N>,
-    // max_offset: Option<N>,
+    min_offset: Option<N>,
+    max_offset: Option<N>,
+    min_angle: Option<N>,
+    max_angle: Option<N>,
+    linear_motor: JointMotor<N, N>,
+    angular_motor: JointMotor<N, N>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> CylindricalConstraint<N> {
@@ -38,8 +61,10 @@ impl<N: RealField> CylindricalConstraint<N> {
         anchor2: Point<N>,
         axis2: Unit<Vector<N>>,
     ) -> Self {
-        // let min_offset = None;
-        // let max_offset = None;
+        let min_offset = None;
+        let max_offset = None;
+        let min_angle = None;
+        let max_angle = None;
 
         CylindricalConstraint {
             b1,
@@ -48,53 +73,219 @@ impl<N: RealField> CylindricalConstraint<N> {
             anchor2,
             axis1,
             axis2,
+            ref1: orthogonal_direction(&axis1),
+            ref2: orthogonal_direction(&axis2),
+            world_axis1: axis1,
             lin_impulses: Vector::zeros(),
             ang_impulses: AngularVector::zeros(),
+            offset_limit_impulse: N::zero(),
+            angle_limit_impulse: N::zero(),
+            linear_motor_impulse: N::zero(),
+            angular_motor_impulse: N::zero(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
-            // min_offset,
-            // max_offset,
+            min_offset,
+            max_offset,
+            min_angle,
+            max_angle,
+            linear_motor: JointMotor::new(),
+            angular_motor: JointMotor::new(),
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
-    // pub fn min_offset(&self) -> Option<N> {
-    //     self.min_offset
-    // }
+    /// The lower limit, if any, of the relative translation (along the joint axis) of the body parts attached to this joint.
+    pub fn min_offset(&self) -> Option<N> {
+        self.min_offset
+    }
+
+    /// The upper limit, if any, of the relative translation (along the joint axis) of the body parts attached to this joint.
+    pub fn max_offset(&self) -> Option<N> {
+        self.max_offset
+    }
+
+    /// Disable the lower limit of the relative translational motion along the joint axis.
+    pub fn disable_min_offset(&mut self) {
+        self.min_offset = None;
+    }
+
+    /// Disable the upper limit of the relative translational motion along the joint axis.
+    pub fn disable_max_offset(&mut self) {
+        self.max_offset = None;
+    }
+
+    /// Enables the lower limit of the relative translational motion along the joint axis.
+    pub fn enable_min_offset(&mut self, limit: N) {
+        self.min_offset = Some(limit);
+        self.assert_limits();
+    }
+
+    /// Enables the upper limit of the relative translational motion along the joint axis.
+    pub fn enable_max_offset(&mut self, limit: N) {
+        self.max_offset = Some(limit);
+        self.assert_limits();
+    }
+
+    /// The lower limit, if any, of the relative rotation (along the joint axis) of the body parts attached to this joint.
+    pub fn min_angle(&self) -> Option<N> {
+        self.min_angle
+    }
+
+    /// The upper limit, if any, of the relative rotation (along the joint axis) of the body parts attached to this joint.
+    pub fn max_angle(&self) -> Option<N> {
+        self.max_angle
+    }
+
+    /// Disable the lower limit of the relative rotational motion along the joint axis.
+    pub fn disable_min_angle(&mut self) {
+        self.min_angle = None;
+    }
+
+    /// Disable the upper limit of the relative rotational motion along the joint axis.
+    pub fn disable_max_angle(&mut self) {
+        self.max_angle = None;
+    }
+
+    /// Enables the lower limit of the relative rotational motion along the joint axis.
+    ///
+    /// The angle is measured in `]-pi, pi]`.
+    pub fn enable_min_angle(&mut self, limit: N) {
+        self.min_angle = Some(limit);
+        self.assert_limits();
+    }
+
+    /// Enables the upper limit of the relative rotational motion along the joint axis.
+    ///
+    /// The angle is measured in `]-pi, pi]`.
+    pub fn enable_max_angle(&mut self, limit: N) {
+        self.max_angle = Some(limit);
+        self.assert_limits();
+    }
+
+    /// Return `true` if the linear motor of this joint is enabled.
+    pub fn is_linear_motor_enabled(&self) -> bool {
+        self.linear_motor.enabled
+    }
 
-    // pub fn max_offset(&self) -> Option<N> {
-    //     self.max_offset
-    // }
+    /// Enable the linear motor of this joint.
+    ///
+    /// The motor will attempt to reach the relative velocity `desired_velocity` along the joint axis, applying at most
+    /// the force `max_force`.
+    pub fn enable_linear_motor(&mut self, desired_velocity: N, max_force: N) {
+        self.linear_motor.desired_velocity = desired_velocity;
+        self.linear_motor.max_force = max_force;
+        self.linear_motor.enabled = true;
+    }
 
-    // pub fn disable_min_offset(&mut self) {
-    //     self.min_offset = None;
-    // }
+    /// Disable the linear motor of this joint.
+    pub fn disable_linear_motor(&mut self) {
+        self.linear_motor.enabled = false;
+    }
 
-    // pub fn disable_max_offset(&mut self) {
-    //     self.max_offset = None;
-    // }
+    /// The desired relative velocity, along the joint axis, of the linear motor.
+    pub fn desired_linear_motor_velocity(&self) -> N {
+        self.linear_motor.desired_velocity
+    }
 
-    // pub fn enable_min_offset(&mut self, limit: N) {
-    //     self.min_offset = Some(limit);
-    //     self.assert_limits();
-    // }
+    /// The maximum force that can be delivered by the linear motor.
+    pub fn max_linear_motor_force(&self) -> N {
+        self.linear_motor.max_force
+    }
 
-    // pub fn enable_max_offset(&mut self, limit: N) {
-    //     self.max_offset = Some(limit);
-    //     self.assert_limits();
-    // }
+    /// Return `true` if the angular motor of this joint is enabled.
+    pub fn is_angular_motor_enabled(&self) -> bool {
+        self.angular_motor.enabled
+    }
+
+    /// Enable the angular motor of this joint.
+    ///
+    /// The motor will attempt to reach the relative angular velocity `desired_velocity` around the joint axis,
+    /// applying at most the torque `max_torque`.
+    pub fn enable_angular_motor(&mut self, desired_velocity: N, max_torque: N) {
+        self.angular_motor.desired_velocity = desired_velocity;
+        self.angular_motor.max_force = max_torque;
+        self.angular_motor.enabled = true;
+    }
+
+    /// Disable the angular motor of this joint.
+    pub fn disable_angular_motor(&mut self) {
+        self.angular_motor.enabled = false;
+    }
+
+    /// The desired relative angular velocity, around the joint axis, of the angular motor.
+    pub fn desired_angular_motor_velocity(&self) -> N {
+        self.angular_motor.desired_velocity
+    }
+
+    /// The maximum torque that can be delivered by the angular motor.
+    pub fn max_angular_motor_torque(&self) -> N {
+        self.angular_motor.max_force
+    }
 
-    // fn assert_limits(&self) {
-    //     if let (Some(min_offset), Some(max_offset)) = (self.min_offset, self.max_offset) {
-    //         assert!(
-    //             min_offset <= max_offset,
-    //             "Cylindrical constraint limits: the min angle must be larger than (or equal to) the max angle.");
-    //     }
-    // }
+    fn has_offset_limits(&self) -> bool {
+        self.min_offset.is_some() || self.max_offset.is_some()
+    }
+
+    fn has_angle_limits(&self) -> bool {
+        self.min_angle.is_some() || self.max_angle.is_some()
+    }
+
+    // The angle of the second body part relative to the first one, around the world-space `axis1`.
+    fn angle(&self, pos1: &Isometry<N>, pos2: &Isometry<N>, axis1: &Unit<Vector<N>>) -> N {
+        let ref1 = pos1 * self.ref1;
+        let ref2 = pos2 * self.ref2;
+        axis1.dot(&ref1.cross(&ref2)).atan2(ref1.dot(&ref2))
+    }
+
+    fn assert_limits(&self) {
+        if let (Some(min_offset), Some(max_offset)) = (self.min_offset, self.max_offset) {
+            assert!(
+                min_offset <= max_offset,
+                "Cylindrical constraint limits: the min offset must be smaller than (or equal to) the max offset.");
+        }
+
+        if let (Some(min_angle), Some(max_angle)) = (self.min_angle, self.max_angle) {
+            assert!(
+                min_angle <= max_angle,
+                "Cylindrical constraint limits: the min angle must be smaller than (or equal to) the max angle.");
+        }
+    }
+
+    joint_allowed_errors_methods!();
+}
+
+// The first direction orthogonal to `axis`, as enumerated by `orthonormal_subspace_basis`.
+pub(crate) fn orthogonal_direction<N: RealField>(axis: &Unit<Vector<N>>) -> Unit<Vector<N>> {
+    let mut res = Vector::zeros();
+    Vector::orthonormal_subspace_basis(&[axis.into_inner()], |dir| {
+        res = *dir;
+        false
+    });
+    Unit::new_unchecked(res)
 }
 
 impl<N: RealField> JointConstraint<N> for CylindricalConstraint<N> {
     fn num_velocity_constraints(&self) -> usize {
-        SPATIAL_DIM - 2
+        let mut num = SPATIAL_DIM - 2;
+
+        if self.has_offset_limits() {
+            num += 1;
+        }
+
+        if self.has_angle_limits() {
+            num += 1;
+        }
+
+        if self.linear_motor.enabled {
+            num += 1;
+        }
+
+        if self.angular_motor.enabled {
+            num += 1;
+        }
+
+        num
     }
 
     fn anchors(&self) -> (BodyPartHandle, BodyPartHandle) {
@@ -103,7 +294,7 @@ impl<N: RealField> JointConstraint<N> for CylindricalConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -129,11 +320,13 @@ impl<N: RealField> JointConstraint<N> for CylindricalConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
 
         let axis1 = pos1 * self.axis1;
+        self.world_axis1 = axis1;
 
         helper::restrict_relative_linear_velocity_to_axis(
             body1,
@@ -146,7 +339,7 @@ impl<N: RealField> JointConstraint<N> for CylindricalConstraint<N> {
             &anchor2,
             &axis1,
             ext_vels,
-            self.lin_impulses.as_slice(),
+            (self.lin_impulses * warmstart).as_slice(),
             0,
             ground_j_id,
             j_id,
@@ -165,7 +358,7 @@ impl<N: RealField> JointConstraint<N> for CylindricalConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            self.ang_impulses.as_slice(),
+            (self.ang_impulses * warmstart).as_slice(),
             DIM - 1,
             ground_j_id,
             j_id,
@@ -178,6 +371,96 @@ impl<N: RealField> JointConstraint<N> for CylindricalConstraint<N> {
          * Limit constraints.
          *
          */
+        unit_constraint::build_linear_limits_velocity_constraint(
+            body1,
+            part1,
+            body2,
+            part2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &axis1,
+            self.min_offset,
+            self.max_offset,
+            ext_vels,
+            self.offset_limit_impulse * warmstart,
+            SPATIAL_DIM - 2,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        let angle = self.angle(&pos1, &pos2, &axis1);
+        unit_constraint::build_angular_limits_velocity_constraint(
+            body1,
+            part1,
+            body2,
+            part2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &axis1,
+            angle,
+            self.min_angle,
+            self.max_angle,
+            ext_vels,
+            self.angle_limit_impulse * warmstart,
+            SPATIAL_DIM - 1,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        /*
+         *
+         * Motor constraints.
+         *
+         */
+        unit_constraint::build_linear_motor_velocity_constraint(
+            params,
+            body1,
+            part1,
+            body2,
+            part2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &axis1,
+            &self.linear_motor,
+            ext_vels,
+            self.linear_motor_impulse * warmstart,
+            SPATIAL_DIM,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        unit_constraint::build_angular_motor_velocity_constraint(
+            params,
+            body1,
+            part1,
+            body2,
+            part2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &axis1,
+            &self.angular_motor,
+            ext_vels,
+            self.angular_motor_impulse * warmstart,
+            SPATIAL_DIM + 1,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
 
         self.bilateral_ground_rng =
             first_bilateral_ground..constraints.velocity.bilateral_ground.len();
@@ -185,29 +468,107 @@ impl<N: RealField> JointConstraint<N> for CylindricalConstraint<N> {
     }
 
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        // The first `DIM - 1` impulses are linear, the next `DIM - 1` are angular, the next two are the limits,
+        // and the last two are the motors.
+        self.offset_limit_impulse = N::zero();
+        self.angle_limit_impulse = N::zero();
+        self.linear_motor_impulse = N::zero();
+        self.angular_motor_impulse = N::zero();
+
         for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
-            if c.impulse_id < DIM {
+            if c.impulse_id < DIM - 1 {
                 self.lin_impulses[c.impulse_id] = c.impulse;
+            } else if c.impulse_id < SPATIAL_DIM - 2 {
+                self.ang_impulses[c.impulse_id - (DIM - 1)] = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM - 2 {
+                self.offset_limit_impulse = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM - 1 {
+                self.angle_limit_impulse = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM {
+                self.linear_motor_impulse = c.impulse;
             } else {
-                self.ang_impulses[c.impulse_id - DIM] = c.impulse;
+                self.angular_motor_impulse = c.impulse;
             }
         }
 
         for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
-            if c.impulse_id < DIM {
+            if c.impulse_id < DIM - 1 {
                 self.lin_impulses[c.impulse_id] = c.impulse;
+            } else if c.impulse_id < SPATIAL_DIM - 2 {
+                self.ang_impulses[c.impulse_id - (DIM - 1)] = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM - 2 {
+                self.offset_limit_impulse = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM - 1 {
+                self.angle_limit_impulse = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM {
+                self.linear_motor_impulse = c.impulse;
             } else {
-                self.ang_impulses[c.impulse_id - DIM] = c.impulse;
+                self.angular_motor_impulse = c.impulse;
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        let mut impulses: Vec<N> = self.lin_impulses.iter().chain(self.ang_impulses.iter()).cloned().collect();
+        impulses.push(self.offset_limit_impulse);
+        impulses.push(self.angle_limit_impulse);
+        impulses.push(self.linear_motor_impulse);
+        impulses.push(self.angular_motor_impulse);
+        impulses
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let nlin = self.lin_impulses.len();
+        let nang = self.ang_impulses.len();
+        self.lin_impulses.copy_from_slice(&impulses[..nlin]);
+        self.ang_impulses.copy_from_slice(&impulses[nlin..nlin + nang]);
+        self.offset_limit_impulse = impulses[nlin + nang];
+        self.angle_limit_impulse = impulses[nlin + nang + 1];
+        self.linear_motor_impulse = impulses[nlin + nang + 2];
+        self.angular_motor_impulse = impulses[nlin + nang + 3];
+    }
+
+    fn reaction_force(&self, dt: N) -> (Vector<N>, AngularVector<N>) {
+        // The impulses are expressed along the directions orthogonal to the axis, as enumerated by the solver helpers.
+        // FIXME: the force and torque applied by the limits and motors along the axis are not taken into account.
+        let mut force = Vector::zeros();
+        let mut i = 0;
+        Vector::orthonormal_subspace_basis(&[self.world_axis1.into_inner()], |dir| {
+            force += dir * self.lin_impulses[i];
+            i += 1;
+            true
+        });
+
+        let mut torque = AngularVector::zeros();
+        let mut i = 0;
+        AngularVector::orthonormal_subspace_basis(&[self.world_axis1.into_inner()], |dir| {
+            torque += dir * self.ang_impulses[i];
+            i += 1;
+            true
+        });
+
+        // The impulses are applied positively to the first body part.
+        (-force / dt, -torque / dt)
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for CylindricalConstraint<N> {
     fn num_position_constraints(&self, bodies: &BodySet<N>) -> usize {
         // FIXME: calling this at each iteration of the non-linear resolution is costly.
         if self.is_active(bodies) {
-            2
+            let mut num = 2;
+
+            if self.has_offset_limits() {
+                num += 1;
+            }
+
+            if self.has_angle_limits() {
+                num += 1;
+            }
+
+            num
         } else {
             0
         }
@@ -263,6 +624,221 @@ impl<N: RealField> NonlinearConstraintGenerator<N> for CylindricalConstraint<N>
             );
         }
 
+        if i == 2 && self.has_offset_limits() {
+            // NOTE: the max limit is only checked when there is a min limit, so a lower bound smaller than any
+            // offset is used when there is none.
+            let min_offset = self.min_offset.unwrap_or_else(|| -N::max_value());
+
+            return unit_constraint::build_linear_limits_position_constraint(
+                params,
+                body1,
+                part1,
+                body2,
+                part2,
+                &anchor1,
+                &anchor2,
+                &axis1,
+                Some(min_offset),
+                self.max_offset,
+                jacobians,
+            );
+        }
+
+        if i >= 2 {
+            let angle = self.angle(&pos1, &pos2, &axis1);
+
+            return unit_constraint::build_angular_limits_position_constraint(
+                params,
+                body1,
+                part1,
+                body2,
+                part2,
+                &anchor1,
+                &anchor2,
+                &axis1,
+                angle,
+                self.min_angle,
+                self.max_angle,
+                jacobians,
+            );
+        }
+
         return None;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::joint::{CylindricalConstraint, JointConstraint};
+    use crate::math::{AngularVector, Inertia, Point, Vector, Velocity};
+    use crate::object::{BodyPartHandle, RigidBodyDesc};
+    use crate::world::World;
+
+    #[test]
+    fn reaction_force_supports_weight() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let mass = 2.0;
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(mass, na::Matrix3::identity()))
+            .build(&mut world)
+            .part_handle();
+
+        // The body can only slide and rotate along the horizontal `x` axis.
+        let constraint = CylindricalConstraint::new(
+            BodyPartHandle::ground(),
+            body,
+            Point::origin(),
+            Vector::x_axis(),
+            Point::origin(),
+            Vector::x_axis(),
+        );
+        let handle = world.add_constraint(constraint);
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let (force, torque) = world.constraint(handle).reaction_force(world.timestep());
+        assert_relative_eq!(force, Vector::y() * mass * 9.81, epsilon = 1.0e-3);
+        assert_relative_eq!(torque, Vector::zeros(), epsilon = 1.0e-3);
+    }
+
+    #[test]
+    fn offset_limits_stop_sliding_body() {
+        let mut world = World::<f64>::new();
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::Matrix3::identity()))
+            .velocity(Velocity::linear(2.0, 0.0, 0.0))
+            .build(&mut world);
+        let handle = body.handle();
+
+        let mut constraint = CylindricalConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Vector::x_axis(),
+            Point::origin(),
+            Vector::x_axis(),
+        );
+        constraint.enable_min_offset(-0.5);
+        constraint.enable_max_offset(1.0);
+        assert_eq!(constraint.num_velocity_constraints(), 5);
+        let _ = world.add_constraint(constraint);
+
+        for _ in 0..100 {
+            world.step();
+            let x = world.rigid_body(handle).unwrap().position().translation.vector.x;
+            assert!(x > -0.5 - 5.0e-2 && x < 1.0 + 5.0e-2, "x: {}", x);
+        }
+
+        // The body is stopped by the upper limit without bouncing back.
+        let x = world.rigid_body(handle).unwrap().position().translation.vector.x;
+        assert_relative_eq!(x, 1.0, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn max_offset_limit_alone_corrects_the_position() {
+        let mut world = World::<f64>::new();
+        // The body starts past the upper limit.
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::Matrix3::identity()))
+            .translation(Vector::x() * 1.5)
+            .build(&mut world);
+        let handle = body.handle();
+
+        let mut constraint = CylindricalConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Vector::x_axis(),
+            Point::origin(),
+            Vector::x_axis(),
+        );
+        constraint.enable_max_offset(1.0);
+        let _ = world.add_constraint(constraint);
+
+        for _ in 0..100 {
+            world.step();
+        }
+
+        let x = world.rigid_body(handle).unwrap().position().translation.vector.x;
+        assert_relative_eq!(x, 1.0, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn angle_limits_stop_spinning_body() {
+        let mut world = World::<f64>::new();
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::Matrix3::identity()))
+            .velocity(Velocity::new(Vector::x(), AngularVector::x() * 3.0))
+            .build(&mut world);
+        let handle = body.handle();
+
+        let mut constraint = CylindricalConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Vector::x_axis(),
+            Point::origin(),
+            Vector::x_axis(),
+        );
+        constraint.enable_min_angle(-0.5);
+        constraint.enable_max_angle(1.0);
+        assert_eq!(constraint.num_velocity_constraints(), 5);
+        let _ = world.add_constraint(constraint);
+
+        for _ in 0..100 {
+            world.step();
+            let angle = world.rigid_body(handle).unwrap().position().rotation.scaled_axis().x;
+            assert!(angle > -0.5 - 5.0e-2 && angle < 1.0 + 5.0e-2, "angle: {}", angle);
+        }
+
+        // The body is stopped by the upper limit, and can still slide freely along the axis.
+        let body = world.rigid_body(handle).unwrap();
+        let dt = world.integration_parameters().dt;
+        assert_relative_eq!(body.position().rotation.scaled_axis(), AngularVector::x() * 1.0, epsilon = 1.0e-2);
+        assert_relative_eq!(body.velocity().linear, Vector::x(), epsilon = 1.0e-3);
+        assert_relative_eq!(body.position().translation.vector, Vector::x() * (100.0 * dt), epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn motors_drive_body_to_desired_velocities() {
+        let mut world = World::<f64>::new();
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::Matrix3::identity()))
+            .build(&mut world);
+        let handle = body.handle();
+
+        let mut constraint = CylindricalConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Vector::x_axis(),
+            Point::origin(),
+            Vector::x_axis(),
+        );
+        constraint.enable_linear_motor(1.0, 1000.0);
+        constraint.enable_angular_motor(2.0, 1.0);
+        assert_eq!(constraint.num_velocity_constraints(), 6);
+        let _ = world.add_constraint(constraint);
+
+        // The angular motor can only apply a unit torque, so it takes two seconds to reach its desired velocity.
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let velocity = *world.rigid_body(handle).unwrap().velocity();
+        assert_relative_eq!(velocity.linear, Vector::x(), epsilon = 1.0e-3);
+        assert_relative_eq!(velocity.angular, AngularVector::x() * 1.0, epsilon = 1.0e-2);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let velocity = *world.rigid_body(handle).unwrap().velocity();
+        assert_relative_eq!(velocity.linear, Vector::x(), epsilon = 1.0e-3);
+        assert_relative_eq!(velocity.angular, AngularVector::x() * 2.0, epsilon = 1.0e-3);
+    }
+}
@@ -0,0 +1,374 @@
+use na::{DVector, RealField, Unit};
+use std::ops::Range;
+
+use crate::joint::{unit_constraint, JointConstraint};
+use crate::math::Point;
+use crate::object::{BodyPartHandle, BodySet};
+use crate::solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
+             NonlinearConstraintGenerator};
+
+/// A constraint that keeps the distance between two points of two body parts within some limits.
+///
+/// With only an upper limit, this behaves like a rope. With equal lower and upper limits, this behaves
+/// like a rigid rod. The relative motion of the body parts is not constrained otherwise.
+pub struct DistanceConstraint<N: RealField> {
+    b1: BodyPartHandle,
+    b2: BodyPartHandle,
+    anchor1: Point<N>,
+    anchor2: Point<N>,
+    limit_impulse: N,
+    bilateral_ground_rng: Range<usize>,
+    bilateral_rng: Range<usize>,
+
+    min_distance: Option<N>,
+    max_distance: Option<N>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
+}
+
+impl<N: RealField> DistanceConstraint<N> {
+    /// Creates a distance constraint between two body parts.
+    ///
+    /// This will ensure the distance between the points identified by `anchor1` and `anchor2`, given in
+    /// the local-space of their corresponding body part, stays within `min_distance` and `max_distance`.
+    pub fn new(
+        b1: BodyPartHandle,
+        b2: BodyPartHandle,
+        anchor1: Point<N>,
+        anchor2: Point<N>,
+        min_distance: Option<N>,
+        max_distance: Option<N>,
+    ) -> Self {
+        let res = DistanceConstraint {
+            b1,
+            b2,
+            anchor1,
+            anchor2,
+            limit_impulse: N::zero(),
+            bilateral_ground_rng: 0..0,
+            bilateral_rng: 0..0,
+            min_distance,
+            max_distance,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
+        };
+
+        res.assert_limits();
+        res
+    }
+
+    /// Change the first anchor, expressed in the local space of the first body part.
+    pub fn set_anchor_1(&mut self, anchor1: Point<N>) {
+        self.anchor1 = anchor1;
+    }
+
+    /// Change the second anchor, expressed in the local space of the second body part.
+    pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
+        self.anchor2 = anchor2;
+    }
+
+    /// The lower limit, if any, of the distance between the anchors.
+    pub fn min_distance(&self) -> Option<N> {
+        self.min_distance
+    }
+
+    /// The upper limit, if any, of the distance between the anchors.
+    pub fn max_distance(&self) -> Option<N> {
+        self.max_distance
+    }
+
+    /// Disable the lower limit of the distance between the anchors.
+    pub fn disable_min_distance(&mut self) {
+        self.min_distance = None;
+    }
+
+    /// Disable the upper limit of the distance between the anchors.
+    pub fn disable_max_distance(&mut self) {
+        self.max_distance = None;
+    }
+
+    /// Enables the lower limit of the distance between the anchors.
+    pub fn enable_min_distance(&mut self, limit: N) {
+        self.min_distance = Some(limit);
+        self.assert_limits();
+    }
+
+    /// Enables the upper limit of the distance between the anchors.
+    pub fn enable_max_distance(&mut self, limit: N) {
+        self.max_distance = Some(limit);
+        self.assert_limits();
+    }
+
+    fn assert_limits(&self) {
+        if let (Some(min_distance), Some(max_distance)) = (self.min_distance, self.max_distance) {
+            assert!(
+                min_distance <= max_distance,
+                "Distance constraint limits: the min distance must be smaller than (or equal to) the max distance.");
+        }
+    }
+
+    joint_allowed_errors_methods!();
+}
+
+impl<N: RealField> JointConstraint<N> for DistanceConstraint<N> {
+    fn num_velocity_constraints(&self) -> usize {
+        1
+    }
+
+    fn anchors(&self) -> (BodyPartHandle, BodyPartHandle) {
+        (self.b1, self.b2)
+    }
+
+    fn velocity_constraints(
+        &mut self,
+        params: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        self.bilateral_ground_rng = 0..0;
+        self.bilateral_rng = 0..0;
+
+        let body1 = try_ret!(bodies.body(self.b1.0));
+        let body2 = try_ret!(bodies.body(self.b2.0));
+        let part1 = try_ret!(body1.part(self.b1.1));
+        let part2 = try_ret!(body2.part(self.b2.1));
+
+        let anchor1 = body1.world_point_at_material_point(part1, &self.anchor1);
+        let anchor2 = body2.world_point_at_material_point(part2, &self.anchor2);
+        // The limits do not have any direction if the anchors coincide.
+        let axis = try_ret!(Unit::try_new(anchor2 - anchor1, N::default_epsilon()));
+
+        let assembly_id1 = body1.companion_id();
+        let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
+
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+        let first_bilateral = constraints.velocity.bilateral.len();
+
+        unit_constraint::build_linear_limits_velocity_constraint(
+            body1,
+            part1,
+            body2,
+            part2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &axis,
+            self.min_distance,
+            self.max_distance,
+            ext_vels,
+            self.limit_impulse * warmstart,
+            0,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = first_bilateral..constraints.velocity.bilateral.len();
+    }
+
+    fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        self.limit_impulse = N::zero();
+
+        for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
+            self.limit_impulse = c.impulse;
+        }
+
+        for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
+            self.limit_impulse = c.impulse;
+        }
+    }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        vec![self.limit_impulse]
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.limit_impulse = impulses[0];
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
+}
+
+impl<N: RealField> NonlinearConstraintGenerator<N> for DistanceConstraint<N> {
+    fn num_position_constraints(&self, bodies: &BodySet<N>) -> usize {
+        if self.is_active(bodies) && (self.min_distance.is_some() || self.max_distance.is_some()) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn position_constraint(
+        &self,
+        params: &IntegrationParameters<N>,
+        _: usize,
+        bodies: &mut BodySet<N>,
+        jacobians: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        let body1 = bodies.body(self.b1.0)?;
+        let body2 = bodies.body(self.b2.0)?;
+        let part1 = body1.part(self.b1.1)?;
+        let part2 = body2.part(self.b2.1)?;
+
+        let anchor1 = body1.world_point_at_material_point(part1, &self.anchor1);
+        let anchor2 = body2.world_point_at_material_point(part2, &self.anchor2);
+        let axis = Unit::try_new(anchor2 - anchor1, N::default_epsilon())?;
+        // NOTE: the max limit is only checked when there is a min limit, and a zero min distance is never active.
+        let min_distance = self.min_distance.unwrap_or_else(N::zero);
+
+        unit_constraint::build_linear_limits_position_constraint(
+            params,
+            body1,
+            part1,
+            body2,
+            part2,
+            &anchor1,
+            &anchor2,
+            &axis,
+            Some(min_distance),
+            self.max_distance,
+            jacobians,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::joint::DistanceConstraint;
+    use crate::math::{Inertia, Point, Vector, Velocity};
+    use crate::object::{BodyPartHandle, RigidBodyDesc};
+    use crate::world::World;
+
+    #[test]
+    fn rope_limits_the_distance_only_when_stretched() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        // A body thrown upward, hanging from a rope of length 2 attached at the origin.
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .translation(Vector::y() * -1.0)
+            .velocity(Velocity::new_with_vectors(Vector::y() * 3.0, na::zero()))
+            .build(&mut world);
+        let handle = body.handle();
+
+        let rope = DistanceConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Point::origin(),
+            None,
+            Some(2.0),
+        );
+        let _ = world.add_constraint(rope);
+
+        // The rope is slack, so the body rises freely at first.
+        for _ in 0..15 {
+            world.step();
+        }
+
+        let y = world.rigid_body(handle).unwrap().position().translation.vector.y;
+        assert!(y > -0.6, "y: {}", y);
+
+        // The limit is only enforced once it is exceeded, so the rope may stretch a bit when it catches the body.
+        for _ in 0..200 {
+            world.step();
+            let distance = world.rigid_body(handle).unwrap().position().translation.vector.norm();
+            assert!(distance < 2.0 + 5.0e-2, "distance: {}", distance);
+        }
+
+        let y = world.rigid_body(handle).unwrap().position().translation.vector.y;
+        assert_relative_eq!(y, -2.0, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn equal_limits_behave_like_a_rod() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .translation(Vector::x())
+            .build(&mut world);
+        let handle = body.handle();
+
+        let mut rod = DistanceConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Point::origin(),
+            None,
+            None,
+        );
+        rod.enable_min_distance(1.0);
+        rod.enable_max_distance(1.0);
+        let _ = world.add_constraint(rod);
+
+        // The body swings like a pendulum, and goes through the lowest point of its circle.
+        let mut min_y = 0.0f64;
+
+        for _ in 0..100 {
+            world.step();
+            let translation = world.rigid_body(handle).unwrap().position().translation.vector;
+            assert_relative_eq!(translation.norm(), 1.0, epsilon = 1.0e-2);
+            min_y = min_y.min(translation.y);
+        }
+
+        assert!(min_y < -0.99, "min_y: {}", min_y);
+    }
+
+    #[test]
+    fn tight_allowed_error_holds_the_rod_length_more_precisely() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.integration_parameters_mut().allowed_linear_error = 0.05;
+
+        // Two identical pendulums, far from each other, one of them with a tighter tolerance.
+        let mut pendulum = |x: f64, allowed_error: Option<f64>| {
+            let anchor = Point::from(Vector::x() * x);
+            let body = RigidBodyDesc::new()
+                .local_inertia(Inertia::new(1.0, na::one()))
+                .translation(anchor.coords + Vector::x())
+                .build(&mut world);
+            let handle = body.handle();
+
+            let mut rod = DistanceConstraint::new(
+                BodyPartHandle::ground(),
+                body.part_handle(),
+                anchor,
+                Point::origin(),
+                Some(1.0),
+                Some(1.0),
+            );
+            rod.set_allowed_linear_error(allowed_error);
+            assert_eq!(rod.allowed_linear_error(), allowed_error);
+            let _ = world.add_constraint(rod);
+            (handle, anchor)
+        };
+
+        let loose = pendulum(0.0, None);
+        let tight = pendulum(10.0, Some(1.0e-4));
+        let mut max_loose_error = 0.0f64;
+        let mut max_tight_error = 0.0f64;
+
+        for _ in 0..100 {
+            world.step();
+
+            let error = |(handle, anchor): (_, Point<f64>)| {
+                let translation = world.rigid_body(handle).unwrap().position().translation.vector;
+                ((translation - anchor.coords).norm() - 1.0).abs()
+            };
+            max_loose_error = max_loose_error.max(error(loose));
+            max_tight_error = max_tight_error.max(error(tight));
+        }
+
+        assert!(max_tight_error < max_loose_error * 0.5, "tight: {}, loose: {}", max_tight_error, max_loose_error);
+    }
+}
@@ -20,6 +20,8 @@ pub struct FixedConstraint<N: RealField> {
     ang_impulses: AngularVector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> FixedConstraint<N> {
@@ -46,6 +48,8 @@ impl<N: RealField> FixedConstraint<N> {
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
@@ -68,6 +72,8 @@ impl<N: RealField> FixedConstraint<N> {
     pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
         self.anchor2 = anchor2
     }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for FixedConstraint<N> {
@@ -81,7 +87,7 @@ impl<N: RealField> JointConstraint<N> for FixedConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -102,6 +108,7 @@ impl<N: RealField> JointConstraint<N> for FixedConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -116,7 +123,7 @@ impl<N: RealField> JointConstraint<N> for FixedConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.lin_impulses,
+            &(self.lin_impulses * warmstart),
             0,
             ground_j_id,
             j_id,
@@ -134,7 +141,7 @@ impl<N: RealField> JointConstraint<N> for FixedConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.ang_impulses,
+            &(self.ang_impulses * warmstart),
             DIM,
             ground_j_id,
             j_id,
@@ -164,6 +171,18 @@ impl<N: RealField> JointConstraint<N> for FixedConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses.iter().chain(self.ang_impulses.iter()).cloned().collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let nlin = self.lin_impulses.len();
+        self.lin_impulses.copy_from_slice(&impulses[..nlin]);
+        self.ang_impulses.copy_from_slice(&impulses[nlin..]);
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for FixedConstraint<N> {
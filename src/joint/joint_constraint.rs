@@ -3,6 +3,7 @@
 use downcast_rs::Downcast;
 use na::{DVector, RealField};
 
+use crate::math::{AngularVector, Vector};
 use crate::object::{BodyPartHandle, BodySet};
 use crate::solver::{ConstraintSet, IntegrationParameters, NonlinearConstraintGenerator};
 
@@ -42,6 +43,43 @@ pub trait JointConstraint<N: RealField>: NonlinearConstraintGenerator<N> + Downc
     );
     /// Called after velocity constraint resolution, allows the joint to keep a cache of impulses generated for each constraint.
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>);
+
+    /// The impulses cached by this joint for warm-starting, in an order specific to each joint.
+    ///
+    /// The default implementation returns an empty vector for the joints that do not cache any impulse.
+    fn cached_impulses(&self) -> Vec<N> {
+        Vec::new()
+    }
+
+    /// Overwrites the impulses cached by this joint with values returned by `self.cached_impulses()`.
+    ///
+    /// The `impulses` slice must have as many elements as `self.cached_impulses()`.
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let _ = impulses;
+    }
+
+    /// Discards the impulses cached by this joint for warm-starting.
+    fn clear_cached_impulses(&mut self) {
+        let impulses = vec![N::zero(); self.cached_impulses().len()];
+        self.set_cached_impulses(&impulses);
+    }
+
+    /// The linear force and torque, in world-space, applied by this joint to its second body part during the last timestep.
+    ///
+    /// This is computed from the impulses cached during the last timestep of length `dt`. The default implementation
+    /// returns zero for the joints that do not compute their reaction forces yet.
+    fn reaction_force(&self, dt: N) -> (Vector<N>, AngularVector<N>) {
+        let _ = dt;
+        (Vector::zeros(), AngularVector::zeros())
+    }
+
+    /// The linear and angular errors this joint wont attempt to correct, if they override the
+    /// `allowed_linear_error` and `allowed_angular_error` of the integration parameters.
+    ///
+    /// The default implementation returns `(None, None)` for the joints that always use the integration parameters.
+    fn allowed_errors(&self) -> (Option<N>, Option<N>) {
+        (None, None)
+    }
 }
 
 impl_downcast!(JointConstraint<N> where N: RealField);
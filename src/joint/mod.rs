@@ -25,12 +25,14 @@ pub use self::rectangular_joint::RectangularJoint;
 pub use self::universal_joint::UniversalJoint;
 
 pub use self::cartesian_constraint::CartesianConstraint;
+pub use self::distance_constraint::DistanceConstraint;
 pub use self::fixed_constraint::FixedConstraint;
 pub use self::joint_constraint::{ConstraintHandle, JointConstraint};
 pub use self::joint_motor::JointMotor;
 pub use self::mouse_constraint::MouseConstraint;
 pub use self::prismatic_constraint::PrismaticConstraint;
 pub use self::revolute_constraint::RevoluteConstraint;
+pub use self::spring_constraint::SpringConstraint;
 
 #[cfg(feature = "dim3")]
 pub use self::ball_constraint::BallConstraint;
@@ -45,6 +47,39 @@ pub use self::rectangular_constraint::RectangularConstraint;
 #[cfg(feature = "dim3")]
 pub use self::universal_constraint::UniversalConstraint;
 
+macro_rules! joint_allowed_errors_methods(
+    () => {
+        /// Sets the amount of linear drift this joint wont attempt to correct.
+        ///
+        /// If `None`, the `allowed_linear_error` of the integration parameters is used instead.
+        pub fn set_allowed_linear_error(&mut self, error: Option<N>) {
+            self.allowed_linear_error = error
+        }
+
+        /// The amount of linear drift this joint wont attempt to correct, if it overrides the integration parameters.
+        pub fn allowed_linear_error(&self) -> Option<N> {
+            self.allowed_linear_error
+        }
+
+        /// Sets the amount of angular drift this joint wont attempt to correct.
+        ///
+        /// If `None`, the `allowed_angular_error` of the integration parameters is used instead.
+        pub fn set_allowed_angular_error(&mut self, error: Option<N>) {
+            self.allowed_angular_error = error
+        }
+
+        /// The amount of angular drift this joint wont attempt to correct, if it overrides the integration parameters.
+        pub fn allowed_angular_error(&self) -> Option<N> {
+            self.allowed_angular_error
+        }
+    };
+    (JointConstraint) => {
+        fn allowed_errors(&self) -> (Option<N>, Option<N>) {
+            (self.allowed_linear_error, self.allowed_angular_error)
+        }
+    }
+);
+
 mod cartesian_joint;
 mod fixed_joint;
 mod free_joint;
@@ -69,12 +104,14 @@ mod rectangular_joint;
 mod universal_joint;
 
 mod cartesian_constraint;
+mod distance_constraint;
 mod fixed_constraint;
 mod joint_constraint;
 mod joint_motor;
 mod mouse_constraint;
 mod prismatic_constraint;
 mod revolute_constraint;
+mod spring_constraint;
 mod unit_constraint;
 
 #[cfg(feature = "dim3")]
@@ -26,6 +26,8 @@ pub struct PinSlotConstraint<N: RealField> {
     bilateral_rng: Range<usize>,
     // min_offset: Option<N>,
     // max_offset: Option<N>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> PinSlotConstraint<N> {
@@ -61,6 +63,8 @@ impl<N: RealField> PinSlotConstraint<N> {
             bilateral_rng: 0..0,
             // min_offset,
             // max_offset,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
@@ -97,6 +101,8 @@ impl<N: RealField> PinSlotConstraint<N> {
     //             "RevoluteJoint constraint limits: the min angle must be larger than (or equal to) the max angle.");
     //     }
     // }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for PinSlotConstraint<N> {
@@ -110,7 +116,7 @@ impl<N: RealField> JointConstraint<N> for PinSlotConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -136,6 +142,7 @@ impl<N: RealField> JointConstraint<N> for PinSlotConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -154,7 +161,7 @@ impl<N: RealField> JointConstraint<N> for PinSlotConstraint<N> {
             &anchor2,
             &axis_v1,
             ext_vels,
-            self.lin_impulses.as_slice(),
+            (self.lin_impulses * warmstart).as_slice(),
             0,
             ground_j_id,
             j_id,
@@ -173,7 +180,7 @@ impl<N: RealField> JointConstraint<N> for PinSlotConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            self.ang_impulses.as_slice(),
+            (self.ang_impulses * warmstart).as_slice(),
             DIM - 1,
             ground_j_id,
             j_id,
@@ -209,6 +216,18 @@ impl<N: RealField> JointConstraint<N> for PinSlotConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses.iter().chain(self.ang_impulses.iter()).cloned().collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let nlin = self.lin_impulses.len();
+        self.lin_impulses.copy_from_slice(&impulses[..nlin]);
+        self.ang_impulses.copy_from_slice(&impulses[nlin..]);
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for PinSlotConstraint<N> {
@@ -22,6 +22,8 @@ pub struct PlanarConstraint<N: RealField> {
     ang_impulses: [N; 2],
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> PlanarConstraint<N> {
@@ -47,8 +49,12 @@ impl<N: RealField> PlanarConstraint<N> {
             ang_impulses: [N::zero(), N::zero()],
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for PlanarConstraint<N> {
@@ -62,7 +68,7 @@ impl<N: RealField> JointConstraint<N> for PlanarConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -88,6 +94,7 @@ impl<N: RealField> JointConstraint<N> for PlanarConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -105,7 +112,7 @@ impl<N: RealField> JointConstraint<N> for PlanarConstraint<N> {
             &anchor2,
             &axis1,
             ext_vels,
-            self.lin_impulse,
+            self.lin_impulse * warmstart,
             0,
             ground_j_id,
             j_id,
@@ -124,7 +131,7 @@ impl<N: RealField> JointConstraint<N> for PlanarConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.ang_impulses[..],
+            &[self.ang_impulses[0] * warmstart, self.ang_impulses[1] * warmstart],
             1,
             ground_j_id,
             j_id,
@@ -160,6 +167,17 @@ impl<N: RealField> JointConstraint<N> for PlanarConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        vec![self.lin_impulse, self.ang_impulses[0], self.ang_impulses[1]]
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.lin_impulse = impulses[0];
+        self.ang_impulses.copy_from_slice(&impulses[1..]);
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for PlanarConstraint<N> {
@@ -23,6 +23,8 @@ pub struct PrismaticConstraint<N: RealField> {
 
     min_offset: Option<N>,
     max_offset: Option<N>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> PrismaticConstraint<N> {
@@ -52,6 +54,8 @@ impl<N: RealField> PrismaticConstraint<N> {
             bilateral_rng: 0..0,
             min_offset,
             max_offset,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
@@ -94,6 +98,8 @@ impl<N: RealField> PrismaticConstraint<N> {
                 "RevoluteJoint constraint limits: the min angle must be larger than (or equal to) the max angle.");
         }
     }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for PrismaticConstraint<N> {
@@ -107,7 +113,7 @@ impl<N: RealField> JointConstraint<N> for PrismaticConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -133,6 +139,7 @@ impl<N: RealField> JointConstraint<N> for PrismaticConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -150,7 +157,7 @@ impl<N: RealField> JointConstraint<N> for PrismaticConstraint<N> {
             &anchor2,
             &axis,
             ext_vels,
-            self.lin_impulses.as_slice(),
+            (self.lin_impulses * warmstart).as_slice(),
             0,
             ground_j_id,
             j_id,
@@ -168,7 +175,7 @@ impl<N: RealField> JointConstraint<N> for PrismaticConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.ang_impulses,
+            &(self.ang_impulses * warmstart),
             DIM - 1,
             ground_j_id,
             j_id,
@@ -194,7 +201,7 @@ impl<N: RealField> JointConstraint<N> for PrismaticConstraint<N> {
             self.min_offset,
             self.max_offset,
             ext_vels,
-            self.limit_impulse,
+            self.limit_impulse * warmstart,
             SPATIAL_DIM - 1,
             ground_j_id,
             j_id,
@@ -228,6 +235,22 @@ impl<N: RealField> JointConstraint<N> for PrismaticConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        let mut impulses: Vec<N> = self.lin_impulses.iter().chain(self.ang_impulses.iter()).cloned().collect();
+        impulses.push(self.limit_impulse);
+        impulses
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let nlin = self.lin_impulses.len();
+        let nang = self.ang_impulses.len();
+        self.lin_impulses.copy_from_slice(&impulses[..nlin]);
+        self.ang_impulses.copy_from_slice(&impulses[nlin..nlin + nang]);
+        self.limit_impulse = impulses[nlin + nang];
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for PrismaticConstraint<N> {
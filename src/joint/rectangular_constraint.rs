@@ -19,6 +19,8 @@ pub struct RectangularConstraint<N: RealField> {
     ang_impulses: Vector3<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> RectangularConstraint<N> {
@@ -43,8 +45,12 @@ impl<N: RealField> RectangularConstraint<N> {
             ang_impulses: Vector3::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for RectangularConstraint<N> {
@@ -58,7 +64,7 @@ impl<N: RealField> JointConstraint<N> for RectangularConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -84,6 +90,7 @@ impl<N: RealField> JointConstraint<N> for RectangularConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -101,7 +108,7 @@ impl<N: RealField> JointConstraint<N> for RectangularConstraint<N> {
             &anchor2,
             &axis1,
             ext_vels,
-            self.lin_impulse,
+            self.lin_impulse * warmstart,
             0,
             ground_j_id,
             j_id,
@@ -119,7 +126,7 @@ impl<N: RealField> JointConstraint<N> for RectangularConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.ang_impulses,
+            &(self.ang_impulses * warmstart),
             1,
             ground_j_id,
             j_id,
@@ -155,6 +162,19 @@ impl<N: RealField> JointConstraint<N> for RectangularConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        let mut impulses = vec![self.lin_impulse];
+        impulses.extend(self.ang_impulses.iter().cloned());
+        impulses
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.lin_impulse = impulses[0];
+        self.ang_impulses.copy_from_slice(&impulses[1..]);
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for RectangularConstraint<N> {
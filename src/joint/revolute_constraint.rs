@@ -1,16 +1,19 @@
-#[cfg(feature = "dim3")]
-use na::Unit;
-use na::{DVector, RealField};
+use na::{DVector, RealField, Unit};
 use std::ops::Range;
 
-use crate::joint::JointConstraint;
-use crate::math::{AngularVector, Point, Vector, DIM, SPATIAL_DIM};
+#[cfg(feature = "dim3")]
+use crate::joint::cylindrical_constraint::orthogonal_direction;
+use crate::joint::{unit_constraint, JointConstraint, JointMotor};
+use crate::math::{AngularVector, Isometry, Point, Vector, DIM, SPATIAL_DIM};
 use crate::object::{BodyPartHandle, BodySet};
 use crate::solver::helper;
 use crate::solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
              NonlinearConstraintGenerator};
 
 /// A constraint that removes all relative motions except the rotation between two body parts.
+///
+/// The rotation can be limited, and driven by a motor. The angle of rotation is zero when both anchor frames
+/// have the same orientation.
 #[cfg(feature = "dim2")]
 pub struct RevoluteConstraint<N: RealField> {
     b1: BodyPartHandle,
@@ -19,14 +22,28 @@ pub struct RevoluteConstraint<N: RealField> {
     anchor2: Point<N>,
     lin_impulses: Vector<N>,
     ang_impulses: AngularVector<N>,
+    angle_limit_impulse: N,
+    motor_impulse: N,
     // FIXME: not actually needed in 2D.
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
-    // min_angle: Option<N>,
-    // max_angle: Option<N>,
+    // The relative angle and angular velocity of the body parts at the beginning of the last timestep.
+    angle: N,
+    angular_velocity: N,
+
+    min_angle: Option<N>,
+    max_angle: Option<N>,
+    motor: JointMotor<N, N>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 /// A constraint that removes all relative motions except one rotation between two body parts.
+///
+/// The rotation can be limited, and driven by a motor. The angle of rotation is measured around the axis, from a
+/// direction orthogonal to `axis1` fixed to the first body part, to a direction orthogonal to `axis2` fixed to the
+/// second one. Those directions are chosen the same way for both axes, so if `axis1` and `axis2` are equal the angle
+/// is zero when both body parts have the same orientation.
 #[cfg(feature = "dim3")]
 pub struct RevoluteConstraint<N: RealField> {
     b1: BodyPartHandle,
@@ -35,12 +52,24 @@ pub struct RevoluteConstraint<N: RealField> {
     anchor2: Point<N>,
     axis1: Unit<AngularVector<N>>,
     axis2: Unit<AngularVector<N>>,
+    // The local directions, orthogonal to the axes, between which the angle of the joint is measured.
+    ref1: Unit<AngularVector<N>>,
+    ref2: Unit<AngularVector<N>>,
     lin_impulses: Vector<N>,
     ang_impulses: AngularVector<N>,
+    angle_limit_impulse: N,
+    motor_impulse: N,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
-    // min_angle: Option<N>,
-    // max_angle: Option<N>,
+    // The relative angle and angular velocity of the body parts at the beginning of the last timestep.
+    angle: N,
+    angular_velocity: N,
+
+    min_angle: Option<N>,
+    max_angle: Option<N>,
+    motor: JointMotor<N, N>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> RevoluteConstraint<N> {
@@ -56,8 +85,6 @@ impl<N: RealField> RevoluteConstraint<N> {
         anchor2: Point<N>,
         axis2: Unit<AngularVector<N>>,
     ) -> Self {
-        // let min_angle = None;
-        // let max_angle = None;
         RevoluteConstraint {
             b1,
             b2,
@@ -65,12 +92,21 @@ impl<N: RealField> RevoluteConstraint<N> {
             anchor2,
             axis1,
             axis2,
+            ref1: orthogonal_direction(&axis1),
+            ref2: orthogonal_direction(&axis2),
             lin_impulses: Vector::zeros(),
             ang_impulses: AngularVector::zeros(),
+            angle_limit_impulse: N::zero(),
+            motor_impulse: N::zero(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
-            // min_angle,
-            // max_angle,
+            angle: N::zero(),
+            angular_velocity: N::zero(),
+            min_angle: None,
+            max_angle: None,
+            motor: JointMotor::new(),
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
@@ -79,9 +115,6 @@ impl<N: RealField> RevoluteConstraint<N> {
     /// Both achors are expressed in the local coordinate system of the corresponding body parts.
     #[cfg(feature = "dim2")]
     pub fn new(b1: BodyPartHandle, b2: BodyPartHandle, anchor1: Point<N>, anchor2: Point<N>) -> Self {
-        // let min_angle = None;
-        // let max_angle = None;
-
         RevoluteConstraint {
             b1,
             b2,
@@ -89,51 +122,153 @@ impl<N: RealField> RevoluteConstraint<N> {
             anchor2,
             lin_impulses: Vector::zeros(),
             ang_impulses: AngularVector::zeros(),
+            angle_limit_impulse: N::zero(),
+            motor_impulse: N::zero(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
-            // min_angle,
-            // max_angle,
+            angle: N::zero(),
+            angular_velocity: N::zero(),
+            min_angle: None,
+            max_angle: None,
+            motor: JointMotor::new(),
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
 
-    // pub fn min_angle(&self) -> Option<N> {
-    //     self.min_angle
-    // }
+    /// The angle of the second body part relative to the first one, at the beginning of the last timestep.
+    ///
+    /// The angle is measured in `]-pi, pi]`.
+    pub fn angle(&self) -> N {
+        self.angle
+    }
+
+    /// The angular velocity, around the joint axis, of the second body part relative to the first one, at the
+    /// beginning of the last timestep.
+    pub fn angular_velocity(&self) -> N {
+        self.angular_velocity
+    }
+
+    /// The lower limit, if any, of the relative rotation of the body parts attached to this joint.
+    pub fn min_angle(&self) -> Option<N> {
+        self.min_angle
+    }
+
+    /// The upper limit, if any, of the relative rotation of the body parts attached to this joint.
+    pub fn max_angle(&self) -> Option<N> {
+        self.max_angle
+    }
+
+    /// Disable the lower limit of the relative rotational motion.
+    pub fn disable_min_angle(&mut self) {
+        self.min_angle = None;
+    }
+
+    /// Disable the upper limit of the relative rotational motion.
+    pub fn disable_max_angle(&mut self) {
+        self.max_angle = None;
+    }
+
+    /// Enables the lower limit of the relative rotational motion.
+    ///
+    /// The angle is measured in `]-pi, pi]`.
+    pub fn enable_min_angle(&mut self, limit: N) {
+        self.min_angle = Some(limit);
+        self.assert_limits();
+    }
+
+    /// Enables the upper limit of the relative rotational motion.
+    ///
+    /// The angle is measured in `]-pi, pi]`.
+    pub fn enable_max_angle(&mut self, limit: N) {
+        self.max_angle = Some(limit);
+        self.assert_limits();
+    }
+
+    /// Return `true` if the motor of this joint is enabled.
+    pub fn is_motor_enabled(&self) -> bool {
+        self.motor.enabled
+    }
+
+    /// Enable the motor of this joint.
+    ///
+    /// The motor will attempt to reach the relative angular velocity `desired_velocity` around the joint axis,
+    /// applying at most the torque `max_torque`.
+    pub fn enable_motor(&mut self, desired_velocity: N, max_torque: N) {
+        self.motor.desired_velocity = desired_velocity;
+        self.motor.max_force = max_torque;
+        self.motor.enabled = true;
+    }
 
-    // pub fn max_angle(&self) -> Option<N> {
-    //     self.max_angle
-    // }
+    /// Disable the motor of this joint.
+    pub fn disable_motor(&mut self) {
+        self.motor.enabled = false;
+    }
 
-    // pub fn disable_min_angle(&mut self) {
-    //     self.min_angle = None;
-    // }
+    /// The desired relative angular velocity, around the joint axis, of the motor.
+    pub fn desired_motor_velocity(&self) -> N {
+        self.motor.desired_velocity
+    }
 
-    // pub fn disable_max_angle(&mut self) {
-    //     self.max_angle = None;
-    // }
+    /// The maximum torque that can be delivered by the motor.
+    pub fn max_motor_torque(&self) -> N {
+        self.motor.max_force
+    }
 
-    // pub fn enable_min_angle(&mut self, limit: N) {
-    //     self.min_angle = Some(limit);
-    //     self.assert_limits();
-    // }
+    fn has_angle_limits(&self) -> bool {
+        self.min_angle.is_some() || self.max_angle.is_some()
+    }
 
-    // pub fn enable_max_angle(&mut self, limit: N) {
-    //     self.max_angle = Some(limit);
-    //     self.assert_limits();
-    // }
+    // The world-space rotation axis of the first body part.
+    #[cfg(feature = "dim2")]
+    fn world_axis1(&self, _: &Isometry<N>) -> Unit<AngularVector<N>> {
+        Unit::new_unchecked(AngularVector::x())
+    }
 
-    // fn assert_limits(&self) {
-    //     if let (Some(min_angle), Some(max_angle)) = (self.min_angle, self.max_angle) {
-    //         assert!(
-    //             min_angle <= max_angle,
-    //             "RevoluteJoint constraint limits: the min angle must be larger than (or equal to) the max angle.");
-    //     }
-    // }
+    // The world-space rotation axis of the first body part.
+    #[cfg(feature = "dim3")]
+    fn world_axis1(&self, pos1: &Isometry<N>) -> Unit<AngularVector<N>> {
+        pos1 * self.axis1
+    }
+
+    // The angle of the second body part relative to the first one.
+    #[cfg(feature = "dim2")]
+    fn relative_angle(&self, pos1: &Isometry<N>, pos2: &Isometry<N>, _: &Unit<AngularVector<N>>) -> N {
+        pos1.rotation.angle_to(&pos2.rotation)
+    }
+
+    // The angle of the second body part relative to the first one, around the world-space `axis1`.
+    #[cfg(feature = "dim3")]
+    fn relative_angle(&self, pos1: &Isometry<N>, pos2: &Isometry<N>, axis1: &Unit<AngularVector<N>>) -> N {
+        let ref1 = pos1 * self.ref1;
+        let ref2 = pos2 * self.ref2;
+        axis1.dot(&ref1.cross(&ref2)).atan2(ref1.dot(&ref2))
+    }
+
+    fn assert_limits(&self) {
+        if let (Some(min_angle), Some(max_angle)) = (self.min_angle, self.max_angle) {
+            assert!(
+                min_angle <= max_angle,
+                "Revolute constraint limits: the min angle must be smaller than (or equal to) the max angle.");
+        }
+    }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for RevoluteConstraint<N> {
     fn num_velocity_constraints(&self) -> usize {
-        SPATIAL_DIM - 1
+        let mut num = SPATIAL_DIM - 1;
+
+        if self.has_angle_limits() {
+            num += 1;
+        }
+
+        if self.motor.enabled {
+            num += 1;
+        }
+
+        num
     }
 
     fn anchors(&self) -> (BodyPartHandle, BodyPartHandle) {
@@ -142,7 +277,7 @@ impl<N: RealField> JointConstraint<N> for RevoluteConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -168,6 +303,7 @@ impl<N: RealField> JointConstraint<N> for RevoluteConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -182,7 +318,7 @@ impl<N: RealField> JointConstraint<N> for RevoluteConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.lin_impulses,
+            &(self.lin_impulses * warmstart),
             0,
             ground_j_id,
             j_id,
@@ -205,7 +341,7 @@ impl<N: RealField> JointConstraint<N> for RevoluteConstraint<N> {
                     &anchor1,
                     &anchor2,
                     ext_vels,
-                    self.ang_impulses.as_slice(),
+                    (self.ang_impulses * warmstart).as_slice(),
                     DIM,
                     ground_j_id,
                     j_id,
@@ -214,11 +350,63 @@ impl<N: RealField> JointConstraint<N> for RevoluteConstraint<N> {
                 );
             }
 
+        let axis1 = self.world_axis1(&pos1);
+        self.angle = self.relative_angle(&pos1, &pos2, &axis1);
+        self.angular_velocity =
+            axis1.dot(&(part2.velocity().angular_vector() - part1.velocity().angular_vector()));
+
         /*
          *
          * Limit constraints.
          *
          */
+        unit_constraint::build_angular_limits_velocity_constraint(
+            body1,
+            part1,
+            body2,
+            part2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &axis1,
+            self.angle,
+            self.min_angle,
+            self.max_angle,
+            ext_vels,
+            self.angle_limit_impulse * warmstart,
+            SPATIAL_DIM - 1,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        /*
+         *
+         * Motor constraints.
+         *
+         */
+        unit_constraint::build_angular_motor_velocity_constraint(
+            params,
+            body1,
+            part1,
+            body2,
+            part2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &axis1,
+            &self.motor,
+            ext_vels,
+            self.motor_impulse * warmstart,
+            SPATIAL_DIM,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
 
         self.bilateral_ground_rng =
             first_bilateral_ground..constraints.velocity.bilateral_ground.len();
@@ -226,33 +414,66 @@ impl<N: RealField> JointConstraint<N> for RevoluteConstraint<N> {
     }
 
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        // The first `DIM` impulses are linear, the next `SPATIAL_DIM - DIM - 1` are angular, and the last two are
+        // the limit and the motor.
+        self.angle_limit_impulse = N::zero();
+        self.motor_impulse = N::zero();
+
         for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
             if c.impulse_id < DIM {
                 self.lin_impulses[c.impulse_id] = c.impulse;
-            } else {
+            } else if c.impulse_id < SPATIAL_DIM - 1 {
                 self.ang_impulses[c.impulse_id - DIM] = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM - 1 {
+                self.angle_limit_impulse = c.impulse;
+            } else {
+                self.motor_impulse = c.impulse;
             }
         }
 
         for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
             if c.impulse_id < DIM {
                 self.lin_impulses[c.impulse_id] = c.impulse;
-            } else {
+            } else if c.impulse_id < SPATIAL_DIM - 1 {
                 self.ang_impulses[c.impulse_id - DIM] = c.impulse;
+            } else if c.impulse_id == SPATIAL_DIM - 1 {
+                self.angle_limit_impulse = c.impulse;
+            } else {
+                self.motor_impulse = c.impulse;
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        let mut impulses: Vec<N> = self.lin_impulses.iter().chain(self.ang_impulses.iter()).cloned().collect();
+        impulses.push(self.angle_limit_impulse);
+        impulses.push(self.motor_impulse);
+        impulses
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let nlin = self.lin_impulses.len();
+        let nang = self.ang_impulses.len();
+        self.lin_impulses.copy_from_slice(&impulses[..nlin]);
+        self.ang_impulses.copy_from_slice(&impulses[nlin..nlin + nang]);
+        self.angle_limit_impulse = impulses[nlin + nang];
+        self.motor_impulse = impulses[nlin + nang + 1];
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for RevoluteConstraint<N> {
     fn num_position_constraints(&self, bodies: &BodySet<N>) -> usize {
         // FIXME: calling this at each iteration of the non-linear resolution is costly.
         if self.is_active(bodies) {
-            if DIM == 3 {
-                2
-            } else {
-                1
+            let mut num = if DIM == 3 { 2 } else { 1 };
+
+            if self.has_angle_limits() {
+                num += 1;
             }
+
+            num
         } else {
             0
         }
@@ -310,6 +531,89 @@ impl<N: RealField> NonlinearConstraintGenerator<N> for RevoluteConstraint<N> {
                 }
             }
 
+        if self.has_angle_limits() {
+            let axis1 = self.world_axis1(&pos1);
+            let angle = self.relative_angle(&pos1, &pos2, &axis1);
+
+            return unit_constraint::build_angular_limits_position_constraint(
+                params,
+                body1,
+                part1,
+                body2,
+                part2,
+                &anchor1,
+                &anchor2,
+                &axis1,
+                angle,
+                self.min_angle,
+                self.max_angle,
+                jacobians,
+            );
+        }
+
         return None;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::joint::RevoluteConstraint;
+    use crate::math::{Inertia, Point};
+    #[cfg(feature = "dim3")]
+    use crate::math::Vector;
+    use crate::object::{BodyPartHandle, RigidBodyDesc};
+    use crate::world::World;
+
+    #[cfg(feature = "dim2")]
+    fn hinge(body: BodyPartHandle) -> RevoluteConstraint<f64> {
+        RevoluteConstraint::new(BodyPartHandle::ground(), body, Point::origin(), Point::origin())
+    }
+
+    #[cfg(feature = "dim3")]
+    fn hinge(body: BodyPartHandle) -> RevoluteConstraint<f64> {
+        RevoluteConstraint::new(
+            BodyPartHandle::ground(),
+            body,
+            Point::origin(),
+            Vector::z_axis(),
+            Point::origin(),
+            Vector::z_axis(),
+        )
+    }
+
+    #[test]
+    fn motor_drives_body_until_the_angle_limit() {
+        let mut world = World::<f64>::new();
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .build(&mut world)
+            .part_handle();
+
+        let mut constraint = hinge(body);
+        constraint.enable_motor(2.0, 10.0);
+        constraint.enable_min_angle(-0.5);
+        constraint.enable_max_angle(1.0);
+        let handle = world.add_constraint(constraint);
+
+        // The motor reaches its desired velocity in a fifth of a second, long before the limit.
+        for _ in 0..20 {
+            world.step();
+        }
+
+        let constraint = world.constraint(handle).downcast_ref::<RevoluteConstraint<f64>>().unwrap();
+        assert!(constraint.is_motor_enabled());
+        assert_relative_eq!(constraint.angular_velocity(), 2.0, epsilon = 1.0e-3);
+        assert!(constraint.angle() > 0.3 && constraint.angle() < 0.6, "angle: {}", constraint.angle());
+
+        // The motor then pushes the body against the upper limit.
+        for _ in 0..100 {
+            world.step();
+            let constraint = world.constraint(handle).downcast_ref::<RevoluteConstraint<f64>>().unwrap();
+            assert!(constraint.angle() < 1.0 + 5.0e-2, "angle: {}", constraint.angle());
+        }
+
+        let constraint = world.constraint(handle).downcast_ref::<RevoluteConstraint<f64>>().unwrap();
+        assert_relative_eq!(constraint.angle(), 1.0, epsilon = 1.0e-2);
+        assert_relative_eq!(constraint.angular_velocity(), 0.0, epsilon = 1.0e-2);
+    }
+}
@@ -0,0 +1,261 @@
+use std::ops::Range;
+use na::{DVector, RealField, Unit};
+
+use crate::joint::JointConstraint;
+use crate::math::Point;
+use crate::object::{BodyPartHandle, BodySet};
+use crate::solver::{helper, BilateralConstraint, BilateralGroundConstraint, ForceDirection, ImpulseLimits};
+use crate::solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
+             NonlinearConstraintGenerator};
+
+/// A damped spring pulling two points of two body parts toward a rest distance.
+///
+/// Unlike the other joint constraints, this generates a soft constraint: the spring stretches
+/// under load, with an elastic force proportional to its `stiffness`, and the relative velocity
+/// of its anchors along its direction is damped proportionally to its `damping`. The constraint
+/// is solved implicitly, so it remains stable even for very stiff springs.
+pub struct SpringConstraint<N: RealField> {
+    b1: BodyPartHandle,
+    b2: BodyPartHandle,
+    anchor1: Point<N>,
+    anchor2: Point<N>,
+    rest_length: N,
+    stiffness: N,
+    damping: N,
+    impulse: N,
+    bilateral_ground_rng: Range<usize>,
+    bilateral_rng: Range<usize>,
+}
+
+impl<N: RealField> SpringConstraint<N> {
+    /// Creates a spring between two body parts.
+    ///
+    /// The spring links the points identified by `anchor1` and `anchor2`, given in the local-space of their
+    /// corresponding body part. Its `stiffness` is in force per unit length, and its `damping` in force
+    /// per unit velocity.
+    pub fn new(
+        b1: BodyPartHandle,
+        b2: BodyPartHandle,
+        anchor1: Point<N>,
+        anchor2: Point<N>,
+        rest_length: N,
+        stiffness: N,
+        damping: N,
+    ) -> Self {
+        SpringConstraint {
+            b1,
+            b2,
+            anchor1,
+            anchor2,
+            rest_length,
+            stiffness,
+            damping,
+            impulse: N::zero(),
+            bilateral_ground_rng: 0..0,
+            bilateral_rng: 0..0,
+        }
+    }
+
+    /// Change the first anchor, expressed in the local space of the first body part.
+    pub fn set_anchor_1(&mut self, anchor1: Point<N>) {
+        self.anchor1 = anchor1;
+    }
+
+    /// Change the second anchor, expressed in the local space of the second body part.
+    pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
+        self.anchor2 = anchor2;
+    }
+
+    /// The distance between the anchors at which the spring does not apply any elastic force.
+    pub fn rest_length(&self) -> N {
+        self.rest_length
+    }
+
+    /// Sets the distance between the anchors at which the spring does not apply any elastic force.
+    pub fn set_rest_length(&mut self, rest_length: N) {
+        self.rest_length = rest_length
+    }
+
+    /// The stiffness of the spring.
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+
+    /// Sets the stiffness of the spring.
+    pub fn set_stiffness(&mut self, stiffness: N) {
+        self.stiffness = stiffness
+    }
+
+    /// The damping of the spring.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the damping of the spring.
+    pub fn set_damping(&mut self, damping: N) {
+        self.damping = damping
+    }
+}
+
+impl<N: RealField> JointConstraint<N> for SpringConstraint<N> {
+    fn num_velocity_constraints(&self) -> usize {
+        1
+    }
+
+    fn anchors(&self) -> (BodyPartHandle, BodyPartHandle) {
+        (self.b1, self.b2)
+    }
+
+    fn velocity_constraints(
+        &mut self,
+        params: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        self.bilateral_ground_rng = 0..0;
+        self.bilateral_rng = 0..0;
+
+        let body1 = try_ret!(bodies.body(self.b1.0));
+        let body2 = try_ret!(bodies.body(self.b2.0));
+        let part1 = try_ret!(body1.part(self.b1.1));
+        let part2 = try_ret!(body2.part(self.b2.1));
+
+        let anchor1 = body1.world_point_at_material_point(part1, &self.anchor1);
+        let anchor2 = body2.world_point_at_material_point(part2, &self.anchor2);
+        // The spring does not have any direction if its anchors coincide.
+        let (dir, length) = try_ret!(Unit::try_new_and_get(anchor2 - anchor1, N::default_epsilon()));
+
+        // The soft constraint formulation of a damped spring, see, e.g., "Soft Constraints" by Erin Catto, GDC 2011.
+        let dt = params.dt;
+        let softness = dt * (self.damping + dt * self.stiffness);
+
+        if softness.is_zero() {
+            return;
+        }
+
+        let cfm = N::one() / softness;
+        let bias = -(length - self.rest_length) * dt * self.stiffness * cfm;
+
+        let assembly_id1 = body1.companion_id();
+        let assembly_id2 = body2.companion_id();
+        let (ext_vels1, ext_vels2) = helper::split_ext_vels(body1, body2, assembly_id1, assembly_id2, ext_vels);
+
+        let limits = ImpulseLimits::Independent {
+            min: -N::max_value(),
+            max: N::max_value(),
+        };
+        let mut rhs = bias;
+        let mut geom = helper::constraint_pair_geometry(
+            body1,
+            part1,
+            body2,
+            part2,
+            &anchor1,
+            &anchor2,
+            &ForceDirection::Linear(dir),
+            ground_j_id,
+            j_id,
+            jacobians,
+            Some(&ext_vels1),
+            Some(&ext_vels2),
+            Some(&mut rhs)
+        );
+        geom.r = N::one() / (N::one() / geom.r + cfm);
+        let warmstart = self.impulse * params.warmstart_coeff;
+
+        if geom.is_ground_constraint() {
+            let first = constraints.velocity.bilateral_ground.len();
+            let mut constraint =
+                BilateralGroundConstraint::new(geom, assembly_id1, assembly_id2, limits, rhs, warmstart, 0);
+            constraint.cfm = cfm;
+            constraints.velocity.bilateral_ground.push(constraint);
+            self.bilateral_ground_rng = first..first + 1;
+        } else {
+            let first = constraints.velocity.bilateral.len();
+            let mut constraint =
+                BilateralConstraint::new(geom, assembly_id1, assembly_id2, limits, rhs, warmstart, 0);
+            constraint.cfm = cfm;
+            constraints.velocity.bilateral.push(constraint);
+            self.bilateral_rng = first..first + 1;
+        }
+    }
+
+    fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        self.impulse = N::zero();
+
+        for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
+            self.impulse = c.impulse;
+        }
+
+        for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
+            self.impulse = c.impulse;
+        }
+    }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        vec![self.impulse]
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.impulse = impulses[0];
+    }
+}
+
+impl<N: RealField> NonlinearConstraintGenerator<N> for SpringConstraint<N> {
+    fn num_position_constraints(&self, _: &BodySet<N>) -> usize {
+        0
+    }
+
+    fn position_constraint(
+        &self,
+        _: &IntegrationParameters<N>,
+        _: usize,
+        _: &mut BodySet<N>,
+        _: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::joint::SpringConstraint;
+    use crate::math::{Inertia, Point, Vector};
+    use crate::object::{BodyPartHandle, RigidBodyDesc};
+    use crate::world::World;
+
+    #[test]
+    fn hanging_body_reaches_equilibrium_stretch() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(2.0, na::one()))
+            .translation(Vector::y() * -1.0)
+            .build(&mut world);
+        let handle = body.handle();
+
+        let spring = SpringConstraint::new(
+            BodyPartHandle::ground(),
+            body.part_handle(),
+            Point::origin(),
+            Point::origin(),
+            1.0,
+            100.0,
+            10.0,
+        );
+        let _ = world.add_constraint(spring);
+
+        for _ in 0..300 {
+            world.step();
+        }
+
+        // At rest, the elastic force balances the weight: k * stretch = m * g.
+        let expected = -1.0 - 2.0 * 9.81 / 100.0;
+        let y = world.rigid_body(handle).unwrap().position().translation.vector.y;
+        assert_relative_eq!(y, expected, epsilon = 1.0e-3);
+    }
+}
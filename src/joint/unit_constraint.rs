@@ -1,6 +1,7 @@
 use na::{DVector, RealField, Unit};
 
-use crate::math::{Point, Vector};
+use crate::joint::JointMotor;
+use crate::math::{AngularVector, Point, Vector};
 use crate::object::{Body, BodyPart};
 use crate::solver::{helper, BilateralConstraint, BilateralGroundConstraint, ConstraintSet,
              ForceDirection, GenericNonlinearConstraint, ImpulseLimits, IntegrationParameters};
@@ -27,18 +28,110 @@ pub fn build_linear_limits_velocity_constraint<N: RealField>(
 ) {
     let offset = axis.dot(&(anchor2 - anchor1));
 
-    let (unilateral, dir) = match (min, max) {
+    build_limits_velocity_constraint(
+        body1,
+        part1,
+        body2,
+        part2,
+        assembly_id1,
+        assembly_id2,
+        anchor1,
+        anchor2,
+        ForceDirection::Linear(*axis),
+        offset,
+        min,
+        max,
+        ext_vels,
+        impulse,
+        impulse_id,
+        ground_j_id,
+        j_id,
+        jacobians,
+        constraints,
+    )
+}
+
+/// Generates a velocity constraint preventing the relative angle, wrt. `axis`, of two body parts to exceed its limits.
+///
+/// The `angle` of the second body part relative to the first one is measured counterclockwise around `axis`.
+pub fn build_angular_limits_velocity_constraint<N: RealField>(
+    body1: &Body<N>,
+    part1: &BodyPart<N>,
+    body2: &Body<N>,
+    part2: &BodyPart<N>,
+    assembly_id1: usize,
+    assembly_id2: usize,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    axis: &Unit<AngularVector<N>>,
+    angle: N,
+    min: Option<N>,
+    max: Option<N>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    build_limits_velocity_constraint(
+        body1,
+        part1,
+        body2,
+        part2,
+        assembly_id1,
+        assembly_id2,
+        anchor1,
+        anchor2,
+        ForceDirection::Angular(*axis),
+        angle,
+        min,
+        max,
+        ext_vels,
+        impulse,
+        impulse_id,
+        ground_j_id,
+        j_id,
+        jacobians,
+        constraints,
+    )
+}
+
+// Generates the limit constraint for an `offset` of the second body part relative to the first one along `dir`.
+fn build_limits_velocity_constraint<N: RealField>(
+    body1: &Body<N>,
+    part1: &BodyPart<N>,
+    body2: &Body<N>,
+    part2: &BodyPart<N>,
+    assembly_id1: usize,
+    assembly_id2: usize,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    dir: ForceDirection<N>,
+    offset: N,
+    min: Option<N>,
+    max: Option<N>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let (unilateral, force) = match (min, max) {
         (None, None) => {
             return;
         }
         (Some(min), Some(max)) => {
             if relative_eq!(min, max) {
-                (false, *axis)
+                (false, dir)
             } else {
                 if offset <= min {
-                    (true, -*axis)
+                    (true, -dir)
                 } else if offset >= max {
-                    (true, *axis)
+                    (true, dir)
                 } else {
                     return;
                 }
@@ -46,14 +139,14 @@ pub fn build_linear_limits_velocity_constraint<N: RealField>(
         }
         (Some(min), None) => {
             if offset <= min {
-                (true, -*axis)
+                (true, -dir)
             } else {
                 return;
             }
         }
         (None, Some(max)) => {
             if offset >= max {
-                (true, *axis)
+                (true, dir)
             } else {
                 return;
             }
@@ -61,7 +154,6 @@ pub fn build_linear_limits_velocity_constraint<N: RealField>(
     };
 
     let (ext_vels1, ext_vels2) = helper::split_ext_vels(body1, body2, assembly_id1, assembly_id2, ext_vels);
-    let force = ForceDirection::Linear(dir);
     let mut rhs = N::zero();
     let geom = helper::constraint_pair_geometry(
         body1,
@@ -121,6 +213,176 @@ pub fn build_linear_limits_velocity_constraint<N: RealField>(
     }
 }
 
+/// Generates a velocity constraint driving the relative velocity, along `axis`, of two body parts toward the
+/// desired velocity of `motor`.
+pub fn build_linear_motor_velocity_constraint<N: RealField>(
+    params: &IntegrationParameters<N>,
+    body1: &Body<N>,
+    part1: &BodyPart<N>,
+    body2: &Body<N>,
+    part2: &BodyPart<N>,
+    assembly_id1: usize,
+    assembly_id2: usize,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    axis: &Unit<Vector<N>>,
+    motor: &JointMotor<N, N>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    build_motor_velocity_constraint(
+        params,
+        body1,
+        part1,
+        body2,
+        part2,
+        assembly_id1,
+        assembly_id2,
+        anchor1,
+        anchor2,
+        ForceDirection::Linear(*axis),
+        motor,
+        ext_vels,
+        impulse,
+        impulse_id,
+        ground_j_id,
+        j_id,
+        jacobians,
+        constraints,
+    )
+}
+
+/// Generates a velocity constraint driving the relative angular velocity, wrt. `axis`, of two body parts toward
+/// the desired velocity of `motor`.
+pub fn build_angular_motor_velocity_constraint<N: RealField>(
+    params: &IntegrationParameters<N>,
+    body1: &Body<N>,
+    part1: &BodyPart<N>,
+    body2: &Body<N>,
+    part2: &BodyPart<N>,
+    assembly_id1: usize,
+    assembly_id2: usize,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    axis: &Unit<AngularVector<N>>,
+    motor: &JointMotor<N, N>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    build_motor_velocity_constraint(
+        params,
+        body1,
+        part1,
+        body2,
+        part2,
+        assembly_id1,
+        assembly_id2,
+        anchor1,
+        anchor2,
+        ForceDirection::Angular(*axis),
+        motor,
+        ext_vels,
+        impulse,
+        impulse_id,
+        ground_j_id,
+        j_id,
+        jacobians,
+        constraints,
+    )
+}
+
+// Generates the motor constraint for the velocity of the second body part relative to the first one along `dir`.
+fn build_motor_velocity_constraint<N: RealField>(
+    params: &IntegrationParameters<N>,
+    body1: &Body<N>,
+    part1: &BodyPart<N>,
+    body2: &Body<N>,
+    part2: &BodyPart<N>,
+    assembly_id1: usize,
+    assembly_id2: usize,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    dir: ForceDirection<N>,
+    motor: &JointMotor<N, N>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    if !motor.enabled {
+        return;
+    }
+
+    let (ext_vels1, ext_vels2) = helper::split_ext_vels(body1, body2, assembly_id1, assembly_id2, ext_vels);
+    let mut rhs = N::zero();
+    let geom = helper::constraint_pair_geometry(
+        body1,
+        part1,
+        body2,
+        part2,
+        anchor1,
+        anchor2,
+        &dir,
+        ground_j_id,
+        j_id,
+        jacobians,
+        Some(&ext_vels1),
+        Some(&ext_vels2),
+        Some(&mut rhs)
+    );
+
+    // The jacobian measures the velocity of the first body part relative to the second one.
+    rhs += motor.desired_velocity;
+
+    // The motor force is bounded so the impulse it applies during one timestep is bounded too.
+    let max_impulse = motor.max_force * params.dt;
+    let limits = ImpulseLimits::Independent {
+        min: -max_impulse,
+        max: max_impulse,
+    };
+
+    if geom.ndofs1 == 0 || geom.ndofs2 == 0 {
+        constraints
+            .velocity
+            .bilateral_ground
+            .push(BilateralGroundConstraint::new(
+                geom,
+                assembly_id1,
+                assembly_id2,
+                limits,
+                rhs,
+                impulse,
+                impulse_id,
+            ));
+    } else {
+        constraints
+            .velocity
+            .bilateral
+            .push(BilateralConstraint::new(
+                geom,
+                assembly_id1,
+                assembly_id2,
+                limits,
+                rhs,
+                impulse,
+                impulse_id,
+            ));
+    }
+}
+
 pub fn build_linear_limits_position_constraint<N: RealField>(
     params: &IntegrationParameters<N>,
     body1: &Body<N>,
@@ -135,22 +397,96 @@ pub fn build_linear_limits_position_constraint<N: RealField>(
     jacobians: &mut [N],
 ) -> Option<GenericNonlinearConstraint<N>> {
     let offset = axis.dot(&(anchor2 - anchor1));
+
+    build_limits_position_constraint(
+        params,
+        body1,
+        part1,
+        body2,
+        part2,
+        anchor1,
+        anchor2,
+        ForceDirection::Linear(*axis),
+        offset,
+        min,
+        max,
+        jacobians,
+    )
+}
+
+/// Generates a position constraint correcting the relative angle, wrt. `axis`, of two body parts exceeding its limits.
+///
+/// The `angle` of the second body part relative to the first one is measured counterclockwise around `axis`.
+pub fn build_angular_limits_position_constraint<N: RealField>(
+    params: &IntegrationParameters<N>,
+    body1: &Body<N>,
+    part1: &BodyPart<N>,
+    body2: &Body<N>,
+    part2: &BodyPart<N>,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    axis: &Unit<AngularVector<N>>,
+    angle: N,
+    min: Option<N>,
+    max: Option<N>,
+    jacobians: &mut [N],
+) -> Option<GenericNonlinearConstraint<N>> {
+    // NOTE: the max limit is only checked when there is a min limit, so a lower bound smaller than any
+    // angle, i.e., smaller than `-pi`, is used when there is none.
+    let min = min.unwrap_or_else(|| -N::two_pi());
+
+    build_limits_position_constraint(
+        params,
+        body1,
+        part1,
+        body2,
+        part2,
+        anchor1,
+        anchor2,
+        ForceDirection::Angular(*axis),
+        angle,
+        Some(min),
+        max,
+        jacobians,
+    )
+}
+
+// Generates the limit constraint for an `offset` of the second body part relative to the first one along `axis`.
+fn build_limits_position_constraint<N: RealField>(
+    params: &IntegrationParameters<N>,
+    body1: &Body<N>,
+    part1: &BodyPart<N>,
+    body2: &Body<N>,
+    part2: &BodyPart<N>,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    axis: ForceDirection<N>,
+    offset: N,
+    min: Option<N>,
+    max: Option<N>,
+    jacobians: &mut [N],
+) -> Option<GenericNonlinearConstraint<N>> {
     let mut error = N::zero();
-    let mut dir = *axis;
+    let mut dir = axis;
 
     if let Some(min) = min {
         error = min - offset;
-        dir = -*axis;
+        dir = -axis;
     }
 
     if error < N::zero() {
         if let Some(max) = max {
             error = offset - max;
-            dir = *axis;
+            dir = axis;
         }
     }
 
-    if error > params.allowed_linear_error {
+    let (is_angular, allowed_error) = match axis {
+        ForceDirection::Linear(_) => (false, params.allowed_linear_error),
+        ForceDirection::Angular(_) => (true, params.allowed_angular_error),
+    };
+
+    if error > allowed_error {
         let mut j_id = 0;
         let mut ground_j_id = 0;
 
@@ -161,7 +497,7 @@ pub fn build_linear_limits_position_constraint<N: RealField>(
             part2,
             anchor1,
             anchor2,
-            &ForceDirection::Linear(dir),
+            &dir,
             &mut ground_j_id,
             &mut j_id,
             jacobians,
@@ -174,7 +510,7 @@ pub fn build_linear_limits_position_constraint<N: RealField>(
         let constraint = GenericNonlinearConstraint::new(
             part1.part_handle(),
             part2.part_handle(),
-            false,
+            is_angular,
             geom.ndofs1,
             geom.ndofs2,
             geom.wj_id1,
@@ -188,28 +524,3 @@ pub fn build_linear_limits_position_constraint<N: RealField>(
         None
     }
 }
-
-/*
-pub fn build_angular_limit_velocity_constraint<N: RealField>(
-    params: &IntegrationParameters<N>,
-    body1: &BodyPart<N>,
-    body2: &BodyPart<N>,
-    pos1: &Isometry<N>,
-    pos2: &Isometry<N>,
-    assembly_id1: usize,
-    assembly_id2: usize,
-    anchor1: &Point<N>,
-    anchor2: &Point<N>,
-    axis: &Unit<Vector<N>>,
-    min: Option<N>,
-    max: Option<N>,
-    ext_vels: &DVector<N>,
-    impulse: N,
-    impulse_id: usize,
-    ground_j_id: &mut usize,
-    j_id: &mut usize,
-    jacobians: &mut [N],
-    constraints: &mut ConstraintSet<N>,
-) {
-}
-*/
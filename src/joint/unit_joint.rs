@@ -76,6 +76,7 @@ pub fn unit_joint_velocity_constraints<N: RealField, J: UnitJoint<N>>(
             impulse: impulses[impulse_id] * params.warmstart_coeff,
             r: N::one() / inv_r,
             rhs,
+            cfm: N::zero(),
             limits,
             impulse_id,
             assembly_id,
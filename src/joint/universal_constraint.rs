@@ -21,6 +21,8 @@ pub struct UniversalConstraint<N: RealField> {
     ang_impulse: N,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    allowed_linear_error: Option<N>,
+    allowed_angular_error: Option<N>,
 }
 
 impl<N: RealField> UniversalConstraint<N> {
@@ -48,8 +50,12 @@ impl<N: RealField> UniversalConstraint<N> {
             ang_impulse: N::zero(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            allowed_linear_error: None,
+            allowed_angular_error: None,
         }
     }
+
+    joint_allowed_errors_methods!();
 }
 
 impl<N: RealField> JointConstraint<N> for UniversalConstraint<N> {
@@ -63,7 +69,7 @@ impl<N: RealField> JointConstraint<N> for UniversalConstraint<N> {
 
     fn velocity_constraints(
         &mut self,
-        _: &IntegrationParameters<N>,
+        params: &IntegrationParameters<N>,
         bodies: &BodySet<N>,
         ext_vels: &DVector<N>,
         ground_j_id: &mut usize,
@@ -89,6 +95,7 @@ impl<N: RealField> JointConstraint<N> for UniversalConstraint<N> {
 
         let assembly_id1 = body1.companion_id();
         let assembly_id2 = body2.companion_id();
+        let warmstart = params.warmstart_coeff;
 
         let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
         let first_bilateral = constraints.velocity.bilateral.len();
@@ -103,7 +110,7 @@ impl<N: RealField> JointConstraint<N> for UniversalConstraint<N> {
             &anchor1,
             &anchor2,
             ext_vels,
-            &self.lin_impulses,
+            &(self.lin_impulses * warmstart),
             0,
             ground_j_id,
             j_id,
@@ -125,7 +132,7 @@ impl<N: RealField> JointConstraint<N> for UniversalConstraint<N> {
                 &anchor2,
                 &orth,
                 ext_vels,
-                self.ang_impulse,
+                self.ang_impulse * warmstart,
                 DIM,
                 ground_j_id,
                 j_id,
@@ -162,6 +169,20 @@ impl<N: RealField> JointConstraint<N> for UniversalConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        let mut impulses = self.lin_impulses.as_slice().to_vec();
+        impulses.push(self.ang_impulse);
+        impulses
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let nlin = self.lin_impulses.len();
+        self.lin_impulses.copy_from_slice(&impulses[..nlin]);
+        self.ang_impulse = impulses[nlin];
+    }
+
+    joint_allowed_errors_methods!(JointConstraint);
 }
 
 impl<N: RealField> NonlinearConstraintGenerator<N> for UniversalConstraint<N> {
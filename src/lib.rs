@@ -88,6 +88,11 @@ extern crate ncollide3d as ncollide;
 extern crate num_traits as num;
 extern crate slab;
 extern crate either;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "serde-serialize")]
+#[macro_use]
+extern crate serde;
 
 /*
  * The two following crates are pulled-in for
@@ -1,6 +1,6 @@
 use na::RealField;
 
-use crate::material::{Material, MaterialCombineMode, MaterialContext, LocalMaterialProperties};
+use crate::material::{Material, MaterialCombineMode, MaterialContext, LocalMaterialProperties, SurfaceTag};
 use crate::math::Vector;
 
 
@@ -16,8 +16,24 @@ pub struct BasicMaterial<N: RealField> {
     pub id: Option<u32>,
     /// Restitution coefficient of the surface.
     pub restitution: N,
-    /// Friction coefficient of the surface.
+    /// The factor the restitution coefficient is multiplied by after each bounce on the same collider.
+    ///
+    /// Set this to a value smaller than 1 to make the bounces decay faster than with a constant restitution.
+    pub restitution_decay: N,
+    /// Dynamic friction coefficient of the surface, used at contacts that are sliding.
     pub friction: N,
+    /// Static friction coefficient of the surface, used at contacts that are not sliding.
+    ///
+    /// If `None`, the dynamic friction coefficient `self.friction` is used instead.
+    pub static_friction: Option<N>,
+    /// The tangential velocity below which a contact is considered not sliding, i.e., uses the static friction coefficient.
+    ///
+    /// If `None`, the `static_friction_velocity_threshold` of the integration parameters is used instead.
+    pub static_friction_velocity_threshold: Option<N>,
+    /// Rolling friction coefficient of the surface, in units of length.
+    ///
+    /// The torque resisting the rolling of a body on this surface is this coefficient multiplied by the normal force.
+    pub rolling_friction: N,
     /// The fictitious velocity at the surface of this material.
     pub surface_velocity: Option<Vector<N>>,
     /// The way restitution coefficients are combined if no match
@@ -26,6 +42,8 @@ pub struct BasicMaterial<N: RealField> {
     /// The way friction coefficients are combined if no match
     /// was found in the material lookup tables.
     pub friction_combine_mode: MaterialCombineMode,
+    /// The tag identifying the kind of surface of this material, reported with the contacts.
+    pub surface_tag: Option<SurfaceTag>,
 }
 
 
@@ -35,12 +53,48 @@ impl<N: RealField> BasicMaterial<N> {
         BasicMaterial {
             id: None,
             restitution,
+            restitution_decay: N::one(),
             friction,
+            static_friction: None,
+            static_friction_velocity_threshold: None,
+            rolling_friction: N::zero(),
             surface_velocity: None,
             restitution_combine_mode: MaterialCombineMode::Average,
-            friction_combine_mode: MaterialCombineMode::Average
+            friction_combine_mode: MaterialCombineMode::Average,
+            surface_tag: None,
         }
     }
+
+    /// Sets the static friction coefficient of this material, used at contacts with a tangential velocity smaller than `velocity_threshold`.
+    pub fn with_static_friction(mut self, static_friction: N, velocity_threshold: N) -> Self {
+        self.static_friction = Some(static_friction);
+        self.static_friction_velocity_threshold = Some(velocity_threshold);
+        self
+    }
+
+    /// Sets the rolling friction coefficient of this material.
+    pub fn with_rolling_friction(mut self, rolling_friction: N) -> Self {
+        self.rolling_friction = rolling_friction;
+        self
+    }
+
+    /// Sets the way the friction coefficients of this material and another material are combined.
+    pub fn with_friction_combine_mode(mut self, mode: MaterialCombineMode) -> Self {
+        self.friction_combine_mode = mode;
+        self
+    }
+
+    /// Sets the way the restitution coefficients of this material and another material are combined.
+    pub fn with_restitution_combine_mode(mut self, mode: MaterialCombineMode) -> Self {
+        self.restitution_combine_mode = mode;
+        self
+    }
+
+    /// Sets the tag identifying the kind of surface of this material.
+    pub fn with_surface_tag(mut self, tag: SurfaceTag) -> Self {
+        self.surface_tag = Some(tag);
+        self
+    }
 }
 
 impl<N: RealField> Material<N> for BasicMaterial<N> {
@@ -48,10 +102,18 @@ impl<N: RealField> Material<N> for BasicMaterial<N> {
         LocalMaterialProperties {
             id: self.id,
             restitution: (self.restitution, self.restitution_combine_mode),
+            restitution_decay: self.restitution_decay,
             friction: (self.friction, self.friction_combine_mode),
+            static_friction: (self.static_friction.unwrap_or(self.friction), self.friction_combine_mode),
+            static_friction_velocity_threshold: self.static_friction_velocity_threshold,
+            rolling_friction: (self.rolling_friction, self.friction_combine_mode),
             surface_velocity: self.surface_velocity.map(|v| context.collider.position() * v).unwrap_or(Vector::zeros()),
         }
     }
+
+    fn surface_tag(&self) -> Option<SurfaceTag> {
+        self.surface_tag
+    }
 }
 
 impl<N: RealField> Default for BasicMaterial<N> {
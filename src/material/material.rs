@@ -4,7 +4,7 @@ use std::ops::Deref;
 use na::{self, RealField};
 
 use ncollide::query::TrackedContact;
-use crate::object::{Body, BodyPart, Collider};
+use crate::object::{Body, BodyPart, Collider, RigidBody};
 use crate::material::MaterialsCoefficientsTable;
 use crate::math::Vector;
 
@@ -37,6 +37,18 @@ impl<'a, N: RealField> MaterialContext<'a, N> {
             is_first
         }
     }
+
+    // The tangential part of the surface velocity of the rigid body involved in the contact, if any.
+    fn body_surface_velocity(&self) -> Vector<N> {
+        match self.body.downcast_ref::<RigidBody<N>>() {
+            Some(rb) => {
+                let velocity = rb.position() * rb.surface_velocity();
+                let normal = self.contact.contact.normal;
+                velocity - *normal * normal.dot(&velocity)
+            }
+            None => Vector::zeros(),
+        }
+    }
 }
 
 /// The way the friction and restitution coefficients of two materials should be combined.
@@ -82,10 +94,18 @@ impl MaterialCombineMode {
 pub struct LocalMaterialProperties<N: RealField> {
     /// The optional material identifier used for pairwise material coefficient lookup table.
     pub id: Option<MaterialId>,
-    /// The friction coefficient and its combination mode.
+    /// The dynamic friction coefficient and its combination mode.
     pub friction: (N, MaterialCombineMode),
+    /// The static friction coefficient and its combination mode.
+    pub static_friction: (N, MaterialCombineMode),
+    /// The tangential velocity below which the static friction coefficient is used, if it is not the default one.
+    pub static_friction_velocity_threshold: Option<N>,
+    /// The rolling friction coefficient and its combination mode.
+    pub rolling_friction: (N, MaterialCombineMode),
     /// The restitution coefficient and its combination mode.
     pub restitution: (N, MaterialCombineMode),
+    /// The factor the restitution coefficient is multiplied by each time the same pair of colliders bounce again.
+    pub restitution_decay: N,
     /// The surface velocity at this point.
     pub surface_velocity: Vector<N>,
 }
@@ -101,6 +121,9 @@ pub trait MaterialClone<N: RealField> {
 /// The identifier of a material.
 pub type MaterialId = u32;
 
+/// The tag identifying the kind of surface of a material, e.g., grass, metal, or wood.
+pub type SurfaceTag = u32;
+
 impl<N: RealField, T: 'static + Material<N> + Clone> MaterialClone<N> for T {
     fn clone_box(&self) -> Box<Material<N>> {
         Box::new(self.clone())
@@ -111,6 +134,14 @@ impl<N: RealField, T: 'static + Material<N> + Clone> MaterialClone<N> for T {
 pub trait Material<N: RealField>: Downcast + Send + Sync + MaterialClone<N> {
     /// Retrieve the local material properties of a collider at the given contact point.
     fn local_properties(&self, context: MaterialContext<N>) -> LocalMaterialProperties<N>;
+
+    /// The tag identifying the kind of surface of this material, if any.
+    ///
+    /// This is not used by the physics engine but is reported with the contacts involving this material,
+    /// e.g., to select the sound of an impact or of a footstep.
+    fn surface_tag(&self) -> Option<SurfaceTag> {
+        None
+    }
 }
 
 impl_downcast!(Material<N> where N: RealField);
@@ -134,8 +165,10 @@ impl<N: RealField> Material<N> {
               M2: ?Sized + Material<N> {
         let props1 = material1.local_properties(context1);
         let props2 = material2.local_properties(context2);
+        let body_surface_velocity = context1.body_surface_velocity() - context2.body_surface_velocity();
         let restitution;
         let friction;
+        let static_friction;
 
         match (props1.id, props2.id) {
             (Some(id1), Some(id2)) => {
@@ -144,23 +177,38 @@ impl<N: RealField> Material<N> {
                     .unwrap_or_else(|| {
                     MaterialCombineMode::combine(props1.restitution, props2.restitution)
                 });
-                friction = table.friction_coefficient(id1, id2)
-                    .map(|coeff| (coeff, MaterialCombineMode::Lookup))
-                    .unwrap_or_else(|| {
+                // The friction coefficient of the lookup table is used for both static and dynamic friction.
+                let lookup_friction = table.friction_coefficient(id1, id2)
+                    .map(|coeff| (coeff, MaterialCombineMode::Lookup));
+                friction = lookup_friction.unwrap_or_else(|| {
                     MaterialCombineMode::combine(props1.friction, props2.friction)
                 });
+                static_friction = lookup_friction.unwrap_or_else(|| {
+                    MaterialCombineMode::combine(props1.static_friction, props2.static_friction)
+                });
             },
             _ => {
                 restitution = MaterialCombineMode::combine(props1.restitution, props2.restitution);
                 friction = MaterialCombineMode::combine(props1.friction, props2.friction);
+                static_friction = MaterialCombineMode::combine(props1.static_friction, props2.static_friction);
             }
         }
 
         LocalMaterialProperties {
             id: None,
             friction,
+            static_friction,
+            // The largest threshold wins.
+            static_friction_velocity_threshold: match (props1.static_friction_velocity_threshold, props2.static_friction_velocity_threshold) {
+                (Some(threshold1), Some(threshold2)) => Some(threshold1.max(threshold2)),
+                (threshold1, threshold2) => threshold1.or(threshold2),
+            },
+            // The lookup table does not have any rolling friction coefficient.
+            rolling_friction: MaterialCombineMode::combine(props1.rolling_friction, props2.rolling_friction),
             restitution,
-            surface_velocity: props1.surface_velocity - props2.surface_velocity,
+            // The strongest decay wins.
+            restitution_decay: props1.restitution_decay.min(props2.restitution_decay),
+            surface_velocity: props1.surface_velocity - props2.surface_velocity + body_surface_velocity,
         }
     }
 }
@@ -197,4 +245,24 @@ impl<N: RealField> Deref for MaterialHandle<N> {
     fn deref(&self) -> &Material<N> {
         &**self.0.deref()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use crate::material::MaterialCombineMode;
+
+    #[test]
+    fn combine_mode_with_highest_precedence_wins() {
+        use MaterialCombineMode::*;
+
+        let combine = |a, b| MaterialCombineMode::combine((0.2f64, a), (0.8, b));
+
+        assert_eq!(combine(Average, Average).0, 0.5);
+        assert_eq!(combine(Average, Min).0, 0.2);
+        assert_eq!(combine(Multiply, Min).0, 0.2 * 0.8);
+        assert_eq!(combine(Multiply, Max).0, 0.8);
+        assert_eq!(combine(Max, Lookup).0, 0.8);
+        // The order of the two materials does not matter.
+        assert_eq!(combine(Min, Multiply).0, combine(Multiply, Min).0);
+    }
+}
@@ -1,6 +1,7 @@
 //! Material data structures.
 
-pub use self::material::{Material, MaterialContext, MaterialCombineMode, MaterialHandle, MaterialId, LocalMaterialProperties};
+pub use self::material::{Material, MaterialContext, MaterialCombineMode, MaterialHandle, MaterialId, LocalMaterialProperties,
+                             SurfaceTag};
 pub use self::basic_material::BasicMaterial;
 pub use self::materials_coefficients_table::MaterialsCoefficientsTable;
 
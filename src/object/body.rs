@@ -1,10 +1,14 @@
 #![allow(missing_docs)] // For downcast.
 
+use std::any::Any;
+
 use downcast_rs::Downcast;
 
 use na::{self, DVectorSlice, DVectorSliceMut, RealField};
 use ncollide::shape::DeformationsType;
 
+#[cfg(feature = "dim3")]
+use crate::math::AngularVector;
 use crate::math::{Force, ForceType, Inertia, Isometry, Point, Vector, Velocity};
 use crate::object::{BodyPartHandle, BodyHandle};
 use crate::solver::{IntegrationParameters, ForceDirection};
@@ -29,6 +33,7 @@ pub enum BodyStatus {
 pub struct ActivationStatus<N: RealField> {
     threshold: Option<N>,
     energy: N,
+    keep_awake_while_forced: bool,
 }
 
 impl<N: RealField> ActivationStatus<N> {
@@ -42,6 +47,7 @@ impl<N: RealField> ActivationStatus<N> {
         ActivationStatus {
             threshold: Some(Self::default_threshold()),
             energy: Self::default_threshold() * na::convert(4.0),
+            keep_awake_while_forced: false,
         }
     }
 
@@ -50,6 +56,7 @@ impl<N: RealField> ActivationStatus<N> {
         ActivationStatus {
             threshold: Some(Self::default_threshold()),
             energy: N::zero(),
+            keep_awake_while_forced: false,
         }
     }
 
@@ -86,6 +93,21 @@ impl<N: RealField> ActivationStatus<N> {
     pub fn set_energy(&mut self, energy: N) {
         self.energy = energy
     }
+
+    /// Whether the body is woken up whenever a non-zero force is applied to it.
+    #[inline]
+    pub fn is_kept_awake_while_forced(&self) -> bool {
+        self.keep_awake_while_forced
+    }
+
+    /// Sets whether the body is woken up whenever a non-zero force is applied to it.
+    ///
+    /// If `true`, a body pushed by a force generator cannot fall asleep even if its
+    /// velocity stays below the deactivation threshold.
+    #[inline]
+    pub fn set_keep_awake_while_forced(&mut self, enabled: bool) {
+        self.keep_awake_while_forced = enabled
+    }
 }
 
 /// Trait implemented by all bodies supported by nphysics.
@@ -96,6 +118,11 @@ pub trait Body<N: RealField>: Downcast + Send + Sync {
     /// Sets the name of this body.
     fn set_name(&mut self, name: String);
 
+    /// The user-defined data attached to this body, if any.
+    fn user_data(&self) -> Option<&(Any + Send + Sync)> {
+        None
+    }
+
     /// Returns `true` if this body is the ground.
     fn is_ground(&self) -> bool {
         false
@@ -146,6 +173,12 @@ pub trait Body<N: RealField>: Downcast + Send + Sync {
     /// If set to `None` the body will never sleep.
     fn set_deactivation_threshold(&mut self, threshold: Option<N>);
 
+    /// Sets whether this body is kept awake as long as non-zero forces are applied to it.
+    ///
+    /// If `true`, this body is woken up by any non-zero force applied to it, even if
+    /// `auto_wake_up` is `false`, e.g., by force generators. Does nothing by default.
+    fn keep_awake_while_forced(&mut self, _enabled: bool) {}
+
     /// The number of degrees of freedom of this body.
     fn ndofs(&self) -> usize;
 
@@ -176,6 +209,18 @@ pub trait Body<N: RealField>: Downcast + Send + Sync {
     /// A reference to the specified body part.
     fn part(&self, i: usize) -> Option<&BodyPart<N>>;
 
+    /// The number of parts of this body.
+    ///
+    /// The default implementation counts the parts until `self.part(i)` returns `None`.
+    fn num_parts(&self) -> usize {
+        (0..).take_while(|i| self.part(*i).is_some()).count()
+    }
+
+    /// An iterator through all the parts of this body.
+    fn parts<'a>(&'a self) -> Box<Iterator<Item = &'a BodyPart<N>> + 'a> {
+        Box::new((0..self.num_parts()).filter_map(move |i| self.part(i)))
+    }
+
     /// If this is a deformable body, returns its deformed positions.
     fn deformed_positions(&self) -> Option<(DeformationsType, &[N])>;
 
@@ -219,6 +264,9 @@ pub trait Body<N: RealField>: Downcast + Send + Sync {
     /// For warmstarting the solver, modifies the delta velocity applied by the internal constraints of this body.
     fn warmstart_internal_velocity_constraints(&mut self, dvels: &mut DVectorSliceMut<N>);
 
+    /// Discards the impulses cached by the internal constraints of this body for warmstarting the solver.
+    fn clear_cached_impulses(&mut self) {}
+
     /// Execute one step for the iterative resolution of this body's internal velocity constraints.
     fn step_solve_internal_velocity_constraints(&mut self, dvels: &mut DVectorSliceMut<N>);
 
@@ -299,6 +347,21 @@ pub trait Body<N: RealField>: Downcast + Send + Sync {
     #[inline]
     fn enable_gravity(&mut self, enabled: bool);
 
+    /// Whether this body can be displaced by the nonlinear position solver to resolve penetrations.
+    ///
+    /// Returns `true` by default.
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        true
+    }
+
+    /// Enable or disable the displacement of this body by the nonlinear position solver.
+    ///
+    /// If disabled, the penetrations between this body and others are resolved by moving only the other
+    /// bodies involved. Joints are not affected by this setting. Does nothing by default.
+    #[inline]
+    fn set_position_correction_enabled(&mut self, _enabled: bool) {}
+
     /*
      * Application of forces/impulses.
      */
@@ -319,6 +382,30 @@ pub trait Body<N: RealField>: Downcast + Send + Sync {
 
     /// Apply a local force at a given local point of a part of this body.
     fn apply_local_force_at_local_point(&mut self, part_id: usize, force: &Vector<N>, point: &Point<N>, force_type: ForceType, auto_wake_up: bool);
+
+    /// Apply a torque to a part of this body.
+    #[cfg(feature = "dim2")]
+    fn apply_torque(&mut self, part_id: usize, torque: N, force_type: ForceType, auto_wake_up: bool) {
+        self.apply_force(part_id, &Force::torque(torque), force_type, auto_wake_up)
+    }
+
+    /// Apply a torque to a part of this body.
+    #[cfg(feature = "dim3")]
+    fn apply_torque(&mut self, part_id: usize, torque: &AngularVector<N>, force_type: ForceType, auto_wake_up: bool) {
+        self.apply_force(part_id, &Force::torque(*torque), force_type, auto_wake_up)
+    }
+
+    /// Apply a local torque to a part of this body.
+    #[cfg(feature = "dim2")]
+    fn apply_local_torque(&mut self, part_id: usize, torque: N, force_type: ForceType, auto_wake_up: bool) {
+        self.apply_local_force(part_id, &Force::torque(torque), force_type, auto_wake_up)
+    }
+
+    /// Apply a local torque to a part of this body.
+    #[cfg(feature = "dim3")]
+    fn apply_local_torque(&mut self, part_id: usize, torque: &AngularVector<N>, force_type: ForceType, auto_wake_up: bool) {
+        self.apply_local_force(part_id, &Force::torque(*torque), force_type, auto_wake_up)
+    }
 }
 
 /// Trait implemented by each part of a body supported by nphysics.
@@ -345,6 +432,30 @@ pub trait BodyPart<N: RealField>: Downcast + Send + Sync {
 
     /// The local-space inertia of this body part.
     fn local_inertia(&self) -> Inertia<N>;
+
+    /// The world-space inertia of this body part, expressed at its center of mass.
+    ///
+    /// This is the same as `self.inertia()` and is provided for symmetry with `self.local_inertia()`.
+    #[inline]
+    fn world_inertia(&self) -> Inertia<N> {
+        self.inertia()
+    }
+
+    /// Expresses the given world-space velocity in the local frame of this body part.
+    ///
+    /// Both the linear and angular components are rotated by the inverse of this body part's orientation.
+    #[inline]
+    fn world_to_local_velocity(&self, velocity: Velocity<N>) -> Velocity<N> {
+        velocity.transformed(&self.position().inverse())
+    }
+
+    /// Expresses the given velocity, given in the local frame of this body part, in world-space.
+    ///
+    /// This is the inverse of `self.world_to_local_velocity`.
+    #[inline]
+    fn local_to_world_velocity(&self, velocity: Velocity<N>) -> Velocity<N> {
+        velocity.transformed(&self.position())
+    }
 }
 
 impl_downcast!(Body<N> where N: RealField);
@@ -361,6 +472,7 @@ bitflags! {
         const LOCAL_COM_CHANGED = 0b001000;
         const DAMPING_CHANGED = 0b010000;
         const STATUS_CHANGED = 0b100000;
+        const IMPULSES_CLEARED = 0b1000000;
     }
 }
 
@@ -398,6 +510,7 @@ impl BodyUpdateStatus {
         local_com_changed, set_local_com_changed, LOCAL_COM_CHANGED
         damping_changed, set_damping_changed, DAMPING_CHANGED
         status_changed, set_status_changed, STATUS_CHANGED
+        impulses_cleared, set_impulses_cleared, IMPULSES_CLEARED
     );
 
     #[inline]
@@ -27,6 +27,12 @@ impl BodyHandle {
     pub fn is_ground(&self) -> bool {
         self.0 == usize::max_value()
     }
+
+    /// The unique identifier corresponding to this handle.
+    #[inline]
+    pub fn uid(&self) -> usize {
+        self.0
+    }
 }
 
 impl BodyPartHandle {
@@ -191,6 +197,18 @@ impl<N: RealField> BodySet<N> {
     pub fn bodies_mut(&mut self) -> impl Iterator<Item = &mut Body<N>> {
         self.bodies.iter_mut().map(|e| &mut **e.1)
     }
+
+    /// Iterator yielding all the bodies on this set together with their handles.
+    #[inline]
+    pub fn bodies_with_handles(&self) -> impl Iterator<Item = (BodyHandle, &Body<N>)> {
+        self.bodies.iter().map(|e| (BodyHandle(e.0), &**e.1))
+    }
+
+    /// Mutable iterator yielding all the bodies on this set together with their handles.
+    #[inline]
+    pub fn bodies_with_handles_mut(&mut self) -> impl Iterator<Item = (BodyHandle, &mut Body<N>)> {
+        self.bodies.iter_mut().map(|e| (BodyHandle(e.0), &mut **e.1))
+    }
 }
 
 /// Iterator yielding all the bodies on a body set.
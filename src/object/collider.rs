@@ -2,11 +2,11 @@ use std::sync::Arc;
 use std::f64;
 use std::mem;
 use std::any::Any;
-use na::RealField;
+use na::{RealField, Unit};
 use ncollide::world::{CollisionObject, CollisionObjectHandle, GeometricQueryType, CollisionGroups};
-use ncollide::shape::{ShapeHandle, Shape};
+use ncollide::shape::{DeformationsType, Plane, ShapeHandle, Shape};
 
-use crate::math::{Isometry, Vector, Rotation};
+use crate::math::{Isometry, Vector, Rotation, DIM};
 use crate::object::{BodyPartHandle, BodyHandle, Body};
 use crate::material::{Material, MaterialHandle};
 use crate::world::{World, ColliderWorld};
@@ -55,6 +55,8 @@ impl<N: RealField> ColliderAnchor<N> {
 pub struct ColliderData<N: RealField> {
     name: String,
     margin: N,
+    // The density used to compute the contribution of this collider to the mass of its body part.
+    density: N,
     anchor: ColliderAnchor<N>,
     // Doubly linked list of colliders attached to a body.
     prev: Option<ColliderHandle>,
@@ -62,6 +64,14 @@ pub struct ColliderData<N: RealField> {
     // NOTE: needed for the collision filter.
     body_status_dependent_ndofs: usize,
     material: MaterialHandle<N>,
+    min_restitution: N,
+    one_way: Option<Unit<Vector<N>>>,
+    // Whether the prediction distances of this collider are user-defined instead of the world's.
+    explicit_prediction: bool,
+    // The body node followed by each vertex of a deformable collider tracking only a subset of its body DOFs.
+    dof_map: Option<Arc<Vec<usize>>>,
+    // Per-subshape materials and the index of the material of each subshape.
+    subshape_materials: Option<(Vec<MaterialHandle<N>>, Arc<Vec<usize>>)>,
     user_data: Option<Box<Any + Send + Sync>>,
 }
 
@@ -77,11 +87,17 @@ impl<N: RealField> ColliderData<N> {
         ColliderData {
             name,
             margin,
+            density: N::zero(),
             anchor,
             prev: None,
             next: None,
             body_status_dependent_ndofs,
             material,
+            min_restitution: N::zero(),
+            one_way: None,
+            explicit_prediction: false,
+            dof_map: None,
+            subshape_materials: None,
             user_data: None
         }
     }
@@ -94,6 +110,14 @@ impl<N: RealField> ColliderData<N> {
         self.margin
     }
 
+    /// The density of this collider.
+    ///
+    /// This is zero if this collider does not contribute to the mass of the body part it is attached to.
+    #[inline]
+    pub fn density(&self) -> N {
+        self.density
+    }
+
     /// Handle to the body this collider is attached to.
     pub fn body(&self) -> BodyHandle {
         self.anchor.body()
@@ -104,6 +128,32 @@ impl<N: RealField> ColliderData<N> {
         &self.anchor
     }
 
+    /// The map between the vertices of this deformable collider and the nodes of its body.
+    ///
+    /// The `i`-th vertex of this collider follows the `dof_map[i]`-th node of its body, allowing this collider
+    /// to track only a subset of the body degrees of freedom. If `None`, the `i`-th vertex of this collider
+    /// follows the `i`-th node of its body.
+    pub fn dof_map(&self) -> Option<&[usize]> {
+        self.dof_map.as_ref().map(|m| &m[..])
+    }
+
+    /// The deformed coordinates of this deformable collider, given the deformed positions `coords` of its body.
+    ///
+    /// If this collider has a DOF map, the coordinates of the tracked nodes are gathered into `buffer`.
+    pub(crate) fn deformations<'a>(&self, coords: &'a [N], buffer: &'a mut Vec<N>) -> &'a [N] {
+        if let Some(dof_map) = &self.dof_map {
+            buffer.clear();
+
+            for i in dof_map.iter() {
+                buffer.extend_from_slice(&coords[i * DIM..(i + 1) * DIM]);
+            }
+
+            &buffer[..]
+        } else {
+            coords
+        }
+    }
+
     /// The position of this collider geometry wrt. the body it is attached to.
     pub fn position_wrt_body(&self) -> Isometry<N> {
         if let ColliderAnchor::OnBodyPart { position_wrt_body_part, .. } = self.anchor {
@@ -143,6 +193,84 @@ impl<N: RealField> ColliderData<N> {
         self.material.make_mut()
     }
 
+    /// The minimum restitution coefficient of the contacts involving this collider.
+    ///
+    /// The restitution coefficient resulting from the combination of the materials in contact
+    /// is raised to this value if it is smaller.
+    #[inline]
+    pub fn min_restitution(&self) -> N {
+        self.min_restitution
+    }
+
+    /// Sets the minimum restitution coefficient of the contacts involving this collider.
+    #[inline]
+    pub fn set_min_restitution(&mut self, min_restitution: N) {
+        self.min_restitution = min_restitution
+    }
+
+    /// The direction, in the local space of this collider, of the contacts blocked by this one-way collider.
+    ///
+    /// If this is `Some(dir)`, this collider only blocks the colliders touching it on the side pointed by `dir`:
+    /// the contacts with a normal (pointing toward the other collider) deviating from `dir` by more than
+    /// 45 degrees are ignored. This is typically used for platforms colliders can pass through from below.
+    #[inline]
+    pub fn one_way(&self) -> Option<&Unit<Vector<N>>> {
+        self.one_way.as_ref()
+    }
+
+    /// Sets the direction, in the local space of this collider, of the contacts blocked by this one-way collider.
+    #[inline]
+    pub fn set_one_way(&mut self, one_way: Option<Unit<Vector<N>>>) {
+        self.one_way = one_way
+    }
+
+    /// Returns `true` if the subshapes of this collider have their own materials.
+    #[inline]
+    pub fn has_subshape_materials(&self) -> bool {
+        self.subshape_materials.is_some()
+    }
+
+    /// The material of the given subshape of this collider.
+    ///
+    /// This is the same as `self.material()` unless per-subshape materials
+    /// have been set on this deformable collider. This also falls back to
+    /// `self.material()` if the subshape is not covered by the materials mapping.
+    #[inline]
+    pub fn subshape_material(&self, subshape_id: usize) -> &Material<N> {
+        self.subshape_materials
+            .as_ref()
+            .and_then(|(materials, mapping)| materials.get(*mapping.get(subshape_id)?))
+            .map(|material| &**material)
+            .unwrap_or(&*self.material)
+    }
+
+    #[inline]
+    pub(crate) fn set_margin(&mut self, margin: N) {
+        self.margin = margin
+    }
+
+    #[inline]
+    pub(crate) fn set_body_parts_mapping(&mut self, mapping: Option<Arc<Vec<usize>>>) {
+        if let ColliderAnchor::OnDeformableBody { body_parts, .. } = &mut self.anchor {
+            *body_parts = mapping
+        }
+    }
+
+    #[inline]
+    pub(crate) fn explicit_prediction(&self) -> bool {
+        self.explicit_prediction
+    }
+
+    #[inline]
+    pub(crate) fn set_explicit_prediction(&mut self, explicit: bool) {
+        self.explicit_prediction = explicit
+    }
+
+    #[inline]
+    pub(crate) fn remove_subshape_materials(&mut self) {
+        self.subshape_materials = None
+    }
+
     #[inline]
     pub(crate) fn body_status_dependent_ndofs(&self) -> usize {
         self.body_status_dependent_ndofs
@@ -205,6 +333,14 @@ impl<N: RealField> Collider<N> {
         self.0.data().margin()
     }
 
+    /// The density of this collider.
+    ///
+    /// This is zero if this collider does not contribute to the mass of the body part it is attached to.
+    #[inline]
+    pub fn density(&self) -> N {
+        self.0.data().density()
+    }
+
     /// Handle to the body this collider is attached to.
     pub fn body(&self) -> BodyHandle {
         self.0.data().body()
@@ -215,6 +351,19 @@ impl<N: RealField> Collider<N> {
         self.0.data().anchor()
     }
 
+    /// The map between the vertices of this deformable collider and the nodes of its body.
+    ///
+    /// If `None`, the `i`-th vertex of this collider follows the `i`-th node of its body.
+    #[inline]
+    pub fn dof_map(&self) -> Option<&[usize]> {
+        self.0.data().dof_map()
+    }
+
+    #[inline]
+    pub(crate) fn deformations<'a>(&self, coords: &'a [N], buffer: &'a mut Vec<N>) -> &'a [N] {
+        self.0.data().deformations(coords, buffer)
+    }
+
     /// The position of this collider geometry wrt. the body it is attached to.
     pub fn position_wrt_body(&self) -> Isometry<N> {
         self.0.data().position_wrt_body()
@@ -231,6 +380,54 @@ impl<N: RealField> Collider<N> {
         self.0.data().material()
     }
 
+    // The handle of the material of this collider.
+    #[inline]
+    pub(crate) fn material_handle(&self) -> &MaterialHandle<N> {
+        &self.0.data().material
+    }
+
+    // Whether the prediction distances of this collider are user-defined instead of the world's.
+    #[inline]
+    pub(crate) fn has_explicit_prediction(&self) -> bool {
+        self.0.data().explicit_prediction()
+    }
+
+    /// The minimum restitution coefficient of the contacts involving this collider.
+    #[inline]
+    pub fn min_restitution(&self) -> N {
+        self.0.data().min_restitution()
+    }
+
+    /// Sets the minimum restitution coefficient of the contacts involving this collider.
+    #[inline]
+    pub fn set_min_restitution(&mut self, min_restitution: N) {
+        self.0.data_mut().set_min_restitution(min_restitution)
+    }
+
+    /// The direction, in the local space of this collider, of the contacts blocked by this one-way collider.
+    #[inline]
+    pub fn one_way(&self) -> Option<&Unit<Vector<N>>> {
+        self.0.data().one_way()
+    }
+
+    /// Sets the direction, in the local space of this collider, of the contacts blocked by this one-way collider.
+    #[inline]
+    pub fn set_one_way(&mut self, one_way: Option<Unit<Vector<N>>>) {
+        self.0.data_mut().set_one_way(one_way)
+    }
+
+    /// Returns `true` if the subshapes of this collider have their own materials.
+    #[inline]
+    pub fn has_subshape_materials(&self) -> bool {
+        self.0.data().has_subshape_materials()
+    }
+
+    /// The material of the given subshape of this collider.
+    #[inline]
+    pub fn subshape_material(&self, subshape_id: usize) -> &Material<N> {
+        self.0.data().subshape_material(subshape_id)
+    }
+
     /// Returns `true` if this collider is a sensor.
     #[inline]
     pub fn is_sensor(&self) -> bool {
@@ -336,7 +533,11 @@ pub struct ColliderDesc<N: RealField> {
     density: N,
     linear_prediction: N,
     angular_prediction: N,
-    is_sensor: bool
+    // Whether the predictions were set by the user, in which case they override the world's.
+    explicit_prediction: bool,
+    is_sensor: bool,
+    min_restitution: N,
+    one_way: Option<Unit<Vector<N>>>,
 }
 
 impl<N: RealField> ColliderDesc<N> {
@@ -356,10 +557,21 @@ impl<N: RealField> ColliderDesc<N> {
             density: N::zero(),
             linear_prediction,
             angular_prediction,
-            is_sensor: false
+            explicit_prediction: false,
+            is_sensor: false,
+            min_restitution: N::zero(),
+            one_way: None,
         }
     }
 
+    /// Creates a new collider builder with an infinite half-space shape.
+    ///
+    /// The half-space is bounded by the plane passing through the collider's origin with the outward
+    /// normal `normal`, expressed in the collider's local frame. Everything behind this plane is solid.
+    pub fn halfspace(normal: Unit<Vector<N>>) -> Self {
+        Self::new(ShapeHandle::new(Plane::new(normal)))
+    }
+
     /// The default margin surrounding a collider: 0.01
     pub fn default_margin() -> N {
         na::convert(0.01)
@@ -381,6 +593,8 @@ impl<N: RealField> ColliderDesc<N> {
     desc_custom_setters!(
         self.translation, set_translation, vector: Vector<N> | { self.position.translation.vector = vector }
         self.material, set_material, material: MaterialHandle<N> | { self.material = Some(material) }
+        self.linear_prediction, set_linear_prediction, linear_prediction: N | { self.linear_prediction = linear_prediction; self.explicit_prediction = true }
+        self.angular_prediction, set_angular_prediction, angular_prediction: N | { self.angular_prediction = angular_prediction; self.explicit_prediction = true }
     );
 
     desc_setters!(
@@ -389,10 +603,10 @@ impl<N: RealField> ColliderDesc<N> {
         density, set_density, density: N
         name, set_name, name: String
         collision_groups, set_collision_groups, collision_groups: CollisionGroups
-        linear_prediction, set_linear_prediction, linear_prediction: N
-        angular_prediction, set_angular_prediction, angular_prediction: N
         sensor, set_is_sensor, is_sensor: bool
         position, set_position, position: Isometry<N>
+        min_restitution, set_min_restitution, min_restitution: N
+        one_way, set_one_way, one_way: Option<Unit<Vector<N>>>
     );
 
     #[cfg(feature = "dim3")]
@@ -419,6 +633,8 @@ impl<N: RealField> ColliderDesc<N> {
         [val] get_linear_prediction -> linear_prediction: N
         [val] get_angular_prediction -> angular_prediction: N
         [val] is_sensor -> is_sensor: bool
+        [val] get_min_restitution -> min_restitution: N
+        [val] get_one_way -> one_way: Option<Unit<Vector<N>>>
         [ref] get_position -> position: Isometry<N>
     );
 
@@ -438,23 +654,32 @@ impl<N: RealField> ColliderDesc<N> {
         self.build_with_infos(parent, body, cworld)
     }
 
+    // The linear and angular prediction distances of the collider built into `cworld`.
+    fn prediction(&self, cworld: &ColliderWorld<N>) -> (N, N) {
+        match cworld.default_prediction() {
+            Some(prediction) if !self.explicit_prediction => prediction,
+            _ => (self.linear_prediction, self.angular_prediction),
+        }
+    }
+
     // Returns `None` if the given body part does not exist.
     pub(crate) fn build_with_infos<'w>(&self,
                                        parent: BodyPartHandle,
                                        body: &mut Body<N>,
                                        cworld: &'w mut ColliderWorld<N>)
                                     -> Option<&'w mut Collider<N>> {
+        let (linear_prediction, angular_prediction) = self.prediction(cworld);
         let query = if self.is_sensor {
-            GeometricQueryType::Proximity(self.linear_prediction)
+            GeometricQueryType::Proximity(linear_prediction)
         } else {
             GeometricQueryType::Contacts(
-                self.margin + self.linear_prediction,
-                self.angular_prediction,
+                self.margin + linear_prediction,
+                angular_prediction,
             )
         };
 
-        let (pos, ndofs) = if parent.is_ground() {
-            (self.position, 0)
+        let (pos, ndofs, density) = if parent.is_ground() {
+            (self.position, 0, N::zero())
         } else {
             if !self.density.is_zero() {
                 let com = self.position * self.shape.center_of_mass();
@@ -464,7 +689,8 @@ impl<N: RealField> ColliderDesc<N> {
 
             (
                 body.part(parent.1)?.position() * self.position,
-                body.status_dependent_ndofs()
+                body.status_dependent_ndofs(),
+                self.density,
             )
         };
 
@@ -472,23 +698,39 @@ impl<N: RealField> ColliderDesc<N> {
         let material = self.material.clone().unwrap_or_else(|| cworld.default_material());
         let mut data = ColliderData::new(self.name.clone(), self.margin, anchor, ndofs, material);
         data.user_data = self.user_data.as_ref().map(|data| data.0.to_any());
+        data.min_restitution = self.min_restitution;
+        data.one_way = self.one_way;
+        data.density = density;
+        data.explicit_prediction = self.explicit_prediction;
         Some(cworld.add(pos, self.shape.clone(), self.collision_groups, query, data))
     }
 }
 
 
 /// A deformable collider builder.
+///
+/// The thickness of a deformable collider is added to its margin so that its boundary is treated as a thick
+/// shell. This makes the contacts involving thin deformable bodies, e.g., cloth, more robust.
+///
+/// A DOF map can be set so that the vertices of the collider shape follow only a subset of the nodes of the
+/// body, e.g., to use a coarse collision proxy for a finely discretized body. The body parts mapping should
+/// then map each subshape of the proxy to a body part close to it.
 pub struct DeformableColliderDesc<N: RealField> {
     name: String,
     user_data: Option<UserDataBox>,
     margin: N,
+    thickness: N,
     collision_groups: CollisionGroups,
     shape: ShapeHandle<N>,
     material: Option<MaterialHandle<N>>,
     linear_prediction: N,
     angular_prediction: N,
+    // Whether the predictions were set by the user, in which case they override the world's.
+    explicit_prediction: bool,
     is_sensor: bool,
-    body_parts_mapping: Option<Arc<Vec<usize>>>
+    body_parts_mapping: Option<Arc<Vec<usize>>>,
+    dof_map: Option<Arc<Vec<usize>>>,
+    subshape_materials: Option<(Vec<MaterialHandle<N>>, Arc<Vec<usize>>)>,
 }
 
 impl<N: RealField> DeformableColliderDesc<N> {
@@ -505,12 +747,16 @@ impl<N: RealField> DeformableColliderDesc<N> {
             user_data: None,
             shape,
             margin: na::convert(0.01),
+            thickness: N::zero(),
             collision_groups: CollisionGroups::default(),
             material: None,
             linear_prediction,
             angular_prediction,
+            explicit_prediction: false,
             is_sensor: false,
-            body_parts_mapping: None
+            body_parts_mapping: None,
+            dof_map: None,
+            subshape_materials: None,
         }
     }
 }
@@ -538,27 +784,33 @@ impl<N: RealField> DeformableColliderDesc<N> {
 
     desc_custom_setters!(
         self.material, set_material, material: MaterialHandle<N> | { self.material = Some(material) }
+        self.materials, set_materials, materials: Vec<MaterialHandle<N>>, mapping: Arc<Vec<usize>> | { self.subshape_materials = Some((materials, mapping)) }
+        self.linear_prediction, set_linear_prediction, linear_prediction: N | { self.linear_prediction = linear_prediction; self.explicit_prediction = true }
+        self.angular_prediction, set_angular_prediction, angular_prediction: N | { self.angular_prediction = angular_prediction; self.explicit_prediction = true }
     );
 
     desc_setters!(
         name, set_name, name: String
         margin, set_margin, margin: N
+        thickness, set_thickness, thickness: N
         collision_groups, set_collision_groups, collision_groups: CollisionGroups
-        linear_prediction, set_linear_prediction, linear_prediction: N
-        angular_prediction, set_angular_prediction, angular_prediction: N
         as_sensor, set_as_sensor, is_sensor: bool
         body_parts_mapping, set_body_parts_mapping, body_parts_mapping: Option<Arc<Vec<usize>>>
+        dof_map, set_dof_map, dof_map: Option<Arc<Vec<usize>>>
     );
 
     desc_custom_getters!(
         self.get_shape: &Shape<N> | { &*self.shape }
         self.get_name: &str | { &self.name }
         self.get_material: Option<&Material<N>> | { self.material.as_ref().map(|m| &**m) }
-
+        self.get_materials: Option<&[MaterialHandle<N>]> | { self.subshape_materials.as_ref().map(|m| &m.0[..]) }
+        self.get_materials_mapping: Option<&[usize]> | { self.subshape_materials.as_ref().map(|m| &m.1[..]) }
+        self.get_dof_map: Option<&[usize]> | { self.dof_map.as_ref().map(|m| &m[..]) }
     );
 
     desc_getters!(
         [val] get_margin -> margin: N
+        [val] get_thickness -> thickness: N
         [val] get_collision_groups -> collision_groups: CollisionGroups
         [val] get_linear_prediction -> linear_prediction: N
         [val] get_angular_prediction -> angular_prediction: N
@@ -572,23 +824,34 @@ impl<N: RealField> DeformableColliderDesc<N> {
         Some(self.build_with_infos(parent, cworld))
     }
 
+    // The linear and angular prediction distances of the collider built into `cworld`.
+    fn prediction(&self, cworld: &ColliderWorld<N>) -> (N, N) {
+        match cworld.default_prediction() {
+            Some(prediction) if !self.explicit_prediction => prediction,
+            _ => (self.linear_prediction, self.angular_prediction),
+        }
+    }
+
     pub(crate) fn build_with_infos<'w>(&self,
                                        parent: &Body<N>,
                                        cworld: &'w mut ColliderWorld<N>)
                                        -> &'w mut Collider<N> {
+        // The boundary of the deformable shape is treated as a shell of the given thickness
+        // by inflating its margin.
+        let margin = self.margin + self.thickness;
+        let (linear_prediction, angular_prediction) = self.prediction(cworld);
         let query = if self.is_sensor {
-            GeometricQueryType::Proximity(self.linear_prediction)
+            GeometricQueryType::Proximity(linear_prediction)
         } else {
             GeometricQueryType::Contacts(
-                self.margin + self.linear_prediction,
-                self.angular_prediction,
+                margin + linear_prediction,
+                angular_prediction,
             )
         };
 
-        let parent_deformation_type = parent
+        let (parent_deformation_type, parent_coords) = parent
             .deformed_positions()
-            .expect("A deformable collider can only be attached to a deformable body.")
-            .0;
+            .expect("A deformable collider can only be attached to a deformable body.");
 
         assert_eq!(
             parent_deformation_type,
@@ -596,13 +859,30 @@ impl<N: RealField> DeformableColliderDesc<N> {
             "Both the deformable shape and deformable body must support the same deformation types."
         );
 
+        if let Some(dof_map) = &self.dof_map {
+            assert_eq!(
+                parent_deformation_type,
+                DeformationsType::Vectors,
+                "A DOF map can only be used with deformations described as vectors."
+            );
+            assert!(dof_map.iter().all(|i| (i + 1) * DIM <= parent_coords.len()), "Invalid DOF map index.");
+        }
+
         let body = parent.handle();
         let ndofs = parent.status_dependent_ndofs();
         let body_parts = self.body_parts_mapping.clone();
         let anchor = ColliderAnchor::OnDeformableBody { body, body_parts };
         let material = self.material.clone().unwrap_or_else(|| cworld.default_material());
-        let mut data = ColliderData::new(self.name.clone(), self.margin, anchor, ndofs, material);
+        let mut data = ColliderData::new(self.name.clone(), margin, anchor, ndofs, material);
         data.user_data = self.user_data.as_ref().map(|data| data.0.to_any());
+        data.explicit_prediction = self.explicit_prediction;
+        data.dof_map = self.dof_map.clone();
+
+        if let Some((materials, mapping)) = &self.subshape_materials {
+            assert!(mapping.iter().all(|i| *i < materials.len()), "Invalid subshape material index.");
+            data.subshape_materials = Some((materials.clone(), mapping.clone()));
+        }
+
         cworld.add(Isometry::identity(), self.shape.clone(), self.collision_groups, query, data)
     }
 }
\ No newline at end of file
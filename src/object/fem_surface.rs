@@ -55,6 +55,7 @@ pub struct FEMSurface<N: RealField> {
 
     // Parameters
     gravity_enabled: bool,
+    position_correction_enabled: bool,
     rest_positions: DVector<N>,
     damping_coeffs: (N, N),
     young_modulus: N,
@@ -146,6 +147,7 @@ impl<N: RealField> FEMSurface<N> {
             plasticity_max_force: N::zero(),
             plasticity_creep: N::zero(),
             gravity_enabled: true,
+            position_correction_enabled: true,
             d0, d1, d2,
             activation: ActivationStatus::new_active(),
             status: BodyStatus::Dynamic,
@@ -505,6 +507,28 @@ impl<N: RealField> FEMSurface<N> {
         (Polyline::new(vertices, Some(indices)), deformation_indices, body_parts)
     }
 
+    /// Returns a polyline at the boundary of this surface, with one vertex per node of this surface,
+    /// as well as the mapping between the polyline segments and this surface body parts.
+    ///
+    /// Unlike `self.boundary_polyline()`, the `i`-th vertex of the returned polyline always corresponds to the
+    /// `i`-th node of this surface so no renumbering of the degrees of freedom is needed. The nodes that are
+    /// not on the boundary are vertices without any adjacent segment.
+    ///
+    /// The output is (polyline, element to body part map).
+    #[cfg(feature = "dim2")]
+    pub fn boundary_polyline_with_all_nodes(&self) -> (Polyline<N>, Vec<usize>) {
+        let indices = self.boundary();
+        let vertices = self.positions
+            .as_slice()
+            .chunks(2)
+            .map(|p| Point2::new(p[0], p[1]))
+            .collect();
+        let body_parts = indices.iter().map(|i| i.1).collect();
+        let indices = indices.into_iter().map(|i| i.0 / 2).collect();
+
+        (Polyline::new(vertices, Some(indices)), body_parts)
+    }
+
     /// Renumber degrees of freedom so that the `deformation_indices[i]`-th DOF becomes the `i`-th one.
     pub fn renumber_dofs(&mut self, deformation_indices: &[usize]) {
         let mut dof_map: Vec<_> = (0..).take(self.positions.len()).collect();
@@ -634,6 +658,16 @@ impl<N: RealField> Body<N> for FEMSurface<N> {
         self.gravity_enabled = enabled
     }
 
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        self.position_correction_enabled
+    }
+
+    #[inline]
+    fn set_position_correction_enabled(&mut self, enabled: bool) {
+        self.position_correction_enabled = enabled
+    }
+
     #[inline]
     fn deformed_positions(&self) -> Option<(DeformationsType, &[N])> {
         Some((DeformationsType::Vectors, self.positions.as_slice()))
@@ -777,10 +811,24 @@ impl<N: RealField> Body<N> for FEMSurface<N> {
         self.activation.set_deactivation_threshold(threshold)
     }
 
+    #[inline]
+    fn keep_awake_while_forced(&mut self, enabled: bool) {
+        self.activation.set_keep_awake_while_forced(enabled)
+    }
+
+    #[inline]
+    fn user_data(&self) -> Option<&(Any + Send + Sync)> {
+        self.user_data.as_ref().map(|d| &**d)
+    }
+
     fn part(&self, id: usize) -> Option<&BodyPart<N>> {
         self.elements.get(id).map(|b| b as &BodyPart<N>)
     }
 
+    fn num_parts(&self) -> usize {
+        self.elements.len()
+    }
+
     fn world_point_at_material_point(&self, part: &BodyPart<N>, point: &Point<N>) -> Point<N> {
         let elt = part.downcast_ref::<TriangularElement<N>>().expect("The provided body part must be a triangular element");
         fem_helper::world_point_at_material_point(FiniteElementIndices::Triangle(elt.indices), &self.positions, point)
@@ -852,7 +900,7 @@ impl<N: RealField> Body<N> for FEMSurface<N> {
             return;
         }
 
-        if auto_wake_up {
+        if auto_wake_up || (self.activation.is_kept_awake_while_forced() && force.iter().any(|f| !f.is_zero())) {
             self.activate()
         }
 
@@ -1110,4 +1158,41 @@ impl<'a, N: RealField> BodyDesc<N> for FEMSurfaceDesc<'a, N> {
 
         vol
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::Polyline;
+    use crate::object::FEMSurfaceDesc;
+    use crate::world::World;
+
+    #[test]
+    fn refresh_collider_after_fracture() {
+        let mut world = World::<f64>::new();
+        let surface = FEMSurfaceDesc::quad(2, 2)
+            .collider_enabled(true)
+            .build(&mut world);
+        let handle = surface.handle();
+
+        // Fracture the quad by removing all the elements of its positive-x half.
+        let positions = surface.positions().clone();
+        surface.elements.retain(|elt| elt.indices.iter().map(|i| positions[*i]).sum::<f64>() < 0.0);
+        let boundary = surface.boundary();
+
+        let collider = world.collider_world().body_colliders(handle).next().unwrap().handle();
+        let aabb = world.collider(collider).unwrap().shape().as_shape::<Polyline<f64>>().unwrap().aabb().clone();
+        assert_relative_eq!(aabb.maxs().x, 0.5);
+
+        world.refresh_deformable_collider(handle);
+
+        let polyline = world.collider(collider).unwrap().shape().as_shape::<Polyline<f64>>().unwrap();
+        assert_eq!(polyline.edges().len(), boundary.len());
+        assert_relative_eq!(polyline.aabb().maxs().x, 0.0);
+        assert_relative_eq!(polyline.aabb().mins().x, -0.5);
+
+        let collider = world.collider(collider).unwrap();
+        for (i, (_, elt)) in boundary.iter().enumerate() {
+            assert_eq!(collider.body_part(i).1, *elt);
+        }
+    }
+}
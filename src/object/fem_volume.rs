@@ -3,6 +3,10 @@ use std::iter;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::any::Any;
+use std::mem;
+use std::io;
+use std::path::Path;
+use std::borrow::Cow;
 use either::Either;
 
 use na::{self, RealField, Point3, Point4, Vector3, Vector6, Matrix3, Matrix3x4, DMatrix, Isometry3,
@@ -27,6 +31,7 @@ pub struct TetrahedralElement<N: RealField> {
     com: Point3<N>,
     rot: Rotation3<N>,
     inv_rot: Rotation3<N>,
+    velocity: Velocity<N>,
     j: Matrix3<N>,
     local_j_inv: Matrix3x4<N>,
     total_strain: Vector6<N>,
@@ -44,18 +49,23 @@ pub struct FEMVolume<N: RealField> {
     handle: BodyHandle,
     elements: Vec<TetrahedralElement<N>>,
     kinematic_nodes: DVector<bool>,
+    // Pinned nodes with their positions in the local-space of the pinned anchor.
+    pinned_nodes: Vec<(usize, Point3<N>)>,
+    pinned_anchor: Isometry3<N>,
     positions: DVector<N>,
     velocities: DVector<N>,
     accelerations: DVector<N>,
     forces: DVector<N>,
     augmented_mass: DMatrix<N>,
     inv_augmented_mass: Cholesky<N, Dynamic>,
+    used_fallback: bool,
 
     // Cache.
     workspace: DVector<N>,
 
     // Parameters
     gravity_enabled: bool,
+    position_correction_enabled: bool,
     rest_positions: DVector<N>,
     damping_coeffs: (N, N),
     young_modulus: N,
@@ -63,6 +73,11 @@ pub struct FEMVolume<N: RealField> {
     plasticity_threshold: N,
     plasticity_creep: N,
     plasticity_max_force: N,
+    inversion_recovery: Option<N>,
+    lumped_mass: bool,
+    polar_decomposition: bool,
+    volume_conservation: bool,
+    mass_regularization: N,
     // Elasticity coefficients computed from the young modulus
     // and poisson ratio.
     d0: N,
@@ -79,6 +94,9 @@ pub struct FEMVolume<N: RealField> {
 
 impl<N: RealField> FEMVolume<N> {
     /// Initializes a new deformable volume from its tetrahedral elements.
+    ///
+    /// The `damping_coeffs` are the Rayleigh damping coefficients proportional to the mass and to the stiffness,
+    /// respectively. They are expressed per second so the damping does not depend on the timestep length.
     pub fn new(handle: BodyHandle, vertices: &[Point3<N>], tetrahedrons: &[Point4<usize>], pos: &Isometry3<N>,
                scale: &Vector3<N>, density: N, young_modulus: N, poisson_ratio: N, damping_coeffs: (N, N)) -> Self {
         let ndofs = vertices.len() * 3;
@@ -118,6 +136,7 @@ impl<N: RealField> FEMVolume<N> {
                 com: Point3::origin(),
                 rot: Rotation3::identity(),
                 inv_rot: Rotation3::identity(),
+                velocity: Velocity::zero(),
                 j: local_j,
                 local_j_inv,
                 total_strain: Vector6::zeros(),
@@ -134,12 +153,15 @@ impl<N: RealField> FEMVolume<N> {
             handle,
             elements,
             kinematic_nodes: DVector::repeat(vertices.len(), false),
+            pinned_nodes: Vec::new(),
+            pinned_anchor: Isometry3::identity(),
             positions: rest_positions.clone(),
             velocities: DVector::zeros(ndofs),
             accelerations: DVector::zeros(ndofs),
             forces: DVector::zeros(ndofs),
             augmented_mass: DMatrix::zeros(ndofs, ndofs),
             inv_augmented_mass: Cholesky::new(DMatrix::zeros(0, 0)).unwrap(),
+            used_fallback: false,
             workspace: DVector::zeros(ndofs),
             rest_positions,
             damping_coeffs,
@@ -150,10 +172,16 @@ impl<N: RealField> FEMVolume<N> {
             plasticity_threshold: N::zero(),
             plasticity_max_force: N::zero(),
             plasticity_creep: N::zero(),
+            inversion_recovery: None,
+            lumped_mass: false,
+            polar_decomposition: false,
+            volume_conservation: false,
+            mass_regularization: N::zero(),
             activation: ActivationStatus::new_active(),
             status: BodyStatus::Dynamic,
             update_status: BodyUpdateStatus::all(),
             gravity_enabled: true,
+            position_correction_enabled: true,
             user_data: None
         }
     }
@@ -187,6 +215,20 @@ impl<N: RealField> FEMVolume<N> {
     }
 
 
+    /// Sets the velocity of each non-kinematic node of this volume from the given velocity field.
+    ///
+    /// The velocity field is evaluated at the current world-space position of each node.
+    pub fn apply_velocity_field<F: Fn(&Point3<N>) -> Vector3<N>>(&mut self, field: F) {
+        self.update_status.set_velocity_changed(true);
+
+        for i in 0..self.kinematic_nodes.len() {
+            if !self.kinematic_nodes[i] {
+                let pt = Point3::from(self.positions.fixed_rows::<U3>(i * DIM).into_owned());
+                self.velocities.fixed_rows_mut::<U3>(i * DIM).copy_from(&field(&pt));
+            }
+        }
+    }
+
     /// Sets the plastic properties of this deformable volume.
     ///
     /// Note that large plasticity creep coefficient can yield to significant instability.
@@ -196,6 +238,87 @@ impl<N: RealField> FEMVolume<N> {
         self.plasticity_max_force = max_force;
     }
 
+    /// Sets the stiffness of the forces pushing inverted elements back toward a positive volume.
+    ///
+    /// An element is inverted when it is squashed so much that its volume becomes negative. Such elements
+    /// tend to stay stuck inverted, so, if this is not `None`, they are subjected to additional forces
+    /// proportional to both their volume loss and the given stiffness multiplied by the young modulus.
+    /// Those forces are integrated explicitly, so a stiffness too large can yield to instabilities.
+    pub fn set_inversion_recovery(&mut self, stiffness: Option<N>) {
+        self.inversion_recovery = stiffness
+    }
+
+    /// The stiffness of the forces pushing inverted elements back toward a positive volume.
+    pub fn inversion_recovery(&self) -> Option<N> {
+        self.inversion_recovery
+    }
+
+    /// Sets whether this deformable volume uses a lumped (diagonal) mass matrix.
+    ///
+    /// If `false`, the consistent mass matrix is used instead. A lumped mass matrix is less
+    /// accurate but cheaper to factorize and yields a better conditioned system.
+    pub fn set_lumped_mass(&mut self, lumped: bool) {
+        self.update_status.set_local_inertia_changed(true);
+        self.lumped_mass = lumped;
+    }
+
+    /// Whether this deformable volume uses a lumped (diagonal) mass matrix.
+    pub fn is_mass_lumped(&self) -> bool {
+        self.lumped_mass
+    }
+
+    /// Sets whether the rotation of each element is extracted with a polar decomposition of its deformation gradient.
+    ///
+    /// If `false`, the rotations are approximated iteratively, starting from the rotations of the previous timestep.
+    /// This is cheaper but less accurate under large shears or rotations, yielding ghost elastic forces. The
+    /// polar decomposition relies on a singular value decomposition of each deformation gradient instead.
+    pub fn set_polar_decomposition(&mut self, enabled: bool) {
+        self.update_status.set_position_changed(true);
+        self.polar_decomposition = enabled;
+    }
+
+    /// Whether the rotation of each element is extracted with a polar decomposition of its deformation gradient.
+    pub fn uses_polar_decomposition(&self) -> bool {
+        self.polar_decomposition
+    }
+
+    /// Sets whether the volume of each element is restored by the position correction.
+    ///
+    /// Linear elasticity does not preserve the volume of largely deformed elements. If enabled, the nodes of each
+    /// element are moved along the gradient of its volume during each position correction iteration, in order to
+    /// restore its rest volume. This is similar to a soft incompressibility constraint.
+    pub fn set_volume_conservation(&mut self, enabled: bool) {
+        self.volume_conservation = enabled;
+    }
+
+    /// Whether the volume of each element is restored by the position correction.
+    pub fn conserves_volume(&self) -> bool {
+        self.volume_conservation
+    }
+
+    /// Sets the regularization added to the diagonal of the augmented mass matrix before its factorization.
+    ///
+    /// A small positive value prevents the factorization from failing on degenerate meshes, e.g., with
+    /// flat tetrahedrons, at the cost of a slight loss of accuracy. Defaults to zero.
+    pub fn set_mass_regularization(&mut self, regularization: N) {
+        self.update_status.set_local_inertia_changed(true);
+        self.mass_regularization = regularization;
+    }
+
+    /// The regularization added to the diagonal of the augmented mass matrix before its factorization.
+    pub fn mass_regularization(&self) -> N {
+        self.mass_regularization
+    }
+
+    /// Whether the factorization of the augmented mass matrix failed during the last update of the dynamics.
+    ///
+    /// This happens when some elements are degenerate, e.g., flat or inverted. In this case, the
+    /// dynamics is approximated by the diagonal of the augmented mass matrix only, i.e., the coupling
+    /// between the nodes is neglected until the next successful factorization.
+    pub fn last_step_used_fallback(&self) -> bool {
+        self.used_fallback
+    }
+
     /// Sets the young modulus of this deformable surface.
     pub fn set_young_modulus(&mut self, young_modulus: N) {
         self.update_status.set_local_inertia_changed(true);
@@ -224,12 +347,82 @@ impl<N: RealField> FEMVolume<N> {
         self.handle
     }
 
+    /// The total elastic energy stored in this deformable volume.
+    ///
+    /// This is computed from the elastic strains of the elements, as assembled during the last timestep.
+    pub fn elastic_energy(&self) -> N {
+        let _2: N = na::convert(2.0);
+        let mut energy = N::zero();
+
+        for elt in &self.elements {
+            let strain = elt.total_strain - elt.plastic_strain;
+            // strain^T * D * strain
+            let normal_sq = strain.x * strain.x + strain.y * strain.y + strain.z * strain.z;
+            let normal_cross = strain.x * strain.y + strain.x * strain.z + strain.y * strain.z;
+            let shear_sq = strain.w * strain.w + strain.a * strain.a + strain.b * strain.b;
+            let strain_energy = self.d0 * normal_sq + _2 * self.d1 * normal_cross + self.d2 * shear_sq;
+
+            energy += strain_energy * elt.volume / _2;
+        }
+
+        energy
+    }
+
+    /// The total strain of the `i`-th element, as assembled during the last timestep.
+    ///
+    /// The strain is expressed in the rest frame of the element, in Voigt notation, i.e., its components are
+    /// `(xx, yy, zz, xy, xz, yz)` where the last three are engineering shear strains. It includes the plastic strain.
+    /// Returns `None` if this volume does not have an `i`-th element.
+    pub fn element_strain(&self, i: usize) -> Option<Vector6<N>> {
+        self.elements.get(i).map(|elt| elt.total_strain)
+    }
+
+    /// The von Mises equivalent stress of the `i`-th element, as assembled during the last timestep.
+    ///
+    /// This is computed from the elastic strain of the element, i.e., its total strain minus its plastic strain.
+    /// Returns `None` if this volume does not have an `i`-th element.
+    pub fn element_von_mises_stress(&self, i: usize) -> Option<N> {
+        let elt = self.elements.get(i)?;
+        let strain = elt.total_strain - elt.plastic_strain;
+        // stress = D * strain
+        let sxx = self.d0 * strain.x + self.d1 * (strain.y + strain.z);
+        let syy = self.d0 * strain.y + self.d1 * (strain.x + strain.z);
+        let szz = self.d0 * strain.z + self.d1 * (strain.x + strain.y);
+        let sxy = self.d2 * strain.w;
+        let sxz = self.d2 * strain.a;
+        let syz = self.d2 * strain.b;
+
+        let _3: N = na::convert(3.0);
+        let _1_2: N = na::convert(1.0 / 2.0);
+        let normal = (sxx - syy) * (sxx - syy) + (syy - szz) * (syy - szz) + (szz - sxx) * (szz - sxx);
+        let shear = sxy * sxy + sxz * sxz + syz * syz;
+        Some((normal * _1_2 + shear * _3).sqrt())
+    }
+
     fn assemble_mass_with_damping(&mut self, dt: N) {
         let mass_damping = dt * self.damping_coeffs.0;
 
         for elt in self.elements.iter() {
             let coeff_mass = elt.density * elt.volume / na::convert::<_, N>(20.0f64) * (N::one() + mass_damping);
 
+            if self.lumped_mass {
+                // Each row of the consistent mass matrix sums to `coeff_mass * 5`.
+                let node_mass = coeff_mass * na::convert(5.0);
+
+                for a in 0..4 {
+                    let ia = elt.indices[a];
+
+                    if !self.kinematic_nodes[ia / DIM] {
+                        let mut node_mass_mat = self.augmented_mass.fixed_slice_mut::<U3, U3>(ia, ia);
+                        node_mass_mat[(0, 0)] += node_mass;
+                        node_mass_mat[(1, 1)] += node_mass;
+                        node_mass_mat[(2, 2)] += node_mass;
+                    }
+                }
+
+                continue;
+            }
+
             for a in 0..4 {
                 let ia = elt.indices[a];
 
@@ -339,6 +532,47 @@ impl<N: RealField> FEMVolume<N> {
 
         self.accelerations.copy_from(&self.forces);
 
+        // Rayleigh damping proportional to the mass. The part proportional to the stiffness is
+        // accounted for with the elastic forces below.
+        let (mass_damping, stiffness_damping) = self.damping_coeffs;
+
+        if !mass_damping.is_zero() {
+            for elt in self.elements.iter() {
+                let coeff_mass = elt.density * elt.volume / na::convert::<_, N>(20.0f64) * mass_damping;
+
+                for a in 0..4 {
+                    let ia = elt.indices[a];
+
+                    if self.kinematic_nodes[ia / DIM] {
+                        continue;
+                    }
+
+                    let mut damping = Vector3::zeros();
+
+                    if self.lumped_mass {
+                        damping += self.velocities.fixed_rows::<U3>(ia) * (coeff_mass * na::convert(5.0));
+                    } else {
+                        for b in 0..4 {
+                            let ib = elt.indices[b];
+
+                            if !self.kinematic_nodes[ib / DIM] {
+                                let mass_contribution = if a == b {
+                                    coeff_mass * na::convert(2.0)
+                                } else {
+                                    coeff_mass
+                                };
+
+                                damping += self.velocities.fixed_rows::<U3>(ib) * mass_contribution;
+                            }
+                        }
+                    }
+
+                    let mut forces_part = self.accelerations.fixed_rows_mut::<U3>(ia);
+                    forces_part -= damping;
+                }
+            }
+        }
+
         // Gravity
         if self.gravity_enabled {
             for elt in self.elements.iter() {
@@ -366,6 +600,7 @@ impl<N: RealField> FEMVolume<N> {
              *
              */
             elt.total_strain = Vector6::zeros();
+            let mut damping_strain = Vector6::zeros();
 
             // Compute plastic strain.
             for a in 0..4 {
@@ -378,6 +613,7 @@ impl<N: RealField> FEMVolume<N> {
                 let pos_part = self.positions.fixed_rows::<U3>(ia);
                 let ref_pos_part = self.rest_positions.fixed_rows::<U3>(ia);
                 let dpos = elt.inv_rot * (vel_part * dt + pos_part) - ref_pos_part;
+                let dvel = elt.inv_rot * (vel_part * stiffness_damping);
                 // total_strain += B_n * dpos
                 elt.total_strain += Vector6::new(
                     bn * dpos.x,
@@ -387,6 +623,15 @@ impl<N: RealField> FEMVolume<N> {
                     dn * dpos.x + bn * dpos.z,
                     dn * dpos.y + cn * dpos.z
                 );
+                // damping_strain += B_n * dvel
+                damping_strain += Vector6::new(
+                    bn * dvel.x,
+                    cn * dvel.y,
+                    dn * dvel.z,
+                    cn * dvel.x + bn * dvel.y,
+                    dn * dvel.x + bn * dvel.z,
+                    dn * dvel.y + cn * dvel.z
+                );
             }
 
             let strain = elt.total_strain - elt.plastic_strain;
@@ -427,7 +672,7 @@ impl<N: RealField> FEMVolume<N> {
                     let dn2 = dn * d2_vol;
 
                     // P_n * strain
-                    let strain = elt.total_strain - elt.plastic_strain;
+                    let strain = elt.total_strain - elt.plastic_strain + damping_strain;
                     #[cfg_attr(rustfmt, rustfmt_skip)]
                     let projected_strain = Vector3::new(
                         bn0 * strain.x + bn1 * strain.y + bn1 * strain.z + cn2 * strain.w + dn2 * strain.a,
@@ -439,6 +684,40 @@ impl<N: RealField> FEMVolume<N> {
                     force_part -= elt.rot * projected_strain;
                 }
             }
+
+            /*
+             *
+             * Inversion recovery.
+             *
+             */
+            if let Some(stiffness) = self.inversion_recovery {
+                // Ratio between the current and the rest volumes of the element.
+                let volume_ratio = (elt.local_j_inv.fixed_slice::<U3, U3>(0, 1) * elt.j).determinant();
+
+                if volume_ratio <= N::zero() {
+                    let ab = elt.j.row(0).transpose();
+                    let ac = elt.j.row(1).transpose();
+                    let ad = elt.j.row(2).transpose();
+
+                    // Gradient of the current volume wrt. the position of each node.
+                    let grad_b = ac.cross(&ad) / _6;
+                    let grad_c = ad.cross(&ab) / _6;
+                    let grad_d = ab.cross(&ac) / _6;
+                    let grad_a = -(grad_b + grad_c + grad_d);
+                    let grads = [grad_a, grad_b, grad_c, grad_d];
+
+                    let coeff = stiffness * self.young_modulus * (_1 - volume_ratio) * elt.volume.signum();
+
+                    for a in 0..4 {
+                        let ia = elt.indices[a];
+
+                        if !self.kinematic_nodes[ia / DIM] {
+                            let mut force_part = self.accelerations.fixed_rows_mut::<U3>(ia);
+                            force_part += grads[a] * coeff;
+                        }
+                    }
+                }
+            }
         }
     }
 
@@ -531,6 +810,27 @@ impl<N: RealField> FEMVolume<N> {
         (TriMesh::new(vertices, indices, None), deformation_indices, body_parts)
     }
 
+    /// Returns a triangle mesh at the boundary of this volume, with one vertex per node of this volume,
+    /// as well as the mapping between the mesh triangles and this volume body parts.
+    ///
+    /// Unlike `self.boundary_mesh()`, the `i`-th vertex of the returned mesh always corresponds to the
+    /// `i`-th node of this volume so no renumbering of the degrees of freedom is needed. The nodes that are
+    /// not on the boundary are vertices without any adjacent triangle.
+    ///
+    /// The output is (triangle mesh, element to body part map).
+    pub fn boundary_mesh_with_all_nodes(&self) -> (TriMesh<N>, Vec<usize>) {
+        let indices = self.boundary();
+        let vertices = self.positions
+            .as_slice()
+            .chunks(3)
+            .map(|p| Point3::new(p[0], p[1], p[2]))
+            .collect();
+        let body_parts = indices.iter().map(|i| i.1).collect();
+        let indices = indices.into_iter().map(|i| i.0 / 3).collect();
+
+        (TriMesh::new(vertices, indices, None), body_parts)
+    }
+
     /// Renumber degrees of freedom so that the `deformation_indices[i]`-th DOF becomes the `i`-th one.
     pub fn renumber_dofs(&mut self, deformation_indices: &[usize]) {
         let mut dof_map: Vec<_> = (0..).take(self.positions.len()).collect();
@@ -566,7 +866,38 @@ impl<N: RealField> FEMVolume<N> {
         self.rest_positions = new_rest_positions;
     }
 
-// FIXME: add a method to apply a transformation to the whole volume.
+    /// Applies the rigid transformation `iso` to the whole volume, i.e., to both its current and rest shapes.
+    ///
+    /// The node velocities are rotated as well, and the elastic and plastic deformations are preserved.
+    /// This is typically used to reposition a volume built from a mesh expressed in another frame.
+    pub fn transform(&mut self, iso: &Isometry3<N>) {
+        let rot = iso.rotation.to_rotation_matrix();
+        let inv_rot = rot.inverse();
+
+        for i in (0..self.positions.len()).step_by(DIM) {
+            let pos = iso * Point3::from(self.positions.fixed_rows::<U3>(i).into_owned());
+            let rest_pos = iso * Point3::from(self.rest_positions.fixed_rows::<U3>(i).into_owned());
+            let vel = rot * self.velocities.fixed_rows::<U3>(i).into_owned();
+            let acc = rot * self.accelerations.fixed_rows::<U3>(i).into_owned();
+            self.positions.fixed_rows_mut::<U3>(i).copy_from(&pos.coords);
+            self.rest_positions.fixed_rows_mut::<U3>(i).copy_from(&rest_pos.coords);
+            self.velocities.fixed_rows_mut::<U3>(i).copy_from(&vel);
+            self.accelerations.fixed_rows_mut::<U3>(i).copy_from(&acc);
+        }
+
+        for elt in &mut self.elements {
+            // The rest edges of the element are rotated, so the inverse of its rest jacobian is rotated
+            // too. Its strains are expressed wrt. the rest frame, hence the rotation of the plastic strain.
+            elt.local_j_inv = rot.matrix() * elt.local_j_inv;
+            elt.rot = rot * elt.rot * inv_rot;
+            elt.inv_rot = elt.rot.inverse();
+            elt.plastic_strain = rotated_strain(&elt.plastic_strain, &rot);
+        }
+
+        self.update_status.set_position_changed(true);
+        self.update_status.set_velocity_changed(true);
+        self.update_status.set_local_inertia_changed(true);
+    }
 
     /// Constructs an axis-aligned cube with regular subdivisions along each axis.
     ///
@@ -664,10 +995,105 @@ impl<N: RealField> FEMVolume<N> {
     pub fn clear_kinematic_nodes(&mut self) {
         self.update_status.set_status_changed(true);
         self.update_status.set_local_inertia_changed(true);
-        self.kinematic_nodes.fill(false)
+        self.kinematic_nodes.fill(false);
+        self.pinned_nodes.clear();
+    }
+
+    /// Pins the given nodes to the anchor frame `anchor`.
+    ///
+    /// The pinned nodes are set as kinematic and will follow the anchor frame as it is moved
+    /// with `self.update_pinned_anchor(...)`. Any previously pinned node remains pinned.
+    pub fn pin_nodes(&mut self, nodes: &[usize], anchor: &Isometry3<N>) {
+        // Express the already pinned nodes wrt. the new anchor.
+        let to_new_anchor = anchor.inverse() * self.pinned_anchor;
+        for pinned in &mut self.pinned_nodes {
+            pinned.1 = to_new_anchor * pinned.1;
+        }
+
+        self.pinned_anchor = *anchor;
+
+        for i in nodes {
+            self.set_node_kinematic(*i, true);
+            let pt = Point3::from(self.positions.fixed_rows::<U3>(*i * DIM).into_owned());
+            self.pinned_nodes.push((*i, anchor.inverse_transform_point(&pt)));
+        }
+    }
+
+    /// Unpins all the nodes pinned with `self.pin_nodes(...)` and set them as non-kinematic.
+    pub fn unpin_nodes(&mut self) {
+        for (i, _) in mem::replace(&mut self.pinned_nodes, Vec::new()) {
+            self.set_node_kinematic(i, false);
+        }
+    }
+
+    /// The indices of the nodes pinned to the anchor frame.
+    pub fn pinned_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.pinned_nodes.iter().map(|p| p.0)
+    }
+
+    /// The current anchor frame the pinned nodes are attached to.
+    pub fn pinned_anchor(&self) -> &Isometry3<N> {
+        &self.pinned_anchor
+    }
+
+    /// Moves the anchor frame of the pinned nodes to `anchor`.
+    ///
+    /// The velocities of the pinned nodes are set so that they reach their target
+    /// positions on the new anchor frame after a timestep of length `dt`.
+    pub fn update_pinned_anchor(&mut self, anchor: &Isometry3<N>, dt: N) {
+        self.pinned_anchor = *anchor;
+        let inv_dt = N::one() / dt;
+
+        for (i, local_pt) in &self.pinned_nodes {
+            let target = anchor * local_pt;
+            let curr = self.positions.fixed_rows::<U3>(*i * DIM).into_owned();
+            self.velocities.fixed_rows_mut::<U3>(*i * DIM).copy_from(&((target.coords - curr) * inv_dt));
+        }
+
+        self.update_status.set_velocity_changed(true);
+    }
+
+    /// The state of this volume modified by the simulation.
+    pub(crate) fn state(&self) -> FEMVolumeState<N> {
+        FEMVolumeState {
+            elements: self.elements.clone(),
+            positions: self.positions.clone(),
+            velocities: self.velocities.clone(),
+            augmented_mass: self.augmented_mass.clone(),
+            inv_augmented_mass: self.inv_augmented_mass.clone(),
+            used_fallback: self.used_fallback,
+            energy: self.activation.energy(),
+        }
+    }
+
+    /// Restores a state previously returned by `self.state()`.
+    ///
+    /// The element rotations are restored as well instead of being recomputed because their
+    /// extraction depends on their previous value.
+    pub(crate) fn set_state(&mut self, state: &FEMVolumeState<N>) {
+        self.elements.clone_from(&state.elements);
+        self.positions.copy_from(&state.positions);
+        self.velocities.copy_from(&state.velocities);
+        self.augmented_mass.copy_from(&state.augmented_mass);
+        self.inv_augmented_mass = state.inv_augmented_mass.clone();
+        self.used_fallback = state.used_fallback;
+        self.activation.set_energy(state.energy);
+        self.update_status.set_velocity_changed(true);
     }
 }
 
+/// The state of a deformable volume modified by the simulation, as saved by a `WorldSnapshot`.
+#[derive(Clone)]
+pub(crate) struct FEMVolumeState<N: RealField> {
+    elements: Vec<TetrahedralElement<N>>,
+    positions: DVector<N>,
+    velocities: DVector<N>,
+    augmented_mass: DMatrix<N>,
+    inv_augmented_mass: Cholesky<N, Dynamic>,
+    used_fallback: bool,
+    energy: N,
+}
+
 impl<N: RealField> Body<N> for FEMVolume<N> {
     #[inline]
     fn name(&self) -> &str {
@@ -689,6 +1115,16 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
         self.gravity_enabled = enabled
     }
 
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        self.position_correction_enabled
+    }
+
+    #[inline]
+    fn set_position_correction_enabled(&mut self, enabled: bool) {
+        self.position_correction_enabled = enabled
+    }
+
     #[inline]
     fn deformed_positions(&self) -> Option<(DeformationsType, &[N])> {
         Some((DeformationsType::Vectors, self.positions.as_slice()))
@@ -701,7 +1137,7 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
     }
 
     fn update_kinematics(&mut self) {
-        if !self.update_status.position_changed() {
+        if !self.update_status.position_changed() && !self.update_status.velocity_changed() {
             return;
         }
 
@@ -723,9 +1159,14 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
 
 
             let g = (elt.local_j_inv.fixed_slice::<U3, U3>(0, 1) * elt.j).transpose();
-            elt.rot = Rotation3::from_matrix_eps(&g, N::default_epsilon(), 20, elt.rot);
+            elt.rot = if self.polar_decomposition {
+                polar_rotation(&g)
+            } else {
+                Rotation3::from_matrix_eps(&g, N::default_epsilon(), 20, elt.rot)
+            };
             elt.inv_rot = elt.rot.inverse();
             elt.com = Point3::from(a + b + c + d) * na::convert::<_, N>(1.0 / 4.0);
+            elt.velocity = tetrahedron_velocity(&elt.com, elt.indices, &self.positions, &self.velocities);
         }
     }
 
@@ -740,11 +1181,37 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
             self.assemble_mass_with_damping(dt);
             self.assemble_stiffness(dt);
 
-            // FIXME: avoid allocation inside Cholesky at each timestep.
-            // FIXME: if Cholesky fails fallback to some sort of mass-spring formulation?
-            //        If we do so we should add a bool to let give the user the ability to check which
-            //        model has been used during the last timestep.
-            self.inv_augmented_mass = Cholesky::new(self.augmented_mass.clone()).expect("Singular system found.");
+            if !self.mass_regularization.is_zero() {
+                for i in 0..self.augmented_mass.nrows() {
+                    self.augmented_mass[(i, i)] += self.mass_regularization;
+                }
+            }
+
+            // Reuse the storage of the previous factorization to avoid allocating a new matrix at each timestep.
+            let placeholder = Cholesky::new(DMatrix::zeros(0, 0)).unwrap();
+            let mut factorization = mem::replace(&mut self.inv_augmented_mass, placeholder).unpack_dirty();
+
+            if factorization.shape() == self.augmented_mass.shape() {
+                factorization.copy_from(&self.augmented_mass);
+            } else {
+                factorization = self.augmented_mass.clone();
+            }
+
+            match Cholesky::new(factorization) {
+                Some(inv_augmented_mass) => {
+                    self.inv_augmented_mass = inv_augmented_mass;
+                    self.used_fallback = false;
+                }
+                None => {
+                    // Degenerate elements make the system singular. Instead of aborting the
+                    // simulation, keep only the diagonal of the system, made positive.
+                    let eps = N::default_epsilon();
+                    let diag = self.augmented_mass.map_diagonal(|m| if m > eps { m } else { eps });
+                    self.inv_augmented_mass = Cholesky::new(DMatrix::from_diagonal(&diag))
+                        .expect("A positive diagonal matrix can always be factorized.");
+                    self.used_fallback = true;
+                }
+            }
         }
 
     }
@@ -828,16 +1295,34 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
         self.update_status.clear();
         self.activation.set_energy(N::zero());
         self.velocities.fill(N::zero());
+
+        for elt in &mut self.elements {
+            elt.velocity = Velocity::zero();
+        }
     }
 
     fn set_deactivation_threshold(&mut self, threshold: Option<N>) {
         self.activation.set_deactivation_threshold(threshold)
     }
 
+    #[inline]
+    fn keep_awake_while_forced(&mut self, enabled: bool) {
+        self.activation.set_keep_awake_while_forced(enabled)
+    }
+
+    #[inline]
+    fn user_data(&self) -> Option<&(Any + Send + Sync)> {
+        self.user_data.as_ref().map(|d| &**d)
+    }
+
     fn part(&self, id: usize) -> Option<&BodyPart<N>> {
         self.elements.get(id).map(|e| e as &BodyPart<N>)
     }
 
+    fn num_parts(&self) -> usize {
+        self.elements.len()
+    }
+
     fn world_point_at_material_point(&self, part: &BodyPart<N>, point: &Point3<N>) -> Point3<N> {
         let elt = part.downcast_ref::<TetrahedralElement<N>>().expect("The provided body part must be tetrahedral element");
         fem_helper::world_point_at_material_point(FiniteElementIndices::Tetrahedron(elt.indices), &self.positions, point)
@@ -890,7 +1375,7 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
 
     #[inline]
     fn has_active_internal_constraints(&mut self) -> bool {
-        false
+        self.volume_conservation
     }
 
     #[inline]
@@ -903,7 +1388,55 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
     fn step_solve_internal_velocity_constraints(&mut self, _: &mut DVectorSliceMut<N>) {}
 
     #[inline]
-    fn step_solve_internal_position_constraints(&mut self, _: &IntegrationParameters<N>) {}
+    fn step_solve_internal_position_constraints(&mut self, params: &IntegrationParameters<N>) {
+        if !self.volume_conservation {
+            return;
+        }
+
+        let _6: N = na::convert(6.0);
+        // The volume of a cube with sides of length `allowed_linear_error`.
+        let allowed_volume_error = params.allowed_linear_error * params.allowed_linear_error * params.allowed_linear_error;
+
+        for elt in &self.elements {
+            let a = self.positions.fixed_rows::<U3>(elt.indices.x).into_owned();
+            let ab = self.positions.fixed_rows::<U3>(elt.indices.y) - a;
+            let ac = self.positions.fixed_rows::<U3>(elt.indices.z) - a;
+            let ad = self.positions.fixed_rows::<U3>(elt.indices.w) - a;
+
+            let error = ab.dot(&ac.cross(&ad)) / _6 - elt.volume;
+            if error.abs() <= allowed_volume_error {
+                continue;
+            }
+
+            // The gradients of the volume wrt. the position of each node.
+            let grad_b = ac.cross(&ad) / _6;
+            let grad_c = ad.cross(&ab) / _6;
+            let grad_d = ab.cross(&ac) / _6;
+            let grad_a = -(grad_b + grad_c + grad_d);
+            let grads = [grad_a, grad_b, grad_c, grad_d];
+
+            let mut denom = N::zero();
+            for k in 0..4 {
+                if !self.kinematic_nodes[elt.indices[k] / DIM] {
+                    denom += grads[k].norm_squared();
+                }
+            }
+
+            if denom.is_zero() {
+                continue;
+            }
+
+            let lambda = -error * params.erp / denom;
+
+            for k in 0..4 {
+                if !self.kinematic_nodes[elt.indices[k] / DIM] {
+                    self.positions.fixed_rows_mut::<U3>(elt.indices[k]).axpy(lambda, &grads[k], N::one());
+                }
+            }
+        }
+
+        self.update_status.set_position_changed(true);
+    }
 
 
     fn apply_force_at_local_point(&mut self, part_id: usize, force: &Vector3<N>, point: &Point3<N>, force_type: ForceType, auto_wake_up: bool) {
@@ -911,7 +1444,7 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
             return;
         }
 
-        if auto_wake_up {
+        if auto_wake_up || (self.activation.is_kept_awake_while_forced() && force.iter().any(|f| !f.is_zero())) {
             self.activate()
         }
 
@@ -942,6 +1475,7 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
                 }
                 self.inv_augmented_mass.solve_mut(dvel);
                 self.velocities += &*dvel;
+                self.update_status.set_velocity_changed(true);
             }
             ForceType::AccelerationChange => {
                 let mass = element.density * element.volume;
@@ -958,6 +1492,7 @@ impl<N: RealField> Body<N> for FEMVolume<N> {
                         self.velocities.fixed_rows_mut::<U3>(element.indices[i]).add_assign(forces[i]);
                     }
                 }
+                self.update_status.set_velocity_changed(true);
             }
         }
     }
@@ -1008,21 +1543,42 @@ impl<N: RealField> BodyPart<N> for TetrahedralElement<N> {
     }
 
     fn velocity(&self) -> Velocity<N> {
-        unimplemented!()
+        self.velocity
     }
 
     fn inertia(&self) -> Inertia<N> {
-        Inertia::new(self.volume * self.density, Matrix3::identity())
+        let mass = self.volume * self.density;
+        let _1_4: N = na::convert(1.0 / 4.0);
+
+        // Vertices relative to the center of mass, computed from the edges stored
+        // as the rows of `self.j`.
+        let ab = self.j.row(0).transpose();
+        let ac = self.j.row(1).transpose();
+        let ad = self.j.row(2).transpose();
+        let a = -(ab + ac + ad) * _1_4;
+        let vertices = [a, a + ab, a + ac, a + ad];
+
+        // Covariance of a tetrahedron wrt. its center of mass: m / 20 * sum(v_i * v_i^T).
+        let mut covariance = Matrix3::zeros();
+        for v in &vertices {
+            covariance += v * v.transpose();
+        }
+        covariance *= mass / na::convert(20.0);
+
+        let angular = Matrix3::from_diagonal_element(covariance.trace()) - covariance;
+        Inertia::new(mass, angular)
     }
 
     fn local_inertia(&self) -> Inertia<N> {
-        Inertia::new(self.volume * self.density, Matrix3::identity())
+        let inertia = self.inertia();
+        let angular = self.inv_rot.matrix() * inertia.angular * self.rot.matrix();
+        Inertia::new(inertia.linear, angular)
     }
 }
 
 enum FEMVolumeDescGeometry<'a, N: RealField> {
     Cube(usize, usize, usize),
-    Tetrahedrons(&'a [Point3<N>], &'a [Point4<usize>])
+    Tetrahedrons(Cow<'a, [Point3<N>]>, Cow<'a, [Point4<usize>]>)
 }
 
 /// A builder for FEMVolume bodies.
@@ -1041,8 +1597,12 @@ pub struct FEMVolumeDesc<'a, N: RealField> {
     stiffness_damping: N,
     density: N,
     plasticity: (N, N, N),
+    inversion_recovery: Option<N>,
     kinematic_nodes: Vec<usize>,
-    status: BodyStatus
+    status: BodyStatus,
+    lumped_mass: bool,
+    polar_decomposition: bool,
+    volume_conservation: bool,
 }
 
 impl<'a, N: RealField> FEMVolumeDesc<'a, N> {
@@ -1062,14 +1622,26 @@ impl<'a, N: RealField> FEMVolumeDesc<'a, N> {
             stiffness_damping: N::zero(),
             density: N::one(),
             plasticity: (N::zero(), N::zero(), N::zero()),
+            inversion_recovery: None,
             kinematic_nodes: Vec::new(),
-            status: BodyStatus::Dynamic
+            status: BodyStatus::Dynamic,
+            lumped_mass: false,
+            polar_decomposition: false,
+            volume_conservation: false,
         }
     }
 
     /// Create a volume form the given tetrahedrons.
     pub fn new(vertices: &'a [Point3<N>], tetrahedrons: &'a [Point4<usize>]) -> Self {
-        Self::with_geometry(FEMVolumeDescGeometry::Tetrahedrons(vertices, tetrahedrons))
+        Self::with_geometry(FEMVolumeDescGeometry::Tetrahedrons(Cow::Borrowed(vertices), Cow::Borrowed(tetrahedrons)))
+    }
+
+    /// Create a volume from the tetrahedral mesh of a pair of TetGen `.node` and `.ele` files.
+    ///
+    /// The tetrahedrons with a negative volume are reoriented. See `utils::parse_tetgen` for details.
+    pub fn from_tetgen(node_path: impl AsRef<Path>, ele_path: impl AsRef<Path>) -> io::Result<Self> {
+        let (vertices, tetrahedrons) = crate::utils::load_tetgen(node_path, ele_path)?;
+        Ok(Self::with_geometry(FEMVolumeDescGeometry::Tetrahedrons(Cow::Owned(vertices), Cow::Owned(tetrahedrons))))
     }
 
     /// Create a cube-shaped body.
@@ -1102,8 +1674,12 @@ impl<'a, N: RealField> FEMVolumeDesc<'a, N> {
         mass_damping, set_mass_damping, mass_damping: N
         stiffness_damping, set_stiffness_damping, stiffness_damping: N
         density, set_density, density: N
+        inversion_recovery, set_inversion_recovery, inversion_recovery: Option<N>
         status, set_status, status: BodyStatus
         position, set_position, position: Isometry3<N>
+        lumped_mass, set_lumped_mass, lumped_mass: bool
+        polar_decomposition, set_polar_decomposition, polar_decomposition: bool
+        volume_conservation, set_volume_conservation, volume_conservation: bool
     );
 
     desc_custom_getters!(
@@ -1123,8 +1699,12 @@ impl<'a, N: RealField> FEMVolumeDesc<'a, N> {
         [val] get_mass_damping -> mass_damping: N
         [val] get_stiffness_damping -> stiffness_damping: N
         [val] get_density -> density: N
+        [val] get_inversion_recovery -> inversion_recovery: Option<N>
         [val] get_status -> status: BodyStatus
         [val] is_collider_enabled -> collider_enabled: bool
+        [val] is_mass_lumped -> lumped_mass: bool
+        [val] uses_polar_decomposition -> polar_decomposition: bool
+        [val] conserves_volume -> volume_conservation: bool
         [ref] get_position -> position: Isometry3<N>
         [ref] get_scale -> scale: Vector3<N>
     );
@@ -1145,7 +1725,7 @@ impl<'a, N: RealField> BodyDesc<N> for FEMVolumeDesc<'a, N> {
                                        nx, ny, nz, self.density, self.young_modulus,
                                        self.poisson_ratio,
                                        (self.mass_damping, self.stiffness_damping)),
-            FEMVolumeDescGeometry::Tetrahedrons(pts, idx) =>
+            FEMVolumeDescGeometry::Tetrahedrons(ref pts, ref idx) =>
                 FEMVolume::new(handle, pts, idx, &self.position, &self.scale,
                                       self.density, self.young_modulus, self.poisson_ratio,
                                       (self.mass_damping, self.stiffness_damping))
@@ -1153,9 +1733,13 @@ impl<'a, N: RealField> BodyDesc<N> for FEMVolumeDesc<'a, N> {
 
         vol.set_deactivation_threshold(self.sleep_threshold);
         vol.set_plasticity(self.plasticity.0, self.plasticity.1, self.plasticity.2);
+        vol.set_inversion_recovery(self.inversion_recovery);
         vol.enable_gravity(self.gravity_enabled);
         vol.set_name(self.name.clone());
         vol.set_status(self.status);
+        vol.set_lumped_mass(self.lumped_mass);
+        vol.set_polar_decomposition(self.polar_decomposition);
+        vol.set_volume_conservation(self.volume_conservation);
         let _ = vol.set_user_data(self.user_data.as_ref().map(|data| data.0.to_any()));
 
         for i in &self.kinematic_nodes {
@@ -1172,4 +1756,661 @@ impl<'a, N: RealField> BodyDesc<N> for FEMVolumeDesc<'a, N> {
 
         vol
     }
-}
\ No newline at end of file
+}
+
+// The rotational part of the polar decomposition of `g`, i.e., the rotation closest to `g`.
+fn polar_rotation<N: RealField>(g: &Matrix3<N>) -> Rotation3<N> {
+    let svd = g.svd(true, true);
+    let mut u = svd.u.unwrap();
+    let v_t = svd.v_t.unwrap();
+
+    // Flip the axis with the smallest singular value if `g` contains a reflection, e.g., for inverted elements.
+    if (u * v_t).determinant() < N::zero() {
+        let i = svd.singular_values.imin();
+        u.column_mut(i).neg_mut();
+    }
+
+    Rotation3::from_matrix_unchecked(u * v_t)
+}
+
+// The velocity of the center of mass `com` of a tetrahedron, and the angular velocity best fitting, in the
+// least-squares sense, the velocities of its vertices. This is exact if the tetrahedron moves rigidly.
+fn tetrahedron_velocity<N: RealField>(
+    com: &Point3<N>,
+    indices: Point4<usize>,
+    positions: &DVector<N>,
+    velocities: &DVector<N>,
+) -> Velocity<N> {
+    let _1_4: N = na::convert(1.0 / 4.0);
+    let linear = (0..4).fold(Vector3::zeros(), |sum, i| sum + velocities.fixed_rows::<U3>(indices[i])) * _1_4;
+    let mut inertia = Matrix3::zeros();
+    let mut momentum = Vector3::zeros();
+
+    for i in 0..4 {
+        let r = positions.fixed_rows::<U3>(indices[i]) - com.coords;
+        let v = velocities.fixed_rows::<U3>(indices[i]) - linear;
+        inertia += Matrix3::from_diagonal_element(r.norm_squared()) - r * r.transpose();
+        momentum += r.cross(&v);
+    }
+
+    let angular = inertia.try_inverse().map(|inv| inv * momentum).unwrap_or_else(Vector3::zeros);
+    Velocity::new(linear, angular)
+}
+
+// The strain `(xx, yy, zz, xy, xz, yz)`, with engineering shear strains, expressed in a frame rotated by `rot`.
+fn rotated_strain<N: RealField>(strain: &Vector6<N>, rot: &Rotation3<N>) -> Vector6<N> {
+    let _2: N = na::convert(2.0);
+    let tensor = Matrix3::new(
+        strain[0], strain[3] / _2, strain[4] / _2,
+        strain[3] / _2, strain[1], strain[5] / _2,
+        strain[4] / _2, strain[5] / _2, strain[2],
+    );
+    let rotated = rot.matrix() * tensor * rot.matrix().transpose();
+
+    Vector6::new(
+        rotated.m11,
+        rotated.m22,
+        rotated.m33,
+        rotated.m12 * _2,
+        rotated.m13 * _2,
+        rotated.m23 * _2,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use na::{Isometry3, Matrix3, Point3, Point4, Rotation3, U3, Vector3, Vector6};
+    use ncollide::shape::{Cuboid, ShapeHandle, TriMesh};
+    use crate::material::{BasicMaterial, MaterialCombineMode, MaterialHandle};
+    use crate::object::{Body, BodyDesc, BodyHandle, BodyPartHandle, ColliderDesc, DeformableColliderDesc, FEMVolume, FEMVolumeDesc};
+    use crate::solver::IntegrationParameters;
+    use crate::world::{ColliderWorld, World};
+
+    #[test]
+    fn tetrahedral_element_inertia() {
+        let vertices = [
+            Point3::origin(),
+            Point3::new(1.0f64, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+        ];
+        let indices = [Point4::new(0, 1, 2, 3)];
+        let density = 2.0;
+        let mut volume = FEMVolume::new(
+            BodyHandle::ground(), &vertices, &indices, &Isometry3::identity(),
+            &Vector3::repeat(1.0), density, 1.0, 0.0, (0.0, 0.0));
+        volume.update_kinematics();
+
+        // Analytic inertia tensor of the unit right tetrahedron wrt. its center of mass.
+        let mass = density / 6.0;
+        let diag = mass / 80.0 * 6.0;
+        let off_diag = mass / 480.0 * 6.0;
+        let expected = Matrix3::new(
+            diag, off_diag, off_diag,
+            off_diag, diag, off_diag,
+            off_diag, off_diag, diag,
+        );
+
+        let actual = volume.part(0).unwrap().world_inertia();
+        assert!(relative_eq!(actual.linear, mass, epsilon = 1.0e-10));
+        assert!(
+            relative_eq!(actual.angular, expected, epsilon = 1.0e-10),
+            format!("Inertia tensors do not match: actual {:?}, expected: {:?}.", actual.angular, expected)
+        );
+    }
+
+    // Simulates a soft beam hanging from its top nodes and returns the number of steps
+    // required to come to rest, as well as its final vertical sag.
+    fn hanging_beam(lumped_mass: bool) -> (usize, f64) {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector3::y() * -9.81);
+
+        let volume = FEMVolumeDesc::cube(1, 4, 1)
+            .scale(Vector3::new(0.5, 2.0, 0.5))
+            .young_modulus(1.0e3)
+            .mass_damping(5.0)
+            .poisson_ratio(0.2)
+            .sleep_threshold(None)
+            .lumped_mass(lumped_mass)
+            .build(&mut world);
+        let handle = volume.handle();
+
+        let top = volume.positions().iter().skip(1).step_by(3).cloned().fold(-1.0e10, f64::max);
+        let bottom = volume.positions().iter().skip(1).step_by(3).cloned().fold(1.0e10, f64::min);
+        let nnodes = volume.positions().len() / 3;
+        for i in 0..nnodes {
+            if volume.positions()[i * 3 + 1] == top {
+                volume.set_node_kinematic(i, true);
+            }
+        }
+
+        let mut rest_step = None;
+        for step in 0..150 {
+            world.step();
+            let volume = world.body(handle).unwrap().downcast_ref::<FEMVolume<f64>>().unwrap();
+            assert!(volume.positions().iter().all(|x| x.is_finite()));
+
+            if volume.velocities().amax() < 1.0e-3 {
+                if rest_step.is_none() {
+                    rest_step = Some(step);
+                }
+            } else {
+                rest_step = None;
+            }
+        }
+
+        let volume = world.body(handle).unwrap().downcast_ref::<FEMVolume<f64>>().unwrap();
+        let new_bottom = volume.positions().iter().skip(1).step_by(3).cloned().fold(1.0e10, f64::min);
+        (rest_step.expect("The beam did not come to rest."), bottom - new_bottom)
+    }
+
+    #[test]
+    fn lumped_and_consistent_mass_settle() {
+        let (consistent_steps, consistent_sag) = hanging_beam(false);
+        let (lumped_steps, lumped_sag) = hanging_beam(true);
+
+        // Both mass matrices have the same total mass so they must reach the same equilibrium.
+        assert!(consistent_sag > 0.0);
+        assert!(relative_eq!(consistent_sag, lumped_sag, max_relative = 1.0e-3));
+        assert!(lumped_steps <= consistent_steps * 3 / 2 && consistent_steps <= lumped_steps * 3 / 2);
+    }
+
+    // Squashes a cube past inversion along the `y` axis, simulates it, and returns the number of inverted elements.
+    fn squashed_cube(inversion_recovery: Option<f64>, nsteps: usize) -> usize {
+        let mut world = World::<f64>::new();
+        let volume = FEMVolumeDesc::cube(2, 2, 2)
+            .young_modulus(1.0)
+            .mass_damping(0.1)
+            .sleep_threshold(None)
+            .inversion_recovery(inversion_recovery)
+            .build(&mut world);
+        let handle = volume.handle();
+
+        let (_, positions) = volume.deformed_positions_mut().unwrap();
+        for y in positions.iter_mut().skip(1).step_by(3) {
+            *y *= -0.2;
+        }
+
+        for _ in 0..nsteps {
+            world.step();
+        }
+
+        let volume = world.body(handle).unwrap().downcast_ref::<FEMVolume<f64>>().unwrap();
+        assert!(volume.positions().iter().all(|x| x.is_finite()));
+        volume.elements.iter().filter(|elt| {
+            (elt.local_j_inv.fixed_slice::<na::U3, na::U3>(0, 1) * elt.j).determinant() <= 0.0
+        }).count()
+    }
+
+    #[test]
+    fn inverted_cube_recovers() {
+        // The elasticity forces alone eventually recover from the inversion, but more slowly.
+        assert!(squashed_cube(None, 10) > 0);
+        assert_eq!(squashed_cube(Some(1.0), 10), 0);
+        assert_eq!(squashed_cube(Some(1.0), 40), 0);
+    }
+
+    // Deforms a cube by a large rotation combined with a strong shear, and returns the largest angle between the
+    // rotation extracted for each element and the actual rotation.
+    fn sheared_cube_rotation_error(polar_decomposition: bool) -> f64 {
+        let mut volume = FEMVolumeDesc::cube(1, 1, 1)
+            .polar_decomposition(polar_decomposition)
+            .build_with_handle(&mut ColliderWorld::new(0.01), BodyHandle::ground());
+
+        // The rotational part of the polar decomposition of `rotation * shear` is `rotation`.
+        let rotation = Rotation3::new(Vector3::new(0.3, 1.0, -0.5).normalize() * 2.8);
+        let shear = Matrix3::new(
+            1.0, 0.98, 0.0,
+            0.98, 1.0, 0.0,
+            0.0, 0.0, 1.0,
+        );
+        let (_, positions) = volume.deformed_positions_mut().unwrap();
+        for pt in positions.chunks_mut(3) {
+            let deformed = rotation * (shear * Vector3::new(pt[0], pt[1], pt[2]));
+            pt.copy_from_slice(deformed.as_slice());
+        }
+
+        volume.update_kinematics();
+        volume.elements.iter().map(|elt| elt.rot.angle_to(&rotation)).fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn polar_decomposition_under_large_shear() {
+        assert!(sheared_cube_rotation_error(true) < 1.0e-8);
+        assert!(sheared_cube_rotation_error(false) > 1.0e-3);
+    }
+
+    // A volume made of a single flat tetrahedron, yielding a zero augmented mass matrix.
+    fn flat_tetrahedron() -> FEMVolume<f64> {
+        let vertices = [
+            Point3::origin(),
+            Point3::new(1.0f64, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(0.5, 0.5, 0.0),
+        ];
+        let indices = [Point4::new(0, 1, 2, 3)];
+        FEMVolume::new(
+            BodyHandle::ground(), &vertices, &indices, &Isometry3::identity(),
+            &Vector3::repeat(1.0), 1.0, 1.0e3, 0.3, (0.2, 0.0))
+    }
+
+    #[test]
+    fn degenerate_mesh_factorization_falls_back() {
+        let mut volume = flat_tetrahedron();
+        volume.update_kinematics();
+        volume.update_dynamics(1.0 / 60.0);
+        volume.update_acceleration(&(Vector3::y() * -9.81), &IntegrationParameters::default());
+
+        assert!(volume.last_step_used_fallback());
+        assert!(volume.accelerations.iter().all(|a| a.is_finite()));
+    }
+
+    #[test]
+    fn degenerate_mesh_factorization_with_regularization() {
+        let mut volume = flat_tetrahedron();
+        volume.set_mass_regularization(1.0e-6);
+        volume.update_kinematics();
+        volume.update_dynamics(1.0 / 60.0);
+        volume.update_acceleration(&(Vector3::y() * -9.81), &IntegrationParameters::default());
+
+        assert!(!volume.last_step_used_fallback());
+        assert!(volume.accelerations.iter().all(|a| a.is_finite()));
+    }
+
+    // Launches a soft cube on the ground and returns the distance it traveled.
+    fn sliding_soft_cube(frictionless_bottom: bool) -> f64 {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector3::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector3::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector3::y() * -10.0)
+            .build(&mut world);
+
+        let volume = FEMVolumeDesc::cube(1, 1, 1)
+            .translation(Vector3::y() * 0.52)
+            .young_modulus(1.0e3)
+            .poisson_ratio(0.2)
+            // The mass damping would slow down the cube even without friction.
+            .mass_damping(0.0)
+            .build(&mut world);
+        let handle = volume.handle();
+
+        // Only the triangles touching the bottom of the cube are frictionless.
+        let (mesh, ids_map, parts_map) = volume.boundary_mesh();
+        volume.renumber_dofs(&ids_map);
+        let mapping = mesh.faces().iter().map(|face| {
+            let bottom = face.indices.iter().any(|i| mesh.points()[*i].y < 0.1);
+            if frictionless_bottom && bottom { 1 } else { 0 }
+        }).collect();
+
+        let mut frictionless = BasicMaterial::new(0.0, 0.0);
+        frictionless.friction_combine_mode = MaterialCombineMode::Multiply;
+        let materials = vec![MaterialHandle::new(BasicMaterial::default()), MaterialHandle::new(frictionless)];
+
+        let nnodes = volume.positions().len() / 3;
+        for i in 0..nnodes {
+            volume.velocities_mut()[i * 3] = 2.0;
+        }
+
+        let _ = DeformableColliderDesc::new(ShapeHandle::new(mesh))
+            .body_parts_mapping(Some(Arc::new(parts_map)))
+            .materials(materials, Arc::new(mapping))
+            .build_parent(handle, &mut world)
+            .unwrap();
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let volume = world.body(handle).unwrap().downcast_ref::<FEMVolume<f64>>().unwrap();
+        volume.positions().iter().step_by(3).sum::<f64>() / (nnodes as f64)
+    }
+
+    #[test]
+    fn partially_frictionless_deformable_collider() {
+        let frictionless_distance = sliding_soft_cube(true);
+        let friction_distance = sliding_soft_cube(false);
+
+        // The frictionless cube keeps its initial velocity of 2m/s during 1s.
+        assert!(frictionless_distance > 1.9);
+        assert!(friction_distance < 1.0);
+    }
+
+    #[test]
+    fn coarse_collision_proxy_tracks_fine_volume() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector3::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector3::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector3::y() * -10.0)
+            .build(&mut world);
+
+        let volume = FEMVolumeDesc::cube(3, 3, 3)
+            .translation(Vector3::y() * 1.0)
+            .young_modulus(1.0e4)
+            .poisson_ratio(0.2)
+            .build(&mut world);
+        let handle = volume.handle();
+
+        // The proxy is a box with 12 triangles, each vertex following one corner of the volume.
+        let corners: Vec<_> = (0..8).map(|c| {
+            let corner = Vector3::new(
+                if c & 4 == 0 { -0.5 } else { 0.5 },
+                if c & 2 == 0 { 0.5 } else { 1.5 },
+                if c & 1 == 0 { -0.5 } else { 0.5 },
+            );
+            (0..volume.positions().len() / 3)
+                .find(|i| relative_eq!(volume.positions().fixed_rows::<U3>(i * 3).into_owned(), corner, epsilon = 1.0e-10))
+                .unwrap()
+        }).collect();
+        let faces = vec![
+            Point3::new(0, 1, 3), Point3::new(0, 3, 2), Point3::new(4, 6, 7), Point3::new(4, 7, 5),
+            Point3::new(0, 4, 5), Point3::new(0, 5, 1), Point3::new(2, 3, 7), Point3::new(2, 7, 6),
+            Point3::new(0, 2, 6), Point3::new(0, 6, 4), Point3::new(1, 5, 7), Point3::new(1, 7, 3),
+        ];
+        // Each triangle is attached to an element touching its first vertex.
+        let parts_map = faces.iter().map(|face| {
+            volume.elements.iter().position(|elt| elt.indices.iter().any(|i| *i == corners[face.x] * 3)).unwrap()
+        }).collect();
+        let vertices = corners.iter().map(|i| Point3::from(volume.positions().fixed_rows::<U3>(i * 3).into_owned())).collect();
+
+        let proxy = DeformableColliderDesc::new(ShapeHandle::new(TriMesh::new(vertices, faces, None)))
+            .body_parts_mapping(Some(Arc::new(parts_map)))
+            .dof_map(Some(Arc::new(corners.clone())))
+            .build_parent(handle, &mut world)
+            .unwrap()
+            .handle();
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        // The proxy vertices follow the corners, and keep the volume from falling through the ground.
+        let volume = world.body(handle).unwrap().downcast_ref::<FEMVolume<f64>>().unwrap();
+        let mesh = world.collider(proxy).unwrap().shape().as_shape::<TriMesh<f64>>().unwrap();
+
+        for (pt, i) in mesh.points().iter().zip(corners.iter()) {
+            assert_relative_eq!(pt.coords, volume.positions().fixed_rows::<U3>(i * 3).into_owned(), epsilon = 1.0e-10);
+        }
+
+        let min_y = volume.positions().iter().skip(1).step_by(3).cloned().fold(f64::MAX, f64::min);
+        assert!(min_y > -0.05 && min_y < 0.05, "min_y: {}", min_y);
+    }
+
+    #[test]
+    fn rotational_velocity_field() {
+        let mut volume = FEMVolume::cube(
+            BodyHandle::ground(), &Isometry3::translation(1.0, 2.0, 3.0), &Vector3::repeat(1.0),
+            2, 2, 2, 1.0, 1.0e3, 0.3, (0.2, 0.0));
+        volume.set_node_kinematic(0, true);
+
+        let angvel = Vector3::new(0.1, 0.2, 0.3);
+        volume.apply_velocity_field(|pt| angvel.cross(&pt.coords));
+
+        for i in 0..volume.positions().len() / 3 {
+            let pt = volume.positions().fixed_rows::<na::U3>(i * 3).into_owned();
+            let vel = volume.velocities().fixed_rows::<na::U3>(i * 3).into_owned();
+            let expected = if i == 0 { Vector3::zeros() } else { angvel.cross(&pt) };
+            assert!(relative_eq!(vel, expected, epsilon = 1.0e-10));
+        }
+    }
+
+    #[test]
+    fn element_velocities_follow_the_nodes() {
+        let mut volume = FEMVolume::cube(
+            BodyHandle::ground(), &Isometry3::translation(1.0, 2.0, 3.0), &Vector3::repeat(1.0),
+            2, 2, 2, 1.0, 1.0e3, 0.3, (0.2, 0.0));
+
+        // The velocities of the elements of a rigidly moving volume match the rigid motion.
+        let linvel = Vector3::new(1.0, -2.0, 0.5);
+        let angvel = Vector3::new(0.1, 0.2, 0.3);
+        volume.apply_velocity_field(|pt| linvel + angvel.cross(&pt.coords));
+        volume.update_kinematics();
+
+        for i in 0..volume.num_parts() {
+            let part = volume.part(i).unwrap();
+            let vel = part.velocity();
+            assert_relative_eq!(vel.linear, linvel + angvel.cross(&part.center_of_mass().coords), epsilon = 1.0e-10);
+            assert_relative_eq!(vel.angular, angvel, epsilon = 1.0e-10);
+        }
+
+        // A falling volume, slightly slowed down by its default mass damping.
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector3::y() * -9.81);
+        let handle = FEMVolumeDesc::cube(2, 2, 2).build(&mut world).handle();
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let volume = world.body(handle).unwrap();
+        for i in 0..volume.num_parts() {
+            let vel = volume.part(i).unwrap().velocity();
+            assert!(vel.as_vector().iter().all(|v| v.is_finite()));
+            assert_relative_eq!(vel.linear, Vector3::y() * -9.81 * 10.0 / 60.0, epsilon = 5.0e-2);
+        }
+    }
+
+    #[test]
+    fn refresh_collider_after_fracture() {
+        let mut world = World::<f64>::new();
+        let volume = FEMVolumeDesc::cube(2, 2, 2)
+            .collider_enabled(true)
+            .build(&mut world);
+        let handle = volume.handle();
+
+        // Fracture the cube by removing all the elements of its positive-x half.
+        let positions = volume.positions().clone();
+        volume.elements.retain(|elt| elt.indices.iter().map(|i| positions[*i]).sum::<f64>() < 0.0);
+        let boundary = volume.boundary();
+
+        let collider = world.collider_world().body_colliders(handle).next().unwrap().handle();
+        let mesh_aabb = world.collider(collider).unwrap().shape().as_shape::<TriMesh<f64>>().unwrap().aabb().clone();
+        assert_relative_eq!(mesh_aabb.maxs().x, 0.5);
+
+        world.refresh_deformable_collider(handle);
+
+        let mesh = world.collider(collider).unwrap().shape().as_shape::<TriMesh<f64>>().unwrap();
+        assert_eq!(mesh.faces().len(), boundary.len());
+        assert_relative_eq!(mesh.aabb().maxs().x, 0.0);
+        assert_relative_eq!(mesh.aabb().mins().x, -0.5);
+
+        let collider = world.collider(collider).unwrap();
+        for (i, (_, elt)) in boundary.iter().enumerate() {
+            assert_eq!(collider.body_part(i).1, *elt);
+        }
+    }
+
+    #[test]
+    fn iterate_fem_volume_parts() {
+        let mut world = World::<f64>::new();
+        let volume = FEMVolumeDesc::cube(2, 1, 1).build(&mut world);
+        let handle = volume.handle();
+
+        // Each cube cell is decomposed into 5 tetrahedra.
+        assert_eq!(volume.num_parts(), 10);
+
+        let body = world.body(handle).unwrap();
+        let mut count = 0;
+        let mut total_mass = 0.0;
+
+        for (i, part) in body.parts().enumerate() {
+            assert_eq!(part.part_handle(), BodyPartHandle(handle, i));
+            total_mass += part.local_inertia().linear;
+            count += 1;
+        }
+
+        assert_eq!(count, 10);
+        assert_relative_eq!(total_mass, 1.0, epsilon = 1.0e-10);
+    }
+
+    // Simulates a freely moving cube with mass damping during one second with the given timestep, and returns
+    // its final speed relative to its initial speed.
+    fn damped_cube_speed_ratio(dt: f64) -> f64 {
+        let mut world = World::<f64>::new();
+        world.set_timestep(dt);
+
+        let volume = FEMVolumeDesc::cube(2, 2, 2)
+            .young_modulus(1.0e3)
+            .mass_damping(1.0)
+            .stiffness_damping(0.1)
+            .sleep_threshold(None)
+            .build(&mut world);
+        let handle = volume.handle();
+
+        for v in volume.generalized_velocity_mut().iter_mut().step_by(3) {
+            *v = 1.0;
+        }
+
+        for _ in 0..(1.0 / dt).round() as usize {
+            world.step();
+        }
+
+        let volume = world.body(handle).unwrap().downcast_ref::<FEMVolume<f64>>().unwrap();
+        volume.velocities().iter().step_by(3).sum::<f64>() / (volume.velocities().len() / 3) as f64
+    }
+
+    #[test]
+    fn damping_does_not_depend_on_timestep() {
+        let coarse = damped_cube_speed_ratio(1.0 / 60.0);
+        let fine = damped_cube_speed_ratio(1.0 / 240.0);
+
+        // The exact speed ratio after one second is `e^-1`.
+        assert!(relative_eq!(coarse, (-1.0f64).exp(), max_relative = 0.05), "coarse: {}", coarse);
+        assert!(relative_eq!(fine, (-1.0f64).exp(), max_relative = 0.05), "fine: {}", fine);
+    }
+
+    // Compresses a cube along the `y` axis by the given ratio, and returns its elastic energy.
+    fn compressed_cube_elastic_energy(ratio: f64) -> f64 {
+        let mut volume = FEMVolumeDesc::cube(2, 2, 2)
+            .build_with_handle(&mut ColliderWorld::new(0.01), BodyHandle::ground());
+
+        let (_, positions) = volume.deformed_positions_mut().unwrap();
+        for pt in positions.chunks_mut(3) {
+            pt[1] *= ratio;
+        }
+
+        // The strains are assembled with the forces.
+        let params = IntegrationParameters::default();
+        volume.update_kinematics();
+        volume.update_dynamics(params.dt);
+        volume.update_acceleration(&Vector3::zeros(), &params);
+        volume.elastic_energy()
+    }
+
+    #[test]
+    fn transformed_volume_keeps_its_deformation() {
+        let mut volume = FEMVolumeDesc::cube(2, 2, 2)
+            .build_with_handle(&mut ColliderWorld::new(0.01), BodyHandle::ground());
+
+        // Compress the cube along the `y` axis, and launch it along the `x` axis.
+        let (_, positions) = volume.deformed_positions_mut().unwrap();
+        for pt in positions.chunks_mut(3) {
+            pt[1] *= 0.9;
+        }
+        for vel in volume.velocities_mut().as_mut_slice().chunks_mut(3) {
+            vel[0] = 1.0;
+        }
+
+        let params = IntegrationParameters::default();
+        let energy = |volume: &mut FEMVolume<f64>| {
+            volume.update_kinematics();
+            volume.update_dynamics(params.dt);
+            volume.update_acceleration(&Vector3::zeros(), &params);
+            volume.elastic_energy()
+        };
+        let energy_before = energy(&mut volume);
+
+        let iso = Isometry3::new(Vector3::new(1.0, 2.0, 3.0), Vector3::new(0.3, -0.5, 1.2));
+        let positions_before = volume.positions().clone();
+        volume.transform(&iso);
+
+        for (before, after) in positions_before.as_slice().chunks(3).zip(volume.positions().as_slice().chunks(3)) {
+            let expected = iso * Point3::new(before[0], before[1], before[2]);
+            assert_relative_eq!(Point3::new(after[0], after[1], after[2]), expected, epsilon = 1.0e-10);
+        }
+
+        for vel in volume.velocities().as_slice().chunks(3) {
+            assert_relative_eq!(Vector3::new(vel[0], vel[1], vel[2]), iso.rotation * Vector3::x(), epsilon = 1.0e-10);
+        }
+
+        assert_relative_eq!(energy(&mut volume), energy_before, max_relative = 1.0e-8);
+    }
+
+    #[test]
+    fn compressed_cube_element_stresses() {
+        let mut volume = FEMVolumeDesc::cube(2, 2, 2)
+            .build_with_handle(&mut ColliderWorld::new(0.01), BodyHandle::ground());
+
+        let (_, positions) = volume.deformed_positions_mut().unwrap();
+        for pt in positions.chunks_mut(3) {
+            pt[1] *= 0.9;
+        }
+
+        let params = IntegrationParameters::default();
+        volume.update_kinematics();
+        volume.update_dynamics(params.dt);
+        volume.update_acceleration(&Vector3::zeros(), &params);
+
+        // With a zero poisson ratio, the uniaxial compression results in a uniaxial stress of `young_modulus * strain`.
+        for i in 0..volume.elements.len() {
+            let strain = volume.element_strain(i).unwrap();
+            assert_relative_eq!(strain, Vector6::new(0.0, -0.1, 0.0, 0.0, 0.0, 0.0), epsilon = 1.0e-10);
+            assert_relative_eq!(volume.element_von_mises_stress(i).unwrap(), 0.3 * 0.1, epsilon = 1.0e-10);
+        }
+
+        let nelements = volume.elements.len();
+        assert!(volume.element_strain(nelements).is_none());
+        assert!(volume.element_von_mises_stress(nelements).is_none());
+    }
+
+    fn squashed_cube_volume(volume_conservation: bool) -> f64 {
+        let mut world = World::new();
+        let volume = FEMVolumeDesc::cube(2, 2, 2)
+            .volume_conservation(volume_conservation)
+            .build(&mut world);
+        let handle = volume.handle();
+
+        let (_, positions) = volume.deformed_positions_mut().unwrap();
+        for pt in positions.chunks_mut(3) {
+            pt[1] *= 0.5;
+        }
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let volume = world.body(handle).unwrap().downcast_ref::<FEMVolume<f64>>().unwrap();
+        volume.elements.iter().map(|elt| {
+            let a = volume.positions.fixed_rows::<U3>(elt.indices.x).into_owned();
+            let ab = volume.positions.fixed_rows::<U3>(elt.indices.y) - a;
+            let ac = volume.positions.fixed_rows::<U3>(elt.indices.z) - a;
+            let ad = volume.positions.fixed_rows::<U3>(elt.indices.w) - a;
+            ab.dot(&ac.cross(&ad)) / 6.0
+        }).sum()
+    }
+
+    #[test]
+    fn volume_conservation_restores_squashed_cube() {
+        // The unit cube is squashed to half its volume.
+        let free = squashed_cube_volume(false);
+        let conserved = squashed_cube_volume(true);
+        assert!(free < 0.6, "free: {}", free);
+        assert_relative_eq!(conserved, 1.0, epsilon = 5.0e-2);
+    }
+
+    #[test]
+    fn elastic_energy_grows_with_compression() {
+        assert_relative_eq!(compressed_cube_elastic_energy(1.0), 0.0, epsilon = 1.0e-10);
+
+        let small = compressed_cube_elastic_energy(0.9);
+        let large = compressed_cube_elastic_energy(0.8);
+        // With a zero poisson ratio, this is `0.5 * young_modulus * strain^2 * volume` for the unit cube.
+        assert_relative_eq!(small, 0.5 * 0.3 * 0.1 * 0.1, epsilon = 1.0e-10);
+        // The energy of linear elasticity is quadratic wrt. the deformation.
+        assert_relative_eq!(large / small, 4.0, max_relative = 1.0e-3);
+    }
+}
@@ -47,6 +47,14 @@ impl<N: RealField> Body<N> for Ground<N> {
     #[inline]
     fn enable_gravity(&mut self, _: bool) {}
 
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn set_position_correction_enabled(&mut self, _: bool) {}
+
     #[inline]
     fn update_kinematics(&mut self) {}
 
@@ -72,6 +80,11 @@ impl<N: RealField> Body<N> for Ground<N> {
         Some(self)
     }
 
+    #[inline]
+    fn num_parts(&self) -> usize {
+        1
+    }
+
     #[inline]
     fn is_ground(&self) -> bool {
         true
@@ -173,6 +186,9 @@ impl<N: RealField> Body<N> for Ground<N> {
     #[inline]
     fn set_deactivation_threshold(&mut self, _: Option<N>) {}
 
+    #[inline]
+    fn keep_awake_while_forced(&mut self, _: bool) {}
+
     #[inline]
     fn world_point_at_material_point(&self, _: &BodyPart<N>, point: &Point<N>) -> Point<N> {
         *point
@@ -86,6 +86,7 @@ pub struct MassConstraintSystem<N: RealField> {
 
     companion_id: usize,
     gravity_enabled: bool,
+    position_correction_enabled: bool,
     activation: ActivationStatus<N>,
     status: BodyStatus,
     update_status: BodyUpdateStatus,
@@ -159,6 +160,7 @@ impl<N: RealField> MassConstraintSystem<N> {
             node_mass,
             inv_node_mass: N::one() / node_mass,
             gravity_enabled: true,
+            position_correction_enabled: true,
             warmstart_coeff: na::convert(0.5),
             plasticity_threshold: N::zero(),
             plasticity_creep: N::zero(),
@@ -212,6 +214,7 @@ impl<N: RealField> MassConstraintSystem<N> {
             status: BodyStatus::Dynamic,
             update_status: BodyUpdateStatus::all(),
             gravity_enabled: true,
+            position_correction_enabled: true,
             mass,
             node_mass,
             inv_node_mass: N::one() / node_mass,
@@ -279,6 +282,160 @@ impl<N: RealField> MassConstraintSystem<N> {
         }
     }
 
+    /// Generate additional constraints resisting the bending of the surface formed by the triangle elements.
+    ///
+    /// For each edge shared by exactly two triangle elements, a constraint is created between the two
+    /// vertices opposite to this edge if it does not already exists.
+    #[cfg(feature = "dim3")]
+    pub fn generate_bending_constraints(&mut self, stiffness: Option<N>) {
+        self.update_status.set_local_inertia_changed(true);
+
+        let mut existing_constraints = HashSet::with_hasher(DeterministicState::new());
+        for constraint in &self.constraints {
+            let _ = existing_constraints.insert(key(constraint.nodes.0, constraint.nodes.1));
+        }
+
+        // The vertices opposite to each edge.
+        let mut opposite_vertices = HashMap::with_hasher(DeterministicState::new());
+        for elt in &self.elements {
+            if let FiniteElementIndices::Triangle(idx) = elt.indices {
+                for (a, b, c) in &[(idx.x, idx.y, idx.z), (idx.y, idx.z, idx.x), (idx.z, idx.x, idx.y)] {
+                    opposite_vertices.entry(key(*a, *b)).or_insert_with(Vec::new).push(*c);
+                }
+            }
+        }
+
+        let mut edges: Vec<_> = opposite_vertices.into_iter().collect();
+        // Sort the edges so the constraints are generated in a deterministic order.
+        edges.sort_by_key(|(edge, _)| *edge);
+
+        for (_, vertices) in edges {
+            if vertices.len() == 2 {
+                let edge = key(vertices[0], vertices[1]);
+
+                if existing_constraints.insert(edge) {
+                    let constraint = LengthConstraint::from_positions(edge, self.positions.as_slice(), stiffness);
+                    self.constraints.push(constraint);
+                }
+            }
+        }
+    }
+
+    /// Generate additional constraints along the diagonals of a triangulation of the polygon
+    /// bounded by the segment elements of this mass-constraint system.
+    ///
+    /// This gives some area stiffness to soft bodies built from a closed polyline. Nothing is
+    /// added if the segment elements do not form a single closed loop.
+    #[cfg(feature = "dim2")]
+    pub fn generate_area_constraints(&mut self, stiffness: Option<N>) {
+        let polygon = try_ret!(self.boundary_loop());
+        self.update_status.set_local_inertia_changed(true);
+
+        let mut existing_constraints = HashSet::with_hasher(DeterministicState::new());
+        for constraint in &self.constraints {
+            let _ = existing_constraints.insert(key(constraint.nodes.0, constraint.nodes.1));
+        }
+
+        for (a, b) in self.ear_clipping_diagonals(polygon) {
+            let edge = key(a * DIM, b * DIM);
+
+            if existing_constraints.insert(edge) {
+                let constraint = LengthConstraint::from_positions(edge, self.positions.as_slice(), stiffness);
+                self.constraints.push(constraint);
+            }
+        }
+    }
+
+    // Orders the nodes of the segment elements as a counterclockwise closed loop.
+    #[cfg(feature = "dim2")]
+    fn boundary_loop(&self) -> Option<Vec<usize>> {
+        let mut neighbors: Vec<Vec<usize>> = iter::repeat(Vec::new()).take(self.num_nodes()).collect();
+        let mut num_segments = 0;
+
+        for elt in &self.elements {
+            if let FiniteElementIndices::Segment(idx) = elt.indices {
+                neighbors[idx.x / DIM].push(idx.y / DIM);
+                neighbors[idx.y / DIM].push(idx.x / DIM);
+                num_segments += 1;
+            }
+        }
+
+        let start = neighbors.iter().position(|nbhs| !nbhs.is_empty())?;
+        let mut polygon = vec![start];
+        let mut prev = start;
+        let mut curr = neighbors[start][0];
+
+        while curr != start {
+            if neighbors[curr].len() != 2 || polygon.len() > num_segments {
+                return None;
+            }
+
+            polygon.push(curr);
+            let next = if neighbors[curr][0] == prev { neighbors[curr][1] } else { neighbors[curr][0] };
+            prev = curr;
+            curr = next;
+        }
+
+        if polygon.len() != num_segments || polygon.len() < 3 {
+            return None;
+        }
+
+        // Ensure a counterclockwise orientation.
+        let mut area = N::zero();
+        for i in 0..polygon.len() {
+            let a = self.node_position(polygon[i]);
+            let b = self.node_position(polygon[(i + 1) % polygon.len()]);
+            area += a.x * b.y - b.x * a.y;
+        }
+
+        if area < N::zero() {
+            polygon.reverse();
+        }
+
+        Some(polygon)
+    }
+
+    // Triangulates the given counterclockwise polygon and returns the diagonals of the triangulation.
+    #[cfg(feature = "dim2")]
+    fn ear_clipping_diagonals(&self, mut polygon: Vec<usize>) -> Vec<(usize, usize)> {
+        fn perp<N: RealField>(a: &Point<N>, b: &Point<N>, c: &Point<N>) -> N {
+            (b - a).perp(&(c - a))
+        }
+
+        let mut diagonals = Vec::new();
+
+        while polygon.len() > 3 {
+            let n = polygon.len();
+            let ear = (0..n).find(|i| {
+                let a = self.node_position(polygon[(i + n - 1) % n]);
+                let b = self.node_position(polygon[*i]);
+                let c = self.node_position(polygon[(i + 1) % n]);
+
+                if perp(&a, &b, &c) <= N::zero() {
+                    return false;
+                }
+
+                // The ear must not contain any other vertex of the polygon.
+                (0..n).filter(|j| *j != (i + n - 1) % n && j != i && *j != (i + 1) % n).all(|j| {
+                    let p = self.node_position(polygon[j]);
+                    perp(&a, &b, &p) < N::zero() || perp(&b, &c, &p) < N::zero() || perp(&c, &a, &p) < N::zero()
+                })
+            });
+
+            // Stop on degenerate polygons.
+            let ear = try_ret!(ear, diagonals);
+            diagonals.push((polygon[(ear + n - 1) % n], polygon[(ear + 1) % n]));
+            let _ = polygon.remove(ear);
+        }
+
+        diagonals
+    }
+
+    #[cfg(feature = "dim2")]
+    fn node_position(&self, i: usize) -> Point<N> {
+        Point::from_slice(&self.positions.as_slice()[i * DIM..i * DIM + DIM])
+    }
+
     /// The number of nodes of this mass-constraint system.
     pub fn num_nodes(&self) -> usize {
         self.positions.len() / DIM
@@ -351,6 +508,16 @@ impl<N: RealField> Body<N> for MassConstraintSystem<N> {
         self.gravity_enabled = enabled
     }
 
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        self.position_correction_enabled
+    }
+
+    #[inline]
+    fn set_position_correction_enabled(&mut self, enabled: bool) {
+        self.position_correction_enabled = enabled
+    }
+
     fn update_kinematics(&mut self) {
         if self.update_status.position_changed() {
             for constraint in &mut self.constraints {
@@ -459,6 +626,16 @@ impl<N: RealField> Body<N> for MassConstraintSystem<N> {
         self.activation.set_deactivation_threshold(threshold)
     }
 
+    #[inline]
+    fn keep_awake_while_forced(&mut self, enabled: bool) {
+        self.activation.set_keep_awake_while_forced(enabled)
+    }
+
+    #[inline]
+    fn user_data(&self) -> Option<&(Any + Send + Sync)> {
+        self.user_data.as_ref().map(|d| &**d)
+    }
+
     fn ndofs(&self) -> usize {
         self.positions.len()
     }
@@ -504,6 +681,10 @@ impl<N: RealField> Body<N> for MassConstraintSystem<N> {
         self.elements.get(id).map(|e| e as &BodyPart<N>)
     }
 
+    fn num_parts(&self) -> usize {
+        self.elements.len()
+    }
+
     fn deformed_positions(&self) -> Option<(DeformationsType, &[N])> {
         Some((DeformationsType::Vectors, self.positions.as_slice()))
     }
@@ -595,6 +776,11 @@ impl<N: RealField> Body<N> for MassConstraintSystem<N> {
         }
     }
 
+    #[inline]
+    fn clear_cached_impulses(&mut self) {
+        self.impulses.fill(N::zero());
+    }
+
     #[inline]
     fn step_solve_internal_velocity_constraints(&mut self, dvels: &mut DVectorSliceMut<N>) {
         // Solve internal constraints using a PGS solver.
@@ -701,7 +887,7 @@ impl<N: RealField> Body<N> for MassConstraintSystem<N> {
             return;
         }
 
-        if auto_wake_up {
+        if auto_wake_up || (self.activation.is_kept_awake_while_forced() && force.iter().any(|f| !f.is_zero())) {
             self.activate()
         }
 
@@ -837,6 +1023,10 @@ pub struct MassConstraintSystemDesc<'a, N: RealField> {
     status: BodyStatus,
     collider_enabled: bool,
     gravity_enabled: bool,
+    #[cfg(feature = "dim2")]
+    area_constraints_enabled: bool,
+    #[cfg(feature = "dim3")]
+    bending_constraints_enabled: bool,
 }
 
 impl<'a, N: RealField> MassConstraintSystemDesc<'a, N> {
@@ -855,7 +1045,11 @@ impl<'a, N: RealField> MassConstraintSystemDesc<'a, N> {
             plasticity: (N::zero(), N::zero(), N::zero()),
             kinematic_nodes: Vec::new(),
             status: BodyStatus::Dynamic,
-            collider_enabled: false
+            collider_enabled: false,
+            #[cfg(feature = "dim2")]
+            area_constraints_enabled: false,
+            #[cfg(feature = "dim3")]
+            bending_constraints_enabled: false,
         }
     }
 
@@ -923,6 +1117,26 @@ impl<'a, N: RealField> MassConstraintSystemDesc<'a, N> {
         [ref] get_scale -> scale: Vector<N>
     );
 
+    #[cfg(feature = "dim2")]
+    desc_setters!(
+        area_constraints_enabled, set_area_constraints_enabled, area_constraints_enabled: bool
+    );
+
+    #[cfg(feature = "dim2")]
+    desc_getters!(
+        [val] is_area_constraints_enabled -> area_constraints_enabled: bool
+    );
+
+    #[cfg(feature = "dim3")]
+    desc_setters!(
+        bending_constraints_enabled, set_bending_constraints_enabled, bending_constraints_enabled: bool
+    );
+
+    #[cfg(feature = "dim3")]
+    desc_getters!(
+        [val] is_bending_constraints_enabled -> bending_constraints_enabled: bool
+    );
+
     /// Build a mass-constraint system.
     pub fn build<'w>(&self, world: &'w mut World<N>) -> &'w mut MassConstraintSystem<N> {
         world.add_body(self)
@@ -998,6 +1212,60 @@ impl<'a, N: RealField> BodyDesc<N> for MassConstraintSystemDesc<'a, N> {
             vol.set_node_kinematic(*i, true)
         }
 
+        #[cfg(feature = "dim2")]
+        {
+            if self.area_constraints_enabled {
+                vol.generate_area_constraints(self.stiffness);
+            }
+        }
+
+        #[cfg(feature = "dim3")]
+        {
+            if self.bending_constraints_enabled {
+                vol.generate_bending_constraints(self.stiffness);
+            }
+        }
+
         vol
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "dim3"))]
+mod test {
+    use na::Point3;
+    use ncollide::shape::TriMesh;
+    use crate::object::{BodyHandle, MassConstraintSystem};
+
+    #[test]
+    fn trimesh_edges_and_bending_constraints() {
+        // Two squares side by side, each split into two triangles.
+        let vertices = vec![
+            Point3::new(0.0f64, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(2.0, 0.0, 1.0),
+        ];
+        let indices = vec![
+            Point3::new(0, 3, 1),
+            Point3::new(1, 3, 4),
+            Point3::new(1, 4, 2),
+            Point3::new(2, 4, 5),
+        ];
+        let mesh = TriMesh::new(vertices, indices, None);
+        let mut system = MassConstraintSystem::from_trimesh(BodyHandle::ground(), &mesh, 1.0, Some(1.0e3));
+
+        // The edges shared by two triangles are constrained only once.
+        assert_eq!(system.constraints.len(), 9);
+
+        // One constraint across each of the three interior edges.
+        system.generate_bending_constraints(Some(1.0e3));
+        assert_eq!(system.constraints.len(), 12);
+        assert!(system.constraints.iter().any(|c| (c.nodes.0.min(c.nodes.1), c.nodes.0.max(c.nodes.1)) == (0, 4 * 3)));
+
+        // Existing constraints are not duplicated.
+        system.generate_bending_constraints(Some(1.0e3));
+        assert_eq!(system.constraints.len(), 12);
+    }
+}
@@ -79,6 +79,7 @@ pub struct MassSpringSystem<N: RealField> {
 
     companion_id: usize,
     gravity_enabled: bool,
+    position_correction_enabled: bool,
     activation: ActivationStatus<N>,
     status: BodyStatus,
     update_status: BodyUpdateStatus,
@@ -161,6 +162,7 @@ impl<N: RealField> MassSpringSystem<N> {
             plasticity_creep: N::zero(),
             plasticity_threshold: N::zero(),
             gravity_enabled: true,
+            position_correction_enabled: true,
             user_data: None
         }
     }
@@ -214,6 +216,7 @@ impl<N: RealField> MassSpringSystem<N> {
             status: BodyStatus::Dynamic,
             update_status: BodyUpdateStatus::all(),
             gravity_enabled: true,
+            position_correction_enabled: true,
             mass,
             node_mass,
             plasticity_max_force: N::zero(),
@@ -485,6 +488,16 @@ impl<N: RealField> Body<N> for MassSpringSystem<N> {
         self.gravity_enabled = enabled
     }
 
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        self.position_correction_enabled
+    }
+
+    #[inline]
+    fn set_position_correction_enabled(&mut self, enabled: bool) {
+        self.position_correction_enabled = enabled
+    }
+
     fn update_kinematics(&mut self) {
         if self.update_status.position_changed() {
             for spring in &mut self.springs {
@@ -556,6 +569,16 @@ impl<N: RealField> Body<N> for MassSpringSystem<N> {
         self.activation.set_deactivation_threshold(threshold)
     }
 
+    #[inline]
+    fn keep_awake_while_forced(&mut self, enabled: bool) {
+        self.activation.set_keep_awake_while_forced(enabled)
+    }
+
+    #[inline]
+    fn user_data(&self) -> Option<&(Any + Send + Sync)> {
+        self.user_data.as_ref().map(|d| &**d)
+    }
+
     fn ndofs(&self) -> usize {
         self.positions.len()
     }
@@ -601,6 +624,10 @@ impl<N: RealField> Body<N> for MassSpringSystem<N> {
         self.elements.get(id).map(|e| e as &BodyPart<N>)
     }
 
+    fn num_parts(&self) -> usize {
+        self.elements.len()
+    }
+
     fn deformed_positions(&self) -> Option<(DeformationsType, &[N])> {
         Some((DeformationsType::Vectors, self.positions.as_slice()))
     }
@@ -682,7 +709,7 @@ impl<N: RealField> Body<N> for MassSpringSystem<N> {
             return;
         }
 
-        if auto_wake_up {
+        if auto_wake_up || (self.activation.is_kept_awake_while_forced() && force.iter().any(|f| !f.is_zero())) {
             self.activate()
         }
 
@@ -983,4 +1010,55 @@ impl<'a, N: RealField> BodyDesc<N> for MassSpringSystemDesc<'a, N> {
 
         vol
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn thick_sheet_rests_on_ground() {
+        use ncollide::procedural;
+        use ncollide::shape::{Cuboid, ShapeHandle, TriMesh};
+        use crate::math::{Isometry, Vector};
+        use crate::object::{ColliderDesc, DeformableColliderDesc, MassSpringSystemDesc};
+        use crate::world::World;
+
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let ground = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(10.0))))
+            .translation(Vector::y() * -10.0)
+            .build(&mut world)
+            .handle();
+
+        // A thin sheet lying horizontally slightly above the ground.
+        let mut quad = procedural::quad(2.0, 2.0, 4, 4);
+        quad.transform_by(&Isometry::new(Vector::y() * 0.2, Vector::x() * std::f64::consts::FRAC_PI_2));
+        let mesh = TriMesh::from(quad);
+        let thickness = 0.05;
+        let handle = MassSpringSystemDesc::from_trimesh(&mesh)
+            .stiffness(1.0e4)
+            .build(&mut world)
+            .handle();
+        let sheet = DeformableColliderDesc::new(ShapeHandle::new(mesh))
+            .thickness(thickness)
+            .build_parent(handle, &mut world)
+            .unwrap()
+            .handle();
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        // Once at rest, the sheet stays in contact with the ground at every step.
+        for _ in 0..60 {
+            world.step();
+            let contacts = world.contact_features(sheet, ground);
+            assert!(contacts.map(|c| !c.is_empty()).unwrap_or(false));
+        }
+
+        let sheet_body = world.body(handle).unwrap();
+        let (_, positions) = sheet_body.deformed_positions().unwrap();
+        let min_y = positions.iter().skip(1).step_by(3).fold(std::f64::MAX, |a, b| a.min(*b));
+        assert!(min_y > thickness * 0.5);
+    }
+}
@@ -5,13 +5,17 @@ pub use self::body_set::{Bodies, BodiesMut, BodyPartHandle, BodySet, BodyHandle,
 pub use self::collider::{Collider, ColliderData, ColliderAnchor, ColliderHandle, ColliderDesc, DeformableColliderDesc};
 pub use self::ground::Ground;
 pub use self::multibody::{Multibody, MultibodyDesc};
+pub(crate) use self::multibody::MultibodyState;
 pub(crate) use self::multibody_link::MultibodyLinkVec;
 pub use self::multibody_link::MultibodyLink;
-pub use self::rigid_body::{RigidBody, RigidBodyDesc};
+pub use self::rigid_body::{GyroMode, RigidBody, RigidBodyDesc};
+pub(crate) use self::rigid_body::RigidBodyState;
 #[cfg(feature = "dim2")]
 pub use self::fem_surface::{FEMSurface, FEMSurfaceDesc};
 #[cfg(feature = "dim3")]
 pub use self::fem_volume::{FEMVolume, FEMVolumeDesc};
+#[cfg(feature = "dim3")]
+pub(crate) use self::fem_volume::FEMVolumeState;
 pub use self::mass_constraint_system::{MassConstraintSystem, MassConstraintSystemDesc};
 pub use self::mass_spring_system::{MassSpringSystem, MassSpringSystemDesc};
 pub(crate) use self::fem_helper::FiniteElementIndices;
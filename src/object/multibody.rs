@@ -32,6 +32,7 @@ pub struct Multibody<N: RealField> {
     inv_augmented_mass: LU<N, Dynamic, Dynamic>,
     status: BodyStatus,
     gravity_enabled: bool,
+    position_correction_enabled: bool,
     update_status: BodyUpdateStatus,
     activation: ActivationStatus<N>,
     ndofs: usize,
@@ -72,6 +73,7 @@ impl<N: RealField> Multibody<N> {
             status: BodyStatus::Dynamic,
             update_status: BodyUpdateStatus::all(),
             gravity_enabled: true,
+            position_correction_enabled: true,
             activation: ActivationStatus::new_active(),
             ndofs: 0,
             companion_id: 0,
@@ -701,6 +703,52 @@ impl<N: RealField> Multibody<N> {
     pub(crate) fn impulses(&self) -> &[N] {
         self.impulses.as_slice()
     }
+
+    /// The state of this multibody modified by the simulation.
+    pub(crate) fn state(&self) -> MultibodyState<N> {
+        MultibodyState {
+            joints: self.rbs.iter().map(|rb| rb.dof.clone()).collect(),
+            velocities: self.velocities.clone(),
+            impulses: self.impulses.clone(),
+            energy: self.activation.energy(),
+        }
+    }
+
+    /// Restores a state previously returned by `self.state()`.
+    ///
+    /// The kinematics and dynamics of this multibody are then updated for a timestep of length `dt`.
+    pub(crate) fn set_state(&mut self, state: &MultibodyState<N>, dt: N) {
+        for (rb, joint) in self.rbs.iter_mut().zip(state.joints.iter()) {
+            rb.dof = (**joint).clone();
+        }
+
+        self.velocities.copy_from(&state.velocities);
+        self.impulses.copy_from(&state.impulses);
+        self.activation.set_energy(state.energy);
+        self.update_status.set_position_changed(true);
+        self.update_status.set_velocity_changed(true);
+        self.update_kinematics();
+        self.update_dynamics(dt);
+    }
+}
+
+/// The state of a multibody modified by the simulation, as saved by a `WorldSnapshot`.
+pub(crate) struct MultibodyState<N: RealField> {
+    joints: Vec<Box<Joint<N>>>,
+    velocities: DVector<N>,
+    impulses: DVector<N>,
+    energy: N,
+}
+
+impl<N: RealField> Clone for MultibodyState<N> {
+    fn clone(&self) -> Self {
+        MultibodyState {
+            joints: self.joints.iter().map(|joint| (**joint).clone()).collect(),
+            velocities: self.velocities.clone(),
+            impulses: self.impulses.clone(),
+            energy: self.energy,
+        }
+    }
 }
 
 /// A temporary workspace for various updates of the multibody.
@@ -764,6 +812,11 @@ impl<N: RealField> Body<N> for Multibody<N> {
         self.link(id).map(|l| l as &BodyPart<N>)
     }
 
+    #[inline]
+    fn num_parts(&self) -> usize {
+        self.rbs.len()
+    }
+
     #[inline]
     fn deformed_positions(&self) -> Option<(DeformationsType, &[N])> {
         None
@@ -871,6 +924,16 @@ impl<N: RealField> Body<N> for Multibody<N> {
         self.gravity_enabled = enabled
     }
 
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        self.position_correction_enabled
+    }
+
+    #[inline]
+    fn set_position_correction_enabled(&mut self, enabled: bool) {
+        self.position_correction_enabled = enabled
+    }
+
     #[inline]
     fn handle(&self) -> BodyHandle {
         self.handle
@@ -898,6 +961,16 @@ impl<N: RealField> Body<N> for Multibody<N> {
         self.activation.set_deactivation_threshold(threshold)
     }
 
+    #[inline]
+    fn keep_awake_while_forced(&mut self, enabled: bool) {
+        self.activation.set_keep_awake_while_forced(enabled)
+    }
+
+    #[inline]
+    fn user_data(&self) -> Option<&(Any + Send + Sync)> {
+        self.user_data.as_ref().map(|d| &**d)
+    }
+
     #[inline]
     fn set_status(&mut self, status: BodyStatus) {
         self.update_status.set_status_changed(true);
@@ -1061,17 +1134,27 @@ impl<N: RealField> Body<N> for Multibody<N> {
         }
     }
 
+    #[inline]
+    fn clear_cached_impulses(&mut self) {
+        // The impulses of the last timestep are cached from the workspace at the next setup.
+        if let Some(workspace) = &mut self.solver_workspace {
+            workspace.constraints.clear();
+        }
+
+        self.impulses.fill(N::zero());
+    }
+
     #[inline]
     fn step_solve_internal_velocity_constraints(&mut self, dvels: &mut DVectorSliceMut<N>) {
         let workspace = self.solver_workspace.as_mut().unwrap();
         for c in &mut workspace.constraints.velocity.unilateral_ground {
             let dim = Dynamic::new(c.ndofs);
-            SORProx::solve_unilateral_ground(c, workspace.jacobians.as_slice(), dvels, dim)
+            let _ = SORProx::solve_unilateral_ground(c, workspace.jacobians.as_slice(), dvels, dim);
         }
 
         for c in &mut workspace.constraints.velocity.bilateral_ground {
             let dim = Dynamic::new(c.ndofs);
-            SORProx::solve_bilateral_ground(c, &[], workspace.jacobians.as_slice(), dvels, dim)
+            let _ = SORProx::solve_bilateral_ground(c, &[], workspace.jacobians.as_slice(), dvels, dim);
         }
     }
 
@@ -1132,7 +1215,7 @@ impl<N: RealField> Body<N> for Multibody<N> {
             return;
         }
 
-        if auto_wake_up {
+        if auto_wake_up || (self.activation.is_kept_awake_while_forced() && force.as_vector().iter().any(|f| !f.is_zero())) {
             self.activate()
         }
 
@@ -1334,4 +1417,35 @@ impl<'a, N: RealField> BodyDesc<N> for MultibodyDesc<'a, N> {
         let _ = self.do_build(&mut mb, cworld, BodyPartHandle::ground());
         mb
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use crate::joint::FixedJoint;
+    use crate::math::{Isometry, Vector};
+    use crate::object::{Body, MultibodyDesc};
+    use crate::world::World;
+
+    #[test]
+    fn iterate_multibody_parts() {
+        let mut world = World::<f64>::new();
+        let joint = FixedJoint::new(Isometry::identity());
+        let mut desc = MultibodyDesc::new(joint);
+        let mut curr = &mut desc;
+
+        for _ in 0..3 {
+            curr = curr.add_child(joint).set_body_shift(Vector::x());
+        }
+
+        let multibody = desc.build(&mut world);
+        assert_eq!(multibody.num_parts(), 4);
+
+        let handles: Vec<_> = multibody.parts().map(|p| p.part_handle()).collect();
+        assert_eq!(handles.len(), 4);
+
+        for (i, handle) in handles.iter().enumerate() {
+            assert_eq!(handle.0, multibody.handle());
+            assert_eq!(handle.1, i);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::any::Any;
-use na::{DVectorSlice, DVectorSliceMut, RealField};
+use na::{DVectorSlice, DVectorSliceMut, MatrixN, RealField};
 
 use crate::math::{Force, Inertia, Isometry, Point, Rotation, Translation, Vector, Velocity,
                   SpatialVector, SPATIAL_DIM, DIM, Dim, ForceType};
@@ -16,6 +16,21 @@ use crate::math::AngularVector;
 use crate::utils::GeneralizedCross;
 
 
+/// The way the gyroscopic forces are taken into account by a rigid body.
+///
+/// This has no effect in 2D where there are no gyroscopic forces.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum GyroMode {
+    /// The gyroscopic forces are applied and the augmented mass is inverted at each timestep.
+    Full,
+    /// The gyroscopic forces are applied but the augmented mass inverse is approximated by the inverse inertia.
+    ///
+    /// This avoids the inversion of the augmented mass at each timestep, at the cost of some accuracy.
+    Approximate,
+    /// The gyroscopic forces are ignored.
+    Off,
+}
+
 /// A rigid body.
 #[derive(Debug)]
 pub struct RigidBody<N: RealField> {
@@ -24,6 +39,7 @@ pub struct RigidBody<N: RealField> {
     position: Isometry<N>,
     velocity: Velocity<N>,
     local_inertia: Inertia<N>,
+    inv_local_inertia: Inertia<N>,
     inertia: Inertia<N>,
     local_com: Point<N>,
     com: Point<N>,
@@ -32,7 +48,10 @@ pub struct RigidBody<N: RealField> {
     external_forces: Force<N>,
     acceleration: Velocity<N>,
     status: BodyStatus,
-    gravity_enabled: bool,
+    gravity_scale: N,
+    surface_velocity: Vector<N>,
+    position_correction_enabled: bool,
+    gyroscopic_mode: GyroMode,
     activation: ActivationStatus<N>,
     jacobian_mask: SpatialVector<N>,
     companion_id: usize,
@@ -52,6 +71,7 @@ impl<N: RealField> RigidBody<N> {
             position,
             velocity: Velocity::zero(),
             local_inertia: inertia,
+            inv_local_inertia: inertia.inverse(),
             inertia,
             local_com: Point::origin(),
             com,
@@ -60,7 +80,10 @@ impl<N: RealField> RigidBody<N> {
             external_forces: Force::zero(),
             acceleration: Velocity::zero(),
             status: BodyStatus::Dynamic,
-            gravity_enabled: true,
+            gravity_scale: N::one(),
+            surface_velocity: Vector::zeros(),
+            position_correction_enabled: true,
+            gyroscopic_mode: GyroMode::Full,
             activation: ActivationStatus::new_active(),
             jacobian_mask: SpatialVector::repeat(N::one()),
             companion_id: 0,
@@ -117,6 +140,13 @@ impl<N: RealField> RigidBody<N> {
         self.jacobian_mask[2].is_zero()
     }
 
+    /// Flags indicating which degrees of freedoms are kinematic.
+    ///
+    /// The first `DIM` flags are the translational ones and the remaining ones are the rotational ones.
+    pub fn kinematic_dofs(&self) -> SpatialVector<bool> {
+        self.jacobian_mask.map(|m| m.is_zero())
+    }
+
     /// Disable all rotations of this rigid body.
     ///
     /// This is the same as setting all the rotations of this rigid body as kinematic and setting
@@ -168,6 +198,51 @@ impl<N: RealField> RigidBody<N> {
         self.set_translations_kinematic(Vector::repeat(false))
     }
 
+    /// The way the gyroscopic forces are taken into account by this rigid body.
+    #[inline]
+    pub fn gyroscopic_mode(&self) -> GyroMode {
+        self.gyroscopic_mode
+    }
+
+    /// Sets the way the gyroscopic forces are taken into account by this rigid body.
+    #[inline]
+    pub fn set_gyroscopic_mode(&mut self, mode: GyroMode) {
+        self.update_status.set_local_inertia_changed(true);
+        self.gyroscopic_mode = mode;
+    }
+
+    /// The factor the gravity is multiplied by before being applied to this rigid body.
+    #[inline]
+    pub fn gravity_scale(&self) -> N {
+        self.gravity_scale
+    }
+
+    /// Sets the factor the gravity is multiplied by before being applied to this rigid body.
+    ///
+    /// A negative scale makes this rigid body accelerate against the gravity, e.g., like a balloon.
+    /// Setting a zero scale is the same as disabling the gravity.
+    #[inline]
+    pub fn set_gravity_scale(&mut self, scale: N) {
+        self.gravity_scale = scale
+    }
+
+    /// The velocity of the surface of this rigid body, expressed in its local frame.
+    #[inline]
+    pub fn surface_velocity(&self) -> &Vector<N> {
+        &self.surface_velocity
+    }
+
+    /// Sets the velocity of the surface of this rigid body, expressed in its local frame.
+    ///
+    /// The surface velocity is taken into account by the friction of the contacts involving the colliders of this
+    /// rigid body, as if its surface was moving without the rigid body itself moving, e.g., like a conveyor belt.
+    /// Only the tangential component of this velocity at each contact point is used. It adds up to the surface
+    /// velocities of the materials of the colliders.
+    #[inline]
+    pub fn set_surface_velocity(&mut self, velocity: Vector<N>) {
+        self.surface_velocity = velocity
+    }
+
     /// The handle of this rigid body.
     #[inline]
     pub fn handle(&self) -> BodyHandle {
@@ -188,6 +263,15 @@ impl<N: RealField> RigidBody<N> {
         &mut self.activation
     }
 
+    /// Wakes up this rigid body, and discards the impulses cached for warm-starting its contacts and joints.
+    ///
+    /// This avoids the "kick" applied by stale impulses at the next timestep, e.g., after teleporting this body.
+    #[inline]
+    pub fn wake_up_clean(&mut self) {
+        self.activate();
+        self.update_status.set_impulses_cleared(true);
+    }
+
     /// Set the center of mass of this rigid body, expressed in its local space.
     #[inline]
     pub fn set_local_center_of_mass(&mut self, local_com: Point<N>) {
@@ -197,9 +281,10 @@ impl<N: RealField> RigidBody<N> {
 
     fn update_inertia_from_local_inertia(&mut self) {
         // Needed for 2D because the inertia is not updated on the `update_dynamics`.
+        self.inv_local_inertia = self.local_inertia.inverse();
         self.inertia = self.local_inertia.transformed(&self.position);
         self.augmented_mass = self.inertia;
-        self.inv_augmented_mass = self.inertia.inverse();
+        self.inv_augmented_mass = self.inv_local_inertia.transformed(&self.position);
     }
 
     /// Set the local inertia of this rigid body, expressed in its local space.
@@ -298,6 +383,93 @@ impl<N: RealField> RigidBody<N> {
         &self.velocity
     }
 
+    /// The sum of the external forces applied to this rigid body since the end of the last timestep.
+    ///
+    /// This accumulates the forces applied with `ForceType::Force` as well as those applied with
+    /// `ForceType::AccelerationChange`, the latter being converted to forces using the augmented mass.
+    /// Gravity is not included. This is reset to zero at the end of each timestep.
+    #[inline]
+    pub fn external_forces(&self) -> &Force<N> {
+        &self.external_forces
+    }
+
+    /// The linear part of the external forces applied to this rigid body since the end of the last timestep.
+    #[inline]
+    pub fn external_linear_force(&self) -> &Vector<N> {
+        &self.external_forces.linear
+    }
+
+    #[cfg(feature = "dim2")]
+    /// The torque applied to this rigid body by the external forces since the end of the last timestep.
+    #[inline]
+    pub fn external_torque(&self) -> N {
+        self.external_forces.angular
+    }
+
+    #[cfg(feature = "dim3")]
+    /// The torque applied to this rigid body by the external forces since the end of the last timestep.
+    #[inline]
+    pub fn external_torque(&self) -> &AngularVector<N> {
+        &self.external_forces.angular
+    }
+
+    /// The impulse to apply to the center of mass of this rigid body so its linear velocity becomes `target_linear`.
+    ///
+    /// Applying the result with `ForceType::Impulse` leaves the angular velocity of this rigid body unchanged.
+    pub fn impulse_to_reach_velocity(&self, target_linear: Vector<N>) -> Force<N> {
+        Force::linear((target_linear - self.velocity.linear) * self.augmented_mass.linear)
+    }
+
+    #[cfg(feature = "dim2")]
+    /// The torque impulse to apply to this rigid body so its angular velocity becomes `target_angular`.
+    ///
+    /// Applying the result with `ForceType::Impulse` leaves the linear velocity of this rigid body unchanged.
+    pub fn impulse_to_reach_angular_velocity(&self, target_angular: N) -> Force<N> {
+        Force::torque((target_angular - self.velocity.angular) * self.augmented_mass.angular)
+    }
+
+    #[cfg(feature = "dim3")]
+    /// The torque impulse to apply to this rigid body so its angular velocity becomes `target_angular`.
+    ///
+    /// Applying the result with `ForceType::Impulse` leaves the linear velocity of this rigid body unchanged.
+    /// The impulse is computed with the world-space inertia tensor of this rigid body.
+    pub fn impulse_to_reach_angular_velocity(&self, target_angular: AngularVector<N>) -> Force<N> {
+        Force::torque(self.augmented_mass.angular * (target_angular - self.velocity.angular))
+    }
+
+    #[cfg(feature = "dim2")]
+    /// Applies the torque impulse that sets the angular velocity of this rigid body to `target_angular`.
+    pub fn reach_angular_velocity(&mut self, target_angular: N) {
+        let impulse = self.impulse_to_reach_angular_velocity(target_angular);
+        self.apply_force(0, &impulse, ForceType::Impulse, true);
+    }
+
+    #[cfg(feature = "dim3")]
+    /// Applies the torque impulse that sets the angular velocity of this rigid body to `target_angular`.
+    pub fn reach_angular_velocity(&mut self, target_angular: AngularVector<N>) {
+        let impulse = self.impulse_to_reach_angular_velocity(target_angular);
+        self.apply_force(0, &impulse, ForceType::Impulse, true);
+    }
+
+    /// The impulse to apply at the world-space `point` so the velocity of this rigid body at this point becomes `target`.
+    ///
+    /// The result is meant to be applied with `apply_force_at_point` and `ForceType::Impulse`. Both the linear and
+    /// angular velocities of this rigid body are affected. A zero impulse is returned if this rigid body has an
+    /// infinite mass.
+    pub fn impulse_to_reach_velocity_at_point(&self, target: Vector<N>, point: &Point<N>) -> Vector<N> {
+        let shift = Point::from(point - self.com);
+        let basis = MatrixN::<N, Dim>::identity();
+        // The force resulting from a unit impulse along the i-th axis at the given point.
+        let unit_force = |i| Force::linear_at_point(basis.column(i).into_owned(), &shift);
+        let point_velocity = Vector::from_fn(|i, _| unit_force(i).as_vector().dot(self.velocity.as_vector()));
+        let effective_inv_mass = MatrixN::<N, Dim>::from_fn(|i, j| {
+            let dvel = self.inv_augmented_mass * unit_force(j);
+            unit_force(i).as_vector().dot(dvel.as_vector())
+        });
+
+        effective_inv_mass.lu().solve(&(target - point_velocity)).unwrap_or_else(Vector::zeros)
+    }
+
     #[inline]
     fn apply_displacement(&mut self, displacement: &Velocity<N>) {
         let rotation = Rotation::new(displacement.angular);
@@ -307,6 +479,44 @@ impl<N: RealField> RigidBody<N> {
         let new_pos = disp * self.position;
         self.set_position(new_pos);
     }
+
+    /// The state of this rigid body modified by the simulation.
+    pub(crate) fn state(&self) -> RigidBodyState<N> {
+        RigidBodyState {
+            position: self.position,
+            velocity: self.velocity,
+            com: self.com,
+            inertia: self.inertia,
+            augmented_mass: self.augmented_mass,
+            inv_augmented_mass: self.inv_augmented_mass,
+            energy: self.activation.energy(),
+        }
+    }
+
+    /// Restores a state previously returned by `self.state()`.
+    pub(crate) fn set_state(&mut self, state: &RigidBodyState<N>) {
+        self.position = state.position;
+        self.velocity = state.velocity;
+        self.com = state.com;
+        self.inertia = state.inertia;
+        self.augmented_mass = state.augmented_mass;
+        self.inv_augmented_mass = state.inv_augmented_mass;
+        self.activation.set_energy(state.energy);
+        self.update_status.set_position_changed(true);
+        self.update_status.set_velocity_changed(true);
+    }
+}
+
+/// The state of a rigid body modified by the simulation, as saved by a `WorldSnapshot`.
+#[derive(Clone)]
+pub(crate) struct RigidBodyState<N: RealField> {
+    position: Isometry<N>,
+    velocity: Velocity<N>,
+    com: Point<N>,
+    inertia: Inertia<N>,
+    augmented_mass: Inertia<N>,
+    inv_augmented_mass: Inertia<N>,
+    energy: N,
 }
 
 
@@ -343,6 +553,16 @@ impl<N: RealField> Body<N> for RigidBody<N> {
         self.activation.set_deactivation_threshold(threshold)
     }
 
+    #[inline]
+    fn keep_awake_while_forced(&mut self, enabled: bool) {
+        self.activation.set_keep_awake_while_forced(enabled)
+    }
+
+    #[inline]
+    fn user_data(&self) -> Option<&(Any + Send + Sync)> {
+        self.user_data.as_ref().map(|d| &**d)
+    }
+
     #[inline]
     fn update_status(&self) -> BodyUpdateStatus {
         self.update_status
@@ -441,17 +661,19 @@ impl<N: RealField> Body<N> for RigidBody<N> {
                 self.inertia = self.local_inertia.transformed(&self.position);
                 self.augmented_mass = self.inertia;
 
-                let i = &self.inertia.angular;
-                let w = &self.velocity.angular;
-                let iw = i * w;
-                let w_dt = w * dt;
-                let w_dt_cross = w_dt.gcross_matrix();
-                let iw_dt_cross = (iw * dt).gcross_matrix();
-                self.augmented_mass.angular += w_dt_cross * i - iw_dt_cross;
-
-                // NOTE: if we did not have the gyroscopic forces, we would not have to invert the inertia
-                // matrix at each time-step => add a flag to disable gyroscopic forces?
-                self.inv_augmented_mass = self.augmented_mass.inverse();
+                if self.gyroscopic_mode == GyroMode::Full {
+                    let i = &self.inertia.angular;
+                    let w = &self.velocity.angular;
+                    let iw = i * w;
+                    let w_dt = w * dt;
+                    let w_dt_cross = w_dt.gcross_matrix();
+                    let iw_dt_cross = (iw * dt).gcross_matrix();
+                    self.augmented_mass.angular += w_dt_cross * i - iw_dt_cross;
+                    self.inv_augmented_mass = self.augmented_mass.inverse();
+                } else {
+                    // The inverse inertia only needs to be rotated, not inverted.
+                    self.inv_augmented_mass = self.inv_local_inertia.transformed(&self.position);
+                }
             }
             _ => {}
         }
@@ -464,7 +686,7 @@ impl<N: RealField> Body<N> for RigidBody<N> {
             BodyStatus::Dynamic => {
                 // The inverse inertia matrix is constant in 2D.
                 #[cfg(feature = "dim3")]
-                    {
+                    if self.gyroscopic_mode != GyroMode::Off {
                         /*
                          * Compute acceleration due to gyroscopic forces.
                          */
@@ -475,8 +697,8 @@ impl<N: RealField> Body<N> for RigidBody<N> {
                         self.acceleration.angular = self.inv_augmented_mass.angular * gyroscopic;
                     }
 
-                if self.inv_augmented_mass.linear != N::zero() && self.gravity_enabled {
-                    self.acceleration.linear = *gravity;
+                if self.inv_augmented_mass.linear != N::zero() && !self.gravity_scale.is_zero() {
+                    self.acceleration.linear = *gravity * self.gravity_scale;
                 }
 
                 self.acceleration += self.inv_augmented_mass * self.external_forces;
@@ -491,6 +713,11 @@ impl<N: RealField> Body<N> for RigidBody<N> {
         Some(self)
     }
 
+    #[inline]
+    fn num_parts(&self) -> usize {
+        1
+    }
+
     #[inline]
     fn apply_displacement(&mut self, displacement: &[N]) {
         self.apply_displacement(&Velocity::from_slice(displacement));
@@ -513,12 +740,22 @@ impl<N: RealField> Body<N> for RigidBody<N> {
 
     #[inline]
     fn gravity_enabled(&self) -> bool {
-        self.gravity_enabled
+        !self.gravity_scale.is_zero()
     }
 
     #[inline]
     fn enable_gravity(&mut self, enabled: bool) {
-        self.gravity_enabled = enabled
+        self.gravity_scale = if enabled { N::one() } else { N::zero() }
+    }
+
+    #[inline]
+    fn position_correction_enabled(&self) -> bool {
+        self.position_correction_enabled
+    }
+
+    #[inline]
+    fn set_position_correction_enabled(&mut self, enabled: bool) {
+        self.position_correction_enabled = enabled
     }
 
     #[inline]
@@ -555,7 +792,9 @@ impl<N: RealField> Body<N> for RigidBody<N> {
                 let imf = *inv_mass * masked_force;
                 jacobians[wj_id..wj_id + SPATIAL_DIM].copy_from_slice(imf.as_slice());
 
-                *inv_r += inv_mass.mass() + masked_force.angular_vector().dot(&imf.angular_vector());
+                // This is `J M^-1 J^T` for the masked jacobian, so pure torques do not involve the mass
+                // of the body and the kinematic DOFs do not contribute.
+                *inv_r += masked_force.as_vector().dot(imf.as_vector());
 
                 if let Some(out_vel) = out_vel {
                     // Don't use the masked force here so the locked
@@ -613,7 +852,7 @@ impl<N: RealField> Body<N> for RigidBody<N> {
             return;
         }
 
-        if auto_wake_up {
+        if auto_wake_up || (self.activation.is_kept_awake_while_forced() && force.as_vector().iter().any(|f| !f.is_zero())) {
             self.activate();
         }
 
@@ -714,11 +953,18 @@ impl<N: RealField> BodyPart<N> for RigidBody<N> {
 /// Because the `.with_` methods takes `self` by-move, it is useful to use when initializing the
 /// `RigidBodyDesc` for the first time. The `.set_` methods are useful when modifying it after
 /// this initialization (including after calls to `.build`).
+///
+/// Sensors are attached like any other collider, using a `ColliderDesc` with `.sensor(true)`: they are
+/// created alongside the rigid body and move with it.
 #[derive(Clone)]
 pub struct RigidBodyDesc<'a, N: RealField> {
     name: String,
     user_data: Option<UserDataBox>,
     gravity_enabled: bool,
+    gravity_scale: N,
+    surface_velocity: Vector<N>,
+    position_correction_enabled: bool,
+    gyroscopic_mode: GyroMode,
     position: Isometry<N>,
     velocity: Velocity<N>,
     local_inertia: Inertia<N>,
@@ -740,6 +986,10 @@ impl<'a, N: RealField> RigidBodyDesc<'a, N> {
             name: String::new(),
             user_data: None,
             gravity_enabled: true,
+            gravity_scale: N::one(),
+            surface_velocity: Vector::zeros(),
+            position_correction_enabled: true,
+            gyroscopic_mode: GyroMode::Full,
             position: Isometry::identity(),
             velocity: Velocity::zero(),
             local_inertia: Inertia::zero(),
@@ -779,6 +1029,10 @@ impl<'a, N: RealField> RigidBodyDesc<'a, N> {
 
     desc_setters!(
         gravity_enabled, enable_gravity, gravity_enabled: bool
+        gravity_scale, set_gravity_scale, gravity_scale: N
+        surface_velocity, set_surface_velocity, surface_velocity: Vector<N>
+        position_correction_enabled, set_position_correction_enabled, position_correction_enabled: bool
+        gyroscopic_mode, set_gyroscopic_mode, gyroscopic_mode: GyroMode
         status, set_status, status: BodyStatus
         name, set_name, name: String
         position, set_position, position: Isometry<N>
@@ -812,10 +1066,14 @@ impl<'a, N: RealField> RigidBodyDesc<'a, N> {
 
     desc_getters!(
         [val] is_gravity_enabled -> gravity_enabled: bool
+        [val] get_gravity_scale -> gravity_scale: N
+        [val] is_position_correction_enabled -> position_correction_enabled: bool
+        [val] get_gyroscopic_mode -> gyroscopic_mode: GyroMode
         [val] get_status -> status: BodyStatus
         [val] get_sleep_threshold -> sleep_threshold: Option<N>
         [ref] get_position -> position: Isometry<N>
         [ref] get_velocity -> velocity: Velocity<N>
+        [ref] get_surface_velocity -> surface_velocity: Vector<N>
         [ref] get_local_inertia -> local_inertia: Inertia<N>
         [ref] get_local_center_of_mass -> local_center_of_mass: Point<N>
     );
@@ -837,7 +1095,10 @@ impl<'a, N: RealField> BodyDesc<N> for RigidBodyDesc<'a, N> {
         rb.set_status(self.status);
         rb.set_deactivation_threshold(self.sleep_threshold);
         rb.set_translations_kinematic(self.kinematic_translations);
-        rb.enable_gravity(self.gravity_enabled);
+        rb.set_gravity_scale(if self.gravity_enabled { self.gravity_scale } else { N::zero() });
+        rb.set_surface_velocity(self.surface_velocity);
+        rb.set_position_correction_enabled(self.position_correction_enabled);
+        rb.set_gyroscopic_mode(self.gyroscopic_mode);
         rb.set_name(self.name.clone());
         let _ = rb.set_user_data(self.user_data.as_ref().map(|data| data.0.to_any()));
 
@@ -857,4 +1118,348 @@ impl<'a, N: RealField> BodyDesc<N> for RigidBodyDesc<'a, N> {
 
         rb
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64;
+    use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+    use crate::math::{AngularVector, Force, ForceType, Inertia, Point, Vector, Velocity, SPATIAL_DIM};
+    use crate::object::{Body, BodyPart, BodyStatus, ColliderDesc, RigidBodyDesc};
+    use crate::solver::ForceDirection;
+    use crate::world::World;
+
+    #[test]
+    fn world_local_velocity_round_trip() {
+        let mut world = World::<f64>::new();
+        #[cfg(feature = "dim2")]
+        let angle = 1.2;
+        #[cfg(feature = "dim3")]
+        let angle = Vector::new(0.3, -1.2, 0.7);
+        let body = RigidBodyDesc::new()
+            .rotation(angle)
+            .translation(Vector::repeat(2.0))
+            .build(&mut world);
+
+        let velocity = Velocity::new_with_vectors(Vector::repeat(1.0) + Vector::x(), AngularVector::repeat(-0.5));
+        let local = body.world_to_local_velocity(velocity);
+        let world_vel = body.local_to_world_velocity(local);
+
+        assert_relative_eq!(local.linear, body.position().rotation.inverse() * velocity.linear, epsilon = 1.0e-10);
+        assert_relative_eq!(world_vel.as_vector(), velocity.as_vector(), epsilon = 1.0e-10);
+    }
+
+    #[test]
+    fn accumulated_external_forces() {
+        let mut world = World::<f64>::new();
+        let handle = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(2.0, na::one()))
+            .build(&mut world)
+            .handle();
+        let body = world.rigid_body_mut(handle).unwrap();
+
+        body.apply_force(0, &Force::linear(Vector::x() * 3.0), ForceType::Force, true);
+        body.apply_force_at_point(0, &Vector::y(), &Point::from(Vector::x()), ForceType::Force, true);
+        body.apply_force(0, &Force::linear(Vector::x()), ForceType::AccelerationChange, true);
+        // Impulses modify the velocity directly.
+        body.apply_force(0, &Force::linear(Vector::x()), ForceType::Impulse, true);
+
+        assert_relative_eq!(*body.external_linear_force(), Vector::x() * 5.0 + Vector::y(), epsilon = 1.0e-10);
+        // The torque is along the `z` axis, i.e., the last component of the angular vector, in both 2D and 3D.
+        let torque = body.external_forces().angular_vector();
+        assert_relative_eq!(torque[torque.len() - 1], 1.0, epsilon = 1.0e-10);
+        assert_relative_eq!(torque.norm(), 1.0, epsilon = 1.0e-10);
+
+        world.step();
+        let body = world.rigid_body(handle).unwrap();
+        assert_eq!(*body.external_linear_force(), Vector::zeros());
+    }
+
+    #[test]
+    fn applied_torque_only_has_an_angular_part() {
+        let mut world = World::<f64>::new();
+        #[cfg(feature = "dim2")]
+        let angle = 1.0;
+        #[cfg(feature = "dim3")]
+        let angle = Vector::y();
+        let handle = RigidBodyDesc::new()
+            .rotation(angle)
+            .build(&mut world)
+            .handle();
+        let body = world.rigid_body_mut(handle).unwrap();
+
+        #[cfg(feature = "dim2")]
+        {
+            body.apply_torque(0, 2.0, ForceType::Force, true);
+            body.apply_local_torque(0, 1.0, ForceType::Force, true);
+        }
+        #[cfg(feature = "dim3")]
+        {
+            body.apply_torque(0, &(AngularVector::x() * 2.0), ForceType::Force, true);
+            body.apply_local_torque(0, &AngularVector::x(), ForceType::Force, true);
+        }
+
+        // The local torque is rotated by the orientation of the body (which does nothing in 2D).
+        #[cfg(feature = "dim2")]
+        let local_torque = AngularVector::x();
+        #[cfg(feature = "dim3")]
+        let local_torque = body.position().rotation * AngularVector::x();
+        assert_eq!(*body.external_linear_force(), Vector::zeros());
+        assert_relative_eq!(body.external_forces().angular_vector(), AngularVector::x() * 2.0 + local_torque, epsilon = 1.0e-10);
+    }
+
+    #[test]
+    fn impulses_to_reach_velocity() {
+        let mut world = World::<f64>::new();
+        let angvel = AngularVector::repeat(0.5);
+        let handle = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(2.0, na::one()))
+            .velocity(Velocity::new_with_vectors(Vector::repeat(1.0) - Vector::x() * 3.0, angvel))
+            .build(&mut world)
+            .handle();
+        let body = world.rigid_body_mut(handle).unwrap();
+
+        // Bring the body to rest, without modifying its rotation.
+        let impulse = body.impulse_to_reach_velocity(Vector::zeros());
+        body.apply_force(0, &impulse, ForceType::Impulse, true);
+        assert_relative_eq!(body.velocity().linear, Vector::zeros(), epsilon = 1.0e-10);
+        assert_relative_eq!(body.velocity().angular_vector(), angvel, epsilon = 1.0e-10);
+
+        // Set the velocity of a point away from the center of mass.
+        let point = body.center_of_mass() + Vector::x() * 2.0;
+        let target = Vector::y() * 3.0;
+        let impulse = body.impulse_to_reach_velocity_at_point(target, &point);
+        body.apply_force_at_point(0, &impulse, &point, ForceType::Impulse, true);
+
+        let velocity = body.velocity();
+        let shift = point - body.center_of_mass();
+        #[cfg(feature = "dim2")]
+        let point_velocity = velocity.linear + Vector::new(-shift.y, shift.x) * velocity.angular;
+        #[cfg(feature = "dim3")]
+        let point_velocity = velocity.linear + velocity.angular.cross(&shift);
+        assert_relative_eq!(point_velocity, target, epsilon = 1.0e-10);
+    }
+
+    #[test]
+    fn impulse_to_reach_angular_velocity() {
+        let mut world = World::<f64>::new();
+        let linvel = Vector::x() * 2.0;
+        let handle = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(2.0, na::one()))
+            .velocity(Velocity::new_with_vectors(linvel, AngularVector::repeat(5.0)))
+            .build(&mut world)
+            .handle();
+        let body = world.rigid_body_mut(handle).unwrap();
+
+        #[cfg(feature = "dim2")]
+        let target = -1.0;
+        #[cfg(feature = "dim3")]
+        let target = Vector::new(1.0, -2.0, 0.5);
+
+        body.reach_angular_velocity(target);
+        assert_relative_eq!(body.velocity().angular, target, epsilon = 1.0e-10);
+        assert_relative_eq!(body.velocity().linear, linvel, epsilon = 1.0e-10);
+
+        // The body keeps its new spin during the next step.
+        world.step();
+        let body = world.rigid_body(handle).unwrap();
+        assert_relative_eq!(body.velocity().angular, target, epsilon = 1.0e-10);
+    }
+
+    #[test]
+    fn sensor_attached_to_moving_body() {
+        let mut world = World::<f64>::new();
+        let target = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(0.5))))
+            .translation(Vector::x() * 5.0)
+            .build(&mut world)
+            .handle();
+
+        let solid_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.1))).density(1.0);
+        let sensor_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(1.0))).sensor(true);
+        let body = RigidBodyDesc::new()
+            .collider(&solid_desc)
+            .collider(&sensor_desc)
+            .velocity(Velocity::new_with_vectors(Vector::x() * 5.0, na::zero()))
+            .build(&mut world)
+            .handle();
+        let sensor = world.collider_world().body_colliders(body).find(|c| c.is_sensor()).unwrap().handle();
+
+        let is_near_target = |world: &World<f64>| {
+            world.collider_world().colliders_in_proximity_of(sensor).unwrap().any(|c| c.handle() == target)
+        };
+
+        world.step();
+        assert!(!is_near_target(&world));
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        // The sensor followed the body until it reached the target.
+        let body_pos = world.rigid_body(body).unwrap().position().translation.vector;
+        let sensor_pos = world.collider(sensor).unwrap().position().translation.vector;
+        assert_relative_eq!(sensor_pos, body_pos, epsilon = 1.0e-10);
+        assert!(body_pos.x > 3.5);
+        assert!(is_near_target(&world));
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn gyroscopic_modes() {
+        use na::Matrix3;
+        use crate::math::Inertia;
+        use crate::object::GyroMode;
+
+        // Returns the relative drifts of the angular momentum and kinetic energy of a spinning asymmetric body,
+        // and whether its augmented mass had to be inverted.
+        let drifts = |mode| {
+            let mut world = World::<f64>::new();
+            let inertia = Inertia::new(1.0, Matrix3::from_diagonal(&Vector::new(1.0, 2.0, 3.0)));
+            let handle = RigidBodyDesc::new()
+                .local_inertia(inertia)
+                .velocity(Velocity::angular(1.0, 0.2, 3.0))
+                .gyroscopic_mode(mode)
+                .build(&mut world)
+                .handle();
+
+            let momentum_and_energy = |world: &World<f64>| {
+                let rb = world.rigid_body(handle).unwrap();
+                let i = inertia.transformed(rb.position()).angular;
+                let w = rb.velocity().angular;
+                (i * w, w.dot(&(i * w)) * 0.5)
+            };
+
+            let (l0, e0) = momentum_and_energy(&world);
+            for _ in 0..300 {
+                world.step();
+            }
+            let (l1, e1) = momentum_and_energy(&world);
+            let rb = world.rigid_body(handle).unwrap();
+            let inverted = rb.augmented_mass().angular != rb.inertia().angular;
+            ((l1 - l0).norm() / l0.norm(), (e1 - e0).abs() / e0, inverted)
+        };
+
+        let full = drifts(GyroMode::Full);
+        let approximate = drifts(GyroMode::Approximate);
+        let off = drifts(GyroMode::Off);
+
+        // The cheaper modes skip the inversion of the augmented mass.
+        assert!(full.2 && !approximate.2 && !off.2);
+        // The approximation is less accurate, but still conserves the angular momentum reasonably well.
+        assert!(full.0 < 0.05 && full.1 < 0.05);
+        assert!(approximate.0 < 0.1 && approximate.1 < 0.1);
+        assert!(full.0 < approximate.0);
+        // Without gyroscopic forces, the angular velocity is constant so the angular momentum is not conserved.
+        assert!(off.0 > 0.2);
+    }
+
+    #[test]
+    fn kinematic_dofs_match_kinematic_flags() {
+        let mut world = World::<f64>::new();
+        let handle = RigidBodyDesc::new().build(&mut world).handle();
+        let rb = world.rigid_body_mut(handle).unwrap();
+        rb.set_translations_kinematic(Vector::<f64>::x().map(|x| x != 0.0));
+        #[cfg(feature = "dim3")]
+        rb.set_rotations_kinematic(Vector::new(false, true, false));
+        #[cfg(feature = "dim2")]
+        rb.set_rotation_kinematic(true);
+
+        let rb = world.rigid_body(handle).unwrap();
+        let dofs = rb.kinematic_dofs();
+        assert_eq!(dofs.fixed_rows::<crate::math::Dim>(0).into_owned(), rb.kinematic_translations());
+        #[cfg(feature = "dim3")]
+        assert_eq!(dofs.fixed_rows::<na::U3>(3).into_owned(), rb.kinematic_rotations());
+        #[cfg(feature = "dim2")]
+        assert_eq!(dofs[2], rb.kinematic_rotation());
+        assert_eq!(dofs.iter().filter(|dof| **dof).count(), 2);
+    }
+
+    #[test]
+    fn gravity_scale_multiplies_gravity() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -10.0);
+        let mut desc = RigidBodyDesc::new().mass(1.0);
+        let underwater = desc.set_gravity_scale(0.3).build(&mut world).handle();
+        let balloon = desc.set_gravity_scale(-0.2).build(&mut world).handle();
+        let disabled = desc.enable_gravity(false).build(&mut world).handle();
+        assert!(!world.rigid_body(disabled).unwrap().gravity_enabled());
+
+        world.step();
+
+        let dt = world.timestep();
+        let vel = |world: &World<f64>, handle| world.rigid_body(handle).unwrap().velocity().linear.y;
+        assert_relative_eq!(vel(&world, underwater), -3.0 * dt, epsilon = 1.0e-10);
+        assert_relative_eq!(vel(&world, balloon), 2.0 * dt, epsilon = 1.0e-10);
+        assert_eq!(vel(&world, disabled), 0.0);
+
+        // Enabling the gravity resets the scale to one.
+        let rb = world.rigid_body_mut(balloon).unwrap();
+        rb.enable_gravity(true);
+        assert_eq!(rb.gravity_scale(), 1.0);
+    }
+
+    #[test]
+    fn surface_velocity_drags_boxes_along() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        // A static belt, rotated upside-down so its local surface velocity points toward `-x` in world-space.
+        let belt_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let belt_collider = ColliderDesc::new(belt_shape);
+        #[cfg(feature = "dim2")]
+        let upside_down = f64::consts::PI;
+        #[cfg(feature = "dim3")]
+        let upside_down = Vector::z() * f64::consts::PI;
+        let belt = RigidBodyDesc::new()
+            .collider(&belt_collider)
+            .status(BodyStatus::Static)
+            .rotation(upside_down)
+            .translation(Vector::y() * -10.0)
+            .surface_velocity(Vector::x() * 2.0)
+            .build(&mut world)
+            .handle();
+        assert_eq!(*world.rigid_body(belt).unwrap().surface_velocity(), Vector::x() * 2.0);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let handle = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let body = world.rigid_body(handle).unwrap();
+        assert_relative_eq!(body.velocity().linear.x, -2.0, epsilon = 1.0e-3);
+        assert!(body.position().translation.vector.x < -2.0);
+    }
+
+    #[test]
+    fn constraint_effective_mass_uses_the_masked_force() {
+        let mut world = World::<f64>::new();
+        let handle = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(2.0, na::one()))
+            .build(&mut world)
+            .handle();
+        let inv_r = |world: &World<f64>, dir: ForceDirection<f64>| {
+            let body = world.rigid_body(handle).unwrap();
+            let mut jacobians = [0.0; SPATIAL_DIM * 2];
+            let mut inv_r = 0.0;
+            body.fill_constraint_geometry(
+                body.part(0).unwrap(), SPATIAL_DIM, &Point::origin(), &dir, 0, SPATIAL_DIM, &mut jacobians, &mut inv_r, None, None,
+            );
+            inv_r
+        };
+
+        // A pure torque does not involve the mass of the body.
+        assert_relative_eq!(inv_r(&world, ForceDirection::Angular(AngularVector::x_axis())), 1.0, epsilon = 1.0e-10);
+        assert_relative_eq!(inv_r(&world, ForceDirection::Linear(Vector::x_axis())), 0.5, epsilon = 1.0e-10);
+
+        // The kinematic translations do not contribute to the effective mass.
+        world.rigid_body_mut(handle).unwrap().set_translations_kinematic(Vector::repeat(true));
+        assert_relative_eq!(inv_r(&world, ForceDirection::Linear(Vector::x_axis())), 0.0, epsilon = 1.0e-10);
+    }
+}
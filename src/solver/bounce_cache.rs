@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crate::object::ColliderHandle;
+use crate::utils::DeterministicState;
+
+/// A cache counting the number of times each pair of colliders started touching.
+///
+/// This is used to decrease the restitution of colliders bouncing repeatedly on each other.
+/// The pairs that have not been touching for `BounceCache::MAX_IDLE_STEPS` timesteps are forgotten.
+#[derive(Clone, Default)]
+pub struct BounceCache {
+    // Number of bounces, whether the pair was touching at the previous and current steps, and the
+    // number of steps since the pair last touched.
+    bounces: HashMap<(ColliderHandle, ColliderHandle), (usize, bool, bool, usize), DeterministicState>,
+}
+
+impl BounceCache {
+    /// The number of timesteps after which a pair of colliders that stopped touching is forgotten.
+    pub const MAX_IDLE_STEPS: usize = 600;
+
+    /// Create a new empty cache.
+    pub fn new() -> Self {
+        BounceCache {
+            bounces: HashMap::with_hasher(DeterministicState::new()),
+        }
+    }
+
+    /// Clear the cache.
+    pub fn clear(&mut self) {
+        self.bounces.clear()
+    }
+
+    /// Starts a new timestep: all the pairs are considered as no longer touching until `touch` is called again.
+    pub fn begin_step(&mut self) {
+        for entry in self.bounces.values_mut() {
+            entry.1 = entry.2;
+            entry.2 = false;
+
+            if entry.1 {
+                entry.3 = 0;
+            } else {
+                entry.3 += 1;
+            }
+        }
+
+        self.bounces.retain(|_, entry| entry.3 < Self::MAX_IDLE_STEPS);
+    }
+
+    /// Forgets all the pairs involving the given collider, e.g., because it has been removed.
+    pub fn remove_collider(&mut self, collider: ColliderHandle) {
+        self.bounces.retain(|pair, _| pair.0 != collider && pair.1 != collider);
+    }
+
+    /// Registers that the given colliders are touching during the current timestep.
+    ///
+    /// Returns the number of times these colliders started touching, including the current one
+    /// if they were not touching at the previous timestep.
+    pub fn touch(&mut self, collider1: ColliderHandle, collider2: ColliderHandle) -> usize {
+        let entry = self.bounces.entry((collider1, collider2)).or_insert((0, false, false, 0));
+
+        if !entry.2 {
+            entry.2 = true;
+
+            if !entry.1 {
+                entry.0 += 1;
+            }
+        }
+
+        entry.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::world::CollisionObjectHandle;
+    use super::BounceCache;
+
+    #[test]
+    fn idle_and_removed_pairs_are_forgotten() {
+        let (a, b, c) = (CollisionObjectHandle(0), CollisionObjectHandle(1), CollisionObjectHandle(2));
+        let mut cache = BounceCache::new();
+
+        assert_eq!(cache.touch(a, b), 1);
+        assert_eq!(cache.touch(a, c), 1);
+        cache.begin_step();
+        cache.begin_step();
+        assert_eq!(cache.touch(a, b), 2);
+
+        cache.remove_collider(b);
+        assert_eq!(cache.bounces.len(), 1);
+
+        for _ in 0..BounceCache::MAX_IDLE_STEPS {
+            cache.begin_step();
+        }
+
+        assert!(cache.bounces.is_empty());
+        assert_eq!(cache.touch(a, c), 1);
+    }
+}
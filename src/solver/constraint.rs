@@ -192,6 +192,13 @@ pub struct BilateralConstraint<N: RealField> {
     /// The target velocity change this constraint must apply.
     pub rhs: N,
 
+    /// The compliance of this constraint, i.e., its Constraint Force Mixing parameter.
+    ///
+    /// This is zero for a hard constraint. Otherwise, the impulse already applied by this constraint,
+    /// multiplied by this compliance, is added to the velocity error it must correct. The scaling
+    /// parameter `self.r` must then take this compliance into account too.
+    pub cfm: N,
+
     /// Limits of impulse applicable by this constraint.
     pub limits: ImpulseLimits<N>,
 
@@ -236,6 +243,7 @@ impl<N: RealField> BilateralConstraint<N> {
             impulse,
             r: geom.r,
             rhs,
+            cfm: N::zero(),
             limits,
             impulse_id,
             assembly_id1: assembly_id1,
@@ -260,6 +268,13 @@ pub struct BilateralGroundConstraint<N: RealField> {
     /// The target velocity change this constraint must apply.
     pub rhs: N,
 
+    /// The compliance of this constraint, i.e., its Constraint Force Mixing parameter.
+    ///
+    /// This is zero for a hard constraint. Otherwise, the impulse already applied by this constraint,
+    /// multiplied by this compliance, is added to the velocity error it must correct. The scaling
+    /// parameter `self.r` must then take this compliance into account too.
+    pub cfm: N,
+
     /// Limits of impulse applicable by this constraint.
     pub limits: ImpulseLimits<N>,
 
@@ -292,6 +307,7 @@ impl<N: RealField> BilateralGroundConstraint<N> {
                 impulse,
                 r: geom.r,
                 rhs,
+                cfm: N::zero(),
                 limits,
                 impulse_id,
                 assembly_id: assembly_id2,
@@ -304,6 +320,7 @@ impl<N: RealField> BilateralGroundConstraint<N> {
                 impulse,
                 r: geom.r,
                 rhs,
+                cfm: N::zero(),
                 limits,
                 impulse_id,
                 assembly_id: assembly_id1,
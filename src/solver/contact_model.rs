@@ -1,17 +1,23 @@
 #![allow(missing_docs)]
 
+use std::any::Any;
+
 use downcast_rs::Downcast;
 use na::{DVector, RealField};
 
 use crate::detection::ColliderContactManifold;
-use crate::object::BodySet;
+use crate::object::{BodySet, ColliderHandle};
 use crate::material::MaterialsCoefficientsTable;
 use crate::solver::{ConstraintSet, IntegrationParameters};
 
 /// The modeling of a contact.
 pub trait ContactModel<N: RealField>: Downcast + Send + Sync {
     /// Maximum number of velocity constraint to be generated for each contact.
-    fn num_velocity_constraints(&self, manifold: &ColliderContactManifold<N>) -> usize;
+    fn num_velocity_constraints(&self, bodies: &BodySet<N>, manifold: &ColliderContactManifold<N>) -> usize;
+    /// Notifies this contact model that a new timestep started.
+    ///
+    /// This is called once per timestep, before `self.constraints` is called for each island.
+    fn begin_step(&mut self) {}
     /// Generate all constraints for the given contact manifolds.
     fn constraints(
         &mut self,
@@ -28,6 +34,23 @@ pub trait ContactModel<N: RealField>: Downcast + Send + Sync {
 
     /// Stores all the impulses found by the solver into a cache for warmstarting.
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>);
+
+    /// Discards all the impulses cached for warmstarting.
+    fn clear_cached_impulses(&mut self) {}
+
+    /// Discards the data cached for the given collider, which has been removed from the world.
+    fn remove_collider(&mut self, _collider: ColliderHandle) {}
+
+    /// A copy of the data cached by this contact model across timesteps, e.g., the impulses cached for warmstarting.
+    ///
+    /// This is used by world snapshots. The default implementation returns `None` for the contact models that do
+    /// not support them.
+    fn snapshot_cache(&self) -> Option<Box<Any + Send + Sync>> {
+        None
+    }
+
+    /// Overwrites the data cached by this contact model with a copy returned by `self.snapshot_cache()`.
+    fn restore_cache(&mut self, _cache: &Any) {}
 }
 
 impl_downcast!(ContactModel<N> where N: RealField);
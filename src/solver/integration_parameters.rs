@@ -1,6 +1,7 @@
 use na::{self, RealField};
 
 /// Parameters for a time-step of the physics engine.
+#[derive(Clone)]
 pub struct IntegrationParameters<N: RealField> {
     /// The timestep (default: `1.0 / 60.0`)
     pub dt: N,
@@ -13,14 +14,25 @@ pub struct IntegrationParameters<N: RealField> {
     pub erp: N,
     /// Each cached impulse are multiplied by this coefficient in `[0, 1]`
     /// when they are re-used to initialize the solver (default `1.0`).
+    ///
+    /// Setting it to zero disables the warmstarting of the contact and joint constraints, which is less stable
+    /// but more responsive. The internal constraints of a `MassConstraintSystem` use its own warmstart coefficient.
     pub warmstart_coeff: N,
     /// Contacts at points where the involved bodies have a relative
     /// velocity smaller than this threshold wont be affected by the restitution force (default: `1.0`).
     pub restitution_velocity_threshold: N,
+    /// Contacts at points where the involved bodies have a relative tangential velocity
+    /// smaller than this threshold use the static friction coefficient instead of the
+    /// dynamic one (default: `0.01`). Materials may override this threshold.
+    pub static_friction_velocity_threshold: N,
     /// Ammount of penetration the engine wont attempt to correct (default: `0.001m`).
+    ///
+    /// Joint constraints may override this with `set_allowed_linear_error`.
     pub allowed_linear_error: N,
     /// Ammount of angular drift of joint limits the engine wont
     /// attempt to correct (default: `0.001rad`).
+    ///
+    /// Joint constraints may override this with `set_allowed_angular_error`.
     pub allowed_angular_error: N,
     /// Maximum linear correction during one step of the non-linear position solver (default: `100.0`).
     pub max_linear_correction: N,
@@ -32,8 +44,57 @@ pub struct IntegrationParameters<N: RealField> {
     pub max_stabilization_multiplier: N,
     /// Maximum number of iterations performed by the velocity constraints solver.
     pub max_velocity_iterations: usize,
+    /// The velocity constraints solver stops iterating once the largest change of constraint impulse during
+    /// one iteration is smaller than this tolerance (default: `1.0e-8`).
+    ///
+    /// The solver still performs at most `max_velocity_iterations`. Setting it to zero disables this early-out.
+    /// Islands involving bodies with internal constraints, e.g., multibodies or deformable bodies, always perform
+    /// all the iterations.
+    pub velocity_solver_tolerance: N,
     /// Maximum number of iterations performed by the position-based constraints solver.
     pub max_position_iterations: usize,
+    /// Whether the cached impulses are rescaled by the ratio between the current and
+    /// the previous timestep length when they are re-used to initialize the solver (default: `true`).
+    ///
+    /// If enabled, the solver multiplies `warmstart_coeff` by this ratio before assembling the constraints.
+    ///
+    /// An impulse is a force integrated over one timestep: a body of mass `m` resting on
+    /// the ground under a gravity `g` requires a contact impulse of magnitude `m * g * dt`
+    /// at each step. Therefore, every impulse computed by the solver is proportional to `dt`.
+    /// Without this scaling, changing `dt` (e.g. for slow motion) initializes the solver with
+    /// impulses of the wrong magnitude, and the limited number of velocity iterations may fail
+    /// to correct them before the bodies start to drift.
+    pub impulse_scaling: bool,
+    /// The maximum number of velocity constraints generated at each timestep (default: `None`).
+    ///
+    /// If the contacts and joints would generate more constraints than this, the contact manifolds with
+    /// the shallowest penetrations are ignored until the limit is met. The joint constraints are never
+    /// ignored. This bounds the computation time of pathological scenes at the cost of some interpenetrations.
+    pub max_constraints: Option<usize>,
+    /// The number of timesteps over which the position correction of a new contact increases to its full
+    /// strength (default: `0`).
+    ///
+    /// During its `i`-th timestep, with `i` starting at 1, a contact only applies `i / contact_stiffness_ramp`
+    /// of its position correction. This avoids bodies created with an initial overlap from popping apart.
+    /// A value of 0 or 1 applies the full correction to every contact.
+    pub contact_stiffness_ramp: usize,
+    /// The maximum linear velocity of a body at the end of a timestep (default: `None`).
+    ///
+    /// Rigid bodies with a faster linear velocity, and deformable bodies with faster nodes, are slowed
+    /// down to this velocity. Velocities with a non-finite norm, e.g., NaN, are reset to zero. Multibodies
+    /// are not affected.
+    pub max_linear_velocity: Option<N>,
+    /// The maximum angular velocity of a rigid body at the end of a timestep (default: `None`).
+    ///
+    /// Angular velocities with a non-finite norm are reset to zero.
+    pub max_angular_velocity: Option<N>,
+    /// The prediction distance of the contacts between two dynamic bodies (default: `None`).
+    ///
+    /// If set, the solver ignores the contacts between two dynamic bodies separated by more than this distance,
+    /// while the contacts involving a static or kinematic body are taken into account up to the prediction
+    /// distance of their colliders. Combined with a large collider prediction, this prevents fast bodies from
+    /// tunneling through static geometry without stopping dynamic bodies before they actually touch each other.
+    pub dynamic_contacts_prediction: Option<N>,
 }
 
 impl<N: RealField> IntegrationParameters<N> {
@@ -57,6 +118,7 @@ impl<N: RealField> IntegrationParameters<N> {
             erp,
             warmstart_coeff,
             restitution_velocity_threshold,
+            static_friction_velocity_threshold: na::convert(0.01),
             allowed_linear_error,
             allowed_angular_error,
             max_linear_correction,
@@ -64,6 +126,23 @@ impl<N: RealField> IntegrationParameters<N> {
             max_stabilization_multiplier,
             max_velocity_iterations,
             max_position_iterations,
+            velocity_solver_tolerance: na::convert(1.0e-8),
+            impulse_scaling: true,
+            max_constraints: None,
+            contact_stiffness_ramp: 0,
+            max_linear_velocity: None,
+            max_angular_velocity: None,
+            dynamic_contacts_prediction: None,
+        }
+    }
+
+    /// The fraction of its position correction applied by a contact that already existed during `age` timesteps.
+    #[inline]
+    pub fn contact_stiffness(&self, age: usize) -> N {
+        if age < self.contact_stiffness_ramp {
+            na::convert((age + 1) as f64 / self.contact_stiffness_ramp as f64)
+        } else {
+            N::one()
         }
     }
 }
@@ -1,5 +1,6 @@
 //! Constraint solver.
 
+pub use self::bounce_cache::BounceCache;
 pub use self::constraint::{
     BilateralConstraint, BilateralGroundConstraint, ConstraintGeometry, ImpulseLimits,
     UnilateralConstraint, UnilateralGroundConstraint,
@@ -17,8 +18,9 @@ pub use self::nonlinear_constraint::{
 pub(crate) use self::nonlinear_sor_prox::NonlinearSORProx;
 pub use self::signorini_coulomb_pyramid_model::SignoriniCoulombPyramidModel;
 pub use self::signorini_model::SignoriniModel;
-pub(crate) use self::sor_prox::SORProx;
+pub(crate) use self::sor_prox::{SORProx, SORProxStats};
 
+mod bounce_cache;
 mod constraint;
 mod constraint_set;
 mod contact_model;
@@ -1,13 +1,18 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use slab::Slab;
 
 use na::{DVector, RealField};
 
 use crate::counters::Counters;
-use crate::detection::ColliderContactManifold;
-use crate::joint::JointConstraint;
-use crate::object::{BodyHandle, BodySet};
+use crate::detection::{ColliderContactManifold, IslandManager};
+use crate::joint::{ConstraintHandle, JointConstraint};
+use crate::math::Velocity;
+use crate::object::{BodyHandle, BodySet, ColliderHandle, RigidBody};
 use crate::material::MaterialsCoefficientsTable;
-use crate::solver::{ConstraintSet, ContactModel, IntegrationParameters, NonlinearSORProx, SORProx};
+use crate::solver::{ConstraintSet, ContactModel, IntegrationParameters, NonlinearSORProx, SORProx,
+             SORProxStats};
 use crate::world::ColliderWorld;
 
 /// Moreau-Jean time-stepping scheme.
@@ -19,6 +24,12 @@ pub struct MoreauJeanSolver<N: RealField> {
     contact_model: Box<ContactModel<N>>,
     constraints: ConstraintSet<N>,
     internal_constraints: Vec<BodyHandle>,
+    // The velocity changes applied by the constraints to each rigid body during the last step, if recorded.
+    applied_impulses: Option<HashMap<BodyHandle, Velocity<N>>>,
+    // The timestep length of the last step, used to rescale the cached impulses.
+    prev_dt: Option<N>,
+    #[cfg(feature = "solver-history")]
+    residual_history: Vec<N>,
 }
 
 impl<N: RealField> MoreauJeanSolver<N> {
@@ -33,44 +44,247 @@ impl<N: RealField> MoreauJeanSolver<N> {
             contact_model,
             constraints,
             internal_constraints: Vec::new(),
+            applied_impulses: None,
+            prev_dt: None,
+            #[cfg(feature = "solver-history")]
+            residual_history: Vec::new(),
         }
     }
 
+    /// The largest change of constraint impulse during each iteration of the velocity constraints resolution of the
+    /// last step.
+    ///
+    /// The residual of each iteration is the largest one among all the islands.
+    #[cfg(feature = "solver-history")]
+    pub fn residual_history(&self) -> &[N] {
+        &self.residual_history[..]
+    }
+
+    /// The constraints assembled for the last island solved during the last step.
+    ///
+    /// The buffers of this constraint set are cleared, but not deallocated, before the resolution of each island.
+    /// Within each buffer, the constraints are ordered deterministically: the joint constraints come first,
+    /// in the order of their handles, followed by the contact constraints, in the order of the contact manifolds.
+    pub fn constraints(&self) -> &ConstraintSet<N> {
+        &self.constraints
+    }
+
+    /// Sets whether the velocity change applied by the constraints to each rigid body is recorded during each step.
+    ///
+    /// Disabled by default.
+    pub fn set_applied_impulses_recording(&mut self, enabled: bool) {
+        if !enabled {
+            self.applied_impulses = None;
+        } else if self.applied_impulses.is_none() {
+            self.applied_impulses = Some(HashMap::new());
+        }
+    }
+
+    /// The velocity change applied by the constraints to the rigid body `handle` during the last step.
+    ///
+    /// This is the sum of the velocity changes due to all the contact and joint constraint impulses, excluding
+    /// the external forces like gravity. Zero is returned if the recording is disabled, or if the body is not
+    /// a rigid body that was simulated during the last step.
+    pub fn applied_impulse(&self, handle: BodyHandle) -> Velocity<N> {
+        self.applied_impulses
+            .as_ref()
+            .and_then(|impulses| impulses.get(&handle))
+            .cloned()
+            .unwrap_or_else(Velocity::zero)
+    }
+
+    /// The timestep length of the last step, if any.
+    pub(crate) fn prev_dt(&self) -> Option<N> {
+        self.prev_dt
+    }
+
+    /// Sets the timestep length of the last step, used to rescale the cached impulses at the next step.
+    pub(crate) fn set_prev_dt(&mut self, prev_dt: Option<N>) {
+        self.prev_dt = prev_dt
+    }
+
     /// Sets the contact model.
     pub fn set_contact_model(&mut self, model: Box<ContactModel<N>>) {
         self.contact_model = model
     }
 
+    /// Discards the impulses cached by the contact model for warmstarting.
+    pub fn clear_cached_impulses(&mut self) {
+        self.contact_model.clear_cached_impulses()
+    }
+
+    /// A copy of the data cached by the contact model across timesteps, if it supports snapshots.
+    pub(crate) fn contact_model_cache(&self) -> Option<Box<Any + Send + Sync>> {
+        self.contact_model.snapshot_cache()
+    }
+
+    /// Overwrites the data cached by the contact model with a copy returned by `self.contact_model_cache()`.
+    pub(crate) fn restore_contact_model_cache(&mut self, cache: &Any) {
+        self.contact_model.restore_cache(cache)
+    }
+
+    /// Discards the data cached by the contact model for the given collider, which has been removed.
+    pub fn remove_collider(&mut self, collider: ColliderHandle) {
+        self.contact_model.remove_collider(collider)
+    }
+
+    /// Removes the contact manifolds with the shallowest penetrations until at most `max_constraints` velocity
+    /// constraints are generated by the `joints` and the remaining `manifolds`.
+    ///
+    /// The remaining manifolds keep their relative order. Returns the number of contacts removed.
+    pub fn limit_constraints(
+        &self,
+        max_constraints: usize,
+        bodies: &BodySet<N>,
+        joints: &Slab<Box<JointConstraint<N>>>,
+        manifolds: &mut Vec<ColliderContactManifold<N>>,
+    ) -> usize {
+        let njoint_constraints: usize = joints
+            .iter()
+            .filter(|(_, joint)| joint.is_active(bodies))
+            .map(|(_, joint)| joint.num_velocity_constraints())
+            .sum();
+        let ncontact_constraints: usize = manifolds
+            .iter()
+            .map(|m| self.contact_model.num_velocity_constraints(bodies, m))
+            .sum();
+        let mut budget = max_constraints.saturating_sub(njoint_constraints);
+
+        if ncontact_constraints <= budget {
+            return 0;
+        }
+
+        let depth = |m: &ColliderContactManifold<N>| {
+            m.deepest_contact().map(|c| c.contact.depth).unwrap_or_else(N::min_value)
+        };
+        let mut priorities: Vec<_> = (0..manifolds.len()).collect();
+        priorities.sort_by(|a, b| {
+            depth(&manifolds[*b]).partial_cmp(&depth(&manifolds[*a])).unwrap_or(Ordering::Equal)
+        });
+
+        let mut kept = vec![false; manifolds.len()];
+
+        for i in priorities {
+            let nconstraints = self.contact_model.num_velocity_constraints(bodies, &manifolds[i]);
+
+            if nconstraints <= budget {
+                budget -= nconstraints;
+                kept[i] = true;
+            }
+        }
+
+        let mut nskipped = 0;
+        let mut kept = kept.into_iter();
+
+        manifolds.retain(|m| {
+            let keep = kept.next().unwrap_or(true);
+
+            if !keep {
+                nskipped += m.len();
+            }
+
+            keep
+        });
+
+        nskipped
+    }
+
     /// Perform one step of the time-stepping scheme.
+    ///
+    /// Each island of `islands` is solved independently. The `manifolds` must be the contact manifolds
+    /// given to the last update of `islands`.
     pub fn step(
         &mut self,
         counters: &mut Counters,
         bodies: &mut BodySet<N>,
         joints: &mut Slab<Box<JointConstraint<N>>>,
         manifolds: &[ColliderContactManifold<N>],
-        island: &[BodyHandle],
+        islands: &IslandManager,
         params: &IntegrationParameters<N>,
         coefficients: &MaterialsCoefficientsTable<N>,
         cworld: &ColliderWorld<N>,
     ) {
-        counters.assembly_started();
-        self.assemble_system(counters, params, coefficients, bodies, joints, manifolds, island);
-        counters.assembly_completed();
+        let _ = self.step_until(counters, bodies, joints, manifolds, islands, params, coefficients, cworld, None);
+    }
+
+    /// Perform one step of the time-stepping scheme, interrupting the constraints resolutions at the `deadline`.
+    ///
+    /// This is the same as `self.step`, except that if a `deadline` is given, the velocity and position constraints
+    /// resolutions are interrupted once it is reached (each of them still performs at least one iteration
+    /// per island). Returns `false` if any of them was interrupted.
+    pub fn step_until(
+        &mut self,
+        counters: &mut Counters,
+        bodies: &mut BodySet<N>,
+        joints: &mut Slab<Box<JointConstraint<N>>>,
+        manifolds: &[ColliderContactManifold<N>],
+        islands: &IslandManager,
+        params: &IntegrationParameters<N>,
+        coefficients: &MaterialsCoefficientsTable<N>,
+        cworld: &ColliderWorld<N>,
+        deadline: Option<f64>,
+    ) -> bool {
+        // The cached impulses are proportional to the timestep length they were computed with.
+        let mut scaled_params;
+        let params = match self.prev_dt {
+            Some(prev_dt) if params.impulse_scaling && !prev_dt.is_zero() && prev_dt != params.dt => {
+                scaled_params = params.clone();
+                scaled_params.warmstart_coeff *= params.dt / prev_dt;
+                &scaled_params
+            }
+            _ => params,
+        };
+        self.prev_dt = Some(params.dt);
+
+        let mut completed = true;
+        let mut nconstraints = 0;
+
+        let mut nvelocity_iterations = 0;
+        let mut max_residual_impulse = N::zero();
+
+        counters.reset_solver_timers();
+        self.contact_model.begin_step();
+        #[cfg(feature = "solver-history")]
+        self.residual_history.clear();
+
+        if let Some(impulses) = &mut self.applied_impulses {
+            impulses.clear();
+        }
+
+        for i in 0..islands.num_islands() {
+            let island = islands.island(i);
+            let island_joints = islands.island_joints(i);
+            let island_manifolds = &manifolds[islands.island_manifolds(i)];
+
+            counters.assembly_resumed();
+            self.assemble_system(counters, params, coefficients, bodies, joints, island_joints, island_manifolds, island);
+            counters.assembly_completed();
 
-        counters.set_nconstraints(self.constraints.velocity.len());
+            nconstraints += self.constraints.velocity.len();
 
-        counters.velocity_resolution_started();
-        self.solve_velocity_constraints(params, bodies);
-        self.save_cache(bodies, joints);
-        counters.velocity_resolution_completed();
+            counters.velocity_resolution_resumed();
+            let velocity_stats = self.solve_velocity_constraints(params, bodies, deadline);
+            nvelocity_iterations = nvelocity_iterations.max(velocity_stats.niterations);
+            max_residual_impulse = max_residual_impulse.max(velocity_stats.residual);
+            self.save_cache(bodies, joints, island_joints);
+            counters.velocity_resolution_completed();
 
-        counters.velocity_update_started();
-        self.update_velocities_and_integrate(params, bodies, island);
-        counters.velocity_update_completed();
+            counters.velocity_update_resumed();
+            self.update_velocities_and_integrate(params, bodies, island);
+            counters.velocity_update_completed();
 
-        counters.position_resolution_started();
-        self.solve_position_constraints(params, cworld, bodies, joints);
-        counters.position_resolution_completed();
+            counters.position_resolution_resumed();
+            let position_completed =
+                self.solve_position_constraints(params, cworld, bodies, joints, island_joints, deadline);
+            counters.position_resolution_completed();
+
+            completed = completed && velocity_stats.completed && position_completed;
+        }
+
+        counters.set_nconstraints(nconstraints);
+        counters.set_nvelocity_iterations(nvelocity_iterations);
+        counters.set_max_residual_impulse(na::try_convert(max_residual_impulse).unwrap_or(0.0));
+        completed
     }
 
     fn assemble_system(
@@ -80,6 +294,7 @@ impl<N: RealField> MoreauJeanSolver<N> {
         coefficients: &MaterialsCoefficientsTable<N>,
         bodies: &mut BodySet<N>,
         joints: &mut Slab<Box<JointConstraint<N>>>,
+        island_joints: &[ConstraintHandle],
         manifolds: &[ColliderContactManifold<N>],
         island: &[BodyHandle],
     ) {
@@ -126,7 +341,9 @@ impl<N: RealField> MoreauJeanSolver<N> {
         let mut jacobian_sz = 0;
         let mut ground_jacobian_sz = 0;
 
-        for (_, g) in joints.iter() {
+        for handle in island_joints {
+            let g = &joints[*handle];
+
             if g.is_active(bodies) {
                 let (b1, b2) = g.anchors();
                 let body1 = try_continue!(bodies.body(b1.0));
@@ -149,7 +366,7 @@ impl<N: RealField> MoreauJeanSolver<N> {
         for m in manifolds {
             let ndofs1 = try_continue!(bodies.body(m.body1())).status_dependent_ndofs();
             let ndofs2 = try_continue!(bodies.body(m.body2())).status_dependent_ndofs();
-            let sz = self.contact_model.num_velocity_constraints(m) * (ndofs1 + ndofs2) * 2;
+            let sz = self.contact_model.num_velocity_constraints(bodies, m) * (ndofs1 + ndofs2) * 2;
 
             if ndofs1 == 0 || ndofs2 == 0 {
                 ground_jacobian_sz += sz;
@@ -169,7 +386,9 @@ impl<N: RealField> MoreauJeanSolver<N> {
         let mut j_id = 0;
         let mut ground_j_id = jacobian_sz;
 
-        for (_, g) in joints {
+        for handle in island_joints {
+            let g = &mut joints[*handle];
+
             if g.is_active(bodies) {
                 g.velocity_constraints(
                     params,
@@ -206,7 +425,17 @@ impl<N: RealField> MoreauJeanSolver<N> {
         }
     }
 
-    fn solve_velocity_constraints(&mut self, params: &IntegrationParameters<N>, bodies: &mut BodySet<N>) {
+    fn solve_velocity_constraints(
+        &mut self,
+        params: &IntegrationParameters<N>,
+        bodies: &mut BodySet<N>,
+        deadline: Option<f64>,
+    ) -> SORProxStats<N> {
+        #[cfg(feature = "solver-history")]
+        let residuals = Some(&mut self.residual_history);
+        #[cfg(not(feature = "solver-history"))]
+        let residuals = None;
+
         SORProx::solve(
             bodies,
             &mut self.constraints.velocity.unilateral_ground,
@@ -217,7 +446,10 @@ impl<N: RealField> MoreauJeanSolver<N> {
             &mut self.mj_lambda_vel,
             &self.jacobians,
             params.max_velocity_iterations,
-        );
+            params.velocity_solver_tolerance,
+            deadline,
+            residuals,
+        )
     }
 
     fn solve_position_constraints(
@@ -226,27 +458,34 @@ impl<N: RealField> MoreauJeanSolver<N> {
         cworld: &ColliderWorld<N>,
         bodies: &mut BodySet<N>,
         joints: &mut Slab<Box<JointConstraint<N>>>,
-    ) {
+        island_joints: &[ConstraintHandle],
+        deadline: Option<f64>,
+    ) -> bool {
         NonlinearSORProx::solve(
             params,
             cworld,
             bodies,
             &mut self.constraints.position.unilateral,
             joints,
+            island_joints,
             &self.internal_constraints,
             &mut self.jacobians,
             params.max_position_iterations,
-        );
+            deadline,
+        )
     }
 
     fn save_cache(
         &mut self,
         bodies: &mut BodySet<N>,
         joints: &mut Slab<Box<JointConstraint<N>>>,
+        island_joints: &[ConstraintHandle],
     ) {
         self.contact_model.cache_impulses(&self.constraints);
 
-        for (_, g) in joints {
+        for handle in island_joints {
+            let g = &mut joints[*handle];
+
             if g.is_active(bodies) {
                 g.cache_impulses(&self.constraints);
             }
@@ -254,9 +493,16 @@ impl<N: RealField> MoreauJeanSolver<N> {
     }
 
     fn resize_buffers(&mut self, ndofs: usize) {
-        // XXX: use resize functions instead of reallocating.
-        self.mj_lambda_vel = DVector::zeros(ndofs);
-        self.ext_vels = DVector::zeros(ndofs);
+        // NOTE: the buffers are only reallocated when the number of degrees of freedom grows.
+        // Because all the islands share the same buffers, they are never shrunk, and only
+        // the rows used by the current island are reset.
+        if self.mj_lambda_vel.len() < ndofs {
+            self.mj_lambda_vel.resize_vertically_mut(ndofs, N::zero());
+            self.ext_vels.resize_vertically_mut(ndofs, N::zero());
+        }
+
+        self.mj_lambda_vel.rows_mut(0, ndofs).fill(N::zero());
+        self.ext_vels.rows_mut(0, ndofs).fill(N::zero());
     }
 
     fn update_velocities_and_integrate(
@@ -276,6 +522,13 @@ impl<N: RealField> MoreauJeanSolver<N> {
                 mb_vels += self.mj_lambda_vel.rows(id, ndofs);
             }
 
+            if let Some(impulses) = &mut self.applied_impulses {
+                if body.is::<RigidBody<N>>() {
+                    let dvel = Velocity::from_slice(&self.mj_lambda_vel.as_slice()[id..id + ndofs]);
+                    let _ = impulses.insert(*handle, dvel);
+                }
+            }
+
             body.integrate(params);
         }
     }
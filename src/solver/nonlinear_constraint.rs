@@ -76,6 +76,8 @@ pub struct NonlinearUnilateralConstraint<N: RealField> {
     pub r: N,
     /// The target position change this constraint must apply.
     pub rhs: N,
+    /// The fraction, in `[0, 1]`, of the position correction this constraint applies.
+    pub stiffness: N,
 
     /// Number of degree of freedom of the first body.
     pub ndofs1: usize,
@@ -115,10 +117,12 @@ impl<N: RealField> NonlinearUnilateralConstraint<N> {
     ) -> Self {
         let r = N::zero();
         let rhs = N::zero();
+        let stiffness = N::one();
 
         NonlinearUnilateralConstraint {
             r,
             rhs,
+            stiffness,
             ndofs1,
             body1,
             collider1,
@@ -2,8 +2,9 @@ use na::{self, Dim, Dynamic, RealField, U1, VectorSliceMutN};
 use slab::Slab;
 use std::ops::MulAssign;
 
+use crate::counters;
 use crate::world::ColliderWorld;
-use crate::joint::JointConstraint;
+use crate::joint::{ConstraintHandle, JointConstraint};
 use crate::object::{BodySet, ColliderAnchor, BodyHandle};
 use crate::solver::{ForceDirection, IntegrationParameters, NonlinearConstraintGenerator,
                     NonlinearUnilateralConstraint, GenericNonlinearConstraint};
@@ -14,17 +15,29 @@ pub(crate) struct NonlinearSORProx;
 
 impl NonlinearSORProx {
     /// Solve a set of nonlinear position-based constraints.
+    ///
+    /// Returns `false` if the resolution was interrupted before `max_iter` iterations because the `deadline` was reached.
     pub fn solve<N: RealField>(
         params: &IntegrationParameters<N>,
         cworld: &ColliderWorld<N>,
         bodies: &mut BodySet<N>,
         constraints: &mut [NonlinearUnilateralConstraint<N>],
         joints_constraints: &Slab<Box<JointConstraint<N>>>, // FIXME: ugly, use a slice of refs instead.
+        island_joints: &[ConstraintHandle],
         internal_constraints: &[BodyHandle],
         jacobians: &mut [N],
         max_iter: usize,
-    ) {
-        for _ in 0..max_iter {
+        deadline: Option<f64>,
+    ) -> bool {
+        // The parameters given to the joints overriding the allowed errors. Only these two tolerances
+        // are modified for each joint, so the parameters are copied once for all the iterations.
+        let mut joint_params = params.clone();
+
+        for i in 0..max_iter {
+            if i != 0 && deadline.map(|d| counters::now() >= d).unwrap_or(false) {
+                return false;
+            }
+
             for constraint in constraints.iter_mut() {
                 // FIXME: specialize for SPATIAL_DIM.
                 let dim1 = Dynamic::new(constraint.ndofs1);
@@ -32,8 +45,17 @@ impl NonlinearSORProx {
                 Self::solve_unilateral(params, cworld, bodies, constraint, jacobians, dim1, dim2);
             }
 
-            for joint in &*joints_constraints {
-                Self::solve_generator(params, bodies, &**joint.1, jacobians)
+            for handle in island_joints {
+                let joint = &*joints_constraints[*handle];
+
+                match joint.allowed_errors() {
+                    (None, None) => Self::solve_generator(params, bodies, joint, jacobians),
+                    (linear, angular) => {
+                        joint_params.allowed_linear_error = linear.unwrap_or(params.allowed_linear_error);
+                        joint_params.allowed_angular_error = angular.unwrap_or(params.allowed_angular_error);
+                        Self::solve_generator(&joint_params, bodies, joint, jacobians)
+                    }
+                }
             }
 
             for constraint in internal_constraints {
@@ -42,6 +64,8 @@ impl NonlinearSORProx {
                 }
             }
         }
+
+        true
     }
 
     fn solve_generator<N: RealField, Gen: ?Sized + NonlinearConstraintGenerator<N>>(
@@ -120,12 +144,16 @@ impl NonlinearSORProx {
 
             if dim1.value() != 0 {
                 if let Some(b1) = bodies.body_mut(constraint.body1.0) {
-                    b1.apply_displacement(&jacobians[0..dim1.value()]);
+                    if b1.position_correction_enabled() {
+                        b1.apply_displacement(&jacobians[0..dim1.value()]);
+                    }
                 }
             }
             if dim2.value() != 0 {
                 if let Some(b2) = bodies.body_mut(constraint.body2.0) {
-                    b2.apply_displacement(&jacobians[dim1.value()..dim1.value() + dim2.value()]);
+                    if b2.position_correction_enabled() {
+                        b2.apply_displacement(&jacobians[dim1.value()..dim1.value() + dim2.value()]);
+                    }
                 }
             }
         }
@@ -149,10 +177,13 @@ impl NonlinearSORProx {
         let pos2;
         let coords1;
         let coords2;
+        // Buffers for the colliders tracking only a subset of their body DOFs.
+        let mut deformations1 = Vec::new();
+        let mut deformations2 = Vec::new();
 
         match collider1.anchor() {
             ColliderAnchor::OnDeformableBody { .. } => {
-                let coords = body1.deformed_positions().unwrap().1;
+                let coords = collider1.deformations(body1.deformed_positions().unwrap().1, &mut deformations1);
                 collider1.shape().as_deformable_shape().unwrap().update_local_approximation(
                     coords,
                     constraint.kinematic.approx1_mut());
@@ -168,7 +199,7 @@ impl NonlinearSORProx {
 
         match collider2.anchor() {
             ColliderAnchor::OnDeformableBody { .. } => {
-                let coords = body2.deformed_positions().unwrap().1;
+                let coords = collider2.deformations(body2.deformed_positions().unwrap().1, &mut deformations2);
                 collider2.shape().as_deformable_shape().unwrap().update_local_approximation(
                     coords,
                     constraint.kinematic.approx2_mut());
@@ -185,7 +216,7 @@ impl NonlinearSORProx {
         if let Some(contact) = constraint
             .kinematic
             .contact(&pos1, &**collider1.shape(), coords1, &pos2, &**collider2.shape(), coords2, &constraint.normal1) {
-            constraint.rhs = Self::clamp_rhs(-contact.depth, false, params);
+            constraint.rhs = Self::clamp_rhs(-contact.depth, false, params) * constraint.stiffness;
 
             if constraint.rhs >= N::zero() {
                 return false;
@@ -196,7 +227,9 @@ impl NonlinearSORProx {
             let j_id1 = constraint.ndofs1 + constraint.ndofs2;
             let j_id2 = (constraint.ndofs1 * 2) + constraint.ndofs2;
 
-            if constraint.ndofs1 != 0 {
+            // Bodies with a disabled position correction do not contribute to the
+            // effective mass so that the full correction is applied to the other body.
+            if constraint.ndofs1 != 0 && body1.position_correction_enabled() {
                 body1.fill_constraint_geometry(
                     part1,
                     constraint.ndofs1,
@@ -211,7 +244,7 @@ impl NonlinearSORProx {
                 );
             }
 
-            if constraint.ndofs2 != 0 {
+            if constraint.ndofs2 != 0 && body2.position_correction_enabled() {
                 body2.fill_constraint_geometry(
                     part2,
                     constraint.ndofs2,
@@ -1,39 +1,102 @@
 use alga::linear::FiniteDimInnerSpace;
-use na::{self, DVector, RealField, Unit};
+use na::{self, DVector, DVectorSlice, RealField, Unit};
+use ncollide::query::TrackedContact;
+use std::any::Any;
 use std::ops::Range;
 
 use crate::detection::ColliderContactManifold;
 use crate::math::{Vector, DIM};
-use crate::object::BodySet;
-use crate::material::{Material, MaterialContext, MaterialsCoefficientsTable};
+#[cfg(feature = "dim2")]
+use crate::math::AngularVector;
+use crate::object::{BodySet, ColliderHandle};
+use crate::material::{Material, MaterialCombineMode, MaterialContext, MaterialsCoefficientsTable};
 use crate::solver::helper;
 use crate::solver::{
-    BilateralConstraint, BilateralGroundConstraint, ConstraintSet, ContactModel, ForceDirection,
+    BilateralConstraint, BilateralGroundConstraint, BounceCache, ConstraintSet, ContactModel, ForceDirection,
     ImpulseCache, ImpulseLimits, IntegrationParameters, SignoriniModel,
 };
 
-/// A contact model generating one non-penetration constraint and two friction constraints per contact.
+/// A contact model generating one non-penetration constraint and some friction constraints per contact.
 ///
-/// This contact model approximates the friction cone at a contact with pyramid.
+/// This contact model approximates the friction cone at a contact with pyramid. In 3D, the number of friction
+/// directions, i.e., half the number of faces of the pyramid, can be increased to reduce the bias of the friction
+/// toward the axes of the tangent space, at the cost of more constraints.
 pub struct SignoriniCoulombPyramidModel<N: RealField> {
-    impulses: ImpulseCache<Vector<N>>,
+    impulses: ImpulseCache<N>,
+    friction_impulses: Vec<N>,
+    friction_directions: usize,
+    // The friction coefficients are scaled so the pyramid circumscribes the friction cone.
+    friction_scale: N,
+    // The number of timesteps each contact has existed for.
+    ages: ImpulseCache<usize>,
+    bounces: BounceCache,
     vel_ground_rng: Range<usize>,
     vel_rng: Range<usize>,
     friction_ground_rng: Range<usize>,
     friction_rng: Range<usize>,
 }
 
+// The data of a `SignoriniCoulombPyramidModel` saved by `ContactModel::snapshot_cache`.
+struct SignoriniCoulombPyramidCache<N: RealField> {
+    impulses: ImpulseCache<N>,
+    friction_impulses: Vec<N>,
+    ages: ImpulseCache<usize>,
+    bounces: BounceCache,
+}
+
 impl<N: RealField> SignoriniCoulombPyramidModel<N> {
     /// Initialize a new signorini-coulomb-pyramid contact model.
     pub fn new() -> Self {
         SignoriniCoulombPyramidModel {
             impulses: ImpulseCache::new(),
+            friction_impulses: Vec::new(),
+            friction_directions: DIM - 1,
+            friction_scale: N::one(),
+            ages: ImpulseCache::new(),
+            bounces: BounceCache::new(),
             vel_ground_rng: 0..0,
             vel_rng: 0..0,
             friction_ground_rng: 0..0,
             friction_rng: 0..0,
         }
     }
+
+    /// Sets the number of friction directions generated for each contact.
+    ///
+    /// The directions are evenly distributed on the tangent plane, so the friction cone is approximated
+    /// by a pyramid with `2 * n` faces. Defaults to 2.
+    #[cfg(feature = "dim3")]
+    pub fn with_friction_directions(mut self, n: usize) -> Self {
+        assert!(n >= 2, "The number of friction directions must be at least 2.");
+        self.friction_directions = n;
+        self.friction_scale = if n == 2 {
+            N::one()
+        } else {
+            (N::frac_pi_2() / na::convert(n as f64)).tan()
+        };
+        self
+    }
+
+    /// The number of friction directions generated for each contact.
+    pub fn friction_directions(&self) -> usize {
+        self.friction_directions
+    }
+
+    // The combined rolling friction coefficient of the local material properties at the given contact.
+    fn rolling_friction(bodies: &BodySet<N>, manifold: &ColliderContactManifold<N>, c: &TrackedContact<N>) -> N {
+        let body1 = try_ret!(bodies.body(manifold.body1()), N::zero());
+        let body2 = try_ret!(bodies.body(manifold.body2()), N::zero());
+        let part1 = try_ret!(body1.part(manifold.body_part1(c.kinematic.feature1()).1), N::zero());
+        let part2 = try_ret!(body2.part(manifold.body_part2(c.kinematic.feature2()).1), N::zero());
+
+        let material1 = manifold.material1(c.kinematic.feature1());
+        let material2 = manifold.material2(c.kinematic.feature2());
+        let context1 = MaterialContext::new(body1, part1, manifold.collider1, c, true);
+        let context2 = MaterialContext::new(body2, part2, manifold.collider2, c, false);
+        let props1 = material1.local_properties(context1);
+        let props2 = material2.local_properties(context2);
+        MaterialCombineMode::combine(props1.rolling_friction, props2.rolling_friction).0
+    }
 }
 
 impl<N: RealField> Default for SignoriniCoulombPyramidModel<N> {
@@ -43,8 +106,21 @@ impl<N: RealField> Default for SignoriniCoulombPyramidModel<N> {
 }
 
 impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
-    fn num_velocity_constraints(&self, c: &ColliderContactManifold<N>) -> usize {
-        DIM * c.len()
+    fn num_velocity_constraints(&self, bodies: &BodySet<N>, c: &ColliderContactManifold<N>) -> usize {
+        // The non-penetration and friction constraints, plus the rolling friction constraints if any.
+        c.contacts()
+            .map(|contact| {
+                if Self::rolling_friction(bodies, c, contact).is_zero() {
+                    self.friction_directions + 1
+                } else {
+                    self.friction_directions + DIM
+                }
+            })
+            .sum()
+    }
+
+    fn begin_step(&mut self) {
+        self.bounces.begin_step();
     }
 
     fn constraints(
@@ -69,21 +145,35 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
             let body2 = try_continue!(bodies.body(manifold.body2()));
 
             for c in manifold.contacts() {
+                if SignoriniModel::is_ignored_by_one_way_collider(c, manifold) {
+                    continue;
+                }
+
                 let part1 = try_continue!(body1.part(manifold.body_part1(c.kinematic.feature1()).1));
                 let part2 = try_continue!(body2.part(manifold.body_part2(c.kinematic.feature2()).1));
 
-                let material1 = manifold.collider1.material();
-                let material2 = manifold.collider2.material();
+                let material1 = manifold.material1(c.kinematic.feature1());
+                let material2 = manifold.material2(c.kinematic.feature2());
                 let context1 = MaterialContext::new(body1, part1, manifold.collider1, c, true);
                 let context2 = MaterialContext::new(body2, part2, manifold.collider2, c, false);
-                let props = Material::combine(coefficients, material1, context1, material2, context2);
+                let mut props = Material::combine(coefficients, material1, context1, material2, context2);
+                SignoriniModel::apply_restitution_decay(&mut self.bounces, manifold, &mut props);
 
                 // if !SignoriniModel::is_constraint_active(c, manifold) {
                 //     continue;
                 // }
 
-                let impulse = self.impulses.get(c.id);
+                let impulse = SignoriniModel::cached_impulse(&self.impulses, c, body1, body2);
                 let impulse_id = self.impulses.entry_id(c.id);
+                // The friction impulses are not warmstarted if the normal impulse is not, e.g., for new contacts.
+                let warmstart_friction = !impulse.is_zero();
+                // The impulses of the friction constraints, followed by those of the rolling friction constraints.
+                let nfriction_impulses = self.friction_directions + DIM - 1;
+                let first_friction_id = impulse_id * nfriction_impulses;
+
+                if self.friction_impulses.len() < first_friction_id + nfriction_impulses {
+                    self.friction_impulses.resize(first_friction_id + nfriction_impulses, N::zero());
+                }
 
                 let ground_constraint = SignoriniModel::build_velocity_constraint(
                     params,
@@ -95,7 +185,7 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                     manifold,
                     ext_vels,
                     c,
-                    impulse[0],
+                    impulse,
                     impulse_id,
                     ground_j_id,
                     j_id,
@@ -103,7 +193,8 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                     constraints,
                 );
 
-                SignoriniModel::build_position_constraint(bodies, manifold, c, constraints);
+                let age = SignoriniModel::age_contact(&mut self.ages, c);
+                SignoriniModel::build_position_constraint(bodies, manifold, c, params.contact_stiffness(age), constraints);
 
                 let dependency;
 
@@ -121,10 +212,12 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                 // Generate friction constraints.
                 let limits = ImpulseLimits::Dependent {
                     dependency,
-                    coeff: props.friction.0,
+                    coeff: props.friction.0 * self.friction_scale,
                 };
 
-                let mut i = 1;
+                let mut tangential_sq_vel = N::zero();
+                let first_friction_ground = constraints.velocity.bilateral_ground.len();
+                let first_friction = constraints.velocity.bilateral.len();
 
                 // FIXME: this compute the contact point locations (with margins) several times,
                 // it was already computed for the signorini law.
@@ -134,7 +227,22 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                     - c.contact.normal.into_inner() * manifold.collider2.margin();
                 let (ext_vels1, ext_vels2) = helper::split_ext_vels(body1, body2, assembly_id1, assembly_id2, ext_vels);
 
-                Vector::orthonormal_subspace_basis(&[c.contact.normal.into_inner()], |friction_dir| {
+                let mut tangents = [Vector::zeros(); DIM - 1];
+                let mut k = 0;
+                Vector::orthonormal_subspace_basis(&[c.contact.normal.into_inner()], |tangent| {
+                    tangents[k] = *tangent;
+                    k += 1;
+                    true
+                });
+
+                for i in 0..self.friction_directions {
+                    #[cfg(feature = "dim2")]
+                    let friction_dir = &tangents[0];
+                    #[cfg(feature = "dim3")]
+                    let friction_dir = &{
+                        let angle = N::pi() * na::convert(i as f64) / na::convert(self.friction_directions as f64);
+                        tangents[0] * angle.cos() + tangents[1] * angle.sin()
+                    };
                     let dir = ForceDirection::Linear(Unit::new_unchecked(*friction_dir));
                     let mut rhs = friction_dir.dot(&props.surface_velocity);
 
@@ -155,7 +263,18 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                         Some(&mut rhs)
                     );
 
-                    let warmstart = impulse[i] * params.warmstart_coeff;
+                    // The tangential relative velocity before the external forces are applied. Otherwise,
+                    // e.g., the gravity would make a body resting on a slope look like it is sliding.
+                    let j1 = DVectorSlice::from_slice(&jacobians[geom.j_id1..], geom.ndofs1);
+                    let j2 = DVectorSlice::from_slice(&jacobians[geom.j_id2..], geom.ndofs2);
+                    let tangential_vel = rhs - j1.dot(&ext_vels1) - j2.dot(&ext_vels2);
+                    tangential_sq_vel += tangential_vel * tangential_vel;
+
+                    let warmstart = if warmstart_friction {
+                        self.friction_impulses[first_friction_id + i] * params.warmstart_coeff
+                    } else {
+                        N::zero()
+                    };
 
                     if geom.is_ground_constraint() {
                         let constraint = BilateralGroundConstraint::new(
@@ -165,7 +284,7 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                             limits,
                             rhs,
                             warmstart,
-                            impulse_id * DIM + i,
+                            first_friction_id + i,
                         );
                         constraints.velocity.bilateral_ground.push(constraint);
                     } else {
@@ -176,15 +295,85 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                             limits,
                             rhs,
                             warmstart,
-                            impulse_id * DIM + i,
+                            first_friction_id + i,
                         );
                         constraints.velocity.bilateral.push(constraint);
                     }
+                }
 
-                    i += 1;
+                let threshold = props.static_friction_velocity_threshold
+                    .unwrap_or(params.static_friction_velocity_threshold);
 
-                    true
-                });
+                if tangential_sq_vel < threshold * threshold {
+                    let limits = ImpulseLimits::Dependent {
+                        dependency,
+                        coeff: props.static_friction.0 * self.friction_scale,
+                    };
+
+                    for c in &mut constraints.velocity.bilateral_ground[first_friction_ground..] {
+                        c.limits = limits;
+                    }
+
+                    for c in &mut constraints.velocity.bilateral[first_friction..] {
+                        c.limits = limits;
+                    }
+                }
+
+                // Generate rolling friction constraints, opposing the relative angular velocity about the tangents.
+                if !props.rolling_friction.0.is_zero() {
+                    let limits = ImpulseLimits::Dependent {
+                        dependency,
+                        coeff: props.rolling_friction.0,
+                    };
+
+                    for k in 0..DIM - 1 {
+                        #[cfg(feature = "dim2")]
+                        let dir = ForceDirection::Angular(AngularVector::x_axis());
+                        #[cfg(feature = "dim3")]
+                        let dir = ForceDirection::Angular(Unit::new_unchecked(tangents[k]));
+                        let mut rhs = N::zero();
+
+                        let geom = helper::constraint_pair_geometry(
+                            body1,
+                            part1,
+                            body2,
+                            part2,
+                            &center1,
+                            &center2,
+                            &dir,
+                            ground_j_id,
+                            j_id,
+                            jacobians,
+                            Some(&ext_vels1),
+                            Some(&ext_vels2),
+                            Some(&mut rhs)
+                        );
+
+                        let id = first_friction_id + self.friction_directions + k;
+                        let warmstart = if warmstart_friction {
+                            self.friction_impulses[id] * params.warmstart_coeff
+                        } else {
+                            N::zero()
+                        };
+
+                        if geom.is_ground_constraint() {
+                            let constraint = BilateralGroundConstraint::new(
+                                geom,
+                                assembly_id1,
+                                assembly_id2,
+                                limits,
+                                rhs,
+                                warmstart,
+                                id,
+                            );
+                            constraints.velocity.bilateral_ground.push(constraint);
+                        } else {
+                            let constraint =
+                                BilateralConstraint::new(geom, assembly_id1, assembly_id2, limits, rhs, warmstart, id);
+                            constraints.velocity.bilateral.push(constraint);
+                        }
+                    }
+                }
             }
         }
 
@@ -202,19 +391,146 @@ impl<N: RealField> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
         let friction = &constraints.velocity.bilateral[self.friction_rng.clone()];
 
         for c in ground_contacts {
-            self.impulses[c.impulse_id][0] = c.impulse;
+            self.impulses[c.impulse_id] = c.impulse;
         }
 
         for c in contacts {
-            self.impulses[c.impulse_id][0] = c.impulse;
+            self.impulses[c.impulse_id] = c.impulse;
         }
 
         for c in ground_friction {
-            self.impulses[c.impulse_id / DIM][c.impulse_id % DIM] = c.impulse;
+            self.friction_impulses[c.impulse_id] = c.impulse;
         }
 
         for c in friction {
-            self.impulses[c.impulse_id / DIM][c.impulse_id % DIM] = c.impulse;
+            self.friction_impulses[c.impulse_id] = c.impulse;
         }
     }
+
+    fn clear_cached_impulses(&mut self) {
+        self.impulses.clear();
+        self.friction_impulses.clear();
+    }
+
+    fn remove_collider(&mut self, collider: ColliderHandle) {
+        self.bounces.remove_collider(collider);
+    }
+
+    fn snapshot_cache(&self) -> Option<Box<Any + Send + Sync>> {
+        let cache = SignoriniCoulombPyramidCache {
+            impulses: self.impulses.clone(),
+            friction_impulses: self.friction_impulses.clone(),
+            ages: self.ages.clone(),
+            bounces: self.bounces.clone(),
+        };
+
+        Some(Box::new(cache))
+    }
+
+    fn restore_cache(&mut self, cache: &Any) {
+        if let Some(cache) = cache.downcast_ref::<SignoriniCoulombPyramidCache<N>>() {
+            self.impulses = cache.impulses.clone();
+            self.friction_impulses = cache.friction_impulses.clone();
+            self.ages = cache.ages.clone();
+            self.bounces = cache.bounces.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+    use crate::material::{BasicMaterial, MaterialHandle};
+    use crate::math::{AngularVector, Vector, Velocity, ANGULAR_DIM};
+    use crate::object::{ColliderDesc, RigidBodyDesc};
+    use crate::solver::SignoriniCoulombPyramidModel;
+    use crate::world::World;
+
+    // Throws a body with the given collider, starting at the given height above a large ground with the given
+    // material, and returns its translation and velocity after `nsteps` timesteps.
+    fn throw_on_ground(
+        model: SignoriniCoulombPyramidModel<f64>,
+        material: MaterialHandle<f64>,
+        collider: ColliderDesc<f64>,
+        height: f64,
+        velocity: Velocity<f64>,
+        nsteps: usize,
+    ) -> (Vector<f64>, Velocity<f64>) {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.set_contact_model(model);
+        let ground = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(50.0))))
+            .translation(Vector::y() * -50.0)
+            .material(material.clone());
+        let _ = ground.build(&mut world);
+
+        let body = RigidBodyDesc::new()
+            .collider(&collider.material(material))
+            .translation(Vector::y() * height)
+            .velocity(velocity)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..nsteps {
+            world.step();
+        }
+
+        let body = world.rigid_body(body).unwrap();
+        (body.position().translation.vector, *body.velocity())
+    }
+
+    // The distance travelled by a box sliding on the ground, thrown along the given direction.
+    #[cfg(feature = "dim3")]
+    fn sliding_distance(model: SignoriniCoulombPyramidModel<f64>, dir: Vector<f64>) -> f64 {
+        let material = MaterialHandle::new(BasicMaterial::default());
+        let collider = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(0.5))))
+            .density(1.0)
+            .margin(0.0);
+        let velocity = Velocity::linear(dir.x * 5.0, 0.0, dir.z * 5.0);
+        let (translation, _) = throw_on_ground(model, material, collider, 0.5, velocity, 200);
+        Vector::new(translation.x, 0.0, translation.z).norm()
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn more_friction_directions_reduce_the_anisotropy() {
+        let axis = Vector::x();
+        let diagonal = Vector::new(1.0, 0.0, 1.0).normalize();
+
+        // With two directions, the friction is stronger along the diagonals of the tangent space.
+        let model = SignoriniCoulombPyramidModel::new;
+        let ratio = sliding_distance(model(), diagonal) / sliding_distance(model(), axis);
+        assert!(ratio < 0.8, "ratio: {}", ratio);
+
+        let model = || SignoriniCoulombPyramidModel::new().with_friction_directions(8);
+        assert_eq!(model().friction_directions(), 8);
+        let ratio = sliding_distance(model(), diagonal) / sliding_distance(model(), axis);
+        assert_relative_eq!(ratio, 1.0, epsilon = 5.0e-2);
+    }
+
+    // The distance travelled by a ball rolling without sliding on a ground with the given rolling friction.
+    fn rolling_distance(rolling_friction: f64) -> (f64, Velocity<f64>) {
+        let material = MaterialHandle::new(BasicMaterial::default().with_rolling_friction(rolling_friction));
+        let collider = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let height = 0.5 + collider.get_margin() * 2.0;
+        let mut angular = AngularVector::zeros();
+        angular[ANGULAR_DIM - 1] = -4.0;
+        let velocity = Velocity::new_with_vectors(Vector::x() * 2.0, angular);
+        let model = SignoriniCoulombPyramidModel::new();
+        let (translation, velocity) = throw_on_ground(model, material, collider, height, velocity, 300);
+        (translation.x, velocity)
+    }
+
+    #[test]
+    fn rolling_friction_stops_rolling_balls() {
+        // Without rolling friction, the ball rolls forever.
+        let (distance, velocity) = rolling_distance(0.0);
+        assert!(distance > 9.5, "distance: {}", distance);
+        assert_relative_eq!(velocity.linear.x, 2.0, epsilon = 5.0e-2);
+
+        let (distance, velocity) = rolling_distance(0.05);
+        assert!(distance > 1.0 && distance < 5.0, "distance: {}", distance);
+        assert_relative_eq!(velocity.linear.x, 0.0, epsilon = 1.0e-2);
+        assert_relative_eq!(velocity.angular_vector()[ANGULAR_DIM - 1], 0.0, epsilon = 1.0e-2);
+    }
 }
@@ -1,13 +1,15 @@
-use na::{self, DVector, RealField};
+use na::{self, DVector, DVectorSlice, RealField};
+use num::Zero;
+use std::any::Any;
 use std::ops::Range;
 
 use ncollide::query::TrackedContact;
 use ncollide::utils::IsometryOps;
 use crate::detection::ColliderContactManifold;
-use crate::object::{BodySet, Body, BodyPart};
+use crate::object::{BodySet, Body, BodyPart, ColliderHandle};
 use crate::material::{Material, MaterialContext, MaterialsCoefficientsTable, LocalMaterialProperties};
 use crate::solver::helper;
-use crate::solver::{ConstraintSet, ContactModel, ForceDirection, ImpulseCache, IntegrationParameters,
+use crate::solver::{BounceCache, ConstraintSet, ContactModel, ForceDirection, ImpulseCache, IntegrationParameters,
              NonlinearUnilateralConstraint, UnilateralConstraint, UnilateralGroundConstraint};
 
 /// A contact model generating one non-penetration constraint per contact.
@@ -15,6 +17,9 @@ use crate::solver::{ConstraintSet, ContactModel, ForceDirection, ImpulseCache, I
 /// This is a frictionless contact model.
 pub struct SignoriniModel<N: RealField> {
     impulses: ImpulseCache<N>,
+    // The number of timesteps each contact has existed for.
+    ages: ImpulseCache<usize>,
+    bounces: BounceCache,
     vel_ground_rng: Range<usize>,
     vel_rng: Range<usize>,
 }
@@ -24,6 +29,8 @@ impl<N: RealField> SignoriniModel<N> {
     pub fn new() -> Self {
         SignoriniModel {
             impulses: ImpulseCache::new(),
+            ages: ImpulseCache::new(),
+            bounces: BounceCache::new(),
             vel_ground_rng: 0..0,
             vel_rng: 0..0,
         }
@@ -57,7 +64,8 @@ impl<N: RealField> SignoriniModel<N> {
         let center2 = c.contact.world2 - c.contact.normal.into_inner() * data2.margin();
         let dir = ForceDirection::Linear(-c.contact.normal);
         let (ext_vels1, ext_vels2) = helper::split_ext_vels(body1, body2, assembly_id1, assembly_id2, ext_vels);
-        let mut rhs = c.contact.normal.dot(&props.surface_velocity);
+        let surface_vel = c.contact.normal.dot(&props.surface_velocity);
+        let mut rhs = surface_vel;
 
         let geom = helper::constraint_pair_geometry(
             body1,
@@ -76,8 +84,18 @@ impl<N: RealField> SignoriniModel<N> {
         );
 
         // Handle restitution.
-        if rhs <= -params.restitution_velocity_threshold {
-            rhs += props.restitution.0 * rhs;
+        // The pre-collision relative normal velocity of the contact points, including the angular velocities
+        // of the bodies but neither the external forces nor the surface velocity. Otherwise, e.g., the
+        // gravity would make a body bounce higher than it fell from.
+        let j1 = DVectorSlice::from_slice(&jacobians[geom.j_id1..], geom.ndofs1);
+        let j2 = DVectorSlice::from_slice(&jacobians[geom.j_id2..], geom.ndofs2);
+        let normal_vel = rhs - surface_vel - j1.dot(&ext_vels1) - j2.dot(&ext_vels2);
+
+        if normal_vel <= -params.restitution_velocity_threshold {
+            let restitution = props.restitution.0
+                .max(data1.min_restitution())
+                .max(data2.min_restitution());
+            rhs += restitution * normal_vel;
         }
 
         // Handle predictive contact if no penetration.
@@ -121,6 +139,67 @@ impl<N: RealField> SignoriniModel<N> {
         }
     }
 
+    /// Decreases the restitution coefficient of `props` depending on the number of times the colliders
+    /// of `manifold` bounced on each other.
+    pub fn apply_restitution_decay(
+        bounces: &mut BounceCache,
+        manifold: &ColliderContactManifold<N>,
+        props: &mut LocalMaterialProperties<N>,
+    ) {
+        if props.restitution_decay < N::one() {
+            let num_bounces = bounces.touch(manifold.collider1.handle(), manifold.collider2.handle());
+            props.restitution.0 *= props.restitution_decay.powi(num_bounces as i32 - 1);
+        }
+    }
+
+    /// Increments the number of timesteps the given contact has existed for, and returns its previous value.
+    pub fn age_contact(ages: &mut ImpulseCache<usize>, c: &TrackedContact<N>) -> usize {
+        let age = ages.get(c.id);
+        let id = ages.entry_id(c.id);
+        ages[id] = age + 1;
+        age
+    }
+
+    /// Checks if the given contact is ignored because it is on the wrong side of a one-way collider.
+    pub fn is_ignored_by_one_way_collider(
+        c: &TrackedContact<N>,
+        manifold: &ColliderContactManifold<N>,
+    ) -> bool {
+        let cos_max_angle = N::frac_pi_4().cos();
+
+        if let Some(dir) = manifold.collider1.one_way() {
+            let dir = manifold.collider1.position().rotation * dir.into_inner();
+            if c.contact.normal.dot(&dir) < cos_max_angle {
+                return true;
+            }
+        }
+
+        if let Some(dir) = manifold.collider2.one_way() {
+            let dir = manifold.collider2.position().rotation * dir.into_inner();
+            if -c.contact.normal.dot(&dir) < cos_max_angle {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The impulse cached for the given contact.
+    ///
+    /// This is zero if the cached impulses of one of the bodies have been cleared since the last timestep.
+    pub fn cached_impulse<I: Copy + Zero>(
+        cache: &ImpulseCache<I>,
+        c: &TrackedContact<N>,
+        body1: &Body<N>,
+        body2: &Body<N>,
+    ) -> I {
+        if body1.update_status().impulses_cleared() || body2.update_status().impulses_cleared() {
+            I::zero()
+        } else {
+            cache.get(c.id)
+        }
+    }
+
     /// Checks if the given constraint is active.
     pub fn is_constraint_active(
         c: &TrackedContact<N>,
@@ -135,10 +214,13 @@ impl<N: RealField> SignoriniModel<N> {
     }
 
     /// Builds non-linear position-based non-penetration constraints for the given contact manifold.
+    ///
+    /// The constraint only applies the fraction `stiffness` of its position correction.
     pub fn build_position_constraint(
         bodies: &BodySet<N>,
         manifold: &ColliderContactManifold<N>,
         c: &TrackedContact<N>,
+        stiffness: N,
         constraints: &mut ConstraintSet<N>,
     ) {
         let data1 = manifold.collider1;
@@ -164,26 +246,29 @@ impl<N: RealField> SignoriniModel<N> {
         kinematic.set_dilation1(total_margin1);
         kinematic.set_dilation2(total_margin2);
 
-        constraints
-            .position
-            .unilateral
-            .push(NonlinearUnilateralConstraint::new(
-                b1,
-                manifold.collider1.handle(),
-                body1.status_dependent_ndofs(),
-                b2,
-                manifold.collider2.handle(),
-                body2.status_dependent_ndofs(),
-                normal1,
-                normal2,
-                kinematic,
-            ));
+        let mut constraint = NonlinearUnilateralConstraint::new(
+            b1,
+            manifold.collider1.handle(),
+            body1.status_dependent_ndofs(),
+            b2,
+            manifold.collider2.handle(),
+            body2.status_dependent_ndofs(),
+            normal1,
+            normal2,
+            kinematic,
+        );
+        constraint.stiffness = stiffness;
+        constraints.position.unilateral.push(constraint);
     }
 }
 
 impl<N: RealField> ContactModel<N> for SignoriniModel<N> {
-    fn num_velocity_constraints(&self, c: &ColliderContactManifold<N>) -> usize {
-        c.manifold.len()
+    fn num_velocity_constraints(&self, _bodies: &BodySet<N>, c: &ColliderContactManifold<N>) -> usize {
+        c.len()
+    }
+
+    fn begin_step(&mut self) {
+        self.bounces.begin_step();
     }
 
     fn constraints(
@@ -206,18 +291,19 @@ impl<N: RealField> ContactModel<N> for SignoriniModel<N> {
             let body2 = try_ret!(bodies.body(manifold.body2()));
 
             for c in manifold.contacts() {
-                 if !Self::is_constraint_active(c, manifold) {
+                 if !Self::is_constraint_active(c, manifold) || Self::is_ignored_by_one_way_collider(c, manifold) {
                      continue;
                  }
 
                 let part1 = try_ret!(body1.part(manifold.body_part1(c.kinematic.feature1()).1));
                 let part2 = try_ret!(body2.part(manifold.body_part2(c.kinematic.feature2()).1));
 
-                let material1 = manifold.collider1.material();
-                let material2 = manifold.collider2.material();
+                let material1 = manifold.material1(c.kinematic.feature1());
+                let material2 = manifold.material2(c.kinematic.feature2());
                 let context1 = MaterialContext::new(body1, part1, manifold.collider1, c, true);
                 let context2 = MaterialContext::new(body2, part2, manifold.collider2, c, false);
-                let props = Material::combine(coefficients, material1, context1, material2, context2);
+                let mut props = Material::combine(coefficients, material1, context1, material2, context2);
+                Self::apply_restitution_decay(&mut self.bounces, manifold, &mut props);
 
                 let _ = Self::build_velocity_constraint(
                     params,
@@ -229,7 +315,7 @@ impl<N: RealField> ContactModel<N> for SignoriniModel<N> {
                     manifold,
                     ext_vels,
                     c,
-                    self.impulses.get(c.id),
+                    Self::cached_impulse(&self.impulses, c, body1, body2),
                     self.impulses.entry_id(c.id),
                     ground_j_id,
                     j_id,
@@ -237,7 +323,8 @@ impl<N: RealField> ContactModel<N> for SignoriniModel<N> {
                     constraints,
                 );
 
-                Self::build_position_constraint(bodies, manifold, c, constraints);
+                let age = Self::age_contact(&mut self.ages, c);
+                Self::build_position_constraint(bodies, manifold, c, params.contact_stiffness(age), constraints);
             }
         }
 
@@ -257,4 +344,26 @@ impl<N: RealField> ContactModel<N> for SignoriniModel<N> {
             self.impulses[c.impulse_id] = c.impulse;
         }
     }
+
+    fn clear_cached_impulses(&mut self) {
+        self.impulses.clear();
+    }
+
+    fn remove_collider(&mut self, collider: ColliderHandle) {
+        self.bounces.remove_collider(collider);
+    }
+
+    fn snapshot_cache(&self) -> Option<Box<Any + Send + Sync>> {
+        Some(Box::new((self.impulses.clone(), self.ages.clone(), self.bounces.clone())))
+    }
+
+    fn restore_cache(&mut self, cache: &Any) {
+        let cache = cache.downcast_ref::<(ImpulseCache<N>, ImpulseCache<usize>, BounceCache)>();
+
+        if let Some((impulses, ages, bounces)) = cache {
+            self.impulses = impulses.clone();
+            self.ages = ages.clone();
+            self.bounces = bounces.clone();
+        }
+    }
 }
@@ -3,6 +3,7 @@ use na::storage::StorageMut;
 
 // FIXME: could we just merge UnilateralConstraint and Bilateral constraint into a single structure
 // without performance impact due to clamping?
+use crate::counters;
 use crate::math::{SpatialDim, SPATIAL_DIM};
 use crate::object::{BodySet, BodyHandle};
 use crate::solver::{BilateralConstraint, BilateralGroundConstraint, ImpulseLimits, UnilateralConstraint,
@@ -11,8 +12,28 @@ use crate::solver::{BilateralConstraint, BilateralGroundConstraint, ImpulseLimit
 /// A SOR-Prox velocity-based constraints solver.
 pub(crate) struct SORProx;
 
+/// Statistics about one resolution performed by the SOR-Prox solver.
+pub(crate) struct SORProxStats<N: RealField> {
+    /// `false` if the resolution was interrupted because the deadline was reached.
+    pub completed: bool,
+    /// The number of iterations performed.
+    pub niterations: usize,
+    /// The largest change of impulse during the last iteration, without the constraints internal to bodies.
+    pub residual: N,
+}
+
 impl SORProx {
     /// Solve the given set of constraints.
+    ///
+    /// If `residuals` is given, the largest change of impulse of each iteration is recorded into it: its `i`-th element
+    /// is set to the max of its current value and of the residual of the `i`-th iteration. The constraints internal to
+    /// bodies are not taken into account.
+    ///
+    /// The resolution stops early once the largest change of impulse during an iteration is smaller than `tolerance`.
+    /// This is never the case if there are constraints internal to bodies, since their residual is not known.
+    ///
+    /// The returned statistics indicate whether the resolution was interrupted before `max_iter` iterations because the
+    /// `deadline` was reached.
     pub fn solve<N: RealField>(
         bodies: &mut BodySet<N>,
         unilateral_ground: &mut [UnilateralGroundConstraint<N>],
@@ -23,7 +44,16 @@ impl SORProx {
         mj_lambda: &mut DVector<N>,
         jacobians: &[N],
         max_iter: usize,
-    ) {
+        tolerance: N,
+        deadline: Option<f64>,
+        mut residuals: Option<&mut Vec<N>>,
+    ) -> SORProxStats<N> {
+        let mut stats = SORProxStats {
+            completed: true,
+            niterations: 0,
+            residual: N::zero(),
+        };
+
         /*
          * Setup constraints.
          */
@@ -58,8 +88,13 @@ impl SORProx {
         /*
          * Solve.
          */
-        for _ in 0..max_iter {
-            Self::step(
+        for i in 0..max_iter {
+            if i != 0 && deadline.map(|d| counters::now() >= d).unwrap_or(false) {
+                stats.completed = false;
+                return stats;
+            }
+
+            stats.residual = Self::step(
                 bodies,
                 unilateral_ground,
                 unilateral,
@@ -68,10 +103,27 @@ impl SORProx {
                 internal,
                 jacobians,
                 mj_lambda,
-            )
+            );
+
+            stats.niterations = i + 1;
+
+            if let Some(residuals) = &mut residuals {
+                if i < residuals.len() {
+                    residuals[i] = residuals[i].max(stats.residual);
+                } else {
+                    residuals.push(stats.residual);
+                }
+            }
+
+            if internal.is_empty() && stats.residual < tolerance {
+                break;
+            }
         }
+
+        stats
     }
 
+    // Performs one iteration, and returns the largest change of impulse, without the internal constraints.
     fn step<N: RealField>(
         bodies: &mut BodySet<N>,
         unilateral_ground: &mut [UnilateralGroundConstraint<N>],
@@ -81,20 +133,23 @@ impl SORProx {
         internal: &[BodyHandle],
         jacobians: &[N],
         mj_lambda: &mut DVector<N>,
-    ) {
+    ) -> N {
+        let mut residual = N::zero();
+
         for c in unilateral.iter_mut() {
-            if c.ndofs1 == SPATIAL_DIM && c.ndofs2 == SPATIAL_DIM {
+            let dlambda = if c.ndofs1 == SPATIAL_DIM && c.ndofs2 == SPATIAL_DIM {
                 // Most common case (between two free rigid bodies).
                 Self::solve_unilateral(c, jacobians, mj_lambda, SpatialDim {}, SpatialDim {})
             } else {
                 let dim1 = Dynamic::new(c.ndofs1);
                 let dim2 = Dynamic::new(c.ndofs2);
                 Self::solve_unilateral(c, jacobians, mj_lambda, dim1, dim2)
-            }
+            };
+            residual = residual.max(dlambda.abs());
         }
 
         for c in unilateral_ground.iter_mut() {
-            if c.ndofs == SPATIAL_DIM {
+            let dlambda = if c.ndofs == SPATIAL_DIM {
                 // Most common case (with one free rigid body).
                 // NOTE: it's weird that the compiler requires the { } even though SpatialDim is the
                 // alias of a marker type.
@@ -102,11 +157,12 @@ impl SORProx {
             } else {
                 let dim = Dynamic::new(c.ndofs);
                 Self::solve_unilateral_ground(c, jacobians, mj_lambda, dim)
-            }
+            };
+            residual = residual.max(dlambda.abs());
         }
 
         for c in bilateral.iter_mut() {
-            if c.ndofs1 == SPATIAL_DIM && c.ndofs2 == SPATIAL_DIM {
+            let dlambda = if c.ndofs1 == SPATIAL_DIM && c.ndofs2 == SPATIAL_DIM {
                 // Most common case (between two free rigid bodies).
                 Self::solve_bilateral(
                     c,
@@ -120,11 +176,12 @@ impl SORProx {
                 let dim1 = Dynamic::new(c.ndofs1);
                 let dim2 = Dynamic::new(c.ndofs2);
                 Self::solve_bilateral(c, unilateral, jacobians, mj_lambda, dim1, dim2)
-            }
+            };
+            residual = residual.max(dlambda.abs());
         }
 
         for c in bilateral_ground.iter_mut() {
-            if c.ndofs == SPATIAL_DIM {
+            let dlambda = if c.ndofs == SPATIAL_DIM {
                 // Most common case (with one free rigid body).
                 Self::solve_bilateral_ground(
                     c,
@@ -136,7 +193,8 @@ impl SORProx {
             } else {
                 let dim = Dynamic::new(c.ndofs);
                 Self::solve_bilateral_ground(c, unilateral_ground, jacobians, mj_lambda, dim)
-            }
+            };
+            residual = residual.max(dlambda.abs());
         }
 
         for handle in internal {
@@ -145,6 +203,8 @@ impl SORProx {
                 body.step_solve_internal_velocity_constraints(&mut dvels);
             }
         }
+
+        residual
     }
 
     fn solve_unilateral<N: RealField, D1: Dim, D2: Dim>(
@@ -153,7 +213,7 @@ impl SORProx {
         mj_lambda: &mut DVector<N>,
         dim1: D1,
         dim2: D2,
-    ) {
+    ) -> N {
         let id1 = c.assembly_id1;
         let id2 = c.assembly_id2;
 
@@ -175,6 +235,8 @@ impl SORProx {
         mj_lambda
             .rows_generic_mut(id2, dim2)
             .axpy(dlambda, &weighted_jacobian2, N::one());
+
+        dlambda
     }
 
     pub fn solve_unilateral_ground<N: RealField, D: Dim, DMJ: Dim, S: StorageMut<N, DMJ>>(
@@ -182,7 +244,7 @@ impl SORProx {
         jacobians: &[N],
         mj_lambda: &mut Vector<N, DMJ, S>,
         dim: D,
-    ) {
+    ) -> N {
         let jacobian = VectorSliceN::from_slice_generic(&jacobians[c.j_id..], dim, U1);
         let weighted_jacobian = VectorSliceN::from_slice_generic(&jacobians[c.wj_id..], dim, U1);
 
@@ -195,6 +257,8 @@ impl SORProx {
         mj_lambda
             .rows_generic_mut(c.assembly_id, dim)
             .axpy(dlambda, &weighted_jacobian, N::one());
+
+        dlambda
     }
 
     fn solve_bilateral<N: RealField, D1: Dim, D2: Dim>(
@@ -204,7 +268,7 @@ impl SORProx {
         mj_lambda: &mut DVector<N>,
         dim1: D1,
         dim2: D2,
-    ) {
+    ) -> N {
         let id1 = c.assembly_id1;
         let id2 = c.assembly_id2;
 
@@ -231,9 +295,11 @@ impl SORProx {
                         mj_lambda
                             .rows_generic_mut(id2, dim2)
                             .axpy(-c.impulse, &wj2, N::one());
+                        let dlambda = -c.impulse;
                         c.impulse = N::zero();
+                        return dlambda;
                     }
-                    return;
+                    return N::zero();
                 }
                 max_impulse = coeff * impulse;
                 min_impulse = -max_impulse;
@@ -246,7 +312,7 @@ impl SORProx {
         let weighted_jacobian2 = VectorSliceN::from_slice_generic(&jacobians[c.wj_id2..], dim2, U1);
 
         let dimpulse = jacobian1.dot(&mj_lambda.rows_generic(id1, dim1))
-            + jacobian2.dot(&mj_lambda.rows_generic(id2, dim2)) + c.rhs;
+            + jacobian2.dot(&mj_lambda.rows_generic(id2, dim2)) + c.rhs + c.cfm * c.impulse;
 
         let new_impulse = na::clamp(c.impulse - c.r * dimpulse, min_impulse, max_impulse);
         let dlambda = new_impulse - c.impulse;
@@ -258,6 +324,8 @@ impl SORProx {
         mj_lambda
             .rows_generic_mut(id2, dim2)
             .axpy(dlambda, &weighted_jacobian2, N::one());
+
+        dlambda
     }
 
     pub fn solve_bilateral_ground<N: RealField, D: Dim, DMJ: Dim, S: StorageMut<N, DMJ>>(
@@ -266,7 +334,7 @@ impl SORProx {
         jacobians: &[N],
         mj_lambda: &mut Vector<N, DMJ, S>,
         dim: D,
-    ) {
+    ) -> N {
         let min_impulse;
         let max_impulse;
 
@@ -286,9 +354,11 @@ impl SORProx {
                             &wj,
                             N::one(),
                         );
+                        let dlambda = -c.impulse;
                         c.impulse = N::zero();
+                        return dlambda;
                     }
-                    return;
+                    return N::zero();
                 }
                 max_impulse = coeff * impulse;
                 min_impulse = -max_impulse;
@@ -298,7 +368,7 @@ impl SORProx {
         let jacobian = VectorSliceN::from_slice_generic(&jacobians[c.j_id..], dim, U1);
         let weighted_jacobian = VectorSliceN::from_slice_generic(&jacobians[c.wj_id..], dim, U1);
 
-        let dimpulse = jacobian.dot(&mj_lambda.rows_generic(c.assembly_id, dim)) + c.rhs;
+        let dimpulse = jacobian.dot(&mj_lambda.rows_generic(c.assembly_id, dim)) + c.rhs + c.cfm * c.impulse;
 
         let new_impulse = na::clamp(c.impulse - c.r * dimpulse, min_impulse, max_impulse);
         let dlambda = new_impulse - c.impulse;
@@ -307,6 +377,8 @@ impl SORProx {
         mj_lambda
             .rows_generic_mut(c.assembly_id, dim)
             .axpy(dlambda, &weighted_jacobian, N::one());
+
+        dlambda
     }
 
     fn warmstart_unilateral<N: RealField, D1: Dim, D2: Dim>(
@@ -0,0 +1,91 @@
+use na::RealField;
+use ncollide::shape::{Cuboid, ShapeHandle};
+
+use crate::material::MaterialHandle;
+use crate::math::Vector;
+use crate::object::{BodyHandle, ColliderDesc, RigidBodyDesc};
+use crate::world::World;
+
+/// Builds a pyramid of boxes resting on the plane `y = 0`, e.g., for stability benchmarks.
+///
+/// The bottom row contains `base_count` boxes, and each row contains one box less than the row below it,
+/// with at least one box per row: a single column of boxes is built if `base_count` is 1. The rows are
+/// centered on the `y` axis and, in 3D, lie on the plane `z = 0`. Each box is a cube with half-extents
+/// `box_half_extent`, a unit density, and the given material. The boxes are separated by their margins so
+/// that the pyramid is at rest on a ground with the default margin.
+///
+/// Returns the handles of the rigid bodies created, row by row from the bottom.
+///
+/// This is a helper for the benchmarks and tests of nphysics and is not part of its public API.
+#[doc(hidden)]
+pub fn build_box_stack<N: RealField>(
+    world: &mut World<N>,
+    base_count: usize,
+    rows: usize,
+    box_half_extent: N,
+    material: MaterialHandle<N>,
+) -> Vec<BodyHandle> {
+    let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(box_half_extent)));
+    let collider_desc = ColliderDesc::new(cuboid).density(N::one()).material(material);
+    let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc);
+
+    let margin = collider_desc.get_margin();
+    let shift = (box_half_extent + margin) * na::convert(2.0);
+    let mut handles = Vec::new();
+
+    for i in 0..rows {
+        let count = base_count.saturating_sub(i).max(1);
+        let y = box_half_extent + margin * na::convert(2.0) + shift * na::convert(i as f64);
+        let x0 = -shift * na::convert((count - 1) as f64 / 2.0);
+
+        for j in 0..count {
+            let x = x0 + shift * na::convert(j as f64);
+            let body = rb_desc
+                .set_translation(Vector::x() * x + Vector::y() * y)
+                .build(world);
+            handles.push(body.handle());
+        }
+    }
+
+    handles
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Cuboid, ShapeHandle};
+    use crate::material::{BasicMaterial, MaterialHandle};
+    use crate::math::Vector;
+    use crate::object::ColliderDesc;
+    use crate::utils;
+    use crate::world::World;
+
+    #[test]
+    fn box_stack_is_stable() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world);
+
+        let material = MaterialHandle::new(BasicMaterial::default());
+        let handles = utils::build_box_stack(&mut world, 4, 6, 0.5, material);
+        // Rows of 4, 3, 2, 1, 1, and 1 boxes.
+        assert_eq!(handles.len(), 4 + 3 + 2 + 1 + 1 + 1);
+
+        let mut initial = Vec::new();
+        world.record_transforms(&mut initial);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let mut settled = Vec::new();
+        world.record_transforms(&mut settled);
+
+        let (distance, angle) = utils::transforms_deviation(&initial, &settled).unwrap();
+        assert!(distance < 0.02, "distance: {}", distance);
+        assert!(angle < 0.01, "angle: {}", angle);
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::BuildHasher;
 
 /// A hasher builder that creates `DefaultHasher` with default keys.
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub struct DeterministicState;
 
 impl DeterministicState {
@@ -1,13 +1,22 @@
 //! Miscellaneous utilities.
 
+#[doc(hidden)]
+pub use self::box_stack::build_box_stack;
 pub use self::deterministic_state::DeterministicState;
 pub use self::generalized_cross::GeneralizedCross;
 pub use self::index_mut2::IndexMut2;
+#[cfg(feature = "dim3")]
+pub use self::tetgen::{load_tetgen, parse_tetgen};
+pub use self::transforms_deviation::transforms_deviation;
 pub use self::user_data::UserData;
 pub(crate) use self::user_data::UserDataBox;
 
 pub mod union_find;
+mod box_stack;
 mod deterministic_state;
 mod generalized_cross;
 mod index_mut2;
+#[cfg(feature = "dim3")]
+mod tetgen;
+mod transforms_deviation;
 mod user_data;
@@ -0,0 +1,183 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use na::{self, Matrix3, Point3, Point4, RealField};
+
+/// Loads a tetrahedral mesh from a pair of TetGen `.node` and `.ele` files.
+///
+/// See `parse_tetgen` for details about the validation of the mesh.
+pub fn load_tetgen<N: RealField>(
+    node_path: impl AsRef<Path>,
+    ele_path: impl AsRef<Path>,
+) -> io::Result<(Vec<Point3<N>>, Vec<Point4<usize>>)> {
+    let node = fs::read_to_string(node_path)?;
+    let ele = fs::read_to_string(ele_path)?;
+    parse_tetgen(&node, &ele)
+}
+
+/// Parses a tetrahedral mesh from the content of a pair of TetGen `.node` and `.ele` files.
+///
+/// Returns the mesh vertices, and the indices of the vertices of each tetrahedron. The nodes may be numbered
+/// from 0 or 1, as long as they are numbered consecutively. Only the four corners of quadratic tetrahedrons
+/// are kept, and the attributes and boundary markers are ignored.
+///
+/// The tetrahedrons with a negative volume have two of their vertices swapped since the deformable
+/// volumes expect positive volumes. An error of kind `InvalidData` is returned if the files are malformed,
+/// if a tetrahedron refers to a vertex that does not exist, or if a tetrahedron has a zero volume.
+pub fn parse_tetgen<N: RealField>(node: &str, ele: &str) -> io::Result<(Vec<Point3<N>>, Vec<Point4<usize>>)> {
+    /*
+     * Parse the vertices.
+     */
+    let mut node_lines = data_lines(node);
+    let header = node_lines.next().ok_or_else(|| invalid_data("Missing .node header."))?;
+    let npoints: usize = parse_field(&header, 0)?;
+
+    if parse_field::<usize>(&header, 1)? != 3 {
+        return Err(invalid_data("Only three-dimensional .node files are supported."));
+    }
+
+    // The counts of the headers are checked at the end, so they are not used to preallocate the buffers.
+    let mut vertices = Vec::new();
+    let mut first_index = 0;
+
+    for (i, line) in node_lines.take(npoints).enumerate() {
+        let index: usize = parse_field(&line, 0)?;
+
+        if i == 0 {
+            first_index = index;
+        } else if index != first_index + i {
+            return Err(invalid_data("The nodes must be numbered consecutively."));
+        }
+
+        let x: f64 = parse_field(&line, 1)?;
+        let y: f64 = parse_field(&line, 2)?;
+        let z: f64 = parse_field(&line, 3)?;
+        vertices.push(Point3::new(na::convert(x), na::convert(y), na::convert(z)));
+    }
+
+    if vertices.len() != npoints {
+        return Err(invalid_data("Missing nodes in the .node file."));
+    }
+
+    /*
+     * Parse the tetrahedrons.
+     */
+    let mut ele_lines = data_lines(ele);
+    let header = ele_lines.next().ok_or_else(|| invalid_data("Missing .ele header."))?;
+    let ntetrahedrons: usize = parse_field(&header, 0)?;
+    let mut tetrahedrons = Vec::new();
+
+    for line in ele_lines.take(ntetrahedrons) {
+        let mut indices = Point4::origin();
+
+        for k in 0..4 {
+            let index: usize = parse_field(&line, k + 1)?;
+
+            if index < first_index || index - first_index >= npoints {
+                return Err(invalid_data("A tetrahedron refers to a node that does not exist."));
+            }
+
+            indices[k] = index - first_index;
+        }
+
+        let a = vertices[indices.x];
+        let edges = Matrix3::from_rows(&[
+            (vertices[indices.y] - a).transpose(),
+            (vertices[indices.z] - a).transpose(),
+            (vertices[indices.w] - a).transpose(),
+        ]);
+        let det: N = edges.determinant();
+
+        if det.is_zero() {
+            return Err(invalid_data("A tetrahedron has a zero volume."));
+        } else if det < N::zero() {
+            indices.coords.swap_rows(1, 2);
+        }
+
+        tetrahedrons.push(indices);
+    }
+
+    if tetrahedrons.len() != ntetrahedrons {
+        return Err(invalid_data("Missing tetrahedrons in the .ele file."));
+    }
+
+    Ok((vertices, tetrahedrons))
+}
+
+// The fields of each non-empty line, without comments.
+fn data_lines<'a>(content: &'a str) -> impl Iterator<Item = Vec<&'a str>> + 'a {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").split_whitespace().collect::<Vec<_>>())
+        .filter(|fields| !fields.is_empty())
+}
+
+fn parse_field<T: FromStr>(fields: &[&str], i: usize) -> io::Result<T> {
+    fields
+        .get(i)
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(|| invalid_data("Missing or invalid field."))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+    use na::Point4;
+    use super::parse_tetgen;
+
+    // A unit cube split into five tetrahedrons, numbered from 1, one of them with a negative orientation.
+    const NODE: &str = "# A unit cube.
+8 3 0 0
+1 0 0 0
+2 1 0 0
+3 0 1 0
+4 1 1 0
+5 0 0 1
+6 1 0 1
+7 0 1 1
+8 1 1 1
+";
+
+    const ELE: &str = "5 4 0
+1 1 2 3 5 # Positive.
+2 2 3 4 8 # Negative.
+3 2 5 6 8
+4 3 7 5 8
+5 2 3 5 8
+";
+
+    #[test]
+    fn parse_tetgen_cube() {
+        let (vertices, tetrahedrons) = parse_tetgen::<f64>(NODE, ELE).unwrap();
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(tetrahedrons.len(), 5);
+        assert_eq!(tetrahedrons[0], Point4::new(0, 1, 2, 4));
+        // The negative tetrahedron has been flipped.
+        assert_eq!(tetrahedrons[1], Point4::new(1, 3, 2, 7));
+
+        let volume: f64 = tetrahedrons.iter().map(|t| {
+            let a = vertices[t.x];
+            (vertices[t.y] - a).cross(&(vertices[t.z] - a)).dot(&(vertices[t.w] - a)) / 6.0
+        }).sum();
+        assert_relative_eq!(volume, 1.0, epsilon = 1.0e-10);
+
+        let out_of_range = ELE.replace("5 2 3 5 8", "5 2 3 5 9");
+        let err = parse_tetgen::<f64>(NODE, &out_of_range).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // A header count larger than the actual number of elements is an error, not a huge allocation.
+        let huge_count = format!("{} 3 0 0\n1 0 0 0\n", usize::max_value());
+        let err = parse_tetgen::<f64>(&huge_count, ELE).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let huge_count = ELE.replacen("5 4 0", &format!("{} 4 0", usize::max_value()), 1);
+        let err = parse_tetgen::<f64>(NODE, &huge_count).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,32 @@
+use na::RealField;
+
+use crate::math::Isometry;
+use crate::object::BodyHandle;
+
+/// Computes the maximum deviation between two sequences of body transforms, e.g., recorded by `World::record_transforms`.
+///
+/// Returns the maximum distance between the translations and the maximum angle between the rotations
+/// of the transforms at the same index. Returns `None` if the sequences do not have the same length or
+/// if the body handles at the same index differ.
+pub fn transforms_deviation<N: RealField>(
+    transforms1: &[(BodyHandle, Isometry<N>)],
+    transforms2: &[(BodyHandle, Isometry<N>)],
+) -> Option<(N, N)> {
+    if transforms1.len() != transforms2.len() {
+        return None;
+    }
+
+    let mut max_distance = N::zero();
+    let mut max_angle = N::zero();
+
+    for ((handle1, pos1), (handle2, pos2)) in transforms1.iter().zip(transforms2.iter()) {
+        if handle1 != handle2 {
+            return None;
+        }
+
+        max_distance = max_distance.max((pos1.translation.vector - pos2.translation.vector).norm());
+        max_angle = max_angle.max((pos1.rotation.inverse() * pos2.rotation).angle());
+    }
+
+    Some((max_distance, max_angle))
+}
@@ -1,5 +1,7 @@
 use std::collections::{hash_map, HashMap};
+use std::sync::Arc;
 
+use either::Either;
 use na::RealField;
 use ncollide::world::{CollisionWorld, GeometricQueryType, CollisionGroups, CollisionObject};
 use ncollide::broad_phase::BroadPhasePairFilter;
@@ -21,7 +23,11 @@ pub struct ColliderWorld<N: RealField> {
     cworld: CollisionWorld<N, ColliderData<N>>,
     collider_lists: HashMap<BodyHandle, (ColliderHandle, ColliderHandle)>, // (head, tail)
     colliders_w_parent: Vec<ColliderHandle>,
-    default_material: MaterialHandle<N>
+    default_material: MaterialHandle<N>,
+    // The predictions given to the colliders without user-defined predictions, once set by the world.
+    default_prediction: Option<(N, N)>,
+    // Buffer for the deformations of the colliders tracking a subset of their body DOFs.
+    deformations: Vec<N>,
 }
 
 impl<N: RealField> ColliderWorld<N> {
@@ -38,13 +44,25 @@ impl<N: RealField> ColliderWorld<N> {
             cworld,
             collider_lists: HashMap::new(),
             colliders_w_parent: Vec::new(),
-            default_material: MaterialHandle::new(BasicMaterial::default())
+            default_material: MaterialHandle::new(BasicMaterial::default()),
+            default_prediction: None,
+            deformations: Vec::new(),
         }
     }
 
     /// Synchronize all colliders with their body parent and the underlying collision world.
     pub fn sync_colliders(&mut self, bodies: &BodySet<N>) {
+        self.do_sync_colliders(bodies, false)
+    }
+
+    /// Synchronize all colliders with their body parent, even if the latter is not active or did not move.
+    pub(crate) fn force_sync_colliders(&mut self, bodies: &BodySet<N>) {
+        self.do_sync_colliders(bodies, true)
+    }
+
+    fn do_sync_colliders(&mut self, bodies: &BodySet<N>, force: bool) {
         let cworld = &mut self.cworld;
+        let deformations = &mut self.deformations;
         self.colliders_w_parent.retain(|collider_id| {
             // FIXME: update only if the position changed (especially for static bodies).
             let collider = try_ret!(cworld.collision_object_mut(*collider_id), false);
@@ -54,7 +72,7 @@ impl<N: RealField> ColliderWorld<N> {
                 .data_mut()
                 .set_body_status_dependent_ndofs(body.status_dependent_ndofs());
 
-            if !body.is_active() || !body.update_status().colliders_need_update() {
+            if !force && (!body.is_active() || !body.update_status().colliders_need_update()) {
                 return true;
             }
 
@@ -62,16 +80,17 @@ impl<N: RealField> ColliderWorld<N> {
                 ColliderAnchor::OnBodyPart { body_part, position_wrt_body_part } => {
                     let part = try_ret!(body.part(body_part.1), false);
                     let part_pos = part.position();
-                    Some(part_pos * position_wrt_body_part)
+                    Either::Left(part_pos * position_wrt_body_part)
                 }
                 ColliderAnchor::OnDeformableBody { .. } => {
-                    None
+                    let coords = body.deformed_positions().unwrap().1;
+                    Either::Right(collider.data().deformations(coords, deformations))
                 }
             };
 
             match new_pos {
-                Some(pos) => cworld.set_position(*collider_id, pos),
-                None => cworld.set_deformations(*collider_id, body.deformed_positions().unwrap().1)
+                Either::Left(pos) => cworld.set_position(*collider_id, pos),
+                Either::Right(coords) => cworld.set_deformations(*collider_id, coords)
             }
 
             true
@@ -83,6 +102,28 @@ impl<N: RealField> ColliderWorld<N> {
         self.default_material.clone()
     }
 
+    /// The linear and angular prediction distances given to the colliders without user-defined predictions.
+    ///
+    /// This is `None` until the predictions are set with `World::set_prediction` or `World::set_angular_prediction`,
+    /// in which case those colliders use the predictions of their descriptor.
+    pub fn default_prediction(&self) -> Option<(N, N)> {
+        self.default_prediction
+    }
+
+    // Sets the default predictions and applies them to the existing colliders without user-defined predictions.
+    pub(crate) fn set_default_prediction(&mut self, linear: N, angular: N) {
+        self.default_prediction = Some((linear, angular));
+        let handles: Vec<_> = self
+            .colliders()
+            .filter(|c| !c.has_explicit_prediction())
+            .map(|c| c.handle())
+            .collect();
+
+        for handle in handles {
+            self.update_prediction(handle, linear, angular);
+        }
+    }
+
     /// The underlying collision world from the ncollide crate.
     #[deprecated(since = "0.11.1", note = "renamed to as_collision_world")]
     pub fn as_collider_world(&self) -> &CollisionWorld<N, ColliderData<N>> {
@@ -244,6 +285,80 @@ impl<N: RealField> ColliderWorld<N> {
         self.cworld.set_position(handle, pos)
     }
 
+    /// Sets the collision margin surrounding the specified collider.
+    ///
+    /// The contact prediction distance of the collider is adjusted accordingly. This has no effect on sensors,
+    /// which have no margin.
+    pub fn set_margin(&mut self, handle: ColliderHandle, margin: N) {
+        let (query_type, pos) = {
+            let collider = try_ret!(self.cworld.collision_object_mut(handle));
+            let query_type = match collider.query_type() {
+                GeometricQueryType::Contacts(linear, angular) => {
+                    GeometricQueryType::Contacts(linear - collider.data().margin() + margin, angular)
+                }
+                GeometricQueryType::Proximity(_) => return,
+            };
+            collider.data_mut().set_margin(margin);
+            (query_type, *collider.position())
+        };
+
+        self.cworld.set_query_type(handle, query_type);
+        // Update the bounding volume of the collider, loosened by its new query limit.
+        self.cworld.set_position(handle, pos)
+    }
+
+    /// Sets the contact prediction distances of the specified collider.
+    ///
+    /// The `linear` prediction is added to the margin of the collider to obtain the distance under which contacts
+    /// are generated. Sensors only use the `linear` prediction, as the distance under which proximities are detected.
+    /// Those predictions are no longer affected by `World::set_prediction` and `World::set_angular_prediction`.
+    pub fn set_prediction(&mut self, handle: ColliderHandle, linear: N, angular: N) {
+        let collider = try_ret!(self.cworld.collision_object_mut(handle));
+        collider.data_mut().set_explicit_prediction(true);
+        self.update_prediction(handle, linear, angular)
+    }
+
+    fn update_prediction(&mut self, handle: ColliderHandle, linear: N, angular: N) {
+        let (query_type, pos) = {
+            let collider = try_ret!(self.cworld.collision_object_mut(handle));
+            let query_type = match collider.query_type() {
+                GeometricQueryType::Contacts(..) => {
+                    GeometricQueryType::Contacts(collider.data().margin() + linear, angular)
+                }
+                GeometricQueryType::Proximity(_) => GeometricQueryType::Proximity(linear),
+            };
+            (query_type, *collider.position())
+        };
+
+        self.cworld.set_query_type(handle, query_type);
+        // Update the bounding volume of the collider, loosened by its new query limit.
+        self.cworld.set_position(handle, pos)
+    }
+
+    /// Replaces the shape of the specified collider.
+    pub(crate) fn set_shape(&mut self, handle: ColliderHandle, shape: ShapeHandle<N>) {
+        self.cworld.set_shape(handle, shape)
+    }
+
+    /// Replaces the shape of the specified deformable collider as well as the mapping between its parts and the body parts.
+    ///
+    /// The per-subshape materials of this collider, if any, are removed since they no longer match the new shape.
+    pub(crate) fn set_deformable_shape(
+        &mut self,
+        handle: ColliderHandle,
+        shape: ShapeHandle<N>,
+        body_parts: Option<Arc<Vec<usize>>>,
+    ) {
+        {
+            let collider = try_ret!(self.cworld.collision_object_mut(handle));
+            let data = collider.data_mut();
+            data.set_body_parts_mapping(body_parts);
+            data.remove_subshape_materials();
+        }
+
+        self.cworld.set_shape(handle, shape)
+    }
+
 //    /// Apply the given deformations to the specified object.
 //    pub(crate) fn set_deformations(
 //        &mut self,
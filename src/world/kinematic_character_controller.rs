@@ -0,0 +1,328 @@
+use na::{self, RealField, Unit};
+use ncollide::shape::Shape;
+use ncollide::world::CollisionGroups;
+
+use crate::math::{Isometry, Translation, Vector};
+use crate::object::BodyHandle;
+use crate::world::World;
+
+/// A controller moving a kinematic rigid body through the world, e.g., the character of a game.
+///
+/// Instead of simply teleporting the body, the controller casts its collider shape along the desired
+/// translation so that it slides along the obstacles it hits, climbs small steps, and snaps to the ground.
+/// Only the first non-sensor collider attached to the body is taken into account.
+pub struct KinematicCharacterController<N: RealField> {
+    up: Unit<Vector<N>>,
+    offset: N,
+    max_slope: N,
+    max_step_height: N,
+    snap_distance: N,
+    max_iterations: usize,
+    grounded: bool,
+}
+
+impl<N: RealField> KinematicCharacterController<N> {
+    /// Creates a new character controller with default parameters.
+    ///
+    /// By default, the up direction is the `y` axis, the character can walk on slopes up to 45 degrees,
+    /// climb steps up to 0.25 units high, and snaps to the ground located up to 0.2 units below it.
+    pub fn new() -> Self {
+        KinematicCharacterController {
+            up: Vector::y_axis(),
+            offset: na::convert(0.01),
+            max_slope: N::frac_pi_4(),
+            max_step_height: na::convert(0.25),
+            snap_distance: na::convert(0.2),
+            max_iterations: 4,
+            grounded: false,
+        }
+    }
+
+    /// The up direction of the character.
+    pub fn up(&self) -> &Unit<Vector<N>> {
+        &self.up
+    }
+
+    /// Sets the up direction of the character.
+    pub fn set_up(&mut self, up: Unit<Vector<N>>) {
+        self.up = up
+    }
+
+    /// The small gap kept between the character and the obstacles it hits.
+    pub fn offset(&self) -> N {
+        self.offset
+    }
+
+    /// Sets the small gap kept between the character and the obstacles it hits.
+    pub fn set_offset(&mut self, offset: N) {
+        self.offset = offset
+    }
+
+    /// The maximum angle, in radians, between the up direction and the normal of a surface the character can walk on.
+    pub fn max_slope(&self) -> N {
+        self.max_slope
+    }
+
+    /// Sets the maximum angle, in radians, between the up direction and the normal of a surface the character can walk on.
+    pub fn set_max_slope(&mut self, max_slope: N) {
+        self.max_slope = max_slope
+    }
+
+    /// The maximum height of the steps the character automatically climbs.
+    pub fn max_step_height(&self) -> N {
+        self.max_step_height
+    }
+
+    /// Sets the maximum height of the steps the character automatically climbs.
+    ///
+    /// Set this to zero to disable the automatic climbing of steps.
+    pub fn set_max_step_height(&mut self, height: N) {
+        self.max_step_height = height
+    }
+
+    /// The maximum distance the character is moved down to stay on the ground.
+    pub fn snap_distance(&self) -> N {
+        self.snap_distance
+    }
+
+    /// Sets the maximum distance the character is moved down to stay on the ground.
+    ///
+    /// Set this to zero to disable the ground snapping.
+    pub fn set_snap_distance(&mut self, distance: N) {
+        self.snap_distance = distance
+    }
+
+    /// The maximum number of obstacles the character can slide along during a single movement.
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Sets the maximum number of obstacles the character can slide along during a single movement.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations
+    }
+
+    /// Returns `true` if the character was standing on a walkable surface at the end of its last movement.
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Moves the kinematic rigid body identified by `handle` by `desired_translation`, sliding along the obstacles on its path.
+    ///
+    /// Returns the translation actually applied to the body. Nothing happens if `handle` does not
+    /// identify a rigid body with at least one non-sensor collider.
+    pub fn move_and_slide(&mut self, world: &mut World<N>, handle: BodyHandle, desired_translation: &Vector<N>) -> Vector<N> {
+        self.grounded = false;
+
+        let (shape, pos_wrt_body, groups) = {
+            let collider = world.collider_world().body_colliders(handle).find(|c| !c.is_sensor());
+            let collider = try_ret!(collider, Vector::zeros());
+            (collider.shape().clone(), collider.position_wrt_body(), *collider.collision_groups())
+        };
+        let body_pos = *try_ret!(world.rigid_body(handle), Vector::zeros()).position();
+        let shape = shape.as_ref();
+        let mut pos = body_pos * pos_wrt_body;
+        let mut remaining = *desired_translation;
+
+        for _ in 0..self.max_iterations {
+            if remaining.norm() <= N::default_epsilon() {
+                break;
+            }
+
+            match self.cast(world, handle, shape, &groups, &pos, &remaining) {
+                None => {
+                    Self::translate(&mut pos, &remaining);
+                    break;
+                }
+                Some((toi, normal)) => {
+                    let moved = self.move_until_hit(&mut pos, &remaining, toi, &normal);
+                    remaining -= moved;
+
+                    if self.is_walkable(&normal) {
+                        self.grounded = true;
+                    } else if let Some(step) = self.step_up(world, handle, shape, &groups, &pos, &remaining) {
+                        Self::translate(&mut pos, &step);
+                        self.grounded = true;
+                        break;
+                    }
+
+                    // Slide along the obstacle.
+                    remaining -= *normal * remaining.dot(&normal);
+                }
+            }
+        }
+
+        // Stick to the ground unless the character is moving up.
+        if self.snap_distance > N::zero() && desired_translation.dot(&self.up) <= N::zero() {
+            let down = -*self.up * self.snap_distance;
+
+            if let Some((toi, normal)) = self.cast(world, handle, shape, &groups, &pos, &down) {
+                if self.is_walkable(&normal) {
+                    let _ = self.move_until_hit(&mut pos, &down, toi, &normal);
+                    self.grounded = true;
+                }
+            }
+        }
+
+        let new_body_pos = pos * pos_wrt_body.inverse();
+        let translation = new_body_pos.translation.vector - body_pos.translation.vector;
+
+        if let Some(rb) = world.rigid_body_mut(handle) {
+            rb.set_position(new_body_pos);
+        }
+
+        translation
+    }
+
+    fn is_walkable(&self, normal: &Unit<Vector<N>>) -> bool {
+        normal.dot(&self.up) >= self.max_slope.cos()
+    }
+
+    fn translate(pos: &mut Isometry<N>, translation: &Vector<N>) {
+        pos.append_translation_mut(&Translation::from(*translation))
+    }
+
+    // Moves `pos` along `translation` until the time of impact `toi`, and then away from the obstacle
+    // along its `normal` so that they are separated by `self.offset`.
+    fn move_until_hit(&self, pos: &mut Isometry<N>, translation: &Vector<N>, toi: N, normal: &Unit<Vector<N>>) -> Vector<N> {
+        let moved = translation * toi + **normal * self.offset;
+        Self::translate(pos, &moved);
+        moved
+    }
+
+    // Attempts to climb the obstacle blocking the horizontal part of `remaining`. Returns the resulting translation.
+    fn step_up(
+        &self,
+        world: &World<N>,
+        handle: BodyHandle,
+        shape: &Shape<N>,
+        groups: &CollisionGroups,
+        pos: &Isometry<N>,
+        remaining: &Vector<N>,
+    ) -> Option<Vector<N>> {
+        let horizontal = remaining - *self.up * remaining.dot(&self.up);
+
+        if self.max_step_height <= N::zero() || horizontal.norm() <= self.offset {
+            return None;
+        }
+
+        let mut stepped = *pos;
+        let up = *self.up * self.max_step_height;
+        let raised = match self.cast(world, handle, shape, groups, &stepped, &up) {
+            Some((toi, normal)) => self.move_until_hit(&mut stepped, &up, toi, &normal),
+            None => {
+                Self::translate(&mut stepped, &up);
+                up
+            }
+        };
+
+        let forward = match self.cast(world, handle, shape, groups, &stepped, &horizontal) {
+            Some((toi, normal)) => self.move_until_hit(&mut stepped, &horizontal, toi, &normal),
+            None => {
+                Self::translate(&mut stepped, &horizontal);
+                horizontal
+            }
+        };
+
+        if forward.norm() <= self.offset {
+            return None;
+        }
+
+        // The character must land on a walkable surface.
+        let down = -raised;
+        let (toi, normal) = self.cast(world, handle, shape, groups, &stepped, &down)?;
+
+        if !self.is_walkable(&normal) {
+            return None;
+        }
+
+        let lowered = self.move_until_hit(&mut stepped, &down, toi, &normal);
+        Some(raised + forward + lowered)
+    }
+
+    // Computes the first obstacle hit by the shape at `pos` moving along `translation`.
+    //
+    // Returns the time of impact (between 0 and 1) and the contact normal pointing toward the character.
+    fn cast(
+        &self,
+        world: &World<N>,
+        handle: BodyHandle,
+        shape: &Shape<N>,
+        groups: &CollisionGroups,
+        pos: &Isometry<N>,
+        translation: &Vector<N>,
+    ) -> Option<(N, Unit<Vector<N>>)> {
+        let (_, hit) = world.cast_shape_filtered(shape, pos, translation, N::one(), groups, |c| c.body() != handle)?;
+        Some((hit.toi, -hit.normal))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Cuboid, ShapeHandle};
+    use crate::math::Vector;
+    use crate::object::{BodyHandle, BodyStatus, ColliderDesc, RigidBodyDesc};
+    use crate::world::{KinematicCharacterController, World};
+
+    // Creates a world with a flat ground at `y = 0`, a box of height `height` spanning `x` from 1 to 3,
+    // and a kinematic character standing at the origin.
+    fn setup(height: f64) -> (World<f64>, BodyHandle) {
+        let mut world = World::new();
+        let block = ShapeHandle::new(Cuboid::new(Vector::repeat(1.0)));
+        let _ = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(10.0))))
+            .translation(Vector::y() * -10.0)
+            .build(&mut world);
+        let _ = ColliderDesc::new(block)
+            .translation(Vector::x() * 2.0 + Vector::y() * (height - 1.0))
+            .build(&mut world);
+
+        let character = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(0.5))));
+        let handle = RigidBodyDesc::new()
+            .status(BodyStatus::Kinematic)
+            .translation(Vector::y() * 0.51)
+            .collider(&character)
+            .build(&mut world)
+            .handle();
+
+        // Ensure the broad phase is up-to-date.
+        world.step();
+        (world, handle)
+    }
+
+    fn position(world: &World<f64>, handle: BodyHandle) -> Vector<f64> {
+        world.rigid_body(handle).unwrap().position().translation.vector
+    }
+
+    #[test]
+    fn character_blocked_by_wall() {
+        let (mut world, handle) = setup(1.0);
+        let mut controller = KinematicCharacterController::new();
+        let _ = controller.move_and_slide(&mut world, handle, &(Vector::x() * 3.0 - Vector::y() * 0.1));
+
+        let pos = position(&world, handle);
+        assert!(controller.is_grounded());
+        // The character must stop before touching the wall.
+        assert!(pos.x < 0.5 && pos.x > 0.5 - controller.offset() * 2.0);
+        assert_relative_eq!(pos.y, 0.5 + controller.offset(), epsilon = 1.0e-3);
+    }
+
+    #[test]
+    fn character_climbs_step_and_snaps_down() {
+        let (mut world, handle) = setup(0.1);
+        let mut controller = KinematicCharacterController::new();
+
+        // Climb onto the step.
+        let _ = controller.move_and_slide(&mut world, handle, &(Vector::x() * 2.0));
+        let pos = position(&world, handle);
+        assert!(controller.is_grounded());
+        assert_relative_eq!(pos.x, 2.0, epsilon = 1.0e-3);
+        assert_relative_eq!(pos.y, 0.6 + controller.offset(), epsilon = 1.0e-3);
+
+        // Walk off the step.
+        let _ = controller.move_and_slide(&mut world, handle, &(Vector::x() * 2.0));
+        let pos = position(&world, handle);
+        assert!(controller.is_grounded());
+        assert_relative_eq!(pos.x, 4.0, epsilon = 1.0e-3);
+        assert_relative_eq!(pos.y, 0.5 + controller.offset(), epsilon = 1.0e-3);
+    }
+}
@@ -1,7 +1,15 @@
 //! The physics world.
 
-pub use self::world::World;
+pub use self::world::{ContactPoint, World, TOI};
 pub use self::collider_world::ColliderWorld;
+pub use self::kinematic_character_controller::KinematicCharacterController;
+pub use self::scene_description::{
+    BodyDescription, ColliderDescription, ConstraintDescription, PoseDescription, SceneDescription, ShapeDescription,
+};
+pub use self::world_snapshot::WorldSnapshot;
 
 mod world;
 mod collider_world;
+mod kinematic_character_controller;
+mod scene_description;
+mod world_snapshot;
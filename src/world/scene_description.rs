@@ -0,0 +1,319 @@
+use na::RealField;
+use ncollide::shape::{Ball, Capsule, Compound, Cuboid, Plane, Shape};
+
+use crate::joint::{CartesianConstraint, ConstraintHandle, DistanceConstraint, FixedConstraint, JointConstraint,
+                   MouseConstraint, PrismaticConstraint, RevoluteConstraint, SpringConstraint};
+#[cfg(feature = "dim3")]
+use crate::joint::{BallConstraint, CylindricalConstraint, PinSlotConstraint, PlanarConstraint, RectangularConstraint,
+                   UniversalConstraint};
+use crate::material::BasicMaterial;
+use crate::math::{Isometry, Velocity};
+use crate::object::{Body, BodyHandle, Collider, ColliderAnchor, Ground, MassConstraintSystem, MassSpringSystem,
+                    Multibody, RigidBody};
+#[cfg(feature = "dim2")]
+use crate::object::FEMSurface;
+#[cfg(feature = "dim3")]
+use crate::object::FEMVolume;
+
+/// A human-readable description of the content of a world.
+///
+/// This is a read-only snapshot meant for inspection and tooling. It can be serialized, e.g., to JSON,
+/// if the `serde-serialize` feature is enabled, but it does not contain enough information to rebuild
+/// the world it has been exported from.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize))]
+pub struct SceneDescription<N: RealField> {
+    /// The bodies of the world.
+    pub bodies: Vec<BodyDescription<N>>,
+    /// The colliders of the world.
+    pub colliders: Vec<ColliderDescription<N>>,
+    /// The joint constraints of the world.
+    pub constraints: Vec<ConstraintDescription>,
+}
+
+/// The description of a body.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize))]
+pub struct BodyDescription<N: RealField> {
+    /// The unique identifier of the body handle.
+    pub handle: usize,
+    /// The name of the body.
+    pub name: String,
+    /// The type of the body, e.g., `"RigidBody"`, or `"Unknown"` for user-defined bodies.
+    pub kind: String,
+    /// The status of the body, e.g., `"Dynamic"`.
+    pub status: String,
+    /// The number of parts of the body.
+    pub num_parts: usize,
+    /// The total mass of the body parts.
+    pub mass: N,
+    /// The position of the first part of the body.
+    pub position: PoseDescription<N>,
+    /// The linear velocity of the first part of the body.
+    pub linear_velocity: Vec<N>,
+    /// The angular velocity of the first part of the body.
+    pub angular_velocity: Vec<N>,
+}
+
+/// The description of a collider.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize))]
+pub struct ColliderDescription<N: RealField> {
+    /// The unique identifier of the collider handle.
+    pub handle: usize,
+    /// The name of the collider.
+    pub name: String,
+    /// The unique identifier of the body this collider is attached to, or `None` for the ground.
+    pub body: Option<usize>,
+    /// The index of the body part this collider is attached to, or `None` for deformable bodies.
+    pub body_part: Option<usize>,
+    /// The shape of the collider.
+    pub shape: ShapeDescription<N>,
+    /// The position of the collider.
+    pub position: PoseDescription<N>,
+    /// The margin of the collider.
+    pub margin: N,
+    /// Whether the collider is a sensor.
+    pub is_sensor: bool,
+    /// The friction coefficient of the collider, if it has a `BasicMaterial`.
+    pub friction: Option<N>,
+    /// The restitution coefficient of the collider, if it has a `BasicMaterial`.
+    pub restitution: Option<N>,
+}
+
+/// The description of a joint constraint.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize))]
+pub struct ConstraintDescription {
+    /// The constraint handle.
+    pub handle: ConstraintHandle,
+    /// The type of the constraint, e.g., `"RevoluteConstraint"`, or `"Unknown"` for user-defined constraints.
+    pub kind: String,
+    /// The unique identifier of the first body attached to the constraint, or `None` for the ground.
+    pub body1: Option<usize>,
+    /// The index of the part of the first body attached to the constraint.
+    pub body_part1: usize,
+    /// The unique identifier of the second body attached to the constraint, or `None` for the ground.
+    pub body2: Option<usize>,
+    /// The index of the part of the second body attached to the constraint.
+    pub body_part2: usize,
+}
+
+/// The description of a position.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize))]
+pub struct PoseDescription<N: RealField> {
+    /// The translational part of the position.
+    pub translation: Vec<N>,
+    /// The rotational part of the position, as an angle in 2D, or a scaled rotation axis in 3D.
+    pub rotation: Vec<N>,
+}
+
+/// The description of a shape.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize))]
+pub enum ShapeDescription<N: RealField> {
+    /// A ball.
+    Ball {
+        /// The radius of the ball.
+        radius: N,
+    },
+    /// A cuboid.
+    Cuboid {
+        /// The half-extents of the cuboid.
+        half_extents: Vec<N>,
+    },
+    /// A capsule aligned with the `y` axis.
+    Capsule {
+        /// The half-height of the capsule.
+        half_height: N,
+        /// The radius of the capsule.
+        radius: N,
+    },
+    /// A plane.
+    Plane {
+        /// The normal of the plane.
+        normal: Vec<N>,
+    },
+    /// A compound shape.
+    Compound {
+        /// The number of sub-shapes of the compound shape.
+        num_shapes: usize,
+    },
+    /// A shape that cannot be described.
+    Unsupported,
+}
+
+impl<N: RealField> PoseDescription<N> {
+    fn new(position: &Isometry<N>) -> Self {
+        PoseDescription {
+            translation: position.translation.vector.as_slice().to_vec(),
+            rotation: position.rotation.scaled_axis().as_slice().to_vec(),
+        }
+    }
+}
+
+impl<N: RealField> BodyDescription<N> {
+    pub(crate) fn new(body: &Body<N>) -> Self {
+        let (position, velocity) = match body.part(0) {
+            Some(part) => (part.position(), part.velocity()),
+            None => (Isometry::identity(), Velocity::zero()),
+        };
+        let mass = body.parts().fold(N::zero(), |mass, part| mass + part.inertia().linear);
+
+        BodyDescription {
+            handle: body.handle().uid(),
+            name: body.name().to_string(),
+            kind: body_kind(body).to_string(),
+            status: format!("{:?}", body.status()),
+            num_parts: body.num_parts(),
+            mass,
+            position: PoseDescription::new(&position),
+            linear_velocity: velocity.linear.as_slice().to_vec(),
+            angular_velocity: velocity.angular_vector().as_slice().to_vec(),
+        }
+    }
+}
+
+impl<N: RealField> ColliderDescription<N> {
+    pub(crate) fn new(collider: &Collider<N>) -> Self {
+        let body_part = match collider.anchor() {
+            ColliderAnchor::OnBodyPart { body_part, .. } => Some(body_part.1),
+            ColliderAnchor::OnDeformableBody { .. } => None,
+        };
+        let material = collider.material().downcast_ref::<BasicMaterial<N>>();
+
+        ColliderDescription {
+            handle: collider.handle().uid(),
+            name: collider.name().to_string(),
+            body: body_uid(collider.body()),
+            body_part,
+            shape: ShapeDescription::new(&**collider.shape()),
+            position: PoseDescription::new(collider.position()),
+            margin: collider.margin(),
+            is_sensor: collider.is_sensor(),
+            friction: material.map(|m| m.friction),
+            restitution: material.map(|m| m.restitution),
+        }
+    }
+}
+
+impl ConstraintDescription {
+    pub(crate) fn new<N: RealField>(handle: ConstraintHandle, constraint: &JointConstraint<N>) -> Self {
+        let (anchor1, anchor2) = constraint.anchors();
+
+        ConstraintDescription {
+            handle,
+            kind: constraint_kind(constraint).to_string(),
+            body1: body_uid(anchor1.0),
+            body_part1: anchor1.1,
+            body2: body_uid(anchor2.0),
+            body_part2: anchor2.1,
+        }
+    }
+}
+
+impl<N: RealField> ShapeDescription<N> {
+    fn new(shape: &Shape<N>) -> Self {
+        if let Some(s) = shape.as_shape::<Ball<N>>() {
+            ShapeDescription::Ball { radius: s.radius() }
+        } else if let Some(s) = shape.as_shape::<Cuboid<N>>() {
+            ShapeDescription::Cuboid { half_extents: s.half_extents().as_slice().to_vec() }
+        } else if let Some(s) = shape.as_shape::<Capsule<N>>() {
+            ShapeDescription::Capsule { half_height: s.half_height(), radius: s.radius() }
+        } else if let Some(s) = shape.as_shape::<Plane<N>>() {
+            ShapeDescription::Plane { normal: s.normal().as_slice().to_vec() }
+        } else if let Some(s) = shape.as_shape::<Compound<N>>() {
+            ShapeDescription::Compound { num_shapes: s.shapes().len() }
+        } else {
+            ShapeDescription::Unsupported
+        }
+    }
+}
+
+fn body_uid(handle: BodyHandle) -> Option<usize> {
+    if handle.is_ground() {
+        None
+    } else {
+        Some(handle.uid())
+    }
+}
+
+fn body_kind<N: RealField>(body: &Body<N>) -> &'static str {
+    if body.is::<RigidBody<N>>() {
+        return "RigidBody";
+    }
+    if body.is::<Multibody<N>>() {
+        return "Multibody";
+    }
+    if body.is::<Ground<N>>() {
+        return "Ground";
+    }
+    if body.is::<MassSpringSystem<N>>() {
+        return "MassSpringSystem";
+    }
+    if body.is::<MassConstraintSystem<N>>() {
+        return "MassConstraintSystem";
+    }
+    #[cfg(feature = "dim2")]
+    {
+        if body.is::<FEMSurface<N>>() {
+            return "FEMSurface";
+        }
+    }
+    #[cfg(feature = "dim3")]
+    {
+        if body.is::<FEMVolume<N>>() {
+            return "FEMVolume";
+        }
+    }
+
+    "Unknown"
+}
+
+fn constraint_kind<N: RealField>(constraint: &JointConstraint<N>) -> &'static str {
+    if constraint.is::<FixedConstraint<N>>() {
+        return "FixedConstraint";
+    }
+    if constraint.is::<RevoluteConstraint<N>>() {
+        return "RevoluteConstraint";
+    }
+    if constraint.is::<PrismaticConstraint<N>>() {
+        return "PrismaticConstraint";
+    }
+    if constraint.is::<CartesianConstraint<N>>() {
+        return "CartesianConstraint";
+    }
+    if constraint.is::<MouseConstraint<N>>() {
+        return "MouseConstraint";
+    }
+    if constraint.is::<SpringConstraint<N>>() {
+        return "SpringConstraint";
+    }
+    if constraint.is::<DistanceConstraint<N>>() {
+        return "DistanceConstraint";
+    }
+    #[cfg(feature = "dim3")]
+    {
+        if constraint.is::<BallConstraint<N>>() {
+            return "BallConstraint";
+        }
+        if constraint.is::<CylindricalConstraint<N>>() {
+            return "CylindricalConstraint";
+        }
+        if constraint.is::<PinSlotConstraint<N>>() {
+            return "PinSlotConstraint";
+        }
+        if constraint.is::<PlanarConstraint<N>>() {
+            return "PlanarConstraint";
+        }
+        if constraint.is::<RectangularConstraint<N>>() {
+            return "RectangularConstraint";
+        }
+        if constraint.is::<UniversalConstraint<N>>() {
+            return "UniversalConstraint";
+        }
+    }
+
+    "Unknown"
+}
@@ -1,22 +1,83 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::f64;
+use std::mem;
+
 use slab::Slab;
 
-use na::{self, RealField};
+use na::{self, RealField, Unit};
 use ncollide;
 use ncollide::events::{ContactEvents, ProximityEvents};
-
-use crate::counters::Counters;
-use crate::detection::{ActivationManager, ColliderContactManifold};
+use ncollide::query::{self, ContactManifold, Proximity, Ray, RayIntersection};
+use ncollide::bounding_volume::{self, AABB, BoundingVolume};
+use ncollide::shape::{Ball, Capsule, Cuboid, FeatureId, Shape, ShapeHandle};
+#[cfg(feature = "dim2")]
+use ncollide::shape::Polyline;
+#[cfg(feature = "dim3")]
+use ncollide::shape::TriMesh;
+use ncollide::world::{CollisionGroups, GeometricQueryType};
+
+use crate::counters::{self, Counters};
+use crate::detection::{ActivationManager, ColliderContactManifold, IslandManager};
 use crate::force_generator::{ForceGenerator, ForceGeneratorHandle};
-use crate::joint::{ConstraintHandle, JointConstraint};
-use crate::math::Vector;
+use crate::joint::{ConstraintHandle, FixedConstraint, JointConstraint};
+#[cfg(feature = "dim2")]
+use crate::joint::RevoluteConstraint;
+#[cfg(feature = "dim3")]
+use crate::joint::BallConstraint;
+use crate::math::{AngularVector, Inertia, Isometry, Point, Rotation, Translation, Vector, Velocity, ANGULAR_DIM, DIM};
 use crate::object::{
-    Body, BodySet, BodyDesc, BodyStatus, Collider, ColliderAnchor,
-    ColliderHandle, Multibody, RigidBody, BodyHandle,
+    Body, BodySet, BodyDesc, BodyPart, BodyPartHandle, BodyStatus, Collider, ColliderAnchor, ColliderDesc,
+    ColliderHandle, Multibody, RigidBody, RigidBodyDesc, BodyHandle,
 };
-use crate::material::MaterialsCoefficientsTable;
+#[cfg(feature = "dim2")]
+use crate::object::FEMSurface;
+#[cfg(feature = "dim3")]
+use crate::object::FEMVolume;
+use crate::material::{MaterialsCoefficientsTable, SurfaceTag};
+use crate::volumetric::Volumetric;
+use crate::utils::GeneralizedCross;
 use crate::solver::{ContactModel, IntegrationParameters, MoreauJeanSolver, SignoriniCoulombPyramidModel};
-use crate::world::ColliderWorld;
+use crate::world::{BodyDescription, ColliderDescription, ColliderWorld, ConstraintDescription, SceneDescription,
+                   WorldSnapshot};
+
+
+/// The first impact of a shape cast through the world.
+#[derive(Copy, Clone, Debug)]
+pub struct TOI<N: RealField> {
+    /// The time of impact, i.e., the shape hits the collider after moving by `velocity * toi`.
+    pub toi: N,
+    /// The contact normal at the time of impact, pointing from the cast shape toward the collider hit.
+    pub normal: Unit<Vector<N>>,
+    /// The contact point on the collider hit, in world-space.
+    pub witness: Point<N>,
+}
+
+/// A contact point between a collider and another collider.
+#[derive(Copy, Clone, Debug)]
+pub struct ContactPoint<N: RealField> {
+    /// The other collider involved in the contact.
+    pub other: ColliderHandle,
+    /// The contact point on the collider shape, in world-space.
+    pub point: Point<N>,
+    /// The contact normal, pointing from the collider toward the other collider.
+    pub normal: Unit<Vector<N>>,
+    /// The penetration depth of the contact, negative if the shapes are separated.
+    ///
+    /// This does not include the colliders margins.
+    pub depth: N,
+    /// The surface tag of the material of the collider at the contact point.
+    pub surface_tag: Option<SurfaceTag>,
+    /// The surface tag of the material of the other collider at the contact point.
+    pub other_surface_tag: Option<SurfaceTag>,
+}
 
+// A modification of the set of bodies, deferred until the beginning of the next timestep.
+enum BodyCommand<N: RealField> {
+    Add(Box<FnOnce(&mut World<N>) + Send + Sync>),
+    Remove(BodyHandle),
+}
 
 /// The physics world.
 pub struct World<N: RealField> {
@@ -26,13 +87,21 @@ pub struct World<N: RealField> {
     cworld: ColliderWorld<N>,
     solver: MoreauJeanSolver<N>,
     activation_manager: ActivationManager<N>,
+    island_manager: IslandManager,
     material_coefficients: MaterialsCoefficientsTable<N>,
     // FIXME: set those two parameters per-collider?
     prediction: N,
+    angular_prediction: N,
     gravity: Vector<N>,
     constraints: Slab<Box<JointConstraint<N>>>,
     forces: Slab<Box<ForceGenerator<N>>>,
+    disabled_forces: HashSet<ForceGeneratorHandle>,
     params: IntegrationParameters<N>,
+    on_body_removed: Option<Box<FnMut(BodyHandle, Option<&(Any + Send + Sync)>) + Send + Sync>>,
+    body_commands: Vec<BodyCommand<N>>,
+    deterministic: bool,
+    // Empty buffer whose allocation is reused to collect the contact manifolds at each timestep.
+    contact_manifolds: Vec<ColliderContactManifold<'static, N>>,
 }
 
 impl<N: RealField> World<N> {
@@ -43,6 +112,7 @@ impl<N: RealField> World<N> {
         let counters = Counters::new(false);
         let bv_margin = na::convert(0.01f64);
         let prediction = na::convert(0.002);
+        let angular_prediction = na::convert(f64::consts::PI / 180.0 * 5.0);
         let bodies = BodySet::new();
         let active_bodies = Vec::new();
         let constraints = Slab::new();
@@ -51,6 +121,7 @@ impl<N: RealField> World<N> {
         let contact_model = Box::new(SignoriniCoulombPyramidModel::new());
         let solver = MoreauJeanSolver::new(contact_model);
         let activation_manager = ActivationManager::new(na::convert(0.01f64));
+        let island_manager = IslandManager::new();
         let gravity = Vector::zeros();
         let params = IntegrationParameters::default();
         let material_coefficients = MaterialsCoefficientsTable::new();
@@ -62,12 +133,19 @@ impl<N: RealField> World<N> {
             cworld,
             solver,
             activation_manager,
+            island_manager,
             material_coefficients,
             prediction,
+            angular_prediction,
             gravity,
             constraints,
             forces,
-            params
+            disabled_forces: HashSet::new(),
+            params,
+            on_body_removed: None,
+            body_commands: Vec::new(),
+            deterministic: false,
+            contact_manifolds: Vec::new(),
         }
     }
 
@@ -76,6 +154,49 @@ impl<N: RealField> World<N> {
         self.prediction
     }
 
+    /// Sets the prediction distance used for collision detection.
+    ///
+    /// This value and `self.angular_prediction()` become the contact prediction distances of all the colliders,
+    /// current and future, without user-defined predictions. The predictions set explicitly on a collider
+    /// descriptor or with `ColliderWorld::set_prediction` are left unchanged.
+    pub fn set_prediction(&mut self, prediction: N) {
+        self.prediction = prediction;
+        self.cworld.set_default_prediction(self.prediction, self.angular_prediction);
+    }
+
+    /// Angular prediction used for collision detection.
+    pub fn angular_prediction(&self) -> N {
+        self.angular_prediction
+    }
+
+    /// Sets the angular prediction, in radians, used for collision detection.
+    ///
+    /// A larger angular prediction lets fast-rotating bodies generate contacts earlier. This value and
+    /// `self.prediction()` become the contact prediction distances of all the colliders, current and future,
+    /// without user-defined predictions. The predictions set explicitly on a collider descriptor or with
+    /// `ColliderWorld::set_prediction` are left unchanged.
+    pub fn set_angular_prediction(&mut self, angular_prediction: N) {
+        self.angular_prediction = angular_prediction;
+        self.cworld.set_default_prediction(self.prediction, self.angular_prediction);
+    }
+
+    /// Sets whether the contacts are solved in an order that does not depend on the broad phase.
+    ///
+    /// If enabled, the contact manifolds are sorted by the handles of their colliders, whatever their order in the
+    /// manifold, and the contacts of each manifold by their features and locations, before being solved. Thus, two
+    /// worlds built identically always solve their constraints in the same order. This is useful for lockstep
+    /// networking. The joint constraints are always solved in the order of their handles. Since the order depends
+    /// on the handles, the bodies and colliders must be added in the same order on all the worlds. This costs the
+    /// sort of the contact manifolds at each timestep. Disabled by default.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    /// Whether the contacts are solved in an order that does not depend on the broad phase.
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     /// Disable the perfomance counters that measure various times and statistics during a timestep.
     pub fn disable_performance_counters(&mut self) {
         self.counters.disable();
@@ -86,16 +207,71 @@ impl<N: RealField> World<N> {
         self.counters.enable();
     }
 
+    /// Enable the recording of the velocity change applied by the constraints to each rigid body during a timestep.
+    ///
+    /// See `World::body_applied_impulse`.
+    pub fn enable_applied_impulses_recording(&mut self) {
+        self.solver.set_applied_impulses_recording(true);
+    }
+
+    /// Disable the recording of the velocity change applied by the constraints to each rigid body during a timestep.
+    pub fn disable_applied_impulses_recording(&mut self) {
+        self.solver.set_applied_impulses_recording(false);
+    }
+
+    /// The velocity change applied by the contact and joint constraints to the rigid body `handle` during the last
+    /// timestep.
+    ///
+    /// This is useful to find out which bodies are pushed the hardest by the solver. The recording must be enabled
+    /// with `World::enable_applied_impulses_recording` beforehand, otherwise zero is returned. Zero is also returned
+    /// if the body is not a rigid body, or if it was not simulated during the last timestep, e.g., if it is asleep.
+    pub fn body_applied_impulse(&self, handle: BodyHandle) -> Velocity<N> {
+        self.solver.applied_impulse(handle)
+    }
+
     /// Retrieve the perfomance counters that measure various times and statistics during a timestep.
     pub fn performance_counters(&self) -> &Counters {
         &self.counters
     }
 
+    /// The largest change of constraint impulse during each iteration of the velocity constraints resolution of the
+    /// last timestep.
+    ///
+    /// This is useful to plot the convergence of the solver when tuning the integration parameters.
+    #[cfg(feature = "solver-history")]
+    pub fn last_step_residual_history(&self) -> &[N] {
+        self.solver.residual_history()
+    }
+
+    /// Sets the callback invoked whenever a body is about to be removed from this world.
+    ///
+    /// The callback is given the handle of the removed body and its user-data, if any.
+    /// It is called by `remove_bodies` before the body is dropped.
+    pub fn set_on_body_removed(&mut self, callback: Box<FnMut(BodyHandle, Option<&(Any + Send + Sync)>) + Send + Sync>) {
+        self.on_body_removed = Some(callback)
+    }
+
     /// Set the contact model for all contacts.
     pub fn set_contact_model<C: ContactModel<N>>(&mut self, model: C) {
         self.solver.set_contact_model(Box::new(model))
     }
 
+    /// Discards the impulses cached by the contacts, the joint constraints, and the bodies for warm-starting the solver.
+    ///
+    /// This avoids the "kick" applied by stale impulses at the next timestep, e.g., after teleporting
+    /// some bodies or on a scene cut. Use `RigidBody::wake_up_clean` to do this for a single rigid body.
+    pub fn clear_cached_impulses(&mut self) {
+        self.solver.clear_cached_impulses();
+
+        for (_, constraint) in self.constraints.iter_mut() {
+            constraint.clear_cached_impulses();
+        }
+
+        for body in self.bodies.bodies_mut() {
+            body.clear_cached_impulses();
+        }
+    }
+
     /// Retrieve a reference to the parameters for the integration.
     pub fn integration_parameters(&self) -> &IntegrationParameters<N> {
         &self.params
@@ -171,6 +347,45 @@ impl<N: RealField> World<N> {
         constraint
     }
 
+    /// Fixes the given body part to the ground in its current position and orientation.
+    ///
+    /// The fixed constraint is attached at the world-space point `at`. Returns `None` if the body part does not exist.
+    pub fn anchor_body_to_ground(&mut self, part: BodyPartHandle, at: &Point<N>) -> Option<ConstraintHandle> {
+        let body = self.bodies.body(part.0)?;
+        let body_part = body.part(part.1)?;
+        let rotation = body_part.position().rotation;
+        let local_at = body.material_point_at_world_point(body_part, at);
+        let constraint =
+            FixedConstraint::new(BodyPartHandle::ground(), part, *at, rotation, local_at, Rotation::identity());
+
+        Some(self.add_constraint(constraint))
+    }
+
+    /// Fixes the point of the given body part currently located at the world-space point `at` to the ground.
+    ///
+    /// The body part may still rotate freely around this point. Returns `None` if the body part does not exist.
+    pub fn anchor_point_to_ground(&mut self, part: BodyPartHandle, at: &Point<N>) -> Option<ConstraintHandle> {
+        let body = self.bodies.body(part.0)?;
+        let local_at = body.material_point_at_world_point(body.part(part.1)?, at);
+        #[cfg(feature = "dim2")]
+        let constraint = RevoluteConstraint::new(BodyPartHandle::ground(), part, *at, local_at);
+        #[cfg(feature = "dim3")]
+        let constraint = BallConstraint::new(BodyPartHandle::ground(), part, *at, local_at);
+
+        Some(self.add_constraint(constraint))
+    }
+
+    /// Adds an infinite ground to the world, i.e., a static half-space with the outward normal `normal`.
+    ///
+    /// The boundary of the half-space passes through the point `normal * offset`. Returns the handle of the
+    /// ground collider, e.g., to customize its material.
+    pub fn add_ground_plane(&mut self, normal: Unit<Vector<N>>, offset: N) -> ColliderHandle {
+        ColliderDesc::halfspace(normal)
+            .translation(*normal * offset)
+            .build(self)
+            .handle()
+    }
+
     /// Remove the specified collider from the world.
     pub fn remove_colliders(&mut self, handles: &[ColliderHandle]) {
         let bodies = &mut self.bodies;
@@ -185,6 +400,10 @@ impl<N: RealField> World<N> {
             }
         }
 
+        for handle in handles {
+            self.solver.remove_collider(*handle);
+        }
+
         self.cworld.remove(handles);
     }
 
@@ -211,9 +430,50 @@ impl<N: RealField> World<N> {
         &mut self,
         handle: ForceGeneratorHandle,
     ) -> Box<ForceGenerator<N>> {
+        let _ = self.disabled_forces.remove(&handle);
         self.forces.remove(handle)
     }
 
+    /// Remove all the force generators for which `f` returns `false`.
+    pub fn retain_force_generators(&mut self, mut f: impl FnMut(ForceGeneratorHandle, &mut ForceGenerator<N>) -> bool) {
+        let disabled_forces = &mut self.disabled_forces;
+        self.forces.retain(|handle, generator| {
+            let keep = f(handle, &mut **generator);
+
+            if !keep {
+                let _ = disabled_forces.remove(&handle);
+            }
+
+            keep
+        })
+    }
+
+    /// An iterator through all the force generators of this world, and their handles.
+    ///
+    /// This includes the disabled force generators.
+    pub fn force_generators<'a>(&'a self) -> impl Iterator<Item = (ForceGeneratorHandle, &'a ForceGenerator<N>)> + 'a {
+        self.forces.iter().map(|(handle, generator)| (handle, &**generator))
+    }
+
+    /// Enables or disables the specified force generator.
+    ///
+    /// A disabled force generator stays in the world, but does not apply any force until it is enabled again.
+    /// Force generators are enabled when they are added to the world.
+    pub fn set_force_generator_enabled(&mut self, handle: ForceGeneratorHandle, enabled: bool) {
+        assert!(self.forces.contains(handle), "Unknown force generator handle.");
+
+        if enabled {
+            let _ = self.disabled_forces.remove(&handle);
+        } else {
+            let _ = self.disabled_forces.insert(handle);
+        }
+    }
+
+    /// Returns `true` if the specified force generator is enabled.
+    pub fn is_force_generator_enabled(&self, handle: ForceGeneratorHandle) -> bool {
+        !self.disabled_forces.contains(&handle)
+    }
+
     /// Set the gravity.
     pub fn set_gravity(&mut self, gravity: Vector<N>) {
         self.gravity = gravity
@@ -226,7 +486,24 @@ impl<N: RealField> World<N> {
 
     /// Execute one time step of the physics simulation.
     pub fn step(&mut self) {
+        self.step_until(None)
+    }
+
+    /// Execute one time step of the physics simulation, interrupting the constraints resolution once
+    /// `max_duration` seconds of wall-clock time have elapsed since the beginning of the step.
+    ///
+    /// When the budget is exceeded, the remaining velocity and position solver iterations are skipped
+    /// (though each solver still performs at least one iteration) and the timestep is completed normally.
+    /// This degrades the accuracy of the simulation gracefully, e.g., by letting stacks jitter or bodies
+    /// penetrate slightly more, instead of exceeding the time budget of a real-time application.
+    /// Use `self.performance_counters().budget_exceeded()` to check whether the last step was interrupted.
+    pub fn step_with_budget(&mut self, max_duration: f64) {
+        self.step_until(Some(counters::now() + max_duration))
+    }
+
+    fn step_until(&mut self, deadline: Option<f64>) {
         self.counters.step_started();
+        self.flush_body_commands();
 
         /*
          *
@@ -240,7 +517,12 @@ impl<N: RealField> World<N> {
 
         let params = &self.params;
         let bodies = &mut self.bodies;
-        self.forces.retain(|_, f| {
+        let disabled_forces = &self.disabled_forces;
+        self.forces.retain(|handle, f| {
+            if disabled_forces.contains(&handle) {
+                return true;
+            }
+
             f.apply(params, bodies)
         });
 
@@ -260,11 +542,9 @@ impl<N: RealField> World<N> {
 
         /*
          *
-         * Handle sleeping and collision
-         * islands.
+         * Handle sleeping.
          *
          */
-        // FIXME: for now, no island is built.
         self.counters.island_construction_started();
         self.active_bodies.clear();
         self.activation_manager.update(
@@ -273,30 +553,28 @@ impl<N: RealField> World<N> {
             &self.constraints,
             &mut self.active_bodies,
         );
-        self.counters.island_construction_completed();
 
         /*
          *
          * Collect contact manifolds.
          *
          */
-        let mut contact_manifolds = Vec::new(); // FIXME: avoid allocations.
-        for (c1, c2, _, manifold) in self.cworld.contact_pairs(false) {
-            let b1 = try_continue!(self.bodies.body(c1.body()));
-            let b2 = try_continue!(self.bodies.body(c2.body()));
-
-            if manifold.len() > 0
-                && b1.status() != BodyStatus::Disabled && b2.status() != BodyStatus::Disabled
-                && ((b1.status_dependent_ndofs() != 0 && b1.is_active())
-                || (b2.status_dependent_ndofs() != 0 && b2.is_active()))
-                {
-                    contact_manifolds.push(ColliderContactManifold::new(c1, c2, manifold));
-                }
+        let mut contact_manifolds = recycle_contact_manifolds(mem::replace(&mut self.contact_manifolds, Vec::new()));
+        Self::collect_contact_manifolds(&self.cworld, &self.bodies, &self.params, &mut contact_manifolds);
+
+        if self.deterministic {
+            sort_contact_manifolds(&mut contact_manifolds);
         }
 
+        let nskipped_contacts = match self.params.max_constraints {
+            Some(max) => self.solver.limit_constraints(max, &self.bodies, &self.constraints, &mut contact_manifolds),
+            None => 0,
+        };
+        self.counters.set_nskipped_contacts(nskipped_contacts);
+
         /*
          *
-         * Solve the system and integrate.
+         * Build the islands.
          *
          */
         for b in self.bodies.bodies_mut() {
@@ -307,16 +585,50 @@ impl<N: RealField> World<N> {
             b.set_companion_id(0);
         }
 
-        self.solver.step(
+        self.island_manager.update(
+            &mut self.bodies,
+            &self.active_bodies,
+            &self.constraints,
+            &mut contact_manifolds,
+        );
+        self.counters.island_construction_completed();
+
+        /*
+         *
+         * Discard the impulses cached by the joints attached
+         * to bodies woken up with `wake_up_clean`. Those of
+         * the contacts are ignored by the contact model.
+         *
+         */
+        let bodies = &self.bodies;
+        let cleared = |handle| bodies.body(handle).map(|b| b.update_status().impulses_cleared()).unwrap_or(false);
+
+        for (_, constraint) in self.constraints.iter_mut() {
+            let (b1, b2) = constraint.anchors();
+
+            if cleared(b1.0) || cleared(b2.0) {
+                constraint.clear_cached_impulses();
+            }
+        }
+
+        /*
+         *
+         * Solve the system and integrate.
+         *
+         */
+        let completed = self.solver.step_until(
             &mut self.counters,
             &mut self.bodies,
             &mut self.constraints,
             &contact_manifolds[..],
-            &self.active_bodies[..],
+            &self.island_manager,
             &self.params,
             &self.material_coefficients,
             &self.cworld,
+            deadline,
         );
+        self.counters.set_budget_exceeded(!completed);
+        self.contact_manifolds = recycle_contact_manifolds(contact_manifolds);
 
         for b in self.bodies.bodies_mut() {
             if b.status() == BodyStatus::Kinematic {
@@ -324,6 +636,25 @@ impl<N: RealField> World<N> {
             }
         }
 
+        /*
+         *
+         * Enforce the maximum velocities.
+         *
+         */
+        let mut nclamped_velocities = 0;
+
+        if params.max_linear_velocity.is_some() || params.max_angular_velocity.is_some() {
+            for b in self.bodies.bodies_mut() {
+                if b.status() == BodyStatus::Dynamic
+                    && clamp_velocity(b, params.max_linear_velocity, params.max_angular_velocity)
+                {
+                    nclamped_velocities += 1;
+                }
+            }
+        }
+
+        self.counters.set_nclamped_velocities(nclamped_velocities);
+
 
         /*
          *
@@ -367,16 +698,132 @@ impl<N: RealField> World<N> {
     /// Remove the specified bodies.
     pub fn remove_bodies(&mut self, handles: &[BodyHandle]) {
         for handle in handles {
+            if let Some(callback) = &mut self.on_body_removed {
+                if let Some(body) = self.bodies.body(*handle) {
+                    callback(*handle, body.user_data())
+                }
+            }
+
             self.bodies.remove_body(*handle);
         }
 
         self.cleanup_after_body_removal();
 
         for handle in handles {
+            for collider in self.cworld.body_colliders(*handle) {
+                self.solver.remove_collider(collider.handle());
+            }
+
             self.cworld.remove_body(*handle);
         }
     }
 
+    /// Queues the removal of the specified body until the beginning of the next timestep.
+    ///
+    /// The queued removals are performed at the beginning of the next call to `self.step()`, before any body is
+    /// updated. They are all performed before the queued additions, so that a body added in the meantime can
+    /// never be removed because it reused the handle of a removed body. The removal is ignored if the body does
+    /// not exist anymore at this point, e.g., if it has been queued for removal several times.
+    pub fn queue_remove_body(&mut self, handle: BodyHandle) {
+        self.body_commands.push(BodyCommand::Remove(handle))
+    }
+
+    /// Queues the addition of a body until the beginning of the next timestep.
+    ///
+    /// The `build` closure is called at the beginning of the next call to `self.step()`, after all the queued
+    /// removals and in the order of the other queued additions, and is expected to add the body to the world it is
+    /// given. The additions and removals it queues itself are deferred to the following timestep.
+    pub fn queue_add_body(&mut self, build: Box<FnOnce(&mut World<N>) + Send + Sync>) {
+        self.body_commands.push(BodyCommand::Add(build))
+    }
+
+    fn flush_body_commands(&mut self) {
+        let commands = mem::replace(&mut self.body_commands, Vec::new());
+
+        // NOTE: the removals are performed first because the added bodies may reuse the handles of removed ones.
+        for command in &commands {
+            if let BodyCommand::Remove(handle) = command {
+                if !handle.is_ground() && self.bodies.contains(*handle) {
+                    self.remove_bodies(&[*handle])
+                }
+            }
+        }
+
+        for command in commands {
+            if let BodyCommand::Add(build) = command {
+                build(self)
+            }
+        }
+    }
+
+    /// Replaces a deformable body by a static rigid body with the current shape of its colliders.
+    ///
+    /// Each deformable collider attached to the body is replaced by a collider with a copy of its
+    /// deformed shape, i.e., a trimesh in 3D or a polyline in 2D, and the same margin, collision groups,
+    /// and material. The deformable body is then removed from the world, together with its colliders
+    /// and the constraints attached to it.
+    ///
+    /// Returns the handle of the static body created, or `None` if the body does not exist or is not
+    /// deformable, in which case the world is left unchanged.
+    pub fn solidify_deformable(&mut self, handle: BodyHandle) -> Option<BodyPartHandle> {
+        let _ = self.bodies.body(handle)?.deformed_positions()?;
+        let mut collider_descs = Vec::new();
+
+        for collider in self.cworld.body_colliders(handle) {
+            if let ColliderAnchor::OnDeformableBody { .. } = collider.anchor() {
+                // The deformed shape is rebuilt so that its bounding volumes fit its current geometry.
+                #[cfg(feature = "dim2")]
+                let shape = collider.shape().as_shape::<Polyline<N>>().map(|polyline| {
+                    let indices = polyline.edges().iter().map(|e| e.indices).collect();
+                    let mut solid = Polyline::new(polyline.points().to_vec(), Some(indices));
+                    solid.set_oriented(polyline.oriented());
+                    ShapeHandle::new(solid)
+                });
+                #[cfg(feature = "dim3")]
+                let shape = collider.shape().as_shape::<TriMesh<N>>().map(|mesh| {
+                    let indices = mesh.faces().iter().map(|f| f.indices).collect();
+                    let mut solid = TriMesh::new(mesh.points().to_vec(), indices, mesh.uvs().map(|uvs| uvs.to_vec()));
+                    solid.set_oriented(mesh.oriented());
+                    ShapeHandle::new(solid)
+                });
+                let shape = try_continue!(shape);
+
+                let margin = collider.margin();
+                let explicit_prediction = collider.has_explicit_prediction();
+                let desc = match collider.query_type() {
+                    GeometricQueryType::Contacts(linear, angular) if explicit_prediction => ColliderDesc::new(shape)
+                        .linear_prediction(linear - margin)
+                        .angular_prediction(angular),
+                    GeometricQueryType::Proximity(linear) if explicit_prediction => ColliderDesc::new(shape)
+                        .sensor(true)
+                        .linear_prediction(linear),
+                    // The colliders following the predictions of the world keep doing so.
+                    GeometricQueryType::Contacts(..) => ColliderDesc::new(shape),
+                    GeometricQueryType::Proximity(_) => ColliderDesc::new(shape).sensor(true),
+                };
+
+                collider_descs.push(desc
+                    .name(collider.name().to_string())
+                    .margin(margin)
+                    .collision_groups(*collider.collision_groups())
+                    .material(collider.material_handle().clone())
+                    .min_restitution(collider.min_restitution())
+                    .one_way(collider.one_way().cloned()));
+            }
+        }
+
+        // The deformed shapes are already expressed in world-space.
+        let mut rb_desc = RigidBodyDesc::new().status(BodyStatus::Static);
+
+        for desc in &collider_descs {
+            let _ = rb_desc.add_collider(desc);
+        }
+
+        let part = rb_desc.build(self).part_handle();
+        self.remove_bodies(&[handle]);
+        Some(part)
+    }
+
     fn cleanup_after_body_removal(&mut self) {
         self.activate_bodies_touching_deleted_bodies();
         self.cleanup_constraints_with_deleted_anchors();
@@ -448,6 +895,117 @@ impl<N: RealField> World<N> {
         self.bodies.body_mut(handle)?.downcast_mut::<Multibody<N>>()
     }
 
+    fn is_collider_enabled(bodies: &BodySet<N>, collider: &Collider<N>) -> bool {
+        bodies.body(collider.body()).map(|b| b.status() != BodyStatus::Disabled).unwrap_or(false)
+    }
+
+    fn active_contact_manifold<'a>(
+        params: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        c1: &'a Collider<N>,
+        c2: &'a Collider<N>,
+        manifold: &'a ContactManifold<N>,
+    ) -> Option<ColliderContactManifold<'a, N>> {
+        let b1 = bodies.body(c1.body())?;
+        let b2 = bodies.body(c2.body())?;
+        let ndofs1 = b1.status_dependent_ndofs();
+        let ndofs2 = b2.status_dependent_ndofs();
+
+        let is_active = manifold.len() > 0
+            && b1.status() != BodyStatus::Disabled && b2.status() != BodyStatus::Disabled
+            && ((ndofs1 != 0 && b1.is_active()) || (ndofs2 != 0 && b2.is_active()));
+
+        if !is_active {
+            return None;
+        }
+
+        let mut result = ColliderContactManifold::new(c1, c2, manifold);
+
+        // Only the pairs of dynamic bodies use the restricted prediction.
+        if ndofs1 != 0 && ndofs2 != 0 {
+            result.max_separation = params.dynamic_contacts_prediction;
+        }
+
+        if result.contacts().next().is_some() {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn collect_contact_manifolds<'a>(
+        cworld: &'a ColliderWorld<N>,
+        bodies: &BodySet<N>,
+        params: &IntegrationParameters<N>,
+        out: &mut Vec<ColliderContactManifold<'a, N>>,
+    ) {
+        for (c1, c2, _, manifold) in cworld.contact_pairs(false) {
+            if let Some(manifold) = Self::active_contact_manifold(params, bodies, c1, c2, manifold) {
+                out.push(manifold);
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn collect_contact_manifolds<'a>(
+        cworld: &'a ColliderWorld<N>,
+        bodies: &BodySet<N>,
+        params: &IntegrationParameters<N>,
+        out: &mut Vec<ColliderContactManifold<'a, N>>,
+    ) {
+        use rayon::prelude::*;
+
+        out.par_extend(cworld.contact_pairs(false).par_bridge().filter_map(|(c1, c2, _, manifold)| {
+            Self::active_contact_manifold(params, bodies, c1, c2, manifold)
+        }));
+
+        // NOTE: the manifolds are collected in an arbitrary order, so they are sorted to
+        // keep the simulation deterministic. Each pair of colliders has at most one manifold.
+        out.par_sort_unstable_by_key(contact_manifold_key);
+    }
+
+    /// Rebuilds the deformable colliders attached to the given body from its current boundary.
+    ///
+    /// This must be called after an operation changing the topology of a deformable body, e.g., a fracture,
+    /// so that its colliders reflect its new boundary. The colliders keep their handles and anchors, but their
+    /// per-subshape materials, if any, are removed since they no longer match the new shape. The colliders tracking
+    /// only a subset of the body DOFs, e.g., coarse collision proxies, are left unchanged.
+    ///
+    /// Only the colliders of `FEMSurface` (2D) and `FEMVolume` (3D) bodies can be rebuilt, since they are derived from
+    /// the elements of those bodies. The colliders of `MassSpringSystem` and `MassConstraintSystem` bodies are the
+    /// meshes these bodies were built from: removing some of their springs or constraints does not change them, so
+    /// this does nothing for those bodies, nor for any other body.
+    pub fn refresh_deformable_collider(&mut self, handle: BodyHandle) {
+        let (shape, parts_map) = try_ret!(self.deformable_boundary(handle));
+        let colliders: Vec<_> = self.cworld
+            .body_colliders(handle)
+            .filter(|c| match c.anchor() {
+                ColliderAnchor::OnDeformableBody { .. } => c.dof_map().is_none(),
+                _ => false
+            })
+            .map(|c| c.handle())
+            .collect();
+
+        for collider in colliders {
+            self.cworld.set_deformable_shape(collider, shape.clone(), Some(parts_map.clone()));
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    fn deformable_boundary(&self, handle: BodyHandle) -> Option<(ShapeHandle<N>, Arc<Vec<usize>>)> {
+        let volume = self.bodies.body(handle)?.downcast_ref::<FEMVolume<N>>()?;
+        let (mesh, parts_map) = volume.boundary_mesh_with_all_nodes();
+        Some((ShapeHandle::new(mesh), Arc::new(parts_map)))
+    }
+
+    #[cfg(feature = "dim2")]
+    fn deformable_boundary(&self, handle: BodyHandle) -> Option<(ShapeHandle<N>, Arc<Vec<usize>>)> {
+        let surface = self.bodies.body(handle)?.downcast_ref::<FEMSurface<N>>()?;
+        let (polyline, parts_map) = surface.boundary_polyline_with_all_nodes();
+        Some((ShapeHandle::new(polyline), Arc::new(parts_map)))
+    }
+
     /// Get a reference to the specified rigid body.
     ///
     /// Returns `None` if the handle does not correspond to a rigid body in this world.
@@ -462,6 +1020,55 @@ impl<N: RealField> World<N> {
         self.bodies.body_mut(handle)?.downcast_mut::<RigidBody<N>>()
     }
 
+    /// The velocity of the body part `part2` relative to the body part `part1`, at the world-space point `point`.
+    ///
+    /// This is the velocity of the material point of `part2` located at `point`, minus the velocity of the material
+    /// point of `part1` located at `point`. A joint attaching both body parts at `point` drives this velocity
+    /// toward zero. Returns `None` if either body part does not exist.
+    pub fn relative_velocity(&self, part1: BodyPartHandle, part2: BodyPartHandle, point: &Point<N>) -> Option<Velocity<N>> {
+        let part1 = self.bodies.body(part1.0)?.part(part1.1)?;
+        let part2 = self.bodies.body(part2.0)?.part(part2.1)?;
+        let velocity1 = part1.velocity().shift(&(point - part1.center_of_mass()));
+        let velocity2 = part2.velocity().shift(&(point - part2.center_of_mass()));
+
+        Some(velocity2 - velocity1)
+    }
+
+    /// The total kinetic energy of the dynamic bodies of this world.
+    pub fn total_kinetic_energy(&self) -> N {
+        let _1_2: N = na::convert(1.0 / 2.0);
+
+        self.dynamic_body_parts().fold(N::zero(), |energy, part| {
+            let velocity = part.velocity();
+            let momentum = part.inertia() * velocity;
+            energy + velocity.as_vector().dot(momentum.as_vector()) * _1_2
+        })
+    }
+
+    /// The total linear momentum of the dynamic bodies of this world.
+    pub fn linear_momentum(&self) -> Vector<N> {
+        self.dynamic_body_parts().fold(Vector::zeros(), |momentum, part| {
+            momentum + (part.inertia() * part.velocity()).linear
+        })
+    }
+
+    /// The total angular momentum of the dynamic bodies of this world, about the world-space point `about`.
+    pub fn angular_momentum(&self, about: &Point<N>) -> AngularVector<N> {
+        self.dynamic_body_parts().fold(AngularVector::zeros(), |momentum, part| {
+            let part_momentum = part.inertia() * part.velocity();
+            let shift = part.center_of_mass() - about;
+            momentum + part_momentum.angular_vector() + shift.gcross(&part_momentum.linear)
+        })
+    }
+
+    // All the parts of the dynamic bodies of this world.
+    fn dynamic_body_parts(&self) -> impl Iterator<Item = &BodyPart<N>> {
+        self.bodies
+            .bodies()
+            .filter(|b| b.status() == BodyStatus::Dynamic)
+            .flat_map(|b| (0..b.num_parts()).filter_map(move |i| b.part(i)))
+    }
+
     /// Reference to the underlying collision world.
     pub fn collider_world(&self) -> &ColliderWorld<N> {
         &self.cworld
@@ -493,6 +1100,68 @@ impl<N: RealField> World<N> {
         self.cworld.collider_mut(handle)
     }
 
+    /// The collision margin surrounding the specified collider.
+    ///
+    /// Returns `None` if the handle does not correspond to a collider in this world.
+    pub fn collider_margin(&self, handle: ColliderHandle) -> Option<N> {
+        self.cworld.collider(handle).map(|co| co.margin())
+    }
+
+    /// Sets the collision margin surrounding the specified collider.
+    ///
+    /// A larger margin makes contacts be generated earlier, i.e., while the colliders are still separated.
+    pub fn set_collider_margin(&mut self, handle: ColliderHandle, margin: N) {
+        self.cworld.set_margin(handle, margin)
+    }
+
+    /// Uniformly scales the shape of the specified collider by `factor`.
+    ///
+    /// Only balls, cuboids, and capsules can be scaled. If the collider contributes to the mass of the body part
+    /// it is attached to, the mass properties of this body part are updated accordingly, and the body is woken up.
+    /// Returns `false`, and does nothing, if the collider does not exist, if its shape cannot be scaled, or if
+    /// `factor` is not positive.
+    pub fn scale_collider(&mut self, handle: ColliderHandle, factor: N) -> bool {
+        if !(factor > N::zero()) {
+            return false;
+        }
+
+        let (shape, density, anchor) = {
+            let collider = try_ret!(self.cworld.collider(handle), false);
+            let anchor = match collider.anchor() {
+                ColliderAnchor::OnBodyPart { body_part, position_wrt_body_part } => Some((*body_part, *position_wrt_body_part)),
+                ColliderAnchor::OnDeformableBody { .. } => None,
+            };
+            (collider.shape().clone(), collider.density(), anchor)
+        };
+        let scaled = try_ret!(scaled_shape(shape.as_ref(), factor), false);
+
+        if let Some((body_part, position_wrt_body_part)) = anchor {
+            if !density.is_zero() {
+                if let Some(body) = self.bodies.body_mut(body_part.0) {
+                    // Add the contribution of the scaled shape before removing the contribution of the old one,
+                    // so the mass of the body part does not drop to zero in-between.
+                    let com = position_wrt_body_part * scaled.center_of_mass();
+                    let inertia = scaled.inertia(density).transformed(&position_wrt_body_part);
+                    body.add_local_inertia_and_com(body_part.1, com, inertia);
+
+                    let com = position_wrt_body_part * shape.center_of_mass();
+                    let inertia = shape.inertia(density).transformed(&position_wrt_body_part);
+                    body.add_local_inertia_and_com(body_part.1, com, Inertia::new(-inertia.linear, -inertia.angular));
+                }
+            }
+        }
+
+        self.cworld.set_shape(handle, scaled);
+
+        let body = self.cworld.collider(handle).map(|c| c.body());
+
+        if let Some(body) = body.and_then(|body| self.bodies.body_mut(body)) {
+            body.activate();
+        }
+
+        true
+    }
+
     /// Gets the handle of the body the specified collider is attached to.
     pub fn collider_body_handle(&self, handle: ColliderHandle) -> Option<BodyHandle> {
         self.collider_anchor(handle).map(|anchor| anchor.body())
@@ -510,12 +1179,84 @@ impl<N: RealField> World<N> {
         self.cworld.colliders()
     }
 
+    /// An iterator through the handles of all the sensors of this world, i.e., the colliders with a proximity query type.
+    pub fn sensors<'a>(&'a self) -> impl Iterator<Item = ColliderHandle> + 'a {
+        self.colliders().filter(|c| c.is_sensor()).map(|c| c.handle())
+    }
+
+    /// Returns `true` if the given collider exists and is a sensor.
+    pub fn is_sensor(&self, handle: ColliderHandle) -> bool {
+        self.collider(handle).map(|c| c.is_sensor()).unwrap_or(false)
+    }
+
+    /// The smallest AABB enclosing all the colliders of this world.
+    ///
+    /// Returns `None` if this world does not contain any collider.
+    pub fn world_aabb(&self) -> Option<AABB<N>> {
+        self.colliders()
+            .map(|c| c.shape().aabb(c.position()))
+            .fold(None, |acc: Option<AABB<N>>, aabb| {
+                Some(acc.map(|acc| acc.merged(&aabb)).unwrap_or(aabb))
+            })
+    }
+
+    /// The number of islands the active bodies were partitioned into during the last timestep.
+    ///
+    /// Each island is a set of bodies connected by contacts or joints, and is solved independently
+    /// from the others. The bodies that are sleeping or not dynamic do not belong to any island.
+    pub fn num_islands(&self) -> usize {
+        self.island_manager.num_islands()
+    }
+
+    /// The handles of the bodies of the `i`-th island computed during the last timestep.
+    ///
+    /// Panics if `i >= self.num_islands()`.
+    pub fn island(&self, i: usize) -> &[BodyHandle] {
+        self.island_manager.island(i)
+    }
+
     /// An iterator through all the bodies on this world.
     pub fn bodies(&self) -> impl Iterator<Item = &Body<N>> { self.bodies.bodies() }
 
     /// A mutable iterator through all the bodies on this world.
     pub fn bodies_mut(&mut self) -> impl Iterator<Item = &mut Body<N>> { self.bodies.bodies_mut() }
 
+    /// An iterator through all the bodies on this world, together with their handles.
+    pub fn bodies_with_handles(&self) -> impl Iterator<Item = (BodyHandle, &Body<N>)> {
+        self.bodies.bodies_with_handles()
+    }
+
+    /// A mutable iterator through all the bodies on this world, together with their handles.
+    pub fn bodies_with_handles_mut(&mut self) -> impl Iterator<Item = (BodyHandle, &mut Body<N>)> {
+        self.bodies.bodies_with_handles_mut()
+    }
+
+    /// Appends to `out` the current position of every part of every body on this world.
+    ///
+    /// The positions are appended in a deterministic order, so the transforms recorded after each
+    /// step can be compared to a baseline with `utils::transforms_deviation`.
+    pub fn record_transforms(&self, out: &mut Vec<(BodyHandle, Isometry<N>)>) {
+        for body in self.bodies() {
+            let handle = body.handle();
+            out.extend(body.parts().map(|part| (handle, part.position())));
+        }
+    }
+
+    /// Saves the current dynamic state of this world so it can be restored later with `self.restore`.
+    ///
+    /// See the documentation of `WorldSnapshot` for details about what the snapshot contains.
+    pub fn snapshot(&self) -> WorldSnapshot<N> {
+        WorldSnapshot::new(&self.params, &self.solver, &self.bodies, &self.constraints)
+    }
+
+    /// Restores the dynamic state of this world saved by `self.snapshot`.
+    ///
+    /// The colliders are moved to the restored positions of the bodies they are attached to. The contacts
+    /// are updated at the next call to `self.step()`.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot<N>) {
+        snapshot.restore(&mut self.params, &mut self.solver, &mut self.bodies, &mut self.cworld, &mut self.constraints)
+    }
+
     /// An iterator through all the bodies with the given name.
     pub fn bodies_with_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Body<N>> {
         self.bodies().filter(move |b| b.name() == name)
@@ -526,6 +1267,15 @@ impl<N: RealField> World<N> {
         self.bodies_mut().filter(move |b| b.name() == name)
     }
 
+    /// A human-readable description of the bodies, colliders, and joint constraints on this world.
+    pub fn export_scene(&self) -> SceneDescription<N> {
+        SceneDescription {
+            bodies: self.bodies().map(BodyDescription::new).collect(),
+            colliders: self.cworld.colliders().map(ColliderDescription::new).collect(),
+            constraints: self.constraints.iter().map(|(h, c)| ConstraintDescription::new(h, &**c)).collect(),
+        }
+    }
+
     /// An iterator through all the contact events generated during the last execution of `self.step()`.
     pub fn contact_events(&self) -> &ContactEvents {
         self.cworld.contact_events()
@@ -535,20 +1285,2758 @@ impl<N: RealField> World<N> {
     pub fn proximity_events(&self) -> &ProximityEvents {
         self.cworld.proximity_events()
     }
-}
 
-impl<N: RealField> Default for World<N> {
-    fn default() -> Self {
-        Self::new()
+    /// The sensor events generated during the last execution of `self.step()`.
+    ///
+    /// Each event is a tuple `(sensor, collider, started)` where `sensor` is the handle of a sensor collider, and
+    /// `collider` is the handle of a collider that started (if `started` is `true`) or stopped intersecting it.
+    /// The proximity events that do not involve any sensor, or that do not change whether the colliders
+    /// intersect, are ignored. If both colliders are sensors, one event is generated for each of them.
+    pub fn sensor_events(&self) -> Vec<(ColliderHandle, ColliderHandle, bool)> {
+        let mut result = Vec::new();
+
+        for event in self.cworld.proximity_events().iter() {
+            let started = event.new_status == Proximity::Intersecting;
+
+            if started == (event.prev_status == Proximity::Intersecting) {
+                continue;
+            }
+
+            for &(sensor, other) in &[(event.collider1, event.collider2), (event.collider2, event.collider1)] {
+                if self.is_sensor(sensor) {
+                    result.push((sensor, other, started));
+                }
+            }
+        }
+
+        result
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::world::World;
+    /// The colliders of this world containing the given point.
+    ///
+    /// The containment test is performed against the exact shape of each collider: its margin is not
+    /// taken into account. Only the colliders compatible with `groups` are taken into account, and the
+    /// colliders attached to disabled bodies are ignored.
+    pub fn colliders_containing_point<'a>(
+        &'a self,
+        point: &'a Point<N>,
+        groups: &'a CollisionGroups,
+    ) -> impl Iterator<Item = ColliderHandle> + 'a {
+        let bodies = &self.bodies;
 
-    #[test]
-    fn world_is_send_sync() {
-        let _ = Box::new(World::<f32>::new()) as Box<Send + Sync>;
+        self.cworld
+            .interferences_with_point(point, groups)
+            .filter(move |collider| Self::is_collider_enabled(bodies, collider))
+            .map(|collider| collider.handle())
+    }
+
+    /// The colliders of this world with an AABB intersecting the given `aabb`.
+    ///
+    /// The AABB of each collider is the one of its exact shape: its margin is not taken into account.
+    /// Only the colliders compatible with `groups` are taken into account, and the colliders attached
+    /// to disabled bodies are ignored.
+    pub fn colliders_in_aabb<'a>(
+        &'a self,
+        aabb: &'a AABB<N>,
+        groups: &'a CollisionGroups,
+    ) -> impl Iterator<Item = ColliderHandle> + 'a {
+        let bodies = &self.bodies;
+
+        // NOTE: the AABBs of the broad phase are enlarged by the collider margins and the
+        // contact prediction, so we filter out the false positives.
+        self.cworld
+            .interferences_with_aabb(aabb, groups)
+            .filter(move |collider| {
+                Self::is_collider_enabled(bodies, collider)
+                    && collider.shape().aabb(collider.position()).intersects(aabb)
+            })
+            .map(|collider| collider.handle())
+    }
+
+    /// Computes the interferences between the colliders of this world and a ray.
+    ///
+    /// Only the colliders compatible with `groups` are taken into account, and the colliders attached
+    /// to disabled bodies are ignored.
+    pub fn interferences_with_ray<'a>(
+        &'a self,
+        ray: &'a Ray<N>,
+        groups: &'a CollisionGroups,
+    ) -> impl Iterator<Item = (ColliderHandle, RayIntersection<N>)> + 'a {
+        let bodies = &self.bodies;
+
+        self.cworld
+            .interferences_with_ray(ray, groups)
+            .filter(move |(collider, _)| Self::is_collider_enabled(bodies, collider))
+            .map(|(collider, inter)| (collider.handle(), inter))
+    }
+
+    /// Casts a ray against the colliders of this world, and returns the closest hit.
+    ///
+    /// This is the intersection with the smallest time of impact among those returned by
+    /// `self.interferences_with_ray(ray, groups)`.
+    pub fn cast_ray(&self, ray: &Ray<N>, groups: &CollisionGroups) -> Option<(ColliderHandle, RayIntersection<N>)> {
+        let mut result: Option<(ColliderHandle, RayIntersection<N>)> = None;
+
+        for (handle, inter) in self.interferences_with_ray(ray, groups) {
+            if result.as_ref().map(|r| inter.toi < r.1.toi).unwrap_or(true) {
+                result = Some((handle, inter));
+            }
+        }
+
+        result
+    }
+
+    /// Casts `shape`, starting at `start_pos` and moving with the linear `velocity`, against the colliders of this world.
+    ///
+    /// Returns the first non-sensor collider hit before the time `max_toi`, together with the time and normal of
+    /// the impact. Only the colliders compatible with `groups` are taken into account, and the colliders the shape
+    /// moves away from are ignored. Returns `None` if `velocity` is zero.
+    pub fn cast_shape(
+        &self,
+        shape: &Shape<N>,
+        start_pos: &Isometry<N>,
+        velocity: &Vector<N>,
+        max_toi: N,
+        groups: &CollisionGroups,
+    ) -> Option<(ColliderHandle, TOI<N>)> {
+        self.cast_shape_filtered(shape, start_pos, velocity, max_toi, groups, |_| true)
+    }
+
+    // Same as `self.cast_shape` but ignores the colliders for which `filter` returns `false`.
+    pub(crate) fn cast_shape_filtered(
+        &self,
+        shape: &Shape<N>,
+        start_pos: &Isometry<N>,
+        velocity: &Vector<N>,
+        max_toi: N,
+        groups: &CollisionGroups,
+        filter: impl Fn(&Collider<N>) -> bool,
+    ) -> Option<(ColliderHandle, TOI<N>)> {
+        if velocity.norm_squared().is_zero() {
+            return None;
+        }
+
+        let mut end_pos = *start_pos;
+        end_pos.append_translation_mut(&Translation::from(velocity * max_toi));
+        let aabb = bounding_volume::aabb(shape, start_pos).merged(&bounding_volume::aabb(shape, &end_pos));
+        let mut result: Option<(ColliderHandle, TOI<N>)> = None;
+
+        for collider in self.cworld.interferences_with_aabb(&aabb, groups) {
+            if collider.is_sensor() || !filter(collider) {
+                continue;
+            }
+
+            let toi = try_continue!(query::time_of_impact(
+                start_pos,
+                velocity,
+                shape,
+                collider.position(),
+                &Vector::zeros(),
+                collider.shape().as_ref(),
+            ));
+
+            if toi > max_toi || result.map(|r| r.1.toi <= toi).unwrap_or(false) {
+                continue;
+            }
+
+            // NOTE: the time of impact may be spuriously zero when the shapes slide along each other,
+            // so we ensure they are actually touching at the time of impact. The shape is moved back
+            // slightly first because the contact computation is not reliable for shapes exactly touching.
+            let back_off = self.prediction / velocity.norm();
+            let mut hit_pos = *start_pos;
+            hit_pos.append_translation_mut(&Translation::from(velocity * (toi - back_off).max(N::zero())));
+            let contact = try_continue!(query::contact(
+                &hit_pos,
+                shape,
+                collider.position(),
+                collider.shape().as_ref(),
+                self.prediction * na::convert(2.0),
+            ));
+
+            if velocity.dot(&contact.normal) > N::zero() {
+                let hit = TOI { toi, normal: contact.normal, witness: contact.world2 };
+                result = Some((collider.handle(), hit));
+            }
+        }
+
+        result
+    }
+
+    /// The shape features involved in each contact point between the two specified colliders.
+    ///
+    /// Each pair contains the feature of `handle1`'s shape followed by the feature of `handle2`'s shape,
+    /// as identified by the contact manifold computed during the last execution of `self.step()`.
+    /// Returns `None` if those colliders are not in contact.
+    pub fn contact_features(&self, handle1: ColliderHandle, handle2: ColliderHandle) -> Option<Vec<(FeatureId, FeatureId)>> {
+        let (c1, _, _, manifold) = self.cworld.contact_pair(handle1, handle2, true)?;
+        let swapped = c1.handle() != handle1;
+
+        Some(manifold.contacts().map(|c| {
+            let features = (c.kinematic.feature1(), c.kinematic.feature2());
+            if swapped { (features.1, features.0) } else { features }
+        }).collect())
+    }
+
+    /// The maximum penetration depth between the two specified colliders, and the corresponding contact normal.
+    ///
+    /// The normal points from `handle1` toward `handle2`. The depth is computed from the contact manifold of
+    /// the last execution of `self.step()` and does not include the colliders margins. Returns `None` if those
+    /// colliders are not penetrating.
+    pub fn penetration(&self, handle1: ColliderHandle, handle2: ColliderHandle) -> Option<(N, Unit<Vector<N>>)> {
+        let (c1, _, _, manifold) = self.cworld.contact_pair(handle1, handle2, true)?;
+        let contact = &manifold.deepest_contact()?.contact;
+
+        if contact.depth < N::zero() {
+            return None;
+        }
+
+        if c1.handle() == handle1 {
+            Some((contact.depth, contact.normal))
+        } else {
+            Some((contact.depth, -contact.normal))
+        }
+    }
+
+    /// All the contact points involving the specified collider, computed during the last execution of `self.step()`.
+    ///
+    /// Returns an empty vector if the collider does not exist or is not in contact.
+    pub fn collider_contacts(&self, handle: ColliderHandle) -> Vec<ContactPoint<N>> {
+        let mut result = Vec::new();
+        let others = try_ret!(self.cworld.colliders_in_contact_with(handle), result);
+
+        for other in others {
+            // NOTE: the pairs yielded by `self.cworld.contacts_with` do not tell which collider
+            // is the first one of the contact manifold, so we retrieve each pair explicitly.
+            let (c1, c2, _, manifold) = try_continue!(self.cworld.contact_pair(handle, other.handle(), true));
+            let is_first = c1.handle() == handle;
+            let manifold = ColliderContactManifold::new(c1, c2, manifold);
+
+            result.extend(manifold.contacts().map(|c| {
+                let contact = &c.contact;
+                let tag1 = manifold.material1(c.kinematic.feature1()).surface_tag();
+                let tag2 = manifold.material2(c.kinematic.feature2()).surface_tag();
+
+                if is_first {
+                    ContactPoint {
+                        other: other.handle(),
+                        point: contact.world1,
+                        normal: contact.normal,
+                        depth: contact.depth,
+                        surface_tag: tag1,
+                        other_surface_tag: tag2,
+                    }
+                } else {
+                    ContactPoint {
+                        other: other.handle(),
+                        point: contact.world2,
+                        normal: -contact.normal,
+                        depth: contact.depth,
+                        surface_tag: tag2,
+                        other_surface_tag: tag1,
+                    }
+                }
+            }));
+        }
+
+        result
+    }
+
+    /// The contact manifolds involving the colliders of the specified body, computed during the last execution of
+    /// `self.step()`.
+    ///
+    /// Each item contains the handles of the two colliders in contact, in the order of their contact manifold, so
+    /// either one may be attached to the body.
+    pub fn contacts_with(&self, handle: BodyHandle)
+        -> impl Iterator<Item = (ColliderHandle, ColliderHandle, &ContactManifold<N>)> {
+        self.contact_manifolds().filter_map(move |(b1, b2, c1, c2, manifold)| {
+            if b1 == handle || b2 == handle {
+                Some((c1, c2, manifold))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// All the contact manifolds computed during the last execution of `self.step()`, together with the handles
+    /// of the bodies of the colliders in contact.
+    ///
+    /// Each item contains the handles of the two bodies, the handles of their two colliders, and the contact
+    /// manifold between those colliders. The underlying contact pairs remain accessible through
+    /// `self.collider_world().contact_pairs()`.
+    pub fn contact_manifolds(&self)
+        -> impl Iterator<Item = (BodyHandle, BodyHandle, ColliderHandle, ColliderHandle, &ContactManifold<N>)> {
+        self.cworld.contact_pairs(true).map(|(c1, c2, _, manifold)| {
+            (c1.body(), c2.body(), c1.handle(), c2.handle(), manifold)
+        })
+    }
+
+    /// Whether any collider of the specified body was in contact during the last execution of `self.step()`.
+    pub fn is_body_in_contact(&self, handle: BodyHandle) -> bool {
+        self.contacts_with(handle).next().is_some()
+    }
+
+    /// Whether the specified body rests on another body, i.e., whether it is "on the ground".
+    ///
+    /// This is the case if any of its contacts computed during the last execution of `self.step()` has a normal
+    /// making an angle smaller than or equal to `max_slope` (in radians) with the `up` direction, the normal
+    /// pointing from the other body toward this body. Returns `false` if the body does not exist.
+    pub fn is_body_supported(&self, handle: BodyHandle, up: Unit<Vector<N>>, max_slope: N) -> bool {
+        let min_cos = max_slope.cos();
+
+        self.cworld.body_colliders(handle).any(|collider| {
+            self.collider_contacts(collider.handle()).iter().any(|contact| {
+                -contact.normal.dot(&up) >= min_cos
+            })
+        })
+    }
+}
+
+// The shape scaled uniformly by `factor`, if it is supported.
+fn scaled_shape<N: RealField>(shape: &Shape<N>, factor: N) -> Option<ShapeHandle<N>> {
+    if let Some(ball) = shape.as_shape::<Ball<N>>() {
+        Some(ShapeHandle::new(Ball::new(ball.radius() * factor)))
+    } else if let Some(cuboid) = shape.as_shape::<Cuboid<N>>() {
+        Some(ShapeHandle::new(Cuboid::new(cuboid.half_extents() * factor)))
+    } else if let Some(capsule) = shape.as_shape::<Capsule<N>>() {
+        Some(ShapeHandle::new(Capsule::new(capsule.half_height() * factor, capsule.radius() * factor)))
+    } else {
+        None
+    }
+}
+
+// The key sorting the contact manifolds by the handles of their colliders, whatever their order in the manifold.
+fn contact_manifold_key<N: RealField>(manifold: &ColliderContactManifold<N>) -> (ColliderHandle, ColliderHandle) {
+    let (handle1, handle2) = (manifold.collider1.handle(), manifold.collider2.handle());
+
+    if handle1 < handle2 {
+        (handle1, handle2)
+    } else {
+        (handle2, handle1)
+    }
+}
+
+// Sorts the contact manifolds, and the contacts of each manifold, in an order that does not depend on the broad phase.
+fn sort_contact_manifolds<N: RealField>(manifolds: &mut [ColliderContactManifold<N>]) {
+    manifolds.sort_by_key(contact_manifold_key);
+
+    for manifold in manifolds {
+        manifold.sort_contacts();
+    }
+}
+
+// Empties `buffer` so that its allocation can be reused to store contact manifolds with another lifetime.
+fn recycle_contact_manifolds<'a, 'b, N: RealField>(
+    mut buffer: Vec<ColliderContactManifold<'a, N>>,
+) -> Vec<ColliderContactManifold<'b, N>> {
+    buffer.clear();
+    // NOTE: the standard library performs this collection in-place since both element types have the same layout.
+    buffer.into_iter().map(|_| unreachable!()).collect()
+}
+
+// Scales down the linear and angular parts of the velocity of `body` that exceed the given norms.
+//
+// Returns `true` if any part was scaled down. The parts with a non-finite norm are reset to zero. The generalized
+// velocities of a multibody are the velocities of its joints, so they are left untouched.
+fn clamp_velocity<N: RealField>(body: &mut Body<N>, max_linear: Option<N>, max_angular: Option<N>) -> bool {
+    let is_rigid = body.downcast_ref::<RigidBody<N>>().is_some();
+
+    if !is_rigid && body.deformed_positions().is_none() {
+        return false;
+    }
+
+    let ndofs = body.ndofs();
+    let mut clamped = false;
+    let mut i = 0;
+
+    // A rigid body velocity is a linear part followed by an angular part, while a deformable
+    // body velocity is the concatenation of the linear velocities of its nodes.
+    while i < ndofs {
+        let (len, max) = if is_rigid && i == DIM {
+            (ANGULAR_DIM, max_angular)
+        } else {
+            (DIM, max_linear)
+        };
+
+        if let Some(max) = max {
+            let norm = body.generalized_velocity().rows(i, len).norm();
+
+            // This also catches the NaN norms, which fail every comparison.
+            if !(norm <= max) {
+                let mut velocities = body.generalized_velocity_mut();
+                let mut velocity = velocities.rows_mut(i, len);
+
+                if norm.is_finite() {
+                    velocity.apply(|v| v * max / norm);
+                } else {
+                    // A non-finite velocity cannot be scaled down, so it is reset instead.
+                    velocity.fill(N::zero());
+                }
+
+                clamped = true;
+            }
+        }
+
+        i += len;
+    }
+
+    clamped
+}
+
+impl<N: RealField> Default for World<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::bounding_volume::AABB;
+    use ncollide::query::Ray;
+    use ncollide::shape::{Ball, Cuboid, FeatureId, ShapeHandle};
+    use ncollide::world::{CollisionGroups, GeometricQueryType};
+    use crate::detection::ColliderContactManifold;
+    use crate::force_generator::ConstantAcceleration;
+    use crate::joint::{FixedConstraint, RevoluteConstraint};
+    use crate::material::{BasicMaterial, MaterialHandle, SurfaceTag};
+    use crate::math::{AngularVector, Inertia, Isometry, Point, Rotation, Translation, Vector, Velocity, ANGULAR_DIM, DIM};
+    use crate::object::{BodyHandle, BodyPartHandle, BodyStatus, ColliderDesc, RigidBodyDesc};
+    use crate::solver::IntegrationParameters;
+    use crate::world::World;
+
+    #[test]
+    fn world_is_send_sync() {
+        let _ = Box::new(World::<f32>::new()) as Box<Send + Sync>;
+    }
+
+    #[test]
+    fn box_on_ground_contact_features() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world)
+            .handle();
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        // The vertices of the bottom face of the box (i.e. with the second bit of
+        // their id set for a negative `y` coordinate) touch the top face of the ground.
+        let features = world.contact_features(collider, ground).unwrap();
+        assert!(!features.is_empty());
+        for feature in &features {
+            match *feature {
+                (FeatureId::Vertex(i), FeatureId::Face(1)) => assert!(i & 0b10 != 0),
+                _ => panic!("Unexpected contact features: {:?}", feature),
+            }
+        }
+
+        let swapped = world.contact_features(ground, collider).unwrap();
+        assert!(swapped.iter().zip(features.iter()).all(|(a, b)| a.0 == b.1 && a.1 == b.0));
+    }
+
+    // A box resting on a flat ground with the given material, with a gravity inclined as if the ground was
+    // a slope with the given angle, so the box slides toward the positive `x` axis.
+    fn slope_world(angle: f64, material: BasicMaterial<f64>) -> (World<f64>, BodyHandle) {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::x() * (9.81 * angle.sin()) - Vector::y() * (9.81 * angle.cos()));
+        let material = MaterialHandle::new(material);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .material(material.clone())
+            .build(&mut world);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0).material(material);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.52)
+            .build(&mut world)
+            .handle();
+
+        (world, body)
+    }
+
+    #[test]
+    fn static_friction_holds_resting_body() {
+        // A slope with a `0.5` gradient.
+        let slide = |static_friction: Option<f64>, initial_speed: f64| {
+            let mut material = BasicMaterial::new(0.0, 0.3);
+            material.static_friction = static_friction;
+            let (mut world, body) = slope_world(0.5f64.atan(), material);
+            world.rigid_body_mut(body).unwrap().set_linear_velocity(Vector::x() * initial_speed);
+
+            for _ in 0..60 {
+                world.step();
+            }
+
+            world.rigid_body(body).unwrap().position().translation.vector.x
+        };
+
+        // The dynamic friction coefficient is too small to hold the box.
+        assert!(slide(None, 0.0) > 0.5);
+        // The static friction coefficient holds the box if it is not already sliding.
+        assert!(slide(Some(0.8), 0.0).abs() < 1.0e-3);
+        assert!(slide(Some(0.8), 1.0) > 1.5);
+    }
+
+    #[test]
+    fn material_static_friction_threshold_catches_slow_sliding_body() {
+        // A box sliding down a slope with a `0.5` gradient.
+        let slide = |material: BasicMaterial<f64>| {
+            let (mut world, body) = slope_world(0.5f64.atan(), material);
+            world.rigid_body_mut(body).unwrap().set_linear_velocity(Vector::x());
+
+            for _ in 0..60 {
+                world.step();
+            }
+
+            let body = world.rigid_body(body).unwrap();
+            (body.position().translation.vector.x, body.velocity().linear.x)
+        };
+
+        // With the default threshold, the box is already sliding so the dynamic friction keeps it sliding.
+        let mut material = BasicMaterial::new(0.0, 0.3);
+        material.static_friction = Some(0.8);
+        let (x, vel) = slide(material);
+        assert!(x > 1.5 && vel > 1.0, "x: {}, vel: {}", x, vel);
+
+        // Below the threshold of the material, the static friction slows the box down until it stops.
+        let (x, vel) = slide(BasicMaterial::new(0.0, 0.3).with_static_friction(0.8, 2.0));
+        assert!(x < 0.3 && vel.abs() < 1.0e-3, "x: {}, vel: {}", x, vel);
+    }
+
+    #[test]
+    fn sensor_events_report_entering_and_leaving_colliders() {
+        let mut world = World::<f64>::new();
+
+        let sensor = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(1.0))))
+            .sensor(true)
+            .build(&mut world)
+            .handle();
+        // A ground collider, overlapping the sensor, that is not moving.
+        let _ = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(0.5))))
+            .translation(Vector::y() * 1.2)
+            .build(&mut world);
+
+        // A ball crossing the sensor.
+        let ball_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&ball_desc)
+            .translation(Vector::x() * -3.0)
+            .velocity(Velocity::new_with_vectors(Vector::x() * 6.0, na::zero()))
+            .build(&mut world)
+            .handle();
+        let ball = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        let mut events = Vec::new();
+        for _ in 0..90 {
+            world.step();
+            events.extend(world.sensor_events());
+        }
+
+        assert_eq!(events, vec![(sensor, ball, true), (sensor, ball, false)]);
+    }
+
+    #[test]
+    fn sensors_iterator_skips_solid_colliders() {
+        let mut world = World::<f64>::new();
+        let shape = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let solid_desc = ColliderDesc::new(shape.clone());
+        let sensor_desc = ColliderDesc::new(shape).sensor(true);
+
+        let solid1 = solid_desc.build(&mut world).handle();
+        let sensor1 = sensor_desc.build(&mut world).handle();
+        let body = RigidBodyDesc::new()
+            .collider(&solid_desc)
+            .collider(&sensor_desc)
+            .build(&mut world)
+            .handle();
+        let sensor2 = world.collider_world().body_colliders(body).find(|c| c.is_sensor()).unwrap().handle();
+        let solid2 = world.collider_world().body_colliders(body).find(|c| !c.is_sensor()).unwrap().handle();
+
+        let mut sensors: Vec<_> = world.sensors().collect();
+        sensors.sort();
+        let mut expected = vec![sensor1, sensor2];
+        expected.sort();
+        assert_eq!(sensors, expected);
+
+        assert!(world.is_sensor(sensor1) && world.is_sensor(sensor2));
+        assert!(!world.is_sensor(solid1) && !world.is_sensor(solid2));
+        world.remove_colliders(&[sensor1]);
+        assert!(!world.is_sensor(sensor1));
+        assert_eq!(world.sensors().collect::<Vec<_>>(), vec![sensor2]);
+    }
+
+    #[test]
+    fn contacts_with_body_on_ground() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world)
+            .handle();
+
+        // A box resting on the ground, and a ball falling from far above.
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let resting = RigidBodyDesc::new()
+            .collider(&ColliderDesc::new(cuboid).density(1.0))
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(resting).next().unwrap().handle();
+        let falling = RigidBodyDesc::new()
+            .collider(&ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0))
+            .translation(Vector::y() * 20.0)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let contacts: Vec<_> = world.contacts_with(resting).collect();
+        assert_eq!(contacts.len(), 1);
+        let (c1, c2, manifold) = contacts[0];
+        assert!((c1, c2) == (collider, ground) || (c1, c2) == (ground, collider));
+        assert_eq!(manifold.len(), 1 << (DIM - 1));
+
+        assert!(world.is_body_in_contact(resting));
+        assert!(!world.is_body_in_contact(falling));
+        assert_eq!(world.contacts_with(falling).count(), 0);
+    }
+
+    // Throws a fast ball along the `x` axis toward a static wall, passing close to a dynamic ball on its way.
+    //
+    // Returns the final position of the thrown ball and the final speed of the other ball.
+    fn throw_ball_past_ball(dynamic_contacts_prediction: Option<f64>) -> (f64, f64) {
+        let mut world = World::<f64>::new();
+        world.integration_parameters_mut().dynamic_contacts_prediction = dynamic_contacts_prediction;
+
+        let mut half_extents = Vector::repeat(5.0);
+        half_extents.x = 0.05;
+        let _ = ColliderDesc::new(ShapeHandle::new(Cuboid::new(half_extents)))
+            .translation(Vector::x() * 20.0)
+            .build(&mut world);
+
+        // The thrown ball moves by 2m at each timestep, so it would go through the wall without
+        // a prediction distance large enough.
+        let ball = ColliderDesc::new(ShapeHandle::new(Ball::new(0.25))).density(1.0);
+        let thrown = RigidBodyDesc::new()
+            .collider(&ball)
+            .velocity(Velocity::new_with_vectors(Vector::x() * 120.0, na::zero()))
+            .build(&mut world)
+            .handle();
+        let mut position = Vector::x() * 3.0;
+        position.y = 0.6;
+        let other = RigidBodyDesc::new()
+            .collider(&ball)
+            .translation(position)
+            .build(&mut world)
+            .handle();
+        world.set_prediction(3.0);
+
+        for _ in 0..30 {
+            world.step();
+        }
+
+        let x = world.rigid_body(thrown).unwrap().position().translation.vector.x;
+        let speed = world.rigid_body(other).unwrap().velocity().linear.norm();
+        (x, speed)
+    }
+
+    #[test]
+    fn dynamic_contacts_prediction() {
+        // The large prediction generates a speculative contact between the balls although they never touch.
+        let (x, speed) = throw_ball_past_ball(None);
+        assert!(x < 19.8, "x: {}", x);
+        assert!(speed > 1.0, "speed: {}", speed);
+
+        // The prediction of the contacts between the balls is restricted, but not the one with the wall.
+        let (x, speed) = throw_ball_past_ball(Some(0.002));
+        assert!(x < 19.8, "x: {}", x);
+        assert!(speed < 1.0e-6, "speed: {}", speed);
+    }
+
+    #[test]
+    fn contact_manifold_len_ignores_contacts_beyond_max_separation() {
+        let mut world = World::<f64>::new();
+        world.set_prediction(1.0);
+
+        // Two balls 0.5m apart, so they only have a speculative contact.
+        let ball = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let body1 = RigidBodyDesc::new().collider(&ball).build(&mut world).handle();
+        let body2 = RigidBodyDesc::new()
+            .collider(&ball)
+            .translation(Vector::x() * 1.5)
+            .build(&mut world)
+            .handle();
+        let handle1 = world.collider_world().body_colliders(body1).next().unwrap().handle();
+        let handle2 = world.collider_world().body_colliders(body2).next().unwrap().handle();
+        world.step();
+
+        let (c1, c2, _, manifold) = world.collider_world().contact_pair(handle1, handle2, false).unwrap();
+        let mut manifold = ColliderContactManifold::new(c1, c2, manifold);
+        assert_eq!(manifold.len(), 1);
+        assert!(manifold.deepest_contact().is_some());
+
+        manifold.max_separation = Some(0.1);
+        assert_eq!(manifold.contacts().count(), 0);
+        assert_eq!(manifold.len(), 0);
+        assert!(manifold.deepest_contact().is_none());
+    }
+
+    #[test]
+    fn momentum_is_conserved_by_collisions() {
+        let mut world = World::<f64>::new();
+        let ball = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5)));
+
+        // A spinning ball hitting another ball off-center.
+        let mut angvel = AngularVector::zeros();
+        angvel[ANGULAR_DIM - 1] = 1.0;
+        let _ = RigidBodyDesc::new()
+            .collider(&ball)
+            .local_inertia(Inertia::new(2.0, na::one()))
+            .translation(Vector::x() * -2.0)
+            .velocity(Velocity::new_with_vectors(Vector::x() * 3.0, angvel))
+            .build(&mut world);
+        let mut position = Vector::x() * 2.0;
+        position.y = 0.3;
+        let _ = RigidBodyDesc::new()
+            .collider(&ball)
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .translation(position)
+            .build(&mut world);
+
+        let about = Point::from(Vector::y());
+        let energy = world.total_kinetic_energy();
+        let linear = world.linear_momentum();
+        let angular = world.angular_momentum(&about);
+        assert_relative_eq!(energy, 0.5 * 2.0 * 9.0 + 0.5, epsilon = 1.0e-10);
+        assert_relative_eq!(linear, Vector::x() * 6.0, epsilon = 1.0e-10);
+        assert_relative_eq!(angular[ANGULAR_DIM - 1], 1.0 + 6.0, epsilon = 1.0e-10);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        // The balls collided, without restitution.
+        assert!(world.total_kinetic_energy() < energy - 1.0);
+        assert_relative_eq!(world.linear_momentum(), linear, epsilon = 1.0e-6);
+        // The contact impulses are applied at points separated by the margins of the colliders.
+        assert_relative_eq!(world.angular_momentum(&about), angular, epsilon = 5.0e-2);
+    }
+
+    #[test]
+    fn contact_manifolds_with_body_handles() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        // A ball resting on a box resting on the ground.
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let boxed = RigidBodyDesc::new()
+            .collider(&ColliderDesc::new(cuboid).density(1.0))
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+        let ball = RigidBodyDesc::new()
+            .collider(&ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0))
+            .translation(Vector::y() * 1.5)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let pairs: Vec<_> = world.contact_manifolds().map(|(b1, b2, c1, c2, manifold)| {
+            assert_eq!(world.collider(c1).unwrap().anchor().body(), b1);
+            assert_eq!(world.collider(c2).unwrap().anchor().body(), b2);
+            assert!(manifold.len() > 0);
+            (b1, b2)
+        }).collect();
+
+        assert_eq!(pairs.len(), 2);
+        for (b1, b2) in &[(BodyHandle::ground(), boxed), (boxed, ball)] {
+            assert!(pairs.contains(&(*b1, *b2)) || pairs.contains(&(*b2, *b1)));
+        }
+    }
+
+    #[test]
+    fn contacts_report_surface_tags() {
+        const GRASS: SurfaceTag = 1;
+        const METAL: SurfaceTag = 2;
+
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let grass = MaterialHandle::new(BasicMaterial::default().with_surface_tag(GRASS));
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .material(grass)
+            .build(&mut world)
+            .handle();
+
+        let metal = MaterialHandle::new(BasicMaterial::default().with_surface_tag(METAL));
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = RigidBodyDesc::new()
+            .collider(&ColliderDesc::new(cuboid).density(1.0).material(metal))
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let contacts = world.collider_contacts(collider);
+        assert!(!contacts.is_empty());
+        assert!(contacts.iter().all(|c| c.surface_tag == Some(METAL) && c.other_surface_tag == Some(GRASS)));
+
+        let ground_contacts = world.collider_contacts(ground);
+        assert!(ground_contacts.iter().all(|c| c.surface_tag == Some(GRASS) && c.other_surface_tag == Some(METAL)));
+    }
+
+    #[test]
+    fn collider_contacts_of_box_on_ground() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world)
+            .handle();
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        // One contact at each vertex of the bottom face of the box, resting at a distance equal to the sum
+        // of the margins of the colliders.
+        let gap = ColliderDesc::<f64>::default_margin() * 2.0;
+        let contacts = world.collider_contacts(collider);
+        assert_eq!(contacts.len(), 1 << (DIM - 1));
+
+        for contact in &contacts {
+            assert_eq!(contact.other, ground);
+            assert!(relative_eq!(contact.point.y, gap, epsilon = 2.0e-3));
+            assert!(contact.point.coords.iter().enumerate().all(|(i, x)| i == 1 || relative_eq!(x.abs(), 0.5, epsilon = 1.0e-3)));
+            assert!(relative_eq!(contact.normal, -Vector::y_axis(), epsilon = 1.0e-6));
+            assert!(relative_eq!(contact.depth, -contact.point.y, epsilon = 1.0e-6));
+        }
+
+        // The same contacts seen from the ground.
+        let ground_contacts = world.collider_contacts(ground);
+        assert_eq!(ground_contacts.len(), contacts.len());
+        assert!(ground_contacts.iter().all(|c| c.other == collider && relative_eq!(c.normal, Vector::y_axis(), epsilon = 1.0e-6)));
+    }
+
+    #[test]
+    fn max_constraints_is_respected() {
+        let build = |max_constraints| {
+            let mut world = World::<f64>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            world.integration_parameters_mut().max_constraints = max_constraints;
+
+            let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = ColliderDesc::new(ground_shape)
+                .translation(Vector::y() * -10.0)
+                .build(&mut world);
+
+            // A column of boxes, each in contact with the next one.
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+
+            for i in 0..5 {
+                let _ = RigidBodyDesc::new()
+                    .collider(&collider_desc)
+                    .translation(Vector::y() * (0.5 + i as f64 * 1.02))
+                    .build(&mut world);
+            }
+
+            // The largest number of constraints and the total number of skipped contacts over all the steps.
+            let mut max_nconstraints = 0;
+            let mut nskipped_contacts = 0;
+
+            for _ in 0..5 {
+                world.step();
+                max_nconstraints = max_nconstraints.max(world.performance_counters().nconstraints());
+                nskipped_contacts += world.performance_counters().nskipped_contacts();
+            }
+
+            (world, max_nconstraints, nskipped_contacts)
+        };
+
+        let (_, nconstraints, nskipped_contacts) = build(None);
+        assert_eq!(nskipped_contacts, 0);
+        assert!(nconstraints > 0);
+
+        // The constraints are counted exactly, so a budget matching the actual number of constraints is enough.
+        let (_, _, nskipped_contacts) = build(Some(nconstraints));
+        assert_eq!(nskipped_contacts, 0);
+
+        let (world, _, _) = build(Some(nconstraints / 2));
+        assert!(world.performance_counters().nconstraints() <= nconstraints / 2);
+        assert!(world.performance_counters().nconstraints() > 0);
+        assert!(world.performance_counters().nskipped_contacts() > 0);
+    }
+
+    #[test]
+    fn disabled_force_generators_are_not_applied() {
+        let mut world = World::<f64>::new();
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .build(&mut world)
+            .handle();
+
+        let mut wind = ConstantAcceleration::new(Vector::x(), Velocity::zero().angular);
+        wind.add_body_part(BodyPartHandle(body, 0));
+        let wind = world.add_force_generator(wind);
+        let mut gust = ConstantAcceleration::new(Vector::x() * 10.0, Velocity::zero().angular);
+        gust.add_body_part(BodyPartHandle(body, 0));
+        let gust = world.add_force_generator(gust);
+
+        let handles: Vec<_> = world.force_generators().map(|(handle, _)| handle).collect();
+        assert_eq!(handles, vec![wind, gust]);
+
+        // Only the wind pushes the body while the gust is disabled.
+        world.set_force_generator_enabled(gust, false);
+        assert!(!world.is_force_generator_enabled(gust));
+        assert!(world.is_force_generator_enabled(wind));
+        world.step();
+        let dt = world.timestep();
+        let velocity = world.rigid_body(body).unwrap().velocity().linear.x;
+        assert_relative_eq!(velocity, dt, epsilon = 1.0e-10);
+
+        world.set_force_generator_enabled(gust, true);
+        world.step();
+        let velocity = world.rigid_body(body).unwrap().velocity().linear.x;
+        assert_relative_eq!(velocity, dt * 12.0, epsilon = 1.0e-10);
+
+        // The disabled state does not leak to a generator reusing the handle of a removed one.
+        world.set_force_generator_enabled(gust, false);
+        world.retain_force_generators(|handle, _| handle != gust);
+        assert_eq!(world.force_generators().count(), 1);
+        let gust = world.add_force_generator(ConstantAcceleration::new(Vector::x(), Velocity::zero().angular));
+        assert!(world.is_force_generator_enabled(gust));
+    }
+
+    #[test]
+    fn body_kept_awake_while_forced() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        // Both bodies float at an equilibrium between the gravity and the force generator.
+        let mut acceleration = ConstantAcceleration::new(Vector::y() * 9.81, Velocity::zero().angular);
+        let forced = RigidBodyDesc::new().build(&mut world).handle();
+        let unforced = RigidBodyDesc::new().build(&mut world).handle();
+        acceleration.add_body_part(BodyPartHandle(forced, 0));
+        acceleration.add_body_part(BodyPartHandle(unforced, 0));
+        let _ = world.add_force_generator(acceleration);
+
+        world.body_mut(forced).unwrap().keep_awake_while_forced(true);
+
+        for _ in 0..200 {
+            world.step();
+        }
+
+        assert!(world.body(forced).unwrap().is_active());
+        assert!(!world.body(unforced).unwrap().is_active());
+    }
+
+    #[test]
+    fn overlapping_boxes_penetration() {
+        let mut world = World::<f64>::new();
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let ground = ColliderDesc::new(cuboid.clone())
+            .build(&mut world)
+            .handle();
+
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.9)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        world.collider_world_mut().update();
+
+        let (depth, normal) = world.penetration(ground, collider).unwrap();
+        assert!(relative_eq!(depth, 0.1, epsilon = 1.0e-7));
+        assert!(relative_eq!(*normal, Vector::y(), epsilon = 1.0e-7));
+
+        let (depth, normal) = world.penetration(collider, ground).unwrap();
+        assert!(relative_eq!(depth, 0.1, epsilon = 1.0e-7));
+        assert!(relative_eq!(*normal, -Vector::y(), epsilon = 1.0e-7));
+    }
+
+    #[test]
+    fn cast_ball_through_gap() {
+        let mut world = World::<f64>::new();
+        let block = ShapeHandle::new(Cuboid::new(Vector::repeat(1.0)));
+        // Two blocks separated by a gap of width 1 around `y = 0`, and a wall behind them.
+        let _ = ColliderDesc::new(block.clone())
+            .translation(Vector::y() * 1.5)
+            .build(&mut world);
+        let lower = ColliderDesc::new(block.clone())
+            .translation(Vector::y() * -1.5)
+            .build(&mut world)
+            .handle();
+        let wall = ColliderDesc::new(block)
+            .translation(Vector::x() * 5.0)
+            .build(&mut world)
+            .handle();
+        world.collider_world_mut().update();
+
+        let ball = Ball::new(0.4);
+        let groups = CollisionGroups::new();
+        let start = Isometry::new(Vector::x() * -3.0, na::zero());
+
+        let (handle, hit) = world.cast_shape(&ball, &start, &Vector::x(), 10.0, &groups).unwrap();
+        assert_eq!(handle, wall);
+        assert_relative_eq!(hit.toi, 6.6, epsilon = 1.0e-5);
+        assert_relative_eq!(*hit.normal, Vector::x(), epsilon = 1.0e-5);
+
+        // The wall is too far away.
+        assert!(world.cast_shape(&ball, &start, &Vector::x(), 5.0, &groups).is_none());
+        // The ball does not move.
+        assert!(world.cast_shape(&ball, &start, &Vector::zeros(), 10.0, &groups).is_none());
+
+        // A ball not aligned with the gap.
+        let start = Isometry::new(Vector::x() * -3.0 - Vector::y() * 0.3, na::zero());
+        let (handle, hit) = world.cast_shape(&ball, &start, &Vector::x(), 10.0, &groups).unwrap();
+        assert_eq!(handle, lower);
+        assert!(hit.toi < 2.0);
+    }
+
+    // Number of steps before a plank spinning toward the ground lies flat on it, i.e., before its whole bottom face
+    // touches the ground, given the angular prediction of the world.
+    fn steps_until_plank_lies_flat(angular_prediction: f64) -> usize {
+        let mut world = World::<f64>::new();
+
+        // The ground is dynamic because no contact is computed between a kinematic body and a static collider.
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground_desc = ColliderDesc::new(ground_shape).density(1.0);
+        let ground_body = RigidBodyDesc::new()
+            .collider(&ground_desc)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world)
+            .handle();
+        let ground = world.collider_world().body_colliders(ground_body).next().unwrap().handle();
+
+        // The plank spins around its edge that lies slightly above the ground.
+        let mut angvel = AngularVector::zeros();
+        let last = angvel.len() - 1;
+        angvel[last] = -0.5;
+        let velocity = Velocity::new_with_vectors(na::zero(), angvel);
+        let position = Isometry::from_parts(
+            Translation::from(Vector::y() * 0.005),
+            Rotation::new(velocity.angular * -0.6),
+        );
+
+        let mut half_extents = Vector::repeat(0.5);
+        half_extents.x = 1.0;
+        half_extents.y = 0.05;
+        let plank_shape = ShapeHandle::new(Cuboid::new(half_extents));
+        let collider_desc = ColliderDesc::new(plank_shape)
+            .translation(Vector::x() * 1.0 + Vector::y() * 0.05);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .position(position)
+            .velocity(velocity)
+            .status(BodyStatus::Kinematic)
+            .build(&mut world)
+            .handle();
+        let plank = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        world.set_prediction(0.3);
+        world.set_angular_prediction(angular_prediction);
+
+        let num_face_contacts = 1 << (DIM - 1);
+
+        for i in 0..100 {
+            world.step();
+
+            if world.contact_features(plank, ground).map(|f| f.len()).unwrap_or(0) >= num_face_contacts {
+                return i;
+            }
+        }
+
+        panic!("The plank never touched the ground with its bottom face.")
+    }
+
+    #[test]
+    fn larger_angular_prediction_generates_contacts_sooner() {
+        let default = steps_until_plank_lies_flat(World::<f64>::new().angular_prediction());
+        let larger = steps_until_plank_lies_flat(0.2);
+        assert!(larger < default);
+    }
+
+    #[test]
+    fn world_prediction_keeps_explicit_collider_predictions() {
+        let mut world = World::<f64>::new();
+        let shape = ShapeHandle::new(Ball::new(1.0));
+        let prediction = |world: &World<f64>, handle| match world.collider(handle).unwrap().query_type() {
+            GeometricQueryType::Contacts(linear, angular) => (linear - ColliderDesc::<f64>::default_margin(), angular),
+            GeometricQueryType::Proximity(linear) => (linear, 0.0),
+        };
+
+        let default = ColliderDesc::new(shape.clone()).build(&mut world).handle();
+        let explicit = ColliderDesc::new(shape.clone())
+            .linear_prediction(0.5)
+            .build(&mut world)
+            .handle();
+        let overridden = ColliderDesc::new(shape.clone()).build(&mut world).handle();
+        world.collider_world_mut().set_prediction(overridden, 0.4, 0.3);
+
+        world.set_prediction(0.1);
+        world.set_angular_prediction(0.2);
+        let later = ColliderDesc::new(shape).build(&mut world).handle();
+
+        assert_relative_eq!(prediction(&world, default).0, 0.1, epsilon = 1.0e-10);
+        assert_relative_eq!(prediction(&world, default).1, 0.2, epsilon = 1.0e-10);
+        assert_relative_eq!(prediction(&world, later).0, 0.1, epsilon = 1.0e-10);
+        assert_relative_eq!(prediction(&world, later).1, 0.2, epsilon = 1.0e-10);
+        assert_relative_eq!(prediction(&world, explicit).0, 0.5, epsilon = 1.0e-10);
+        assert_relative_eq!(prediction(&world, overridden).0, 0.4, epsilon = 1.0e-10);
+        assert_relative_eq!(prediction(&world, overridden).1, 0.3, epsilon = 1.0e-10);
+    }
+
+    #[test]
+    fn ball_bounces_on_min_restitution_collider() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .min_restitution(0.8)
+            .build(&mut world);
+
+        // The ball has the default material, without any restitution.
+        let rad = 0.5;
+        let drop_height = 2.0;
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(rad))).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * (drop_height + rad))
+            .build(&mut world)
+            .handle();
+
+        let mut bounced = false;
+        let mut bounce_height = 0.0f64;
+
+        for _ in 0..150 {
+            world.step();
+            let rb = world.rigid_body(body).unwrap();
+            bounced = bounced || rb.velocity().linear.y > 0.0;
+
+            if bounced {
+                bounce_height = bounce_height.max(rb.position().translation.vector.y - rad);
+            }
+        }
+
+        assert!(bounce_height > drop_height * 0.5);
+    }
+
+    #[test]
+    fn restitution_decays_with_bounces() {
+        use crate::material::{BasicMaterial, MaterialHandle};
+
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .material(MaterialHandle::new(BasicMaterial::new(0.9, 0.0)))
+            .build(&mut world);
+
+        // Two balls with the same restitution, but the second one loses half of it at each bounce.
+        let rad = 0.5;
+        let drop_height = 2.0;
+        let mut decaying = BasicMaterial::new(0.9, 0.0);
+        decaying.restitution_decay = 0.5;
+        let materials = [BasicMaterial::new(0.9, 0.0), decaying];
+        let bodies: Vec<_> = materials.iter().enumerate().map(|(i, material)| {
+            let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(rad)))
+                .density(1.0)
+                .material(MaterialHandle::new(*material));
+            RigidBodyDesc::new()
+                .collider(&collider_desc)
+                .translation(Vector::x() * (i as f64 * 4.0) + Vector::y() * (drop_height + rad))
+                .build(&mut world)
+                .handle()
+        }).collect();
+
+        // The heights reached by each ball at the apex of each bounce.
+        let mut apexes = vec![Vec::new(), Vec::new()];
+        let mut rising = [false, false];
+
+        for _ in 0..300 {
+            world.step();
+
+            for i in 0..2 {
+                let rb = world.rigid_body(bodies[i]).unwrap();
+                let vel = rb.velocity().linear.y;
+
+                if rising[i] && vel <= 0.0 {
+                    apexes[i].push(rb.position().translation.vector.y - rad);
+                }
+                rising[i] = vel > 0.0;
+            }
+        }
+
+        let (constant, decaying) = (&apexes[0], &apexes[1]);
+        assert!(constant.len() >= 2 && decaying.len() >= 2);
+        // The first bounces are identical.
+        assert_relative_eq!(constant[0], decaying[0], epsilon = 1.0e-3);
+        // The restitution of the next bounces is smaller.
+        assert!(decaying[1] < constant[1] * 0.5);
+    }
+
+    #[test]
+    fn larger_margin_generates_contacts_earlier() {
+        let mut world = World::<f64>::new();
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world)
+            .handle();
+
+        // A ball slightly above the ground.
+        let rad = 0.5;
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(rad))).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * (rad + 0.05))
+            .build(&mut world)
+            .handle();
+        let ball = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        world.step();
+        assert_eq!(world.collider_margin(ball), Some(ColliderDesc::<f64>::default_margin()));
+        assert!(world.contact_features(ball, ground).map(|c| c.is_empty()).unwrap_or(true));
+
+        world.set_collider_margin(ball, 0.1);
+        world.step();
+        assert_eq!(world.collider_margin(ball), Some(0.1));
+        assert!(!world.contact_features(ball, ground).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ball_jumps_through_one_way_platform() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let platform_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(0.1) + Vector::x() * 5.0));
+        let _ = ColliderDesc::new(platform_shape)
+            .translation(Vector::y() * 2.0)
+            .one_way(Some(Vector::y_axis()))
+            .build(&mut world);
+
+        // A ball thrown up from below the platform.
+        let rad = 0.5;
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(rad))).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .velocity(Velocity::new_with_vectors(Vector::y() * 8.0, na::zero()))
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..180 {
+            world.step();
+        }
+
+        // The ball went through the platform and landed on top of it.
+        let rb = world.rigid_body(body).unwrap();
+        assert_relative_eq!(rb.position().translation.vector.y, 2.1 + rad, epsilon = 0.05);
+        assert!(rb.velocity().linear.norm() < 1.0e-2);
+    }
+
+    #[test]
+    fn world_aabb_encloses_all_colliders() {
+        let mut world = World::<f64>::new();
+        assert!(world.world_aabb().is_none());
+
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc);
+        let _ = rb_desc.set_translation(Vector::repeat(-3.0)).build(&mut world);
+        let _ = rb_desc.set_translation(Vector::repeat(4.0)).build(&mut world);
+        let _ = rb_desc.set_translation(Vector::x() * 10.0).build(&mut world);
+
+        let aabb = world.world_aabb().unwrap();
+        let expected_mins = Vector::repeat(-3.5);
+        let mut expected_maxs = Vector::repeat(4.5);
+        expected_maxs.x = 10.5;
+
+        assert!(relative_eq!(aabb.mins().coords, expected_mins, epsilon = 1.0e-7));
+        assert!(relative_eq!(aabb.maxs().coords, expected_maxs, epsilon = 1.0e-7));
+    }
+
+    #[test]
+    fn independent_stacks_are_separate_islands() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc).sleep_threshold(None);
+        let mut stacks = Vec::new();
+
+        // Three stacks of two boxes lying on the same static ground.
+        for i in 0..3 {
+            let x = i as f64 * 3.0;
+            let bottom = rb_desc.set_translation(Vector::x() * x + Vector::y() * 0.5).build(&mut world).handle();
+            let top = rb_desc.set_translation(Vector::x() * x + Vector::y() * 1.5).build(&mut world).handle();
+            stacks.push((bottom, top));
+        }
+
+        // A kinematic body touching the first two stacks.
+        let _ = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::x() * 1.5 + Vector::y() * 0.5)
+            .status(BodyStatus::Kinematic)
+            .build(&mut world);
+
+        for _ in 0..5 {
+            world.step();
+        }
+
+        assert_eq!(world.num_islands(), 3);
+
+        for (bottom, top) in &stacks {
+            let island = (0..world.num_islands())
+                .map(|i| world.island(i))
+                .find(|island| island.contains(bottom))
+                .unwrap();
+            assert_eq!(island.len(), 2);
+            assert!(island.contains(top));
+        }
+
+        // A joint merges the islands of the bodies it is attached to.
+        let (b1, b2) = (stacks[1].1, stacks[2].1);
+        let _ = world.add_constraint(FixedConstraint::new(
+            BodyPartHandle(b1, 0),
+            BodyPartHandle(b2, 0),
+            Point::origin(),
+            Rotation::identity(),
+            Point::from(Vector::x() * -3.0),
+            Rotation::identity(),
+        ));
+        world.step();
+        assert_eq!(world.num_islands(), 2);
+    }
+
+    #[test]
+    fn solver_buffers_are_reused() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world);
+
+        let rad = 0.5;
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(rad)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let shift = (rad + collider_desc.get_margin()) * 2.0;
+        let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc).sleep_threshold(None);
+
+        for i in 0..3 {
+            for j in 0..3 {
+                let _ = rb_desc
+                    .set_translation(Vector::x() * (i as f64 * shift) + Vector::y() * (shift / 2.0 + j as f64 * shift))
+                    .build(&mut world);
+            }
+        }
+
+        // Let the contacts settle so the number of constraints becomes stable.
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let buffers = |world: &World<f64>| {
+            let constraints = &world.solver.constraints().velocity;
+            (constraints.unilateral_ground.as_ptr(), constraints.unilateral.as_ptr(),
+             constraints.bilateral_ground.as_ptr(), constraints.bilateral.as_ptr())
+        };
+        let expected = buffers(&world);
+        assert!(world.solver.constraints().velocity.len() > 0);
+
+        for _ in 0..10 {
+            world.step();
+            assert_eq!(buffers(&world), expected);
+        }
+    }
+
+    #[test]
+    fn solver_constraints_are_ordered_deterministically() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.set_deterministic(true);
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc).sleep_threshold(None);
+        let mut build = |world: &mut World<f64>, at: Vector<f64>| rb_desc.set_translation(at).build(world).handle();
+
+        // Two boxes on the ground, welded together, and a third box resting on the first one.
+        let b1 = build(&mut world, Vector::y() * 0.5);
+        let b2 = build(&mut world, Vector::x() * 2.0 + Vector::y() * 0.5);
+        let b3 = build(&mut world, Vector::y() * 1.5);
+        let _ = world.add_constraint(FixedConstraint::new(
+            BodyPartHandle(b1, 0),
+            BodyPartHandle(b2, 0),
+            Point::origin(),
+            Rotation::identity(),
+            Point::from(Vector::x() * -2.0),
+            Rotation::identity(),
+        ));
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let id = |b| world.body(b).unwrap().companion_id();
+        let (id1, id2, id3) = (id(b1), id(b2), id(b3));
+        let constraints = &world.solver.constraints().velocity;
+
+        // The joint constraints come before the friction constraints between the first and third boxes.
+        let bilateral: Vec<_> = constraints.bilateral
+            .iter()
+            .map(|c| (c.assembly_id1.min(c.assembly_id2), c.assembly_id1.max(c.assembly_id2)))
+            .collect();
+        let joint = (id1.min(id2), id1.max(id2));
+        let stacked = (id1.min(id3), id1.max(id3));
+        let njoint_constraints = bilateral.iter().take_while(|ids| **ids == joint).count();
+        assert!(njoint_constraints > 0);
+        assert!(bilateral.len() > njoint_constraints);
+        assert!(bilateral[njoint_constraints..].iter().all(|ids| *ids == stacked));
+
+        // The contacts with the ground follow the order of the collider handles of their manifolds.
+        let ground_bodies: Vec<_> = constraints.unilateral_ground.iter().map(|c| c.assembly_id).collect();
+        let nfirst = ground_bodies.iter().take_while(|id| **id == id1).count();
+        assert!(nfirst > 0);
+        assert!(ground_bodies.len() > nfirst);
+        assert!(ground_bodies[nfirst..].iter().all(|id| *id == id2));
+    }
+
+    #[test]
+    fn stack_is_stable_across_timesteps_with_impulse_scaling() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.integration_parameters_mut().impulse_scaling = true;
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world);
+
+        let rad = 0.5;
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(rad)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let shift = (rad + collider_desc.get_margin()) * 2.0;
+        let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc);
+
+        let num = 4;
+        let mut handles = Vec::new();
+        for i in 0..num {
+            let body = rb_desc
+                .set_translation(Vector::y() * (shift / 2.0 + i as f64 * shift))
+                .build(&mut world);
+            handles.push(body.handle());
+        }
+
+        // Alternate between a regular and a 10 times smaller timestep.
+        for i in 0..600 {
+            let dt = if (i / 50) % 2 == 0 { 1.0 / 60.0 } else { 1.0 / 600.0 };
+            world.set_timestep(dt);
+            world.step();
+        }
+
+        for (i, handle) in handles.iter().enumerate() {
+            let pos = world.rigid_body(*handle).unwrap().position().translation.vector;
+            let expected = Vector::y() * (shift / 2.0 + i as f64 * shift);
+            assert!((pos - expected).norm() < 0.05 * rad);
+        }
+    }
+
+    #[test]
+    fn impulse_scaling_rescales_joint_warmstart() {
+        // Velocity of a body hanging from a fixed joint right after the timestep is reduced tenfold.
+        let velocity_after_timestep_change = |impulse_scaling: bool| {
+            let mut world = World::<f64>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            world.integration_parameters_mut().impulse_scaling = impulse_scaling;
+            world.integration_parameters_mut().max_velocity_iterations = 50;
+
+            let bob = RigidBodyDesc::new()
+                .local_inertia(Inertia::new(1.0, na::one()))
+                .translation(-Vector::y())
+                .sleep_threshold(None)
+                .build(&mut world)
+                .part_handle();
+            let _ = world.add_constraint(FixedConstraint::new(
+                BodyPartHandle::ground(),
+                bob,
+                Point::origin(),
+                na::one(),
+                Point::from(Vector::y()),
+                na::one(),
+            ));
+
+            for _ in 0..60 {
+                world.step();
+            }
+
+            // Without any solver iteration, the velocity of the bob only results from the warmstart impulses.
+            world.integration_parameters_mut().max_velocity_iterations = 0;
+            world.set_timestep(1.0 / 600.0);
+            world.step();
+            world.rigid_body(bob.0).unwrap().velocity().linear.y
+        };
+
+        // The rescaled impulses still hold the bob against the gravity.
+        assert!(velocity_after_timestep_change(true).abs() < 9.81 / 600.0 * 0.1);
+        // The impulses computed with the previous timestep are ten times too large.
+        assert_relative_eq!(
+            velocity_after_timestep_change(false),
+            9.81 * (1.0 / 60.0 - 1.0 / 600.0),
+            epsilon = 9.81 / 600.0 * 0.1
+        );
+    }
+
+    #[test]
+    fn body_removal_callback() {
+        use std::sync::{Arc, Mutex};
+
+        let mut world = World::<f32>::new();
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let removed2 = removed.clone();
+        world.set_on_body_removed(Box::new(move |handle, data| {
+            let tag = data.and_then(|d| d.downcast_ref::<usize>()).cloned();
+            removed2.lock().unwrap().push((handle, tag));
+        }));
+
+        let h1 = RigidBodyDesc::new().user_data(1usize).build(&mut world).handle();
+        let h2 = RigidBodyDesc::new().build(&mut world).handle();
+
+        world.remove_bodies(&[h1]);
+        assert_eq!(*removed.lock().unwrap(), vec![(h1, Some(1))]);
+        assert!(world.body(h1).is_none());
+
+        world.remove_bodies(&[h2]);
+        assert_eq!(*removed.lock().unwrap(), vec![(h1, Some(1)), (h2, None)]);
+    }
+
+    #[test]
+    fn queued_body_removals_are_flushed_before_additions() {
+        let mut world = World::<f64>::new();
+        let ball = ShapeHandle::new(Ball::new(0.5));
+        let collider_desc = ColliderDesc::new(ball).density(1.0);
+        let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc);
+
+        let h1 = rb_desc.set_translation(Vector::x() * 0.0).build(&mut world).handle();
+        let h2 = rb_desc.set_translation(Vector::x() * 5.0).build(&mut world).handle();
+        let h3 = rb_desc.set_translation(Vector::x() * 10.0).build(&mut world).handle();
+        let _ = world.add_constraint(FixedConstraint::new(
+            BodyPartHandle(h1, 0),
+            BodyPartHandle(h2, 0),
+            Point::origin(),
+            na::one(),
+            Point::origin(),
+            na::one(),
+        ));
+
+        world.queue_remove_body(h1);
+        world.queue_add_body(Box::new(|world| {
+            let ball = ShapeHandle::new(Ball::new(0.5));
+            let _ = RigidBodyDesc::new()
+                .collider(&ColliderDesc::new(ball).density(1.0))
+                .translation(Vector::y() * 20.0)
+                .build(world);
+        }));
+        // The added body may reuse the handle of `h1`, but it is added after all the removals.
+        world.queue_remove_body(h1);
+        world.queue_remove_body(h3);
+
+        // Nothing changes until the next step.
+        assert!(world.body(h1).is_some() && world.body(h3).is_some());
+        assert_eq!(world.constraints.len(), 1);
+
+        world.step();
+
+        // The duplicate removal is ignored, and the constraint attached to the removed body is removed as well.
+        assert!(world.body(h2).is_some());
+        assert_eq!(world.bodies_with_handles().count(), 2);
+        assert_eq!(world.constraints.len(), 0);
+
+        // Each remaining collider is attached to a remaining body.
+        let ys: Vec<_> = world.colliders().map(|c| {
+            assert!(world.body(c.body()).is_some());
+            c.position().translation.vector.y
+        }).collect();
+        assert_eq!(ys.len(), 2);
+        assert!(ys.iter().any(|y| *y > 19.0));
+    }
+
+    #[test]
+    fn record_transforms_against_baseline() {
+        use crate::math::{AngularVector, Inertia, Isometry, Rotation, Translation};
+        use crate::utils;
+
+        // A ball falling on the ground, and a projectile far from the ground with a closed-form trajectory.
+        let linvel = Vector::x() + Vector::y() * 2.0;
+        let angvel = AngularVector::repeat(1.5);
+        let build = || {
+            let mut world = World::<f64>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = ColliderDesc::new(ground_shape)
+                .translation(Vector::y() * -10.0)
+                .build(&mut world);
+
+            let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+            let falling = RigidBodyDesc::new()
+                .collider(&collider_desc)
+                .translation(Vector::y() * 2.0)
+                .build(&mut world)
+                .handle();
+            let projectile = RigidBodyDesc::new()
+                .local_inertia(Inertia::new(1.0, na::one()))
+                .translation(Vector::y() * 100.0)
+                .velocity(Velocity::new_with_vectors(linvel, angvel))
+                .build(&mut world)
+                .handle();
+
+            (world, falling, projectile)
+        };
+
+        let (mut world, falling, projectile) = build();
+        let (mut replay, _, _) = build();
+        let mut trace = Vec::new();
+        let mut replay_trace = Vec::new();
+        let mut baseline = Vec::new();
+        let dt = world.timestep();
+
+        for i in 1..=50 {
+            world.step();
+            replay.step();
+            world.record_transforms(&mut trace);
+            replay.record_transforms(&mut replay_trace);
+
+            // The baseline of the projectile is computed analytically for the symplectic Euler integrator.
+            let n = i as f64;
+            let translation = Vector::y() * 100.0 + linvel * (n * dt) + world.gravity() * (dt * dt * n * (n + 1.0) / 2.0);
+            let rotation = Rotation::from_scaled_axis(angvel * (n * dt));
+            baseline.push((projectile, Isometry::from_parts(Translation::from(translation), rotation)));
+        }
+
+        assert_eq!(trace.len(), 100);
+        // Replaying the same scene yields the same trajectories.
+        let (distance, angle) = utils::transforms_deviation(&trace, &replay_trace).unwrap();
+        assert!(distance < 1.0e-12 && angle < 1.0e-12);
+
+        let projectile_trace: Vec<_> = trace.iter().filter(|t| t.0 == projectile).cloned().collect();
+        let (distance, angle) = utils::transforms_deviation(&projectile_trace, &baseline).unwrap();
+        assert!(distance < 1.0e-8 && angle < 1.0e-8);
+
+        // The falling ball landed on the ground.
+        let last = trace.iter().rev().find(|t| t.0 == falling).unwrap();
+        assert_relative_eq!(last.1.translation.vector.y, 0.5, epsilon = 0.05);
+    }
+
+    #[test]
+    fn step_with_budget_returns_in_time() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world);
+
+        let rad = 0.5;
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(rad)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let shift = (rad + collider_desc.get_margin()) * 2.0;
+        let mut rb_desc = RigidBodyDesc::new().collider(&collider_desc);
+
+        for i in 0..3 {
+            for j in 0..3 {
+                let _ = rb_desc
+                    .set_translation(Vector::x() * (i as f64 * shift) + Vector::y() * (shift / 2.0 + j as f64 * shift))
+                    .build(&mut world);
+            }
+        }
+
+        world.step();
+        assert!(!world.performance_counters().budget_exceeded());
+
+        // Without a budget, such a step would take minutes.
+        world.integration_parameters_mut().max_velocity_iterations = 10_000_000;
+        world.integration_parameters_mut().max_position_iterations = 10_000_000;
+
+        for _ in 0..3 {
+            world.step_with_budget(0.05);
+            assert!(world.performance_counters().budget_exceeded());
+            assert!(world.performance_counters().nvelocity_iterations() < 10_000_000);
+        }
+
+        *world.integration_parameters_mut() = IntegrationParameters::default();
+        world.step_with_budget(100.0);
+        assert!(!world.performance_counters().budget_exceeded());
+    }
+
+    #[test]
+    fn export_small_scene() {
+        use crate::world::ShapeDescription;
+
+        let mut world = World::<f64>::new();
+        let _ = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(1.0))))
+            .name("ground".to_string())
+            .build(&mut world);
+
+        let ball = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let b1 = RigidBodyDesc::new()
+            .collider(&ball)
+            .name("ball".to_string())
+            .translation(Vector::y() * 3.0)
+            .build(&mut world)
+            .handle();
+        let b2 = RigidBodyDesc::new()
+            .collider(&ball)
+            .translation(Vector::x() * 3.0)
+            .build(&mut world)
+            .handle();
+        let _ = world.add_constraint(FixedConstraint::new(
+            BodyPartHandle(b1, 0),
+            BodyPartHandle(b2, 0),
+            Point::origin(),
+            Rotation::identity(),
+            Point::from(Vector::x() * -3.0),
+            Rotation::identity(),
+        ));
+
+        let scene = world.export_scene();
+        assert_eq!(scene.bodies.len(), 2);
+        assert_eq!(scene.colliders.len(), 3);
+        assert_eq!(scene.constraints.len(), 1);
+
+        let body = scene.bodies.iter().find(|b| b.name == "ball").unwrap();
+        assert_eq!(body.handle, b1.uid());
+        assert_eq!(body.kind, "RigidBody");
+        assert_eq!(body.status, "Dynamic");
+        assert_eq!(body.position.translation, (Vector::y() * 3.0).as_slice().to_vec());
+
+        let ground = scene.colliders.iter().find(|c| c.name == "ground").unwrap();
+        assert_eq!(ground.body, None);
+        match ground.shape {
+            ShapeDescription::Cuboid { ref half_extents } => assert_eq!(half_extents, &vec![1.0; DIM]),
+            _ => panic!("The ground should be described as a cuboid."),
+        }
+
+        let constraint = &scene.constraints[0];
+        assert_eq!(constraint.kind, "FixedConstraint");
+        assert_eq!((constraint.body1, constraint.body2), (Some(b1.uid()), Some(b2.uid())));
+
+        #[cfg(feature = "serde-serialize")]
+        {
+            let json = serde_json::to_string(&scene).unwrap();
+            assert!(json.contains(r#""kind":"RigidBody""#));
+            assert!(json.contains(r#""Ball":{"radius":0.5}"#));
+            assert!(json.contains(r#""kind":"FixedConstraint""#));
+        }
+    }
+
+    #[test]
+    fn disabled_position_correction_shifts_depenetration_to_other_body() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::zeros());
+
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let player = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .position_correction_enabled(false)
+            .build(&mut world)
+            .handle();
+        let other = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::x() * 0.8)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let player_pos = world.rigid_body(player).unwrap().position().translation.vector;
+        let other_pos = world.rigid_body(other).unwrap().position().translation.vector;
+        let allowed_error = world.integration_parameters().allowed_linear_error;
+
+        assert_eq!(player_pos, Vector::zeros());
+        assert!(other_pos.x > 1.0 - allowed_error * 1.5);
+        assert!(relative_eq!(other_pos.y, 0.0, epsilon = 1.0e-7));
+    }
+
+    #[test]
+    fn cast_ray_ignores_disabled_bodies() {
+        let mut world = World::<f64>::new();
+        let ground = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(1.0))))
+            .build(&mut world)
+            .handle();
+
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let _ = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 6.0)
+            .status(BodyStatus::Disabled)
+            .build(&mut world);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 3.0)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        world.collider_world_mut().update();
+
+        let ray = Ray::new(Point::from(Vector::y() * 10.0), -Vector::y());
+        let groups = CollisionGroups::new();
+        let mut hits: Vec<_> = world.interferences_with_ray(&ray, &groups).map(|hit| hit.0).collect();
+        hits.sort();
+        let mut expected = vec![ground, collider];
+        expected.sort();
+        assert_eq!(hits, expected);
+
+        let (handle, inter) = world.cast_ray(&ray, &groups).unwrap();
+        assert_eq!(handle, collider);
+        assert!(relative_eq!(inter.toi, 6.5, epsilon = 1.0e-7));
+
+        let ray = Ray::new(Point::from(Vector::y() * 10.0), Vector::y());
+        assert!(world.cast_ray(&ray, &groups).is_none());
+    }
+
+    #[test]
+    fn colliders_containing_point_and_in_aabb() {
+        let mut world = World::<f64>::new();
+        let ground = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(1.0))))
+            .margin(0.5)
+            .build(&mut world)
+            .handle();
+
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let _ = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 3.0)
+            .status(BodyStatus::Disabled)
+            .build(&mut world);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::x() * 3.0)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(body).next().unwrap().handle();
+
+        world.collider_world_mut().update();
+        let groups = CollisionGroups::new();
+
+        let hits: Vec<_> = world.colliders_containing_point(&Point::from(Vector::x() * 3.2), &groups).collect();
+        assert_eq!(hits, vec![collider]);
+        let hits: Vec<_> = world.colliders_containing_point(&Point::from(Vector::y() * 3.0), &groups).collect();
+        assert!(hits.is_empty());
+        // The margin of the ground is not part of the containment test.
+        let hits: Vec<_> = world.colliders_containing_point(&Point::from(Vector::x() * 1.2), &groups).collect();
+        assert!(hits.is_empty());
+
+        let aabb = AABB::new(Point::from(Vector::repeat(-2.0)), Point::from(Vector::repeat(4.0)));
+        let mut hits: Vec<_> = world.colliders_in_aabb(&aabb, &groups).collect();
+        hits.sort();
+        let mut expected = vec![ground, collider];
+        expected.sort();
+        assert_eq!(hits, expected);
+
+        let aabb = AABB::new(Point::from(Vector::repeat(1.2)), Point::from(Vector::repeat(1.4)));
+        assert_eq!(world.colliders_in_aabb(&aabb, &groups).count(), 0);
+    }
+
+    #[test]
+    fn bodies_rest_on_ground_plane() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let _ = world.add_ground_plane(Vector::y_axis(), 1.0);
+
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Ball::new(0.5))).density(1.0);
+        let handles: Vec<_> = [-1000.0, 0.0, 25.0, 1000.0]
+            .iter()
+            .map(|x| {
+                RigidBodyDesc::new()
+                    .collider(&collider_desc)
+                    .translation(Vector::x() * *x + Vector::y() * 3.0)
+                    .build(&mut world)
+                    .handle()
+            })
+            .collect();
+
+        for _ in 0..200 {
+            world.step();
+        }
+
+        // The ball and the ground are separated by their margins.
+        let rest_height = 1.5 + ColliderDesc::<f64>::default_margin() * 2.0;
+        let allowed_error = world.integration_parameters().allowed_linear_error;
+
+        for handle in handles {
+            let y = world.rigid_body(handle).unwrap().position().translation.vector.y;
+            assert!(relative_eq!(y, rest_height, epsilon = allowed_error * 2.0), "y = {}", y);
+        }
+    }
+
+    // Drops a tilted stack of boxes on the ground in deterministic mode, and returns the final body positions.
+    fn tumbling_stack_positions() -> Vec<Isometry<f64>> {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.set_deterministic(true);
+        assert!(world.is_deterministic());
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let handles: Vec<_> = (0..6).map(|i| {
+            let mut translation = Vector::y() * (0.6 + i as f64 * 1.1);
+            translation.x = i as f64 * 0.2;
+            RigidBodyDesc::new()
+                .collider(&collider_desc)
+                .translation(translation)
+                .build(&mut world)
+                .handle()
+        }).collect();
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        handles.iter().map(|h| *world.rigid_body(*h).unwrap().position()).collect()
+    }
+
+    #[test]
+    fn deterministic_mode_reproduces_the_simulation() {
+        assert!(tumbling_stack_positions() == tumbling_stack_positions());
+    }
+
+    #[test]
+    fn deterministic_order_does_not_depend_on_the_broad_phase() {
+        use std::cmp::Ordering;
+        use crate::detection::ColliderContactManifold;
+        use super::{contact_manifold_key, sort_contact_manifolds};
+
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+
+        for i in 0..6 {
+            let _ = RigidBodyDesc::new()
+                .collider(&collider_desc)
+                .translation(Vector::y() * (0.5 + i as f64) + Vector::x() * (i as f64 * 0.2))
+                .build(&mut world);
+        }
+
+        world.step();
+
+        let mut manifolds = Vec::new();
+        World::collect_contact_manifolds(&world.cworld, &world.bodies, &world.params, &mut manifolds);
+        assert!(manifolds.len() > 1);
+
+        // The same manifolds found in another order by the broad phase.
+        let mut shuffled = manifolds.clone();
+        shuffled.reverse();
+        shuffled.rotate_left(1);
+
+        sort_contact_manifolds(&mut manifolds);
+        sort_contact_manifolds(&mut shuffled);
+
+        let contacts = |manifolds: &[ColliderContactManifold<f64>]| -> Vec<_> {
+            manifolds.iter().flat_map(|m| m.contacts().map(|c| c.contact.world1)).collect()
+        };
+        let keys = |manifolds: &[ColliderContactManifold<f64>]| -> Vec<_> {
+            manifolds.iter().map(contact_manifold_key).collect()
+        };
+        assert_eq!(keys(&manifolds), keys(&shuffled));
+        assert_eq!(contacts(&manifolds), contacts(&shuffled));
+
+        // The contacts of each manifold are sorted.
+        for manifold in &manifolds {
+            let sorted: Vec<_> = manifold.contacts().collect();
+            assert!(sorted.windows(2).all(|w| manifold.compare_contacts(w[0], w[1]) != Ordering::Greater));
+        }
+    }
+
+    #[test]
+    fn snapshot_restore_is_deterministic() {
+        use crate::joint::{CartesianConstraint, RevoluteJoint};
+        use crate::object::MultibodyDesc;
+
+        // A few bodies far enough from each other to never come into contact.
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ball = ShapeHandle::new(Ball::new(0.5));
+        let collider = ColliderDesc::new(ball).density(1.0);
+        let _ = RigidBodyDesc::new()
+            .collider(&collider)
+            .translation(Vector::x() * -10.0)
+            .velocity(Velocity::new_with_vectors(Vector::zeros(), AngularVector::repeat(2.0)))
+            .build(&mut world);
+
+        let anchored = RigidBodyDesc::new()
+            .collider(&collider)
+            .velocity(Velocity::new_with_vectors(Vector::zeros(), AngularVector::repeat(-1.0)))
+            .build(&mut world)
+            .part_handle();
+        let _ = world.add_constraint(CartesianConstraint::new(
+            BodyPartHandle::ground(),
+            anchored,
+            Point::origin(),
+            Rotation::identity(),
+            Point::origin(),
+            Rotation::identity(),
+        ));
+
+        #[cfg(feature = "dim2")]
+        let revolute = || RevoluteJoint::new(0.5);
+        #[cfg(feature = "dim3")]
+        let revolute = || RevoluteJoint::new(Vector::z_axis(), 0.5);
+        let mut pendulum = MultibodyDesc::new(revolute())
+            .parent_shift(Vector::x() * 10.0)
+            .body_shift(Vector::y() * 2.0)
+            .collider(&collider);
+        let _ = pendulum.add_child(revolute())
+            .set_body_shift(Vector::y() * 2.0)
+            .add_collider(&collider);
+        let _ = pendulum.build(&mut world);
+
+        #[cfg(feature = "dim3")]
+        {
+            use crate::object::{Body, FEMVolumeDesc};
+
+            let volume = FEMVolumeDesc::cube(2, 2, 2)
+                .position(Isometry::translation(0.0, 0.0, 10.0))
+                .young_modulus(1.0e3)
+                .sleep_threshold(None)
+                .build(&mut world);
+
+            for (i, v) in volume.generalized_velocity_mut().iter_mut().enumerate() {
+                *v = (i % 5) as f64 * 0.1;
+            }
+        }
+
+        let record = |world: &World<f64>| {
+            let mut transforms = Vec::new();
+            world.record_transforms(&mut transforms);
+            let positions: Vec<f64> = world.bodies()
+                .filter_map(|b| b.deformed_positions())
+                .flat_map(|(_, positions)| positions.iter().cloned())
+                .collect();
+            (transforms, positions)
+        };
+        let collider_positions = |world: &World<f64>| -> Vec<Isometry<f64>> {
+            world.colliders().map(|c| *c.position()).collect()
+        };
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let snapshot = world.snapshot();
+        let snapshot_colliders = collider_positions(&world);
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        let expected = record(&world);
+        assert_ne!(collider_positions(&world), snapshot_colliders);
+
+        world.restore(&snapshot);
+        assert_eq!(collider_positions(&world), snapshot_colliders);
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        assert_eq!(record(&world), expected);
+    }
+
+    #[test]
+    fn snapshot_restore_with_contacts_is_exact() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let ground = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(10.0))))
+            .translation(Vector::y() * -10.0);
+        let _ = ground.build(&mut world);
+
+        // A box sliding on the ground until the friction stops it.
+        let collider = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)))).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider)
+            .translation(Vector::y() * 0.5)
+            .velocity(Velocity::new_with_vectors(Vector::x() * 2.0, AngularVector::zeros()))
+            .build(&mut world)
+            .handle();
+        let position = |world: &World<f64>| world.rigid_body(body).unwrap().position().translation.vector;
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        let snapshot = world.snapshot();
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        let expected = position(&world);
+        world.restore(&snapshot);
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        // The contact impulses cached for warm-starting are restored too, so the trajectory is reproduced exactly.
+        assert_eq!(position(&world), expected);
+    }
+
+    #[test]
+    fn restitution_uses_contact_point_velocity() {
+        use crate::material::{BasicMaterial, MaterialHandle};
+
+        let mut world = World::<f64>::new();
+        let material = MaterialHandle::new(BasicMaterial::new(1.0, 0.0));
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .material(material.clone())
+            .build(&mut world);
+
+        // A bar spinning without any linear velocity, so that its right end hits the ground.
+        let half_extents = Vector::x() + Vector::repeat(0.1) - Vector::x() * 0.1;
+        let collider_desc = ColliderDesc::new(ShapeHandle::new(Cuboid::new(half_extents)))
+            .density(1.0)
+            .material(material);
+        #[cfg(feature = "dim2")]
+        let angvel = AngularVector::new(-3.0);
+        #[cfg(feature = "dim3")]
+        let angvel = -AngularVector::z() * 3.0;
+        let handle = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.2)
+            .velocity(Velocity::new_with_vectors(Vector::zeros(), angvel))
+            .build(&mut world)
+            .handle();
+
+        // The vertical velocity of the bottom-right corner of the bar.
+        let corner_vel = |world: &World<f64>| {
+            let rb = world.rigid_body(handle).unwrap();
+            let corner = rb.position() * Point::from(Vector::x() - Vector::y() * 0.1);
+            let vel = rb.velocity().as_slice().to_vec();
+            let r = corner.coords - rb.position().translation.vector;
+            #[cfg(feature = "dim2")]
+            return vel[1] + vel[2] * r.x;
+            #[cfg(feature = "dim3")]
+            return vel[1] + vel[5] * r.x - vel[3] * r.z;
+        };
+        assert_relative_eq!(corner_vel(&world), -3.0, epsilon = 1.0e-6);
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        // The corner bounced back with the speed it had when it hit the ground.
+        let bounce_vel = corner_vel(&world);
+        assert!((bounce_vel - 3.0).abs() < 0.2, "bounce velocity: {}", bounce_vel);
+    }
+
+    #[test]
+    fn solidified_deformable_blocks_bodies() {
+        #[cfg(feature = "dim2")]
+        use crate::object::FEMSurfaceDesc;
+        #[cfg(feature = "dim3")]
+        use crate::object::FEMVolumeDesc;
+
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = ColliderDesc::new(ground_shape)
+            .translation(Vector::y() * -10.0)
+            .build(&mut world);
+
+        // A soft unit cube (or square in 2D) resting on the ground.
+        #[cfg(feature = "dim2")]
+        let soft = FEMSurfaceDesc::quad(2, 2)
+            .translation(Vector::y() * 0.6)
+            .collider_enabled(true)
+            .build(&mut world)
+            .handle();
+        #[cfg(feature = "dim3")]
+        let soft = FEMVolumeDesc::cube(2, 2, 2)
+            .translation(Vector::y() * 0.6)
+            .young_modulus(1.0e3)
+            .collider_enabled(true)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..200 {
+            world.step();
+        }
+
+        let (_, positions) = world.body(soft).unwrap().deformed_positions().unwrap();
+        let top = positions.iter().skip(1).step_by(DIM).fold(std::f64::MIN, |a, b| a.max(*b));
+
+        let solid = world.solidify_deformable(soft).unwrap();
+        assert!(world.body(soft).is_none());
+        assert_eq!(world.collider_world().body_colliders(soft).count(), 0);
+        assert_eq!(world.body(solid.0).unwrap().status(), BodyStatus::Static);
+        assert_eq!(world.collider_world().body_colliders(solid.0).count(), 1);
+        assert!(world.solidify_deformable(solid.0).is_none());
+
+        // A ball dropped on top of the solidified body rests on it instead of falling through.
+        let rad = 0.2;
+        let ball = ColliderDesc::new(ShapeHandle::new(Ball::new(rad))).density(1.0);
+        let ball = RigidBodyDesc::new()
+            .collider(&ball)
+            .translation(Vector::y() * (top + 1.0))
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..200 {
+            world.step();
+        }
+
+        let y = world.rigid_body(ball).unwrap().position().translation.vector.y;
+        assert!(y > top + rad - 0.05, "ball: {}, top: {}", y, top);
+    }
+
+    #[test]
+    fn contact_stiffness_ramp_smooths_initial_overlap() {
+        // The height of a box created overlapping the ground, after each of the first timesteps.
+        fn heights(ramp: usize) -> Vec<f64> {
+            let mut world = World::<f64>::new();
+            world.integration_parameters_mut().contact_stiffness_ramp = ramp;
+
+            let ground_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = ColliderDesc::new(ground_shape)
+                .translation(Vector::y() * -10.0)
+                .build(&mut world);
+
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+            let handle = RigidBodyDesc::new()
+                .collider(&collider_desc)
+                .translation(Vector::y() * 0.2)
+                .build(&mut world)
+                .handle();
+
+            (0..60).map(|_| {
+                world.step();
+                world.rigid_body(handle).unwrap().position().translation.vector.y
+            }).collect()
+        }
+
+        let max_displacement = |heights: &[f64]| {
+            heights.windows(2).fold(heights[0] - 0.2, |max, h| max.max(h[1] - h[0]))
+        };
+
+        let popped = heights(0);
+        let ramped = heights(10);
+
+        // Both boxes end up resting on the ground.
+        assert_relative_eq!(popped[59], 0.5, epsilon = 0.05);
+        assert_relative_eq!(ramped[59], 0.5, epsilon = 0.05);
+        // But the ramped box is pushed out much more gradually.
+        assert!(ramped[0] - 0.2 < (popped[0] - 0.2) / 5.0);
+        assert!(max_displacement(&ramped) < max_displacement(&popped) / 2.0);
+    }
+
+    #[test]
+    fn bodies_with_handles_match_body_lookup() {
+        let mut world = World::<f64>::new();
+        let mut handles = Vec::new();
+
+        for i in 0..3 {
+            let handle = RigidBodyDesc::new()
+                .translation(Vector::x() * (i as f64))
+                .build(&mut world)
+                .handle();
+            handles.push(handle);
+        }
+
+        // Removing a body must not shift the handles of the remaining ones.
+        world.remove_bodies(&[handles[1]]);
+        let _ = handles.remove(1);
+
+        let mut seen = Vec::new();
+
+        for (handle, body) in world.bodies_with_handles() {
+            assert_eq!(body.handle(), handle);
+            assert!(std::ptr::eq(body, world.body(handle).unwrap()));
+            seen.push(handle);
+        }
+
+        assert_eq!(seen, handles);
+
+        for (handle, body) in world.bodies_with_handles_mut() {
+            body.set_status(BodyStatus::Static);
+            assert_eq!(body.handle(), handle);
+        }
+
+        for handle in handles {
+            assert_eq!(world.body(handle).unwrap().status(), BodyStatus::Static);
+        }
+    }
+
+    #[test]
+    fn scaled_ball_rests_higher_and_is_heavier() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let ground = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        let ball = ShapeHandle::new(Ball::new(0.5));
+        let collider_desc = ColliderDesc::new(ball).density(1.0);
+        let body = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.6)
+            .build(&mut world)
+            .handle();
+        let collider = world.collider_world().body_colliders(body).next().unwrap().handle();
+        let mass = world.rigid_body(body).unwrap().augmented_mass().linear;
+
+        // The ground plane cannot be scaled.
+        assert!(!world.scale_collider(ground, 2.0));
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let y = world.rigid_body(body).unwrap().position().translation.vector.y;
+        assert_relative_eq!(y, 0.5, epsilon = 2.0e-2);
+
+        // The scaling factor must be positive.
+        assert!(!world.scale_collider(collider, 0.0));
+        assert!(!world.scale_collider(collider, -1.0));
+
+        // The scaled ball is woken up so it can move up.
+        world.body_mut(body).unwrap().deactivate();
+        assert!(world.scale_collider(collider, 2.0));
+        assert!(world.body(body).unwrap().is_active());
+        let scaled_mass = world.rigid_body(body).unwrap().augmented_mass().linear;
+        assert_relative_eq!(scaled_mass, mass * 2.0f64.powi(DIM as i32), epsilon = 1.0e-8);
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let y = world.rigid_body(body).unwrap().position().translation.vector.y;
+        assert_relative_eq!(y, 1.0, epsilon = 2.0e-2);
+    }
+
+    #[test]
+    fn max_velocities_clamp_fast_bodies_only() {
+        let mut world = World::<f64>::new();
+        world.integration_parameters_mut().max_linear_velocity = Some(10.0);
+        world.integration_parameters_mut().max_angular_velocity = Some(1.0);
+
+        let fast = RigidBodyDesc::new()
+            .velocity(Velocity::new_with_vectors(Vector::x() * 100.0, AngularVector::repeat(5.0)))
+            .build(&mut world)
+            .handle();
+        let slow = RigidBodyDesc::new()
+            .translation(Vector::y() * 10.0)
+            .velocity(Velocity::new_with_vectors(Vector::x() * 2.0, AngularVector::repeat(0.5)))
+            .build(&mut world)
+            .handle();
+
+        world.step();
+
+        let fast_velocity = world.rigid_body(fast).unwrap().velocity();
+        assert_relative_eq!(fast_velocity.linear, Vector::x() * 10.0, epsilon = 1.0e-10);
+        assert_relative_eq!(fast_velocity.angular_vector().norm(), 1.0, epsilon = 1.0e-10);
+
+        let slow_velocity = world.rigid_body(slow).unwrap().velocity();
+        assert_relative_eq!(slow_velocity.linear, Vector::x() * 2.0, epsilon = 1.0e-10);
+        assert_relative_eq!(slow_velocity.angular_vector(), AngularVector::repeat(0.5), epsilon = 1.0e-10);
+        assert_eq!(world.performance_counters().nclamped_velocities(), 1);
+    }
+
+    #[test]
+    fn max_velocities_reset_non_finite_velocities() {
+        let mut world = World::<f64>::new();
+        world.integration_parameters_mut().max_linear_velocity = Some(10.0);
+        world.integration_parameters_mut().max_angular_velocity = Some(1.0);
+
+        let nan = RigidBodyDesc::new()
+            .velocity(Velocity::new_with_vectors(Vector::x() * f64::NAN, AngularVector::repeat(0.5)))
+            .build(&mut world)
+            .handle();
+        let infinite = RigidBodyDesc::new()
+            .translation(Vector::y() * 10.0)
+            .velocity(Velocity::new_with_vectors(Vector::x() * 2.0, AngularVector::repeat(f64::INFINITY)))
+            .build(&mut world)
+            .handle();
+
+        world.step();
+
+        let nan_velocity = world.rigid_body(nan).unwrap().velocity();
+        assert_eq!(nan_velocity.linear, Vector::zeros());
+        assert_relative_eq!(nan_velocity.angular_vector(), AngularVector::repeat(0.5), epsilon = 1.0e-10);
+
+        let infinite_velocity = world.rigid_body(infinite).unwrap().velocity();
+        assert_relative_eq!(infinite_velocity.linear, Vector::x() * 2.0, epsilon = 1.0e-10);
+        assert_eq!(infinite_velocity.angular_vector(), AngularVector::zeros());
+        assert_eq!(world.performance_counters().nclamped_velocities(), 2);
+    }
+
+    #[test]
+    fn box_on_the_floor_is_supported() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let on_floor = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+        let in_air = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 10.0)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..5 {
+            world.step();
+        }
+
+        assert!(world.is_body_supported(on_floor, Vector::y_axis(), 0.1));
+        assert!(!world.is_body_supported(in_air, Vector::y_axis(), 0.1));
+        // The floor is below the box, not above it.
+        assert!(!world.is_body_supported(on_floor, -Vector::y_axis(), 0.1));
+    }
+
+    #[test]
+    fn applied_impulse_is_velocity_change_minus_gravity() {
+        let mut world = World::<f64>::new();
+        let gravity = Vector::y() * -9.81;
+        world.set_gravity(gravity);
+        world.enable_applied_impulses_recording();
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        // A box dropped on the ground, so the contact impulses vary during the fall, the impact, and the rest.
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+        let handle = RigidBodyDesc::new()
+            .collider(&collider_desc)
+            .translation(Vector::y() * 1.0)
+            .build(&mut world)
+            .handle();
+
+        let mut max_impulse = 0.0f64;
+
+        for _ in 0..40 {
+            let before = *world.rigid_body(handle).unwrap().velocity();
+            world.step();
+            let after = *world.rigid_body(handle).unwrap().velocity();
+
+            let impulse = world.body_applied_impulse(handle);
+            let expected_linear = after.linear - before.linear - gravity * world.timestep();
+            assert_relative_eq!(impulse.linear, expected_linear, epsilon = 1.0e-10);
+            assert_relative_eq!(impulse.angular, after.angular - before.angular, epsilon = 1.0e-10);
+            max_impulse = max_impulse.max(impulse.linear.y);
+        }
+
+        // The ground pushed the box up at some point.
+        assert!(max_impulse > 0.0);
+
+        world.disable_applied_impulses_recording();
+        world.step();
+        assert_eq!(world.body_applied_impulse(handle).as_vector(), Velocity::zero().as_vector());
+    }
+
+    #[test]
+    fn relative_velocity_vanishes_at_revolute_anchor() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        // A pendulum swinging around the origin.
+        let body = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .translation(Vector::x())
+            .build(&mut world)
+            .part_handle();
+        let anchor2 = Point::from(-Vector::x());
+        #[cfg(feature = "dim2")]
+        let revolute = RevoluteConstraint::new(BodyPartHandle::ground(), body, Point::origin(), anchor2);
+        #[cfg(feature = "dim3")]
+        let revolute = RevoluteConstraint::new(
+            BodyPartHandle::ground(), body, Point::origin(), Vector::z_axis(), anchor2, Vector::z_axis());
+        let _ = world.add_constraint(revolute);
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        let position = *world.rigid_body(body.0).unwrap().position();
+        let anchor = position * anchor2;
+        let velocity = world.relative_velocity(BodyPartHandle::ground(), body, &anchor).unwrap();
+        assert!(world.rigid_body(body.0).unwrap().velocity().linear.norm() > 1.0);
+        assert_relative_eq!(velocity.linear, Vector::zeros(), epsilon = 5.0e-2);
+
+        // Away from the anchor, the relative velocity is the velocity of the pendulum.
+        let tip = position * Point::from(Vector::x());
+        let velocity = world.relative_velocity(BodyPartHandle::ground(), body, &tip).unwrap();
+        assert!(velocity.linear.norm() > 1.0);
+    }
+
+    #[test]
+    #[cfg(feature = "solver-history")]
+    fn residual_history_decreases() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.integration_parameters_mut().max_velocity_iterations = 20;
+        world.integration_parameters_mut().velocity_solver_tolerance = 0.0;
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+
+        for i in 0..3 {
+            let _ = RigidBodyDesc::new()
+                .collider(&collider_desc)
+                .translation(Vector::y() * (0.5 + i as f64 * 1.01))
+                .build(&mut world);
+        }
+
+        // The contacts are detected during the first step, and solved without any warm-starting during the second.
+        world.step();
+        world.step();
+
+        let history = world.last_step_residual_history();
+        assert_eq!(history.len(), 20);
+        // The residual is not strictly decreasing, but it must keep dropping from one batch of iterations to the next.
+        let max = |batch: &[f64]| batch.iter().cloned().fold(0.0, f64::max);
+        let min = |batch: &[f64]| batch.iter().cloned().fold(f64::INFINITY, f64::min);
+        let batches: Vec<_> = history.chunks(5).collect();
+        assert!(batches.windows(2).all(|w| min(w[1]) < max(w[0])));
+        assert!(history[19] < history[0] * 0.7);
+    }
+
+    #[test]
+    fn solver_statistics_are_reported() {
+        let stack = |niterations: usize| {
+            let mut world = World::<f64>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            world.integration_parameters_mut().max_velocity_iterations = niterations;
+            world.integration_parameters_mut().velocity_solver_tolerance = 0.0;
+            let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let collider_desc = ColliderDesc::new(cuboid).density(1.0);
+
+            for i in 0..3 {
+                let _ = RigidBodyDesc::new()
+                    .collider(&collider_desc)
+                    .translation(Vector::y() * (0.5 + i as f64 * 1.01))
+                    .build(&mut world);
+            }
+
+            world.step();
+            world.step();
+            world
+        };
+
+        let few = stack(2);
+        let many = stack(50);
+
+        for (world, niterations) in &[(&few, 2), (&many, 50)] {
+            let counters = world.performance_counters();
+            assert_eq!(counters.nvelocity_iterations(), *niterations);
+            assert!(counters.nconstraints() > 0);
+        }
+
+        // The solver converges with more iterations.
+        let few_residual = few.performance_counters().max_residual_impulse();
+        let many_residual = many.performance_counters().max_residual_impulse();
+        assert!(few_residual > 0.0);
+        assert!(many_residual < few_residual * 0.5, "few: {}, many: {}", few_residual, many_residual);
+    }
+
+    #[test]
+    fn velocity_solver_stops_once_converged() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.integration_parameters_mut().max_velocity_iterations = 100;
+        world.integration_parameters_mut().velocity_solver_tolerance = 1.0e-6;
+        let _ = world.add_ground_plane(Vector::y_axis(), 0.0);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let handle = RigidBodyDesc::new()
+            .collider(&ColliderDesc::new(cuboid).density(1.0))
+            .translation(Vector::y() * 0.5)
+            .build(&mut world)
+            .handle();
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        // The impulses of the resting box are warm-started, so few iterations are needed.
+        let counters = world.performance_counters();
+        assert!(counters.nvelocity_iterations() < 100, "iterations: {}", counters.nvelocity_iterations());
+        assert!(counters.max_residual_impulse() < 1.0e-6);
+
+        // The box rests on the ground, up to the collider margins.
+        let y = world.rigid_body(handle).unwrap().position().translation.vector.y;
+        assert_relative_eq!(y, 0.5, epsilon = 5.0e-2);
+    }
+
+    #[test]
+    fn cleared_impulses_do_not_warmstart_the_solver() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let ground = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(5.0))))
+            .translation(Vector::y() * -5.0);
+        let _ = ground.build(&mut world);
+
+        let collider = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)))).density(1.0);
+        let body = RigidBodyDesc::new().collider(&collider).translation(Vector::y() * 0.5).build(&mut world).handle();
+
+        // A rod holding a second body, so some impulses are cached by a joint too.
+        let bob = RigidBodyDesc::new()
+            .local_inertia(Inertia::new(1.0, na::one()))
+            .translation(Vector::x() * 10.0 - Vector::y())
+            .build(&mut world)
+            .part_handle();
+        let rod = FixedConstraint::new(
+            BodyPartHandle::ground(),
+            bob,
+            Point::from(Vector::x() * 10.0),
+            na::one(),
+            Point::from(Vector::y()),
+            na::one(),
+        );
+        let rod = world.add_constraint(rod);
+
+        // Without any solver iteration, the velocity of the box only results from the warmstart impulses.
+        let fall_velocity = |world: &mut World<f64>| {
+            world.integration_parameters_mut().max_velocity_iterations = 50;
+
+            for _ in 0..60 {
+                world.step();
+            }
+
+            world.integration_parameters_mut().max_velocity_iterations = 0;
+            world.step();
+            world.rigid_body(body).unwrap().velocity().linear.y
+        };
+
+        // The cached impulses hold the box against the gravity.
+        let dt = world.integration_parameters().dt;
+        assert!(fall_velocity(&mut world).abs() < 9.81 * dt * 0.1);
+
+        assert!(world.constraint(rod).cached_impulses().iter().any(|i| *i != 0.0));
+        world.clear_cached_impulses();
+        assert!(world.constraint(rod).cached_impulses().iter().all(|i| *i == 0.0));
+        world.step();
+        assert_relative_eq!(world.rigid_body(body).unwrap().velocity().linear.y, -9.81 * dt, epsilon = 9.81 * dt * 0.1);
+
+        // Same for a single body.
+        assert!(fall_velocity(&mut world).abs() < 9.81 * dt * 0.1);
+        world.rigid_body_mut(body).unwrap().wake_up_clean();
+        world.step();
+        assert_relative_eq!(world.rigid_body(body).unwrap().velocity().linear.y, -9.81 * dt, epsilon = 9.81 * dt * 0.1);
+    }
+
+    #[test]
+    fn bodies_anchored_to_ground() {
+        let mut world = World::<f64>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let collider = ColliderDesc::new(ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)))).density(1.0);
+        let mut desc = RigidBodyDesc::new().collider(&collider);
+        #[cfg(feature = "dim2")]
+        let _ = desc.set_rotation(0.3);
+        #[cfg(feature = "dim3")]
+        let _ = desc.set_rotation(Vector::z() * 0.3);
+
+        // A body fixed at one of its corners stays in place.
+        let fixed = desc.set_translation(Vector::x() * -5.0).build(&mut world).part_handle();
+        let initial_position = *world.rigid_body(fixed.0).unwrap().position();
+        let corner = initial_position * Point::from(Vector::repeat(0.5));
+        assert!(world.anchor_body_to_ground(fixed, &corner).is_some());
+
+        // A body pinned at one of its corners swings around it.
+        let pinned = desc.set_translation(Vector::x() * 5.0).build(&mut world).part_handle();
+        let pinned_corner = *world.rigid_body(pinned.0).unwrap().position() * Point::from(Vector::repeat(0.5));
+        assert!(world.anchor_point_to_ground(pinned, &pinned_corner).is_some());
+
+        let removed = desc.build(&mut world).part_handle();
+        world.remove_bodies(&[removed.0]);
+        assert!(world.anchor_point_to_ground(removed, &corner).is_none());
+
+        for _ in 0..100 {
+            world.step();
+        }
+
+        let position = world.rigid_body(fixed.0).unwrap().position();
+        assert_relative_eq!(position.translation.vector, initial_position.translation.vector, epsilon = 1.0e-3);
+        assert_relative_eq!(position.rotation, initial_position.rotation, epsilon = 1.0e-3);
+
+        let position = world.rigid_body(pinned.0).unwrap().position();
+        assert_relative_eq!(*position * Point::from(Vector::repeat(0.5)), pinned_corner, epsilon = 1.0e-2);
+        assert!((position.translation.vector - Vector::x() * 5.0).norm() > 0.1);
     }
 }
@@ -0,0 +1,171 @@
+use std::any::Any;
+use std::sync::Arc;
+use slab::Slab;
+
+use na::{DVector, RealField};
+
+use crate::joint::{ConstraintHandle, JointConstraint};
+use crate::object::{Body, BodyHandle, BodySet, Multibody, MultibodyState, RigidBody, RigidBodyState};
+#[cfg(feature = "dim3")]
+use crate::object::{FEMVolume, FEMVolumeState};
+use crate::solver::{IntegrationParameters, MoreauJeanSolver};
+use crate::world::ColliderWorld;
+
+/// A snapshot of the dynamic state of a world, e.g., for rollback networking.
+///
+/// It is created by `World::snapshot` and can be restored any number of times by `World::restore`.
+/// It contains the positions, velocities, and activation energies of the bodies, the impulses cached by the
+/// joint constraints for warm-starting, and the data cached by the contact model across timesteps, i.e., the
+/// contact impulses, the contact ages, and the bounce counts for the built-in contact models. The bodies and
+/// constraints themselves are not part of the snapshot: those added after it has been taken are left untouched
+/// by the restoration, and those removed are ignored.
+///
+/// The contact manifolds are not part of the snapshot either: they are recomputed from the restored positions
+/// at the next timestep. The contact model cache is restored only if the contact model is the same as when the
+/// snapshot was taken.
+#[derive(Clone)]
+pub struct WorldSnapshot<N: RealField> {
+    t: N,
+    prev_dt: Option<N>,
+    bodies: Vec<(BodyHandle, BodyState<N>)>,
+    constraints: Vec<(ConstraintHandle, Vec<N>)>,
+    contact_model_cache: Option<Arc<Any + Send + Sync>>,
+}
+
+#[derive(Clone)]
+enum BodyState<N: RealField> {
+    RigidBody(RigidBodyState<N>),
+    Multibody(MultibodyState<N>),
+    #[cfg(feature = "dim3")]
+    FEMVolume(FEMVolumeState<N>),
+    // The other deformable bodies only have their degrees of freedom saved.
+    Deformable {
+        positions: DVector<N>,
+        velocities: DVector<N>,
+        energy: N,
+    },
+}
+
+impl<N: RealField> BodyState<N> {
+    fn new(body: &Body<N>) -> Option<Self> {
+        if let Some(rb) = body.downcast_ref::<RigidBody<N>>() {
+            return Some(BodyState::RigidBody(rb.state()));
+        }
+        if let Some(mb) = body.downcast_ref::<Multibody<N>>() {
+            return Some(BodyState::Multibody(mb.state()));
+        }
+        #[cfg(feature = "dim3")]
+        {
+            if let Some(volume) = body.downcast_ref::<FEMVolume<N>>() {
+                return Some(BodyState::FEMVolume(volume.state()));
+            }
+        }
+
+        let (_, positions) = body.deformed_positions()?;
+
+        Some(BodyState::Deformable {
+            positions: DVector::from_column_slice(positions),
+            velocities: body.generalized_velocity().into_owned(),
+            energy: body.activation_status().energy(),
+        })
+    }
+
+    fn restore(&self, body: &mut Body<N>, dt: N) {
+        match self {
+            BodyState::RigidBody(state) => {
+                if let Some(rb) = body.downcast_mut::<RigidBody<N>>() {
+                    rb.set_state(state)
+                }
+            }
+            BodyState::Multibody(state) => {
+                if let Some(mb) = body.downcast_mut::<Multibody<N>>() {
+                    mb.set_state(state, dt)
+                }
+            }
+            #[cfg(feature = "dim3")]
+            BodyState::FEMVolume(state) => {
+                if let Some(volume) = body.downcast_mut::<FEMVolume<N>>() {
+                    volume.set_state(state)
+                }
+            }
+            BodyState::Deformable { positions, velocities, energy } => {
+                if let Some((_, curr_positions)) = body.deformed_positions_mut() {
+                    curr_positions.copy_from_slice(positions.as_slice());
+                }
+
+                body.generalized_velocity_mut().copy_from(velocities);
+                body.update_kinematics();
+                body.update_dynamics(dt);
+
+                if energy.is_zero() {
+                    body.deactivate()
+                } else {
+                    body.activate_with_energy(*energy)
+                }
+            }
+        }
+    }
+}
+
+impl<N: RealField> WorldSnapshot<N> {
+    pub(crate) fn new(
+        params: &IntegrationParameters<N>,
+        solver: &MoreauJeanSolver<N>,
+        bodies: &BodySet<N>,
+        constraints: &Slab<Box<JointConstraint<N>>>,
+    ) -> Self {
+        WorldSnapshot {
+            t: params.t,
+            prev_dt: solver.prev_dt(),
+            bodies: bodies
+                .bodies()
+                .filter_map(|body| BodyState::new(body).map(|state| (body.handle(), state)))
+                .collect(),
+            constraints: constraints
+                .iter()
+                .map(|(handle, constraint)| (handle, constraint.cached_impulses()))
+                .collect(),
+            contact_model_cache: solver.contact_model_cache().map(Arc::from),
+        }
+    }
+
+    pub(crate) fn restore(
+        &self,
+        params: &mut IntegrationParameters<N>,
+        solver: &mut MoreauJeanSolver<N>,
+        bodies: &mut BodySet<N>,
+        cworld: &mut ColliderWorld<N>,
+        constraints: &mut Slab<Box<JointConstraint<N>>>,
+    ) {
+        params.t = self.t;
+        solver.set_prev_dt(self.prev_dt);
+
+        for (handle, state) in &self.bodies {
+            if let Some(body) = bodies.body_mut(*handle) {
+                state.restore(body, params.dt);
+            }
+        }
+
+        for (handle, impulses) in &self.constraints {
+            if let Some(constraint) = constraints.get_mut(*handle) {
+                // The constraint may have been replaced by another one since the snapshot has been taken.
+                if impulses.len() == constraint.cached_impulses().len() {
+                    constraint.set_cached_impulses(impulses);
+                }
+            }
+        }
+
+        if let Some(cache) = &self.contact_model_cache {
+            solver.restore_contact_model_cache(&**cache);
+        }
+
+        cworld.force_sync_colliders(bodies);
+
+        // Like at the end of a timestep, the restored bodies are up-to-date.
+        for (handle, _) in &self.bodies {
+            if let Some(body) = bodies.body_mut(*handle) {
+                body.clear_update_flags();
+            }
+        }
+    }
+}